@@ -0,0 +1,14 @@
+//! check that parsing any input into a pattern expression and
+//! a verb invocation never panics, and that the parts can be
+//! displayed back
+#![no_main]
+use {
+    broot::command::CommandParts,
+    libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|raw: &str| {
+    let parts = CommandParts::from(raw.to_string());
+    let _ = parts.to_string();
+    let _ = parts.split();
+});
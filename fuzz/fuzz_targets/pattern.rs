@@ -0,0 +1,15 @@
+//! check that building a pattern from any input either gives
+//! a pattern or an error, but never panics
+#![no_main]
+use {
+    broot::{
+        command::CommandParts,
+        pattern::{Pattern, SearchModeMap},
+    },
+    libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|raw: &str| {
+    let parts = CommandParts::from(raw.to_string());
+    let _ = Pattern::new(&parts.pattern, &SearchModeMap::default());
+});
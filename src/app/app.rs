@@ -2,21 +2,33 @@ use {
     super::*,
     crate::{
         browser::BrowserState,
+        cli::apply_conf_to_tree_options,
         command::{Command, Sequence},
-        conf::Conf,
-        display::{Areas, Screen, W},
+        conf::{find_local_conf, Conf},
+        display::{Areas, PanelsDirection, PanelsLayout, Screen, W},
         errors::ProgramError,
         file_sum, git,
+        image::kitty,
         launchable::Launchable,
+        path,
         skin::*,
         task_sync::{Dam, Either},
+        tree::{TreeOptions, DELTAS_DURATION},
         verb::Internal,
     },
-    crossbeam::channel::unbounded,
+    crossbeam::channel::{unbounded, Receiver, Sender},
     crossterm::event::KeyModifiers,
     std::{
+        collections::HashMap,
         io::Write,
-        path::PathBuf,
+        mem,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+        time::Duration,
     },
     strict::NonEmptyVec,
     termimad::{Event, EventSource},
@@ -24,17 +36,64 @@ use {
 
 const ESCAPE_TO_QUIT: bool = false;
 
-#[cfg(feature="client-server")]
-use std::sync::{Arc, Mutex};
+/// the delay between two checks of the files followed in previews
+const FOLLOW_PERIOD: Duration = Duration::from_millis(500);
+
+/// the delay between two checks of the modification of the configuration files
+const CONF_WATCH_PERIOD: Duration = Duration::from_secs(1);
+
+/// the panels of a tab which isn't the displayed one
+struct Tab {
+    panels: NonEmptyVec<Panel>,
+    active_panel_idx: usize,
+    preview: Option<PanelId>,
+}
 
 /// The GUI
 pub struct App {
     panels: NonEmptyVec<Panel>,
     active_panel_idx: usize,
+    tabs: Vec<Tab>, // the other tabs, in order, the displayed one being missing
+    tab_idx: usize, // the position of the displayed tab among all tabs
     quitting: bool,
     launch_at_end: Option<Launchable>, // what must be launched after end
     created_panels_count: usize,
     preview: Option<PanelId>, // the panel dedicated to preview, if any
+    layout: PanelsLayout, // how panels are placed on screen
+    commander: bool, // whether two tree panels are always kept
+    linked_panels: bool, // whether the selection is synchronized between the two tree panels
+    pattern_broadcast: bool, // whether the typed patterns are applied to all tree panels
+
+    // the channel for unparsed raw sequences which may come from
+    // the --cmd argument, from the server module, or from verbs
+    tx_seqs: Sender<Sequence>,
+    rx_seqs: Receiver<Sequence>,
+
+    // set while a thread waits to wake up the loop, so that
+    // the followed files are checked again
+    follow_tick_pending: Arc<AtomicBool>,
+
+    // set when the configuration files changed or when their
+    // reload was asked, for the loop to read them again
+    conf_changed: Arc<AtomicBool>,
+
+    // the configuration files, imported ones included, whose
+    // modifications are watched
+    conf_files: Arc<Mutex<Vec<PathBuf>>>,
+
+    // the .broot.toml file applying to the tree of the active panel
+    local_conf: Option<PathBuf>,
+
+    // the root for which the local configuration file was searched
+    local_conf_root: Option<PathBuf>,
+
+    // the local configuration files already read
+    local_confs: HashMap<PathBuf, Conf>,
+
+    // the settings of the tree before and after the default flags of
+    // the local configuration file were applied, so that the changes
+    // of the flags can be undone on leaving
+    local_flags_backup: Option<(PanelId, TreeOptions, TreeOptions)>,
 
     #[cfg(feature="client-server")]
     root: Arc<Mutex<PathBuf>>,
@@ -46,34 +105,292 @@ impl App {
         con: &AppContext,
         screen: &Screen,
     ) -> Result<App, ProgramError> {
-        let panel = Panel::new(
-            PanelId::from(0),
-            Box::new(
-                BrowserState::new(
-                    con.launch_args.root.clone(),
-                    con.launch_args.tree_options.clone(),
-                    screen,
-                    con,
-                    &Dam::unlimited(),
-                )?
-                .expect("Failed to create BrowserState"),
-            ),
-            Areas::create(&mut Vec::new(), 0, screen, false)?,
-            con,
-        );
+        let session = if con.launch_args.restore_session {
+            Session::load().unwrap_or_else(|e| {
+                warn!("unable to read the session: {}", e);
+                None
+            })
+        } else {
+            None
+        };
+        let mut layout = con.panels_layout.clone();
+        let panels = match session {
+            Some(session) => Self::create_session_panels(&session, &mut layout, screen, con)?,
+            None => Self::create_tab_panels(&con.launch_args.root, 0, screen, con)?,
+        };
+        let created_panels_count = panels.len().get();
+        let (tx_seqs, rx_seqs) = unbounded::<Sequence>();
         Ok(App {
             active_panel_idx: 0,
-            panels: panel.into(),
+            panels,
+            tabs: Vec::new(),
+            tab_idx: 0,
             quitting: false,
             launch_at_end: None,
-            created_panels_count: 1,
+            created_panels_count,
             preview: None,
+            layout,
+            commander: con.commander,
+            linked_panels: false,
+            pattern_broadcast: false,
+            tx_seqs,
+            rx_seqs,
+            follow_tick_pending: Arc::new(AtomicBool::new(false)),
+            conf_changed: Arc::new(AtomicBool::new(false)),
+            conf_files: Arc::new(Mutex::new(Vec::new())),
+            local_conf: None,
+            local_conf_root: None,
+            local_confs: HashMap::new(),
+            local_flags_backup: None,
 
             #[cfg(feature="client-server")]
             root: Arc::new(Mutex::new(con.launch_args.root.clone())),
         })
     }
 
+    /// create the panels of a new tab: a tree on the root, and a
+    /// second one in commander mode
+    fn create_tab_panels(
+        root: &Path,
+        first_id: usize,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<NonEmptyVec<Panel>, ProgramError> {
+        let new_state = || -> Result<Box<dyn AppState>, ProgramError> {
+            let state = BrowserState::new(
+                root.to_path_buf(),
+                con.launch_args.tree_options.clone(),
+                screen,
+                con,
+                &Dam::unlimited(),
+            )?
+            .expect("Failed to create BrowserState");
+            Ok(Box::new(state))
+        };
+        let panel = Panel::new(
+            PanelId::from(first_id),
+            new_state()?,
+            Areas::create(&mut Vec::new(), 0, screen, &con.panels_layout, false)?,
+            con,
+        );
+        let mut panels: NonEmptyVec<Panel> = panel.into();
+        if con.commander {
+            // a second tree, on the same root, with which
+            // files can be exchanged
+            let areas = Areas::create(panels.as_mut_slice(), 1, screen, &con.panels_layout, false)?;
+            panels.insert(1, Panel::new(PanelId::from(first_id + 1), new_state()?, areas, con));
+        }
+        Ok(panels)
+    }
+
+    /// create the tree panels saved in the session, with their sizes,
+    /// dropping the ones which don't fit the screen
+    fn create_session_panels(
+        session: &Session,
+        layout: &mut PanelsLayout,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<NonEmptyVec<Panel>, ProgramError> {
+        if session.sizes.len() > 1 {
+            layout.set_sizes(session.sizes.clone());
+        }
+        let new_state = |panel_session: &PanelSession| -> Result<Box<dyn AppState>, ProgramError> {
+            let state = BrowserState::new(
+                panel_session.root.clone(),
+                panel_session.tree_options(&con.launch_args.tree_options),
+                screen,
+                con,
+                &Dam::unlimited(),
+            )?
+            .expect("Failed to create BrowserState");
+            Ok(Box::new(state))
+        };
+        let panel = Panel::new(
+            PanelId::from(0),
+            new_state(&session.panels[0])?,
+            Areas::create(&mut Vec::new(), 0, screen, layout, false)?,
+            con,
+        );
+        let mut panels: NonEmptyVec<Panel> = panel.into();
+        for (idx, panel_session) in session.panels.iter().enumerate().skip(1) {
+            let areas = match Areas::create(panels.as_mut_slice(), idx, screen, layout, false) {
+                Ok(areas) => areas,
+                Err(_) => break, // not enough space on screen
+            };
+            panels.insert(idx, Panel::new(PanelId::from(idx), new_state(panel_session)?, areas, con));
+        }
+        Ok(panels)
+    }
+
+    /// save the tree panels of the displayed tab, with their sizes, so
+    /// that they can be restored on next launch
+    fn save_session(&self) {
+        let direction = self.layout.direction;
+        let mut session = Session::default();
+        for idx in self.laid_out_panels() {
+            let state = self.panels[idx].state();
+            if let (Some(root), Some(options)) = (state.tree_root(), state.tree_options()) {
+                session.panels.push(PanelSession::new(root.to_path_buf(), options));
+                session.sizes.push(self.panels[idx].areas.span(direction).1);
+            }
+        }
+        if session.panels.is_empty() {
+            return;
+        }
+        if let Err(e) = session.save() {
+            warn!("unable to save the session: {}", e);
+        }
+    }
+
+    fn tabs_count(&self) -> usize {
+        self.tabs.len() + 1
+    }
+
+    /// display the panels of the given tab, returning the
+    /// previously displayed ones
+    fn swap_tab(&mut self, tab: Tab) -> Tab {
+        Tab {
+            panels: mem::replace(&mut self.panels, tab.panels),
+            active_panel_idx: mem::replace(&mut self.active_panel_idx, tab.active_panel_idx),
+            preview: mem::replace(&mut self.preview, tab.preview),
+        }
+    }
+
+    /// the panels of the displayed tab were hidden or the screen
+    /// changed: they must be resized and refreshed
+    fn refresh_tab(&mut self, screen: &Screen, con: &AppContext) -> Result<(), ProgramError> {
+        Areas::resize_all(
+            self.panels.as_mut_slice(),
+            screen,
+            &self.layout,
+            self.preview.is_some(),
+        )?;
+        for panel in &mut self.panels {
+            panel.mut_state().refresh(screen, con);
+        }
+        let other_path = self.get_other_panel_path();
+        self.mut_panel().refresh_input_status(&other_path, con);
+        Ok(())
+    }
+
+    /// open a new tab, after the current one, with a tree on
+    /// the selected directory
+    fn new_tab(&mut self, screen: &mut Screen, con: &AppContext) -> Result<(), ProgramError> {
+        let root = path::closest_dir(self.state().selected_path());
+        screen.set_tab_bar(true);
+        let panels = Self::create_tab_panels(&root, self.created_panels_count, screen, con)?;
+        self.created_panels_count += panels.len().get();
+        let previous = self.swap_tab(Tab {
+            panels,
+            active_panel_idx: 0,
+            preview: None,
+        });
+        self.tabs.insert(self.tab_idx, previous);
+        self.tab_idx += 1;
+        self.refresh_tab(screen, con)
+    }
+
+    /// display the tab at the given position among all tabs
+    fn switch_to_tab(
+        &mut self,
+        idx: usize,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if idx == self.tab_idx || idx >= self.tabs_count() {
+            return Ok(());
+        }
+        let tab = self.tabs.remove(if idx > self.tab_idx { idx - 1 } else { idx });
+        let previous = self.swap_tab(tab);
+        let previous_idx = if idx < self.tab_idx { self.tab_idx - 1 } else { self.tab_idx };
+        self.tabs.insert(previous_idx, previous);
+        self.tab_idx = idx;
+        self.refresh_tab(screen, con)
+    }
+
+    /// close the displayed tab and display the next one (or the
+    /// previous one if it was the last). Return false if there's
+    /// no other tab
+    fn close_tab(&mut self, screen: &mut Screen, con: &AppContext) -> Result<bool, ProgramError> {
+        if self.tabs.is_empty() {
+            return Ok(false);
+        }
+        if self.tab_idx == self.tabs.len() {
+            self.tab_idx -= 1;
+        }
+        let tab = self.tabs.remove(self.tab_idx);
+        self.swap_tab(tab);
+        if self.tabs.is_empty() {
+            screen.set_tab_bar(false);
+        }
+        self.refresh_tab(screen, con)?;
+        Ok(true)
+    }
+
+    /// the labels of all tabs, in order: the number of the tab
+    /// and the name of the root of its focused panel
+    fn tab_labels(&self) -> Vec<String> {
+        let label = |idx: usize, panel: &Panel| {
+            let root = panel.root().unwrap_or_default();
+            let name = root.file_name().unwrap_or_else(|| root.as_os_str());
+            format!(" {}:{} ", idx + 1, name.to_string_lossy())
+        };
+        (0..self.tabs_count())
+            .map(|idx| {
+                if idx == self.tab_idx {
+                    label(idx, self.panel())
+                } else {
+                    let tab = &self.tabs[if idx > self.tab_idx { idx - 1 } else { idx }];
+                    label(idx, &tab.panels[tab.active_panel_idx])
+                }
+            })
+            .collect()
+    }
+
+    /// display the tab at the clicked position of the tab bar
+    fn on_tab_bar_click(
+        &mut self,
+        x: u16,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        let mut end = 0;
+        for (idx, label) in self.tab_labels().iter().enumerate() {
+            end += label.chars().count() as u16;
+            if x < end {
+                return self.switch_to_tab(idx, screen, con);
+            }
+        }
+        Ok(())
+    }
+
+    /// draw the tab bar, when there are several tabs
+    fn display_tab_bar(
+        &self,
+        w: &mut W,
+        screen: &Screen,
+        panel_skin: &PanelSkin,
+    ) -> Result<(), ProgramError> {
+        let y = match screen.tab_bar_y() {
+            Some(y) => y,
+            None => return Ok(()),
+        };
+        screen.goto(w, 0, y)?;
+        let mut remaining = screen.width as usize;
+        for (idx, label) in self.tab_labels().iter().enumerate() {
+            let label: String = label.chars().take(remaining).collect();
+            remaining -= label.chars().count();
+            let style = if idx == self.tab_idx {
+                &panel_skin.styles.tab_current
+            } else {
+                &panel_skin.styles.tab
+            };
+            style.queue_str(w, &label)?;
+        }
+        panel_skin.styles.tab.queue_str(w, &" ".repeat(remaining))?;
+        Ok(())
+    }
+
     /// return the current index of the panel whith given id
     fn panel_idx(&self, id: PanelId) -> Option<usize> {
         self.panels.iter().position(|panel| panel.id==id)
@@ -96,9 +413,34 @@ impl App {
         }
     }
 
-    /// return true when the panel has been removed (ie it wasn't the last one)
+    /// tell whether the panel can't be closed because it's one of
+    /// the two tree panels kept in commander mode
+    fn is_kept_by_commander(&self, panel_idx: usize) -> bool {
+        let is_tree_panel = |panel: &Panel| {
+            Some(panel.id) != self.preview && panel.floating_over.is_none()
+        };
+        if !self.commander || !is_tree_panel(&self.panels[panel_idx]) {
+            return false;
+        }
+        let non_preview_count = self.panels.iter()
+            .filter(|panel| is_tree_panel(panel))
+            .count();
+        non_preview_count <= 2
+    }
+
+    /// return true when the panel has been removed (ie it wasn't the last one
+    /// nor one of the panels kept in commander mode)
     fn close_panel(&mut self, panel_idx: usize, screen: &Screen) -> bool {
-        let active_panel_id = self.panels[self.active_panel_idx].id;
+        if self.is_kept_by_commander(panel_idx) {
+            return false;
+        }
+        let mut active_panel_id = self.panels[self.active_panel_idx].id;
+        if panel_idx == self.active_panel_idx {
+            // a floating panel gives the focus back to its opener
+            if let Some(id) = self.panels[panel_idx].floating_over {
+                active_panel_id = id;
+            }
+        }
         if let Some(preview_id) = self.preview {
             if self.panels.has_len(2) && self.panels[panel_idx].id != preview_id {
                 // we don't want to stay with just the preview
@@ -109,8 +451,12 @@ impl App {
             if self.preview == Some(removed_panel.id) {
                 self.preview = None;
             }
-            Areas::resize_all(self.panels.as_mut_slice(), screen, self.preview.is_some())
-                .expect("removing a panel should be easy");
+            Areas::resize_all(
+                self.panels.as_mut_slice(),
+                screen,
+                &self.layout,
+                self.preview.is_some(),
+            ).expect("removing a panel should be easy");
             self.active_panel_idx = self.panels.iter()
                 .position(|p| p.id == active_panel_id)
                 .unwrap_or(self.panels.len().get()-1);
@@ -120,6 +466,333 @@ impl App {
         }
     }
 
+    /// insert a panel with the given state next to the active one
+    /// (or at the end for the preview), and focus it unless it's
+    /// the preview
+    fn open_panel(
+        &mut self,
+        state: Box<dyn AppState>,
+        purpose: PanelPurpose,
+        direction: HDir,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        // transient states float over the other panels when configured
+        // so, or when there's not enough room to split
+        let transient = purpose.is_arg_edition() || state.get_type() == AppStateType::Help;
+        if transient && con.floating_panels {
+            self.open_floating_panel(state, purpose, screen, con);
+            return Ok(());
+        }
+        let insertion_idx = if purpose.is_preview() {
+            self.panels.len().get()
+        } else if direction == HDir::Right {
+            self.active_panel_idx + 1
+        } else {
+            self.active_panel_idx
+        };
+        let with_preview = purpose.is_preview() || self.preview.is_some();
+        let areas = match Areas::create(
+            self.panels.as_mut_slice(),
+            insertion_idx,
+            screen,
+            &self.layout,
+            with_preview,
+        ) {
+            Ok(areas) => areas,
+            Err(_) if transient => {
+                self.open_floating_panel(state, purpose, screen, con);
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+        let panel_id = self.created_panels_count.into();
+        let mut panel = Panel::new(panel_id, state, areas, con);
+        panel.purpose = purpose;
+        self.created_panels_count += 1;
+        self.panels.insert(insertion_idx, panel);
+        if purpose.is_preview() {
+            debug_assert!(self.preview.is_none());
+            self.preview = Some(panel_id);
+        } else {
+            self.active_panel_idx = insertion_idx;
+        }
+        Ok(())
+    }
+
+    /// open a panel floating in the middle of the screen, over
+    /// the other ones, and focus it
+    fn open_floating_panel(
+        &mut self,
+        state: Box<dyn AppState>,
+        purpose: PanelPurpose,
+        screen: &Screen,
+        con: &AppContext,
+    ) {
+        let panel_id = self.created_panels_count.into();
+        let mut panel = Panel::new(panel_id, state, Areas::floating(screen), con);
+        panel.purpose = purpose;
+        panel.floating_over = Some(self.panel().id);
+        self.created_panels_count += 1;
+        let idx = self.panels.len().get();
+        self.panels.insert(idx, panel);
+        self.active_panel_idx = idx;
+    }
+
+    /// resize the panel by moving its separator with the next panel (or
+    /// with the previous one when it's the last panel), keeping the
+    /// minimal panel size. Return true when the layout changed
+    fn resize_panel(
+        &mut self,
+        panel_idx: usize,
+        delta: i16,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<bool, ProgramError> {
+        let laid_out = self.laid_out_panels();
+        let pos = match laid_out.iter().position(|&idx| idx == panel_idx) {
+            Some(pos) => pos,
+            None => return Ok(false), // floating panels can't be resized
+        };
+        let nb_panels = laid_out.len();
+        if nb_panels < 2 || delta == 0 {
+            return Ok(false);
+        }
+        let direction = self.layout.direction;
+        let mut sizes: Vec<u16> = laid_out.iter()
+            .map(|&idx| self.panels[idx].areas.span(direction).1)
+            .collect();
+        // idx is the position of the panel before the separator to move
+        let (idx, delta) = if pos + 1 < nb_panels {
+            (pos, delta as i32)
+        } else {
+            (pos - 1, -(delta as i32))
+        };
+        let min = self.layout.min_panel_size() as i32;
+        let pair_size = sizes[idx] as i32 + sizes[idx + 1] as i32;
+        if pair_size < 2 * min {
+            return Ok(false);
+        }
+        let new_size = (sizes[idx] as i32 + delta).max(min).min(pair_size - min);
+        if new_size == sizes[idx] as i32 {
+            return Ok(false);
+        }
+        sizes[idx] = new_size as u16;
+        sizes[idx + 1] = (pair_size - new_size) as u16;
+        self.layout.set_sizes(sizes);
+        Areas::resize_all(
+            self.panels.as_mut_slice(),
+            screen,
+            &self.layout,
+            self.preview.is_some(),
+        )?;
+        for panel in &mut self.panels {
+            panel.mut_state().refresh(screen, con);
+        }
+        Ok(true)
+    }
+
+    /// move the separator nearest to the clicked point, so that
+    /// it's at this point
+    fn move_separator_to(
+        &mut self,
+        x: u16,
+        y: u16,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        let direction = self.layout.direction;
+        let pos = match direction {
+            PanelsDirection::Columns => x,
+            PanelsDirection::Rows => y,
+        } as i32;
+        // the separator before a panel is at its start
+        let laid_out = self.laid_out_panels();
+        let nearest = laid_out.windows(2)
+            .map(|pair| (pair[0], self.panels[pair[1]].areas.span(direction).0 as i32))
+            .min_by_key(|&(_, start)| (start - pos).abs());
+        if let Some((idx, start)) = nearest {
+            self.resize_panel(idx, (pos - start) as i16, screen, con)?;
+        }
+        Ok(())
+    }
+
+    /// when a displayed state follows a changing file, make sure
+    /// the loop will be woken up (by an empty sequence) so that
+    /// the panels are redrawn and the file checked again
+    fn schedule_follow_tick(&self) {
+        let following = self.panels.iter().any(|panel| panel.state().is_following());
+        if following && !self.follow_tick_pending.swap(true, Ordering::SeqCst) {
+            let tx_seqs = self.tx_seqs.clone();
+            let pending = Arc::clone(&self.follow_tick_pending);
+            thread::spawn(move || {
+                thread::sleep(FOLLOW_PERIOD);
+                pending.store(false, Ordering::SeqCst);
+                tx_seqs.send(Sequence::new_single(String::new())).ok();
+            });
+        }
+    }
+
+    /// watch the modification dates of the configuration files and,
+    /// when one changes, wake up the loop (with an empty sequence)
+    /// for the configuration to be reloaded.
+    /// The list of files may change on reload, when the imports change.
+    fn watch_conf_files(&self) {
+        let tx_seqs = self.tx_seqs.clone();
+        let conf_changed = Arc::clone(&self.conf_changed);
+        let conf_files = Arc::clone(&self.conf_files);
+        thread::spawn(move || {
+            let modified = || -> Vec<_> {
+                conf_files.lock().unwrap().iter()
+                    .map(|path| (path.clone(), path.metadata().and_then(|m| m.modified()).ok()))
+                    .collect()
+            };
+            let mut last_modified = modified();
+            loop {
+                thread::sleep(CONF_WATCH_PERIOD);
+                let current_modified = modified();
+                if current_modified != last_modified {
+                    let same_files = current_modified.iter().map(|(path, _)| path)
+                        .eq(last_modified.iter().map(|(path, _)| path));
+                    last_modified = current_modified;
+                    if !same_files {
+                        continue; // the list was changed by a reload
+                    }
+                    conf_changed.store(true, Ordering::SeqCst);
+                    if tx_seqs.send(Sequence::new_single(String::new())).is_err() {
+                        break; // the app is closed
+                    }
+                }
+            }
+        });
+    }
+
+    /// update the local configuration file applying to the tree of
+    /// the active panel and tell whether it changed. The file is
+    /// only searched when the root of the tree changes.
+    fn update_local_conf(&mut self) -> bool {
+        let root = match self.panel().state().tree_root() {
+            Some(root) => root,
+            None => return false, // the other states keep the local conf
+        };
+        if self.local_conf_root.as_deref() == Some(root) {
+            return false;
+        }
+        let root = root.to_path_buf();
+        let local_conf = find_local_conf(&root);
+        self.local_conf_root = Some(root);
+        if local_conf == self.local_conf {
+            return false;
+        }
+        self.local_conf = local_conf;
+        true
+    }
+
+    /// build the configuration with the local configuration file
+    /// applying to the active tree, if any and if it's trusted.
+    /// Return the configuration and the reason why the local file
+    /// wasn't applied, if so.
+    fn conf_with_local(&mut self, base_conf: &Conf) -> (Conf, Option<String>) {
+        let mut conf = base_conf.clone();
+        let path = match &self.local_conf {
+            Some(path) => path,
+            None => return (conf, None),
+        };
+        if !base_conf.trusts_local_file(path) {
+            return (conf, Some(format!(
+                "{:?} not applied: its directory isn't in trusted_local_confs",
+                path,
+            )));
+        }
+        if !self.local_confs.contains_key(path) {
+            match Conf::read_local_file(path) {
+                Ok(local) => {
+                    self.local_confs.insert(path.clone(), local);
+                }
+                Err(e) => {
+                    return (conf, Some(format!("Local configuration not applied: {}", e)));
+                }
+            }
+        }
+        if let Some(local) = self.local_confs.get(path) {
+            conf.add_local(local);
+        }
+        (conf, None)
+    }
+
+    /// apply the default flags of a local configuration file to
+    /// the tree of the active panel, which just entered its directory
+    fn apply_local_flags(&mut self, flags: &str) {
+        let mut options = match self.panel().state().tree_options() {
+            Some(options) => options.clone(),
+            None => return,
+        };
+        let backup = options.clone();
+        if let Err(e) = options.apply_flags(flags) {
+            self.mut_panel().set_error(e.to_string());
+            return;
+        }
+        self.mut_panel().mut_state().set_tree_settings(&options);
+        self.local_flags_backup = Some((self.panel().id, backup, options));
+    }
+
+    /// give back to the tree the settings it had before the default
+    /// flags of the local configuration file were applied, keeping
+    /// the settings the user changed since
+    fn revert_local_flags(&mut self) {
+        if let Some((panel_id, before, after)) = self.local_flags_backup.take() {
+            if let Some(idx) = self.panel_idx(panel_id) {
+                let state = self.panels[idx].mut_state();
+                if let Some(mut options) = state.tree_options().cloned() {
+                    options.revert_flags(&before, &after);
+                    state.set_tree_settings(&options);
+                }
+            }
+        }
+    }
+
+    /// apply the pattern command to the tree panels other than
+    /// the active one, and show the pattern in their input
+    fn broadcast_pattern(
+        &mut self,
+        w: &mut W,
+        pattern_cmd: &Command,
+        screen: &mut Screen,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        let raw = match pattern_cmd {
+            Command::PatternEdit { raw, .. } => raw,
+            _ => return Ok(()),
+        };
+        let preview = self.preview;
+        for idx in self.laid_out_panels() {
+            if idx == self.active_panel_idx || self.panels[idx].state().tree_root().is_none() {
+                continue;
+            }
+            self.panels[idx].set_input_content(raw);
+            self.panels[idx].apply_command(
+                w,
+                pattern_cmd,
+                &None,
+                screen,
+                panel_skin,
+                preview,
+                con,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// the indexes of the panels which aren't floating, in order
+    fn laid_out_panels(&self) -> Vec<usize> {
+        (0..self.panels.len().get())
+            .filter(|&idx| self.panels[idx].floating_over.is_none())
+            .collect()
+    }
+
     fn remove_state(&mut self, screen: &Screen) -> bool {
         self.panels[self.active_panel_idx].remove_state()
             || self.close_panel(self.active_panel_idx, screen)
@@ -132,7 +805,18 @@ impl App {
         skin: &AppSkin,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
-        for (idx, panel) in self.panels.as_mut_slice().iter_mut().enumerate() {
+        if con.kitty_graphics {
+            // images aren't erased by the drawing of chars
+            kitty::delete_images(w)?;
+        }
+        self.display_tab_bar(w, screen, &skin.focused)?;
+        // floating panels are drawn last, over the other ones
+        let mut panels: Vec<(usize, &mut Panel)> = self.panels.as_mut_slice()
+            .iter_mut()
+            .enumerate()
+            .collect();
+        panels.sort_by_key(|(_, panel)| panel.floating_over.is_some());
+        for (idx, panel) in panels {
             let focused = idx == self.active_panel_idx;
             let skin = if focused { &skin.focused } else { &skin.unfocused };
             time!(
@@ -146,15 +830,25 @@ impl App {
 
     /// if there are exactly two panels, return the selection
     /// in the non focused panel
-    fn get_other_panel_path(&self) -> Option<PathBuf> {
-        if self.panels.len().get() == 2 {
-            let non_focused_panel_idx = if self.active_panel_idx == 0 { 1 } else { 0 };
-            Some(self.panels[non_focused_panel_idx].state().selected_path().to_path_buf())
-        } else {
-            None
+    /// return the index of the other panel, when there's exactly
+    /// one panel besides the active one and the preview
+    fn other_panel_idx(&self) -> Option<usize> {
+        let mut others = (0..self.panels.len().get()).filter(|&idx| {
+            idx != self.active_panel_idx
+                && Some(self.panels[idx].id) != self.preview
+                && self.panels[idx].floating_over.is_none()
+        });
+        match (others.next(), others.next()) {
+            (Some(idx), None) => Some(idx),
+            _ => None,
         }
     }
 
+    fn get_other_panel_path(&self) -> Option<PathBuf> {
+        self.other_panel_idx()
+            .map(|idx| self.panels[idx].state().selected_path().to_path_buf())
+    }
+
     /// apply a command, and returns a command, which may be the same (modified or not)
     ///  or a new one.
     fn apply_command(
@@ -167,6 +861,7 @@ impl App {
     ) -> Result<(), ProgramError> {
         use AppStateCmdResult::*;
         let mut error: Option<String> = None;
+        let mut message: Option<String> = None;
         let is_input_invocation = cmd.is_verb_invocated_from_input();
         let other_path = self.get_other_panel_path();
         let preview = self.preview;
@@ -231,6 +926,8 @@ impl App {
                             con,
                         )?;
                     }
+                } else if self.is_kept_by_commander(close_idx) {
+                    error = Some("in commander mode, two tree panels are kept".to_string());
                 } else {
                     self.quitting = true;
                 }
@@ -238,6 +935,76 @@ impl App {
             DisplayError(txt) => {
                 error = Some(txt);
             }
+            DisplayMessage(txt) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                message = Some(txt);
+            }
+            ExecuteSequence { sequence } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                // the sequence will be executed in the main loop
+                self.tx_seqs.send(sequence).unwrap();
+            }
+            HandleInApp(Internal::reload_config) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input();
+                }
+                // the configuration is reloaded in the main loop, which owns the context
+                self.conf_changed.store(true, Ordering::SeqCst);
+            }
+            HandleInApp(Internal::tab_new) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input();
+                }
+                self.new_tab(screen, con)?;
+            }
+            HandleInApp(Internal::tab_next) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input();
+                }
+                self.switch_to_tab((self.tab_idx + 1) % self.tabs_count(), screen, con)?;
+            }
+            HandleInApp(Internal::tab_close) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input();
+                }
+                if !self.close_tab(screen, con)? {
+                    error = Some("there's no other tab".to_string());
+                }
+            }
+            HandleInApp(Internal::toggle_link_panels) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input();
+                }
+                if self.linked_panels {
+                    self.linked_panels = false;
+                    message = Some("panels aren't linked anymore".to_string());
+                } else if self.other_panel_idx().is_some() {
+                    self.linked_panels = true;
+                    message = Some("panels are linked".to_string());
+                } else {
+                    error = Some("linking panels needs two tree panels".to_string());
+                }
+            }
+            HandleInApp(Internal::broadcast_pattern) => {
+                self.mut_panel().clear_input_invocation();
+                let pattern_cmd = Command::from_raw(self.panel().get_input_content(), false);
+                self.broadcast_pattern(w, &pattern_cmd, screen, panel_skin, con)?;
+            }
+            HandleInApp(Internal::toggle_pattern_broadcast) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                self.pattern_broadcast = !self.pattern_broadcast;
+                message = Some(if self.pattern_broadcast {
+                    "patterns are applied to all tree panels".to_string()
+                } else {
+                    "patterns aren't broadcast anymore".to_string()
+                });
+            }
             HandleInApp(internal) => {
                 let new_active_panel_idx = match internal {
                     Internal::panel_left if self.active_panel_idx > 0 => {
@@ -246,6 +1013,9 @@ impl App {
                     Internal::panel_right if self.active_panel_idx + 1 < self.panels.len().get() => {
                         Some(self.active_panel_idx + 1)
                     }
+                    Internal::next_panel if self.panels.len().get() > 1 => {
+                        Some((self.active_panel_idx + 1) % self.panels.len().get())
+                    }
                     _ => {
                         debug!("unhandled propagated internal. cmd={:?}", &cmd);
                         None
@@ -273,31 +1043,34 @@ impl App {
                 if is_input_invocation {
                     self.mut_panel().clear_input_invocation();
                 }
-                let insertion_idx = if purpose.is_preview() {
-                    self.panels.len().get()
-                } else if direction == HDir::Right {
-                    self.active_panel_idx + 1
-                } else {
-                    self.active_panel_idx
+                if let Err(e) = self.open_panel(state, purpose, direction, screen, con) {
+                    error = Some(e.to_string());
+                }
+            }
+            NewStateInPanel { state, direction } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let nb_panels = self.panels.len().get();
+                let adjacent_idx = match direction {
+                    HDir::Left => self.active_panel_idx.checked_sub(1),
+                    HDir::Right => Some(self.active_panel_idx + 1).filter(|&idx| idx < nb_panels),
                 };
-                let with_preview = purpose.is_preview() || self.preview.is_some();
-                match Areas::create(self.panels.as_mut_slice(), insertion_idx, screen, with_preview) {
-                    Ok(areas) => {
-                        let panel_id = self.created_panels_count.into();
-                        let mut panel = Panel::new(panel_id, state, areas, con);
-                        panel.purpose = purpose;
-                        self.created_panels_count += 1;
-                        self.panels.insert(insertion_idx, panel);
-                        if purpose.is_preview() {
-                            debug_assert!(self.preview.is_none());
-                            self.preview = Some(panel_id);
-                        } else {
-                            self.active_panel_idx = insertion_idx;
-                        }
-                    }
-                    Err(e) => {
+                let adjacent_idx = adjacent_idx
+                    .filter(|&idx| Some(self.panels[idx].id) != self.preview);
+                if let Some(idx) = adjacent_idx {
+                    self.panels[idx].clear_input();
+                    self.panels[idx].push_state(state);
+                } else {
+                    // there's no panel to reuse, we open one but
+                    // the focus stays on the current panel
+                    let active_panel_id = self.panel().id;
+                    if let Err(e) = self.open_panel(state, PanelPurpose::None, direction, screen, con) {
                         error = Some(e.to_string());
                     }
+                    if let Some(idx) = self.panel_idx(active_panel_id) {
+                        self.active_panel_idx = idx;
+                    }
                 }
             }
             NewState(state) => {
@@ -306,6 +1079,41 @@ impl App {
                 let other_path = self.get_other_panel_path();
                 self.mut_panel().refresh_input_status(&other_path, con);
             }
+            BroadcastTreeOptions { state, options } => {
+                if let Some(state) = state {
+                    self.mut_panel().clear_input();
+                    self.mut_panel().push_state(state);
+                }
+                for idx in 0..self.panels.len().get() {
+                    if idx == self.active_panel_idx {
+                        continue;
+                    }
+                    let new_state = self.panels[idx].state().with_tree_options(&options, screen, con);
+                    if let Some(new_state) = new_state {
+                        self.panels[idx].push_state(new_state);
+                    }
+                }
+                let other_path = self.get_other_panel_path();
+                self.mut_panel().refresh_input_status(&other_path, con);
+            }
+            BackTo(None) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                message = Some(self.panel().states_breadcrumb());
+            }
+            BackTo(Some(pos)) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                if self.mut_panel().back_to(pos) {
+                    self.mut_state().refresh(screen, con);
+                    let other_path = self.get_other_panel_path();
+                    self.mut_panel().refresh_input_status(&other_path, con);
+                } else {
+                    error = Some(format!("no state to go back to at position {}", pos));
+                }
+            }
             PopState => {
                 if is_input_invocation {
                     self.mut_panel().clear_input();
@@ -337,9 +1145,27 @@ impl App {
                     self.quitting = true;
                 }
             }
+            PopStateAndRefresh { clear_cache } => {
+                self.mut_panel().clear_input();
+                self.remove_state(screen);
+                if clear_cache {
+                    clear_caches();
+                }
+                for i in 0..self.panels.len().get() {
+                    self.panels[i].mut_state().refresh(screen, con);
+                }
+                let other_path = self.get_other_panel_path();
+                self.mut_panel().refresh_input_status(&other_path, con);
+            }
             Quit => {
                 self.quitting = true;
             }
+            ResizePanel(delta) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                self.resize_panel(self.active_panel_idx, delta, screen, con)?;
+            }
             RefreshState { clear_cache } => {
                 if is_input_invocation {
                     self.mut_panel().clear_input_invocation();
@@ -350,12 +1176,43 @@ impl App {
                 for i in 0..self.panels.len().get() {
                     self.panels[i].mut_state().refresh(screen, con);
                 }
+                if con.show_refresh_deltas {
+                    // an empty sequence is sent to have the screen redrawn
+                    // when the deltas aren't to be displayed anymore
+                    let tx_seqs = self.tx_seqs.clone();
+                    thread::spawn(move || {
+                        thread::sleep(DELTAS_DURATION);
+                        tx_seqs.send(Sequence::new_single(String::new())).ok();
+                    });
+                }
+            }
+            RefreshPaths { paths } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                clear_caches();
+                for i in 0..self.panels.len().get() {
+                    self.panels[i].mut_state().refresh_paths(&paths, screen, con);
+                }
+            }
+            SetInputInvocation(invocation) => {
+                self.mut_panel().set_input_invocation(invocation);
+                let other_path = self.get_other_panel_path();
+                self.mut_panel().refresh_input_status(&other_path, con);
+            }
+        }
+        if self.pattern_broadcast {
+            if let Command::PatternEdit { .. } = cmd {
+                self.broadcast_pattern(w, &cmd, screen, panel_skin, con)?;
             }
         }
         if let Some(text) = error {
             self.mut_panel().set_error(text);
+        } else if let Some(text) = message {
+            self.mut_panel().set_message(text);
         }
         self.update_preview(con);
+        self.sync_linked_panels(screen);
 
         #[cfg(feature="client-server")]
         if let Ok(mut root) = self.root.lock() { // when does this not work ?
@@ -370,16 +1227,54 @@ impl App {
         if let Some(preview_idx) = preview_idx {
             let path = self.state().selected_path();
             let old_path = self.panels[preview_idx].state().selected_path();
-            if path != old_path && path.is_file() {
+            // special files (fifos, sockets, devices) and broken links
+            // get a metadata card
+            if path != old_path && path.symlink_metadata().is_ok() {
                 let path = path.to_path_buf();
-                self.panels[preview_idx].mut_state().set_selected_path(path, con);
+                let tree_options = self.state().tree_options().cloned();
+                self.panels[preview_idx]
+                    .mut_state()
+                    .set_selected_path(path, tree_options.as_ref(), con);
             }
         }
     }
 
-    fn clicked_panel_index(&self, x: u16, _y: u16, screen: &Screen) -> usize {
-        let len = self.panels.len().get();
-        (len * x as usize) / (screen.width as usize + 1)
+    /// when panels are linked, select in the other tree panel the
+    /// entry at the same path relatively to its root, and give both
+    /// panels the root of the other one so that entries without
+    /// counterpart are highlighted
+    fn sync_linked_panels(&mut self, screen: &Screen) {
+        let other_idx = match self.other_panel_idx() {
+            Some(idx) => idx,
+            None => return,
+        };
+        let roots = if self.linked_panels {
+            (self.panel().root(), self.panels[other_idx].root())
+        } else {
+            (None, None)
+        };
+        let (root, other_root) = match roots {
+            (Some(root), Some(other_root)) => {
+                let counterpart = self.state().selected_path()
+                    .strip_prefix(&root)
+                    .ok()
+                    .map(|subpath| other_root.join(subpath));
+                if let Some(counterpart) = counterpart {
+                    self.panels[other_idx].mut_state().select_linked_path(&counterpart, screen);
+                }
+                (Some(root), Some(other_root))
+            }
+            _ => (None, None),
+        };
+        self.mut_state().set_linked_root(other_root);
+        self.panels[other_idx].mut_state().set_linked_root(root);
+    }
+
+    fn clicked_panel_index(&self, x: u16, y: u16) -> usize {
+        // floating panels are the last ones, displayed over the others
+        self.panels.iter()
+            .rposition(|panel| panel.areas.contains(x, y))
+            .unwrap_or(self.active_panel_idx)
     }
 
     fn do_pending_tasks(
@@ -407,7 +1302,7 @@ impl App {
         mut self,
         w: &mut W,
         screen: &mut Screen,
-        con: &AppContext,
+        mut con: AppContext,
         conf: &Conf,
     ) -> Result<Option<Launchable>, ProgramError> {
         // we listen for events in a separate thread so that we can go on listening
@@ -416,63 +1311,133 @@ impl App {
         let rx_events = event_source.receiver();
         let mut dam = Dam::from(rx_events);
 
-        let skin = AppSkin::new(conf);
+        let mut skin = AppSkin::new(conf);
 
         screen.clear_bottom_right_char(w, &skin.focused)?;
 
-        // we create a channel for unparsed raw sequence which may come
-        // from the --cmd argument or from the server module
-        let (tx_seqs, rx_seqs) = unbounded::<Sequence>();
+        if let Some(message) = &con.startup_message {
+            self.mut_panel().set_message(message.clone());
+        }
 
         if let Some(raw_sequence) = &con.launch_args.commands {
-            tx_seqs.send(Sequence::new_local(raw_sequence.to_string())).unwrap();
+            self.tx_seqs.send(Sequence::new_local(raw_sequence.to_string())).unwrap();
         }
 
         #[cfg(feature="client-server")]
         let _server = con.launch_args.listen.as_ref()
             .map(|server_name| crate::net::Server::new(
                 &server_name,
-                tx_seqs.clone(),
+                self.tx_seqs.clone(),
                 Arc::clone(&self.root),
             ))
             .transpose()?;
 
+        *self.conf_files.lock().unwrap() = conf.files.clone();
+        self.watch_conf_files();
+
+        // the configuration of the files, without the local one, which
+        // is only read again when they change
+        let mut base_conf = conf.clone();
+
         loop {
+            // the local configuration changes when the tree of the
+            // active panel enters or leaves the directory of a .broot.toml
+            let local_conf_changed = self.update_local_conf();
+            let mut conf_reloaded = false;
+            if self.conf_changed.swap(false, Ordering::SeqCst) {
+                match con.launch_args.reread_conf() {
+                    Ok(conf) => {
+                        base_conf = conf;
+                        self.local_confs.clear();
+                        conf_reloaded = true;
+                    }
+                    Err(e) => {
+                        self.mut_panel().set_error(format!("Configuration not reloaded: {}", e));
+                    }
+                }
+            }
+            if conf_reloaded || local_conf_changed {
+                let (conf, local_error) = self.conf_with_local(&base_conf);
+                *self.conf_files.lock().unwrap() = conf.files.clone();
+                skin = AppSkin::new(&conf);
+                con = con.reloaded(&conf);
+                if local_conf_changed {
+                    self.revert_local_flags();
+                    if !conf.local_default_flags.is_empty() {
+                        self.apply_local_flags(&conf.local_default_flags);
+                    }
+                }
+                // the display settings of the configuration replace the
+                // ones of the trees, in all tabs. The states are refreshed
+                // in place, so that the patterns and selections are kept.
+                // The panels of the other tabs are refreshed when their
+                // tab is displayed again
+                for tab in &mut self.tabs {
+                    for panel in &mut tab.panels {
+                        apply_conf_to_panel(panel, &conf);
+                    }
+                }
+                for panel in &mut self.panels {
+                    apply_conf_to_panel(panel, &conf);
+                    panel.mut_state().refresh(screen, &con);
+                }
+                let other_path = self.get_other_panel_path();
+                self.mut_panel().refresh_input_status(&other_path, &con);
+                if let Some(local_error) = local_error {
+                    self.mut_panel().set_error(local_error);
+                } else if conf_reloaded {
+                    self.mut_panel().set_message("Configuration reloaded".to_string());
+                }
+            }
             if !self.quitting {
-                self.display_panels(w, screen, &skin, con)?;
+                self.display_panels(w, screen, &skin, &con)?;
                 w.flush()?;
-                if self.do_pending_tasks(screen, con, &mut dam)? {
+                if self.do_pending_tasks(screen, &con, &mut dam)? {
                     let other_path = self.get_other_panel_path();
-                    self.mut_panel().refresh_input_status(&other_path, con);
-                    self.display_panels(w, screen, &skin, con)?;
+                    self.mut_panel().refresh_input_status(&other_path, &con);
+                    self.display_panels(w, screen, &skin, &con)?;
                     w.flush()?;
                 }
+                self.schedule_follow_tick();
             }
 
-            match dam.next(&rx_seqs) {
+            match dam.next(&self.rx_seqs) {
                 Either::First(Some(event)) => {
                     debug!("event: {:?}", &event);
                     match event {
+                        Event::Click(x, y, KeyModifiers::NONE) if Some(y) == screen.tab_bar_y() => {
+                            self.on_tab_bar_click(x, screen, &con)?;
+                        }
+                        Event::Click(x, y, KeyModifiers::CONTROL) if self.panels.len().get() > 1 => {
+                            // drags aren't reported by termimad, so a separator is
+                            // moved by a ctrl-click where it should go
+                            self.move_separator_to(x, y, screen, &con)?;
+                        }
                         Event::Click(x, y, KeyModifiers::NONE)
-                            if self.clicked_panel_index(x, y, screen) != self.active_panel_idx =>
+                            if self.clicked_panel_index(x, y) != self.active_panel_idx =>
                         {
                             // panel activation click
                             // this will be cleaner when if let will be allowed in match guards with
                             // chaining (currently experimental)
-                            self.active_panel_idx = self.clicked_panel_index(x, y, screen);
+                            self.active_panel_idx = self.clicked_panel_index(x, y);
                         }
                         Event::Resize(w, h) => {
-                            screen.set_terminal_size(w, h, con);
-                            Areas::resize_all(self.panels.as_mut_slice(), screen, self.preview.is_some())?;
+                            screen.set_terminal_size(w, h, &con);
+                            Areas::resize_all(
+                                self.panels.as_mut_slice(),
+                                screen,
+                                &self.layout,
+                                self.preview.is_some(),
+                            )?;
                             for panel in &mut self.panels {
-                                panel.mut_state().refresh(screen, con);
+                                panel.mut_state().refresh(screen, &con);
                             }
                         }
                         _ => {
                             // event handled by the panel
-                            let cmd = self.mut_panel().add_event(w, event, con)?;
+                            let cmd = self.mut_panel().add_event(w, event, &con)?;
                             debug!("command after add_event: {:?}", &cmd);
-                            self.apply_command(w, cmd, screen, &skin.focused, con)?;
+                            self.apply_command(w, cmd, screen, &skin.focused, &con)?;
                         }
                     }
                     event_source.unblock(self.quitting);
@@ -484,16 +1449,17 @@ impl App {
                 }
                 Either::Second(Some(raw_sequence)) => {
                     debug!("got sequence: {:?}", &raw_sequence);
-                    for (input, arg_cmd) in raw_sequence.parse(con)? {
+                    for (input, arg_cmd) in raw_sequence.parse(&con)? {
                         self.mut_panel().set_input_content(&input);
-                        self.apply_command(w, arg_cmd, screen, &skin.focused, con)?;
-                        self.display_panels(w, screen, &skin, con)?;
+                        self.apply_command(w, arg_cmd, screen, &skin.focused, &con)?;
+                        self.display_panels(w, screen, &skin, &con)?;
                         w.flush()?;
-                        self.do_pending_tasks(screen, con, &mut dam)?;
-                        self.display_panels(w, screen, &skin, con)?;
+                        self.do_pending_tasks(screen, &con, &mut dam)?;
+                        self.display_panels(w, screen, &skin, &con)?;
                         w.flush()?;
                         if self.quitting {
                             // is that a 100% safe way of quitting ?
+                            self.save_session();
                             return Ok(self.launch_at_end.take());
                         }
                     }
@@ -504,6 +1470,7 @@ impl App {
             }
         }
 
+        self.save_session();
         Ok(self.launch_at_end.take())
     }
 }
@@ -515,3 +1482,12 @@ fn clear_caches() {
     file_sum::clear_cache();
     git::clear_status_computer_cache();
 }
+
+/// replace the display settings of the tree of the panel, if any,
+/// with the ones of the configuration
+fn apply_conf_to_panel(panel: &mut Panel, conf: &Conf) {
+    if let Some(mut options) = panel.state().tree_options().cloned() {
+        apply_conf_to_tree_options(&mut options, conf);
+        panel.mut_state().set_tree_settings(&options);
+    }
+}
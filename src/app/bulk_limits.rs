@@ -0,0 +1,116 @@
+use {
+    crate::{
+        file_sum::FileSum,
+        task_sync::Dam,
+    },
+    std::{
+        collections::HashSet,
+        fmt,
+        path::{Path, PathBuf},
+    },
+};
+
+/// the limits over which a verb acting on the marked entries must
+/// be confirmed, so that a pattern accidentally matching the whole
+/// tree doesn't lead to a destructive bulk action
+#[derive(Debug, Clone, Copy)]
+pub struct BulkLimits {
+    pub max_count: usize,
+    pub max_size: u64, // in bytes
+}
+
+/// the number and the total size of the entries a verb is
+/// about to act on
+#[derive(Debug, Clone, Copy)]
+pub struct BulkSummary {
+    pub count: usize,
+    pub size: Option<u64>, // not computed when the count is over the limit
+}
+
+impl Default for BulkLimits {
+    fn default() -> Self {
+        Self {
+            max_count: 100,
+            max_size: 10 * 1_000_000_000,
+        }
+    }
+}
+
+fn size_of(path: &Path) -> u64 {
+    let sum = if path.is_dir() {
+        FileSum::from_dir(path, &Dam::unlimited()).unwrap_or_else(FileSum::zero)
+    } else {
+        FileSum::from_file(path)
+    };
+    sum.to_size()
+}
+
+/// the paths which aren't inside another one of the list, so that
+/// the size of nested entries isn't counted twice
+fn outer_paths(paths: &[PathBuf]) -> impl Iterator<Item = &Path> {
+    let set: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    paths
+        .iter()
+        .map(PathBuf::as_path)
+        .filter(move |path| !path.ancestors().skip(1).any(|a| set.contains(a)))
+}
+
+impl BulkLimits {
+    /// return the summary of the paths when they exceed one of
+    /// the limits, in which case the user should confirm
+    pub fn check(&self, paths: &[PathBuf]) -> Option<BulkSummary> {
+        if paths.is_empty() {
+            return None;
+        }
+        let count = paths.len();
+        if count > self.max_count {
+            // no need to compute the size, which may be long
+            return Some(BulkSummary { count, size: None });
+        }
+        let size = outer_paths(paths).map(size_of).sum();
+        if size > self.max_size {
+            Some(BulkSummary { count, size: Some(size) })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for BulkSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.size {
+            Some(size) => write!(
+                f,
+                "**{}** entries totalling **{}**",
+                self.count,
+                file_size::fit_4(size).trim(),
+            ),
+            None => write!(f, "**{}** entries", self.count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod bulk_limits_tests {
+    use super::*;
+    #[test]
+    fn check_outer_paths() {
+        let paths: Vec<PathBuf> = ["/a/b", "/a", "/a/b/c", "/ab", "/d/e"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let outer: Vec<&Path> = outer_paths(&paths).collect();
+        assert_eq!(outer, vec![Path::new("/a"), Path::new("/ab"), Path::new("/d/e")]);
+    }
+    #[test]
+    fn check_count_limit() {
+        let limits = BulkLimits { max_count: 2, max_size: u64::MAX };
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| PathBuf::from(format!("/not/a/real/path/{}", i)))
+            .collect();
+        let summary = limits.check(&paths).unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.size, None);
+        assert!(limits.check(&paths[..2]).is_none());
+    }
+}
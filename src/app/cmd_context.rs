@@ -18,4 +18,5 @@ pub struct CmdContext<'c> {
     pub areas: &'c Areas,
     pub preview: Option<PanelId>, // id of the app's preview panel
     pub panel_purpose: PanelPurpose,
+    pub panel_root: &'c Option<PathBuf>, // root of the topmost tree of the panel
 }
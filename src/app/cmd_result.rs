@@ -2,11 +2,16 @@ use {
     super::*,
     crate::{
         browser::BrowserState,
+        command::Sequence,
         errors::TreeBuildError,
         launchable::Launchable,
-        verb::Internal,
+        tree::TreeOptions,
+        verb::{Internal, VerbInvocation},
+    },
+    std::{
+        fmt,
+        path::PathBuf,
     },
-    std::fmt,
 };
 
 /// Either left or right
@@ -21,11 +26,20 @@ pub enum AppStateCmdResult {
     ApplyOnPanel {
         id: PanelId,
     },
+    BackTo(Option<usize>), // the position of the state to go back to, None to list them
+    BroadcastTreeOptions {
+        state: Option<Box<dyn AppState>>, // the new state of the current panel, if any
+        options: Box<TreeOptions>, // the options to apply to the trees of the other panels
+    },
     ClosePanel {
         validate_purpose: bool,
         id: Option<PanelId>, // None if current panel
     },
     DisplayError(String),
+    DisplayMessage(String),
+    ExecuteSequence {
+        sequence: Sequence,
+    },
     HandleInApp(Internal), // command must be handled at the app level
     Keep,
     Launch(Box<Launchable>),
@@ -35,12 +49,24 @@ pub enum AppStateCmdResult {
         direction: HDir,
     },
     NewState(Box<dyn AppState>),
+    NewStateInPanel {
+        state: Box<dyn AppState>, // to push on the adjacent panel, created if needed
+        direction: HDir,
+    },
     PopStateAndReapply, // the state asks the command be executed on a previous state
+    PopStateAndRefresh {
+        clear_cache: bool,
+    },
     PopState,
     Quit,
     RefreshState {
         clear_cache: bool,
     },
+    RefreshPaths {
+        paths: Vec<PathBuf>, // paths which may have been changed, created or removed
+    },
+    ResizePanel(i16), // the number of cells to add to the current panel
+    SetInputInvocation(VerbInvocation), // the verb invocation of the input must be replaced
 }
 
 impl AppStateCmdResult {
@@ -82,6 +108,8 @@ impl fmt::Debug for AppStateCmdResult {
             "{}",
             match self {
                 AppStateCmdResult::ApplyOnPanel { .. } => "ApplyOnPanel",
+                AppStateCmdResult::BackTo(_) => "BackTo",
+                AppStateCmdResult::BroadcastTreeOptions { .. } => "BroadcastTreeOptions",
                 AppStateCmdResult::ClosePanel {
                     validate_purpose: false, ..
                 } => "CancelPanel",
@@ -89,15 +117,22 @@ impl fmt::Debug for AppStateCmdResult {
                     validate_purpose: true, ..
                 } => "OkPanel",
                 AppStateCmdResult::DisplayError(_) => "DisplayError",
+                AppStateCmdResult::DisplayMessage(_) => "DisplayMessage",
+                AppStateCmdResult::ExecuteSequence { .. } => "ExecuteSequence",
                 AppStateCmdResult::Keep => "Keep",
                 AppStateCmdResult::Launch(_) => "Launch",
                 AppStateCmdResult::NewState { .. } => "NewState",
                 AppStateCmdResult::NewPanel { .. } => "NewPanel",
+                AppStateCmdResult::NewStateInPanel { .. } => "NewStateInPanel",
                 AppStateCmdResult::PopStateAndReapply => "PopStateAndReapply",
+                AppStateCmdResult::PopStateAndRefresh { .. } => "PopStateAndRefresh",
                 AppStateCmdResult::PopState => "PopState",
                 AppStateCmdResult::HandleInApp(_) => "HandleInApp",
                 AppStateCmdResult::Quit => "Quit",
                 AppStateCmdResult::RefreshState { .. } => "RefreshState",
+                AppStateCmdResult::RefreshPaths { .. } => "RefreshPaths",
+                AppStateCmdResult::ResizePanel(_) => "ResizePanel",
+                AppStateCmdResult::SetInputInvocation(_) => "SetInputInvocation",
             }
         )
     }
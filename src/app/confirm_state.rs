@@ -0,0 +1,234 @@
+use {
+    super::*,
+    crate::{
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        mover,
+        skin::PanelSkin,
+        verb::*,
+    },
+    std::path::{Path, PathBuf},
+    termimad::{Area, FmtText, TextView},
+};
+
+/// what's done once the user confirms
+enum ConfirmedAction {
+    External(ExternalExecution),
+    /// the deletion, without going through the trash, of the
+    /// marked entries or of the selection when none is marked
+    Removal,
+}
+
+/// a modal state asking the user to confirm the execution of
+/// a verb before it's done, either because the verb is configured
+/// so, or because it acts on too many marked entries, or before
+/// a permanent deletion. Typing *y* executes the verb while any
+/// other key cancels it.
+pub struct ConfirmState {
+    action: ConfirmedAction,
+    path: PathBuf,
+    line: LineNumber,
+    stype: SelectionType,
+    marked: Vec<PathBuf>,
+    other_path: Option<PathBuf>,
+    args: Option<String>,
+    bulk: Option<BulkSummary>, // set when the marked entries exceed the limits
+}
+
+impl ConfirmState {
+    pub fn new(
+        external: ExternalExecution,
+        sel: Selection<'_>,
+        other_path: &Option<PathBuf>,
+        args: &Option<String>,
+        bulk: Option<BulkSummary>,
+    ) -> Self {
+        Self {
+            action: ConfirmedAction::External(external),
+            path: sel.path.to_path_buf(),
+            line: sel.line,
+            stype: sel.stype,
+            marked: sel.marked.to_vec(),
+            other_path: other_path.clone(),
+            args: args.clone(),
+            bulk,
+        }
+    }
+
+    /// return the state asking for the confirmation of the permanent
+    /// deletion of the marked entries, or of the selection
+    pub fn removal(sel: Selection<'_>, bulk: Option<BulkSummary>) -> Self {
+        Self {
+            action: ConfirmedAction::Removal,
+            path: sel.path.to_path_buf(),
+            line: sel.line,
+            stype: sel.stype,
+            marked: sel.marked.to_vec(),
+            other_path: None,
+            args: None,
+            bulk,
+        }
+    }
+
+    /// the paths to delete on a removal
+    fn removed_paths(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            vec![self.path.clone()]
+        } else {
+            self.marked.clone()
+        }
+    }
+
+    fn remove(&self) -> AppStateCmdResult {
+        for path in self.removed_paths() {
+            if let Err(e) = mover::remove_path(&path) {
+                return AppStateCmdResult::DisplayError(
+                    format!("{} not deleted: {}", path.to_string_lossy(), e),
+                );
+            }
+        }
+        AppStateCmdResult::PopStateAndRefresh { clear_cache: true }
+    }
+
+    fn exec_string(&self, external: &ExternalExecution) -> String {
+        external.shell_exec_string(self.selection(), &self.other_path, &self.args)
+    }
+
+    fn markdown(&self) -> String {
+        match &self.action {
+            ConfirmedAction::External(external) => {
+                let intro = match &self.bulk {
+                    Some(bulk) => format!("**{}** will act on {} and execute", external.name(), bulk),
+                    None => format!("**{}** will execute", external.name()),
+                };
+                format!(
+                    "\n{}\n\n`{}`\n\nType *y* to confirm, any other key to cancel.",
+                    intro,
+                    self.exec_string(external),
+                )
+            }
+            ConfirmedAction::Removal => {
+                let mut md = match &self.bulk {
+                    Some(bulk) => format!("\n{} will be deleted:\n\n", bulk),
+                    None => "\nThose entries will be deleted:\n\n".to_string(),
+                };
+                for path in self.removed_paths() {
+                    md.push_str(&format!("* `{}`\n", path.to_string_lossy()));
+                }
+                md.push_str(
+                    "\nThey don't go to the trash: **this can't be undone**.\n\n\
+                    Type *y* to confirm, any other key to cancel."
+                );
+                md
+            }
+        }
+    }
+}
+
+impl AppState for ConfirmState {
+
+    fn selected_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.path,
+            line: self.line,
+            stype: self.stype,
+            marked: &self.marked,
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match cc.cmd {
+            Command::PatternEdit { raw, .. } if raw == "y" || raw == "Y" => {
+                let external = match &self.action {
+                    ConfirmedAction::External(external) => external,
+                    ConfirmedAction::Removal => return Ok(self.remove()),
+                };
+                match external.to_cmd_result(
+                    w,
+                    self.selection(),
+                    &self.other_path,
+                    &self.args,
+                    cc.panel_root.as_deref(),
+                    cc.con,
+                )? {
+                    AppStateCmdResult::RefreshState { clear_cache } => {
+                        AppStateCmdResult::PopStateAndRefresh { clear_cache }
+                    }
+                    AppStateCmdResult::RefreshPaths { .. } => {
+                        AppStateCmdResult::PopStateAndRefresh { clear_cache: true }
+                    }
+                    AppStateCmdResult::DisplayMessage(_) => {
+                        AppStateCmdResult::PopStateAndRefresh { clear_cache: false }
+                    }
+                    result => result,
+                }
+            }
+            _ => AppStateCmdResult::PopState, // any other key cancels
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        _internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(AppStateCmdResult::PopState)
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.action {
+            ConfirmedAction::External(external) => format!(
+                "Execute `{}` ? *y*/*N*",
+                self.exec_string(external),
+            ),
+            ConfirmedAction::Removal => format!(
+                "Delete {} entries permanently ? *y*/*N*",
+                self.removed_paths().len(),
+            ),
+        })
+    }
+}
@@ -1,14 +1,18 @@
 use {
     super::*,
     crate::{
-        cli::AppLaunchArgs,
+        cli::{apply_conf_to_tree_options, AppLaunchArgs},
         conf::Conf,
-        display::{Cols, DEFAULT_COLS},
+        display::{Cols, PanelsLayout, DEFAULT_COLS},
+        handlers::HandlerMap,
+        image::{kitty, sixel},
+        list::ListConf,
         pattern::SearchModeMap,
         skin::ExtColorMap,
-        tree::SpecialPath,
+        tree::{PageSelection, SpecialPath},
         verb::VerbStore,
     },
+    std::collections::HashMap,
 };
 
 /// The immutable container that can be passed around
@@ -35,6 +39,43 @@ pub struct AppContext {
 
     pub show_selection_mark: bool,
 
+    /// whether text previews show line numbers (when None, they're
+    /// shown only if the preview is wide enough or filtered)
+    pub show_line_numbers: Option<bool>,
+
+    /// how many bytes of a file are read for a text preview,
+    /// before the user asks for more
+    pub max_preview_size: u64,
+
+    /// whether the lines which appeared or changed size are marked
+    /// for a few seconds after a refresh
+    pub show_refresh_deltas: bool,
+
+    /// how the selection moves when the tree is scrolled by a page
+    pub page_selection: PageSelection,
+
+    /// the direction of the panels and their minimal sizes
+    pub panels_layout: PanelsLayout,
+
+    /// whether broot starts with two tree panels, and keeps them
+    pub commander: bool,
+
+    /// whether a change of the tree options in a panel, like
+    /// showing hidden files, is applied to all panels
+    pub broadcast_tree_options: bool,
+
+    /// whether the help and the panels opened to edit an argument
+    /// float over the other panels instead of splitting the screen
+    pub floating_panels: bool,
+
+    /// the number of marked entries, or their total size, over
+    /// which a verb must be confirmed
+    pub bulk_limits: BulkLimits,
+
+    /// how many times the content of the files is overwritten
+    /// by :rm secure before they're deleted
+    pub secure_delete_passes: usize,
+
     /// mapping from file extension to colors (comes from conf)
     pub ext_colors: ExtColorMap,
 
@@ -47,6 +88,32 @@ pub struct AppContext {
 
     /// whether we can use 24 bits colors for previewed images
     pub true_colors: bool,
+
+    /// whether images are displayed with the kitty graphics protocol
+    pub kitty_graphics: bool,
+
+    /// whether images are displayed as sixel graphics, when the
+    /// kitty protocol isn't available
+    pub sixel_graphics: bool,
+
+    /// the uid files are expected to be owned by. When None, the
+    /// owner of the parent directory is expected
+    pub expected_owner_uid: Option<u32>,
+
+    /// the lists defined in configuration, opened with `:open_list`
+    pub lists: Vec<ListConf>,
+
+    /// the named sets of flags defined in configuration, applied
+    /// to the tree with `:profile`
+    pub profiles: HashMap<String, String>,
+
+    /// the chains of handlers used to preview and open files,
+    /// by extension, limited to the ones available on the machine
+    pub handlers: HandlerMap,
+
+    /// a message to display in the status line at launch, instead
+    /// of writing it on stdout
+    pub startup_message: Option<String>,
 }
 
 impl AppContext {
@@ -62,6 +129,57 @@ impl AppContext {
         } else {
             are_true_colors_available()
         };
+        let kitty_graphics = if let Some(value) = config.kitty_graphics {
+            value
+        } else {
+            kitty::is_kitty_graphics_protocol_supported()
+        };
+        let sixel_graphics = if kitty_graphics {
+            false
+        } else if let Some(value) = config.sixel_graphics {
+            value
+        } else {
+            sixel::is_sixel_supported()
+        };
+        #[cfg(unix)]
+        let expected_owner_uid = config.expected_owner
+            .as_ref()
+            .and_then(|name| {
+                let uid = crate::permissions::user_id(name);
+                if uid.is_none() {
+                    warn!("unknown expected owner: {:?}", name);
+                }
+                uid
+            });
+        #[cfg(not(unix))]
+        let expected_owner_uid = None;
+        let mut handlers = config.handlers.clone();
+        handlers.keep_available();
+        let mut panels_layout = PanelsLayout::default();
+        if let Some(direction) = config.panels_direction {
+            panels_layout.direction = direction;
+        }
+        if let Some(width) = config.min_panel_width {
+            panels_layout.min_panel_width = width;
+        }
+        if let Some(height) = config.min_panel_height {
+            panels_layout.min_panel_height = height;
+        }
+        if let Some(titles) = config.panel_titles {
+            panels_layout.titles = titles;
+        }
+        let default_bulk_limits = BulkLimits::default();
+        let bulk_limits = BulkLimits {
+            max_count: config.bulk_confirm_count
+                .map_or(default_bulk_limits.max_count, usize::from),
+            max_size: config.bulk_confirm_gigabytes
+                .map_or(default_bulk_limits.max_size, |gb| u64::from(gb) * 1_000_000_000),
+        };
+        let startup_message = if launch_args.quiet || config.startup_messages.is_empty() {
+            None
+        } else {
+            Some(config.startup_messages.join(" "))
+        };
         Self {
             config_path,
             launch_args,
@@ -70,12 +188,40 @@ impl AppContext {
             search_modes: config.search_modes.clone(),
             cols: config.cols_order.unwrap_or(DEFAULT_COLS),
             show_selection_mark: config.show_selection_mark.unwrap_or(false),
+            show_line_numbers: config.show_line_numbers,
+            max_preview_size: u64::from(config.max_preview_size.unwrap_or(10)) * 1_000_000,
+            show_refresh_deltas: config.show_refresh_deltas.unwrap_or(false),
+            page_selection: config.page_selection.unwrap_or_default(),
+            panels_layout,
+            commander: config.commander.unwrap_or(false),
+            broadcast_tree_options: config.broadcast_tree_options.unwrap_or(false),
+            floating_panels: config.floating_panels.unwrap_or(false),
+            bulk_limits,
+            secure_delete_passes: config.secure_delete_passes.map_or(3, usize::from),
             ext_colors: config.ext_colors.clone(),
             syntax_theme: config.syntax_theme.clone(),
             standard_status,
             true_colors,
+            kitty_graphics,
+            sixel_graphics,
+            expected_owner_uid,
+            lists: config.lists.clone(),
+            profiles: config.profiles.clone(),
+            handlers,
+            startup_message,
         }
     }
+    /// build the context applying a configuration which was read
+    /// again, keeping the launch arguments
+    pub fn reloaded(self, config: &Conf) -> Self {
+        let mut launch_args = self.launch_args;
+        apply_conf_to_tree_options(&mut launch_args.tree_options, config);
+        let mut verb_store = VerbStore::default();
+        verb_store.init(config);
+        let mut con = Self::from(launch_args, verb_store, config);
+        con.startup_message = None;
+        con
+    }
 }
 
 /// try to determine whether the terminal supports true
@@ -1,11 +1,14 @@
 mod app;
+mod bulk_limits;
 mod cmd_context;
 mod cmd_result;
+mod confirm_state;
 mod context;
 mod panel;
 mod panel_id;
 mod panel_purpose;
 mod selection;
+mod session;
 mod state;
 mod state_type;
 mod status;
@@ -13,13 +16,16 @@ mod standard_status;
 
 pub use {
     app::App,
+    bulk_limits::{BulkLimits, BulkSummary},
     cmd_result::*,
     cmd_context::CmdContext,
+    confirm_state::ConfirmState,
     context::AppContext,
     panel::Panel,
     panel_id::PanelId,
     panel_purpose::PanelPurpose,
     selection::{LineNumber, Selection, SelectionType},
+    session::{PanelSession, Session},
     state::AppState,
     state_type::AppStateType,
     status::Status,
@@ -17,7 +17,7 @@ use {
         verb::*,
     },
     minimad::{Alignment, Composite},
-    std::path::PathBuf,
+    std::path::{Path, PathBuf},
     termimad::Event,
 };
 
@@ -27,6 +27,7 @@ pub struct Panel {
     pub areas: Areas,
     status: Status,
     pub purpose: PanelPurpose,
+    pub floating_over: Option<PanelId>, // when floating, the panel which gets the focus back
     input: PanelInput,
 }
 
@@ -47,6 +48,7 @@ impl Panel {
             areas,
             status,
             purpose: PanelPurpose::None,
+            floating_over: None,
             input,
         }
     }
@@ -55,6 +57,10 @@ impl Panel {
         self.status = Status::from_error(text);
     }
 
+    pub fn set_message(&mut self, text: String) {
+        self.status = Status::from_message(text);
+    }
+
     pub fn apply_command(
         &mut self,
         w: &mut W,
@@ -66,6 +72,7 @@ impl Panel {
         con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
         let state_idx = self.states.len()-1;
+        let panel_root = self.root();
         let cc = CmdContext {
             cmd,
             other_path,
@@ -74,6 +81,7 @@ impl Panel {
             areas: &self.areas,
             panel_purpose: self.purpose,
             preview,
+            panel_root: &panel_root,
         };
         let result = self.states[state_idx].on_command(w, &cc, screen);
         let has_previous_state = self.states.len() > 1;
@@ -82,6 +90,15 @@ impl Panel {
         result
     }
 
+    /// return the root of the tree of the last pushed state having
+    /// one (a preview or a help state has none), if any
+    pub fn root(&self) -> Option<PathBuf> {
+        self.states.iter()
+            .rev()
+            .find_map(|state| state.tree_root())
+            .map(Path::to_path_buf)
+    }
+
     /// called on focusing the panel and before the display,
     /// this updates the status from the command read in the input
     pub fn refresh_input_status(
@@ -118,8 +135,10 @@ impl Panel {
         event: Event,
         con: &AppContext,
     ) -> Result<Command, ProgramError> {
-        let sel = self.states[self.states.len()-1].selection();
-        self.input.on_event(w, event, con, sel)
+        // the state and the input are distinct fields, so that the
+        // selection can be borrowed while the input is changed
+        let state = self.states.last().unwrap();
+        self.input.on_event(w, event, con, state.selection(), state.get_type())
     }
 
     pub fn push_state(&mut self, new_state: Box<dyn AppState>) {
@@ -147,6 +166,14 @@ impl Panel {
         }
     }
 
+    /// replace the verb invocation of the input, keeping the filter
+    pub fn set_input_invocation(&mut self, invocation: VerbInvocation) {
+        let mut command_parts = CommandParts::from(self.input.get_content());
+        command_parts.verb_invocation = Some(invocation);
+        let new_input = format!("{}", command_parts);
+        self.input.set_content(&new_input);
+    }
+
     pub fn set_input_content(&mut self, content: &str) {
         self.input.set_content(content);
     }
@@ -164,6 +191,28 @@ impl Panel {
         }
     }
 
+    /// describe the states of the panel, from the first one to
+    /// the current one, with the positions to give to `:back_to`
+    pub fn states_breadcrumb(&self) -> String {
+        self.states.iter()
+            .enumerate()
+            .map(|(idx, state)| format!("**{}** {}", idx + 1, state.label()))
+            .collect::<Vec<String>>()
+            .join(" > ")
+    }
+
+    /// remove the states above the one at the given position
+    /// (starting at 1), return false if there's no such state
+    /// or if it's already the current one
+    pub fn back_to(&mut self, pos: usize) -> bool {
+        if pos == 0 || pos >= self.states.len() {
+            return false;
+        }
+        self.states.truncate(pos);
+        self.input.set_content(&self.state().get_starting_input());
+        true
+    }
+
     /// return true when the element has been removed
     pub fn remove_state(&mut self) -> bool {
         if self.states.len() > 1 {
@@ -185,6 +234,7 @@ impl Panel {
     ) -> Result<(), ProgramError> {
         let state_area = self.areas.state.clone();
         self.mut_state().display(w, screen, state_area, panel_skin, con)?;
+        self.write_title(w, panel_skin, screen)?;
         if active || !WIDE_STATUS {
             self.write_status(w, panel_skin, screen)?;
         }
@@ -204,6 +254,50 @@ impl Panel {
         Ok(())
     }
 
+    /// write the title bar, if the panel has one: the root on the
+    /// left, the sort and the flags on the right
+    fn write_title(
+        &self,
+        w: &mut W,
+        panel_skin: &PanelSkin,
+        screen: &Screen,
+    ) -> Result<(), ProgramError> {
+        let area = match &self.areas.title {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+        let state = self.state();
+        let root = self.root()
+            .map_or_else(|| state.label(), |root| root.to_string_lossy().to_string());
+        let mut right = String::new();
+        if let Some(options) = state.tree_options() {
+            if options.sort.is_some() {
+                right.push_str(&format!(" sort:{}", options.sort.name()));
+            }
+        }
+        for flag in state.get_flags() {
+            right.push_str(&format!(" {}:{}", flag.name, flag.value));
+        }
+        right.push(' ');
+        let width = area.width as usize;
+        if right.chars().count() >= width {
+            right.clear();
+        }
+        let left_width = width - right.chars().count();
+        let mut left = format!(" {}", root);
+        let left_len = left.chars().count();
+        if left_len > left_width {
+            // the end of the path is the most significant part
+            left = std::iter::once('…')
+                .chain(left.chars().skip(left_len + 1 - left_width))
+                .collect();
+        }
+        let padding = " ".repeat(left_width - left.chars().count());
+        screen.goto(w, area.left, area.top)?;
+        panel_skin.styles.panel_title.queue_str(w, &format!("{}{}{}", left, padding, right))?;
+        Ok(())
+    }
+
     fn write_status(
         &self,
         w: &mut W,
@@ -211,7 +305,16 @@ impl Panel {
         screen: &Screen,
     ) -> Result<(), ProgramError> {
         let task = self.state().get_pending_task();
-        status_line::write(w, task, &self.status, &self.areas.status, panel_skin, screen)
+        let has_invocation = CommandParts::from(self.input.get_content())
+            .verb_invocation
+            .is_some();
+        let echo = if self.status.error || has_invocation {
+            None
+        } else {
+            self.state().echo().map(Status::from_message)
+        };
+        let status = echo.as_ref().unwrap_or(&self.status);
+        status_line::write(w, task, status, &self.areas.status, panel_skin, screen)
     }
 
     fn write_purpose(
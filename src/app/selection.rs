@@ -1,6 +1,6 @@
 
 use {
-    std::path::Path,
+    std::path::{Path, PathBuf},
 };
 
 /// the id of a line, starting at 1
@@ -15,6 +15,19 @@ pub struct Selection<'s> {
     pub path: &'s Path,
     pub line: LineNumber, // the line number in the file (0 if none selected)
     pub stype: SelectionType,
+    pub marked: &'s [PathBuf], // the paths marked by the user, usually empty
+}
+
+impl<'s> Selection<'s> {
+    /// build a selection for one of the marked paths
+    pub fn of_marked(path: &'s Path) -> Self {
+        Self {
+            path,
+            line: 0,
+            stype: SelectionType::of(path),
+            marked: &[],
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -25,6 +38,13 @@ pub enum SelectionType {
 }
 
 impl SelectionType {
+    pub fn of(path: &Path) -> Self {
+        if path.is_dir() {
+            Self::Directory
+        } else {
+            Self::File
+        }
+    }
     pub fn respects(self, constraint: Self) -> bool {
         constraint == Self::Any || self == constraint
     }
@@ -0,0 +1,185 @@
+use {
+    crate::{
+        conf,
+        errors::ConfError,
+        tree::TreeOptions,
+    },
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+    toml::{self, Value},
+};
+
+/// what's needed to reopen a tree panel
+#[derive(Debug, Clone)]
+pub struct PanelSession {
+    pub root: PathBuf,
+    pub options_args: Vec<String>, // the tree options, as launch arguments
+}
+
+/// the tree panels which were open when broot was quitted, and
+/// their sizes, so that the same layout can be reopened
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    pub panels: Vec<PanelSession>,
+    pub sizes: Vec<u16>,
+}
+
+impl PanelSession {
+    pub fn new(root: PathBuf, options: &TreeOptions) -> Self {
+        let options_args = options
+            .to_launch_args()
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        Self { root, options_args }
+    }
+
+    /// build the tree options of the panel, starting from the
+    /// base ones (which hold the settings not saved in session)
+    pub fn tree_options(&self, base: &TreeOptions) -> TreeOptions {
+        let mut options = base.clone();
+        let clap_app = crate::clap::clap_app().setting(clap::AppSettings::NoBinaryName);
+        match clap_app.get_matches_from_safe(&self.options_args) {
+            Ok(matches) => options.apply(&matches),
+            Err(e) => warn!("invalid options in session: {:?}", e),
+        }
+        options
+    }
+}
+
+impl Session {
+    /// the path to the file where the session is kept
+    pub fn path() -> PathBuf {
+        conf::dir().join("session.toml")
+    }
+
+    /// read the session saved on last quit, if any. Panels
+    /// whose root doesn't exist anymore are dropped
+    pub fn load() -> Result<Option<Self>, ConfError> {
+        Self::load_from(&Self::path())
+    }
+
+    /// read the session from the given file, if it exists
+    pub fn load_from(path: &Path) -> Result<Option<Self>, ConfError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let root: Value = fs::read_to_string(path)?.parse()?;
+        let mut session = Session::default();
+        if let Some(Value::Array(panels)) = root.get("panels") {
+            for panel in panels {
+                let root = match panel.get("root").and_then(Value::as_str) {
+                    Some(root) => PathBuf::from(root),
+                    None => continue,
+                };
+                if !root.is_dir() {
+                    continue;
+                }
+                let options_args = match panel.get("options") {
+                    Some(Value::Array(args)) => args
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                session.panels.push(PanelSession { root, options_args });
+            }
+        }
+        if let Some(Value::Array(sizes)) = root.get("sizes") {
+            session.sizes = sizes
+                .iter()
+                .filter_map(Value::as_integer)
+                .map(|size| size as u16)
+                .collect();
+        }
+        if session.sizes.len() != session.panels.len() {
+            // some panels were dropped, their sizes can't be used
+            session.sizes.clear();
+        }
+        Ok(if session.panels.is_empty() {
+            None
+        } else {
+            Some(session)
+        })
+    }
+
+    /// write the session in its file, replacing the previous one
+    pub fn save(&self) -> Result<(), ConfError> {
+        fs::create_dir_all(conf::dir())?;
+        self.save_to(&Self::path())
+    }
+
+    /// write the session in the given file
+    pub fn save_to(&self, path: &Path) -> Result<(), ConfError> {
+        let panels = self.panels
+            .iter()
+            .map(|panel| {
+                let mut table = toml::value::Table::new();
+                table.insert(
+                    "root".to_string(),
+                    Value::String(panel.root.to_string_lossy().to_string()),
+                );
+                table.insert(
+                    "options".to_string(),
+                    Value::Array(panel.options_args.iter().cloned().map(Value::String).collect()),
+                );
+                Value::Table(table)
+            })
+            .collect();
+        let mut root = toml::value::Table::new();
+        root.insert(
+            "sizes".to_string(),
+            Value::Array(self.sizes.iter().map(|&size| Value::Integer(size as i64)).collect()),
+        );
+        root.insert("panels".to_string(), Value::Array(panels));
+        fs::write(path, Value::Table(root).to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+
+    use {
+        super::*,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn check_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let base = TreeOptions::default();
+        let mut options = base.clone();
+        options.show_hidden = true;
+        options.show_sizes = true;
+        let session = Session {
+            panels: vec![
+                PanelSession::new(dir.path().to_path_buf(), &options),
+                PanelSession::new(dir.path().join("missing"), &base),
+            ],
+            sizes: vec![40, 60],
+        };
+        let path = dir.path().join("session.toml");
+        session.save_to(&path).unwrap();
+        let loaded = Session::load_from(&path).unwrap().unwrap();
+        // the panel whose root doesn't exist anymore is dropped, with the sizes
+        assert_eq!(loaded.panels.len(), 1);
+        assert_eq!(loaded.panels[0].root, dir.path());
+        assert!(loaded.sizes.is_empty());
+        let loaded_options = loaded.panels[0].tree_options(&base);
+        assert!(loaded_options.show_hidden);
+        assert!(loaded_options.show_sizes);
+        assert!(!loaded_options.show_dates);
+        // the sizes are kept when all panels are restored
+        let session = Session {
+            panels: vec![loaded.panels[0].clone(), loaded.panels[0].clone()],
+            sizes: vec![40, 60],
+        };
+        session.save_to(&path).unwrap();
+        assert_eq!(Session::load_from(&path).unwrap().unwrap().sizes, vec![40, 60]);
+        assert!(Session::load_from(&dir.path().join("none.toml")).unwrap().is_none());
+    }
+}
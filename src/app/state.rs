@@ -6,17 +6,27 @@ use {
         errors::ProgramError,
         flag::Flag,
         help::HelpState,
+        jobs::JobsState,
+        path,
         pattern::*,
         preview::{PreviewMode, PreviewState},
         print,
         skin::PanelSkin,
         task_sync::Dam,
+        trash_can::{EmptyTrashState, TrashState},
+        tree::TreeOptions,
         verb::*,
     },
-    std::path::{Path, PathBuf},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
     termimad::Area,
 };
 
+/// max size of a file whose content can be copied to the clipboard
+const MAX_COPIED_CONTENT_SIZE: u64 = 1024 * 1024;
+
 /// a whole application state, stackable to allow reverting
 ///  to a previous one
 pub trait AppState {
@@ -35,6 +45,7 @@ pub trait AppState {
 
     fn on_double_click(
         &mut self,
+        _w: &mut W,
         _x: u16,
         _y: u16,
         _screen: &mut Screen,
@@ -81,6 +92,18 @@ pub trait AppState {
         let con = &cc.con;
         Ok(match internal_exec.internal {
             Internal::back => AppStateCmdResult::PopState,
+            Internal::back_to => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg.map(|arg| arg.trim().parse::<usize>()) {
+                    None => AppStateCmdResult::BackTo(None),
+                    Some(Ok(pos)) if pos > 0 => AppStateCmdResult::BackTo(Some(pos)),
+                    _ => AppStateCmdResult::DisplayError(
+                        "a state position is expected, eg `:back_to 2`".to_string()
+                    ),
+                }
+            }
             Internal::copy_path => {
                 cli_clipboard::set_contents( self.selected_path().to_string_lossy().into_owned() )
 					.map_err( |_| ProgramError::ClipboardError )?
@@ -88,6 +111,7 @@ pub trait AppState {
 
 				AppStateCmdResult::Keep
             }
+            Internal::copy_content => copy_content(self.selected_path())?,
             Internal::close_panel_ok => AppStateCmdResult::ClosePanel {
                 validate_purpose: true,
                 id: None,
@@ -102,14 +126,53 @@ pub trait AppState {
                     .unwrap_or(internal_exec.bang);
                 if bang && cc.preview.is_none() {
                     AppStateCmdResult::NewPanel {
-                        state: Box::new(HelpState::new(screen, con)),
+                        state: Box::new(HelpState::new(screen, con, self.selection())),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    }
+                } else {
+                    AppStateCmdResult::NewState(Box::new(HelpState::new(screen, con, self.selection())))
+                }
+            }
+            Internal::jobs => {
+                let bang = input_invocation
+                    .map(|inv| inv.bang)
+                    .unwrap_or(internal_exec.bang);
+                let state = JobsState::new(path::closest_dir(self.selected_path()));
+                if bang && cc.preview.is_none() {
+                    AppStateCmdResult::NewPanel {
+                        state: Box::new(state),
                         purpose: PanelPurpose::None,
                         direction: HDir::Right,
                     }
                 } else {
-                    AppStateCmdResult::NewState(Box::new(HelpState::new(screen, con)))
+                    AppStateCmdResult::NewState(Box::new(state))
                 }
             }
+            Internal::trash => {
+                let bang = input_invocation
+                    .map(|inv| inv.bang)
+                    .unwrap_or(internal_exec.bang);
+                let date_time_format = self.tree_options()
+                    .map_or(TreeOptions::default().date_time_format, |options| options.date_time_format);
+                match TrashState::new(date_time_format) {
+                    Ok(state) if bang && cc.preview.is_none() => AppStateCmdResult::NewPanel {
+                        state: Box::new(state),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    },
+                    Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+                    Err(e) => AppStateCmdResult::DisplayError(format!("can't read the trash: {}", e)),
+                }
+            }
+            Internal::restore => AppStateCmdResult::DisplayError(
+                "items can only be restored from the trash, open it with `:trash`".to_string()
+            ),
+            Internal::empty_trash => match EmptyTrashState::new() {
+                Ok(Some(state)) => AppStateCmdResult::NewState(Box::new(state)),
+                Ok(None) => AppStateCmdResult::DisplayMessage("the trash is already empty".to_string()),
+                Err(e) => AppStateCmdResult::DisplayError(format!("can't read the trash: {}", e)),
+            },
             Internal::open_preview => self.open_preview(None, false, cc),
             Internal::preview_image => self.open_preview(Some(PreviewMode::Image), false, cc),
             Internal::preview_text => self.open_preview(Some(PreviewMode::Text), false, cc),
@@ -141,6 +204,32 @@ pub trait AppState {
                     AppStateCmdResult::HandleInApp(Internal::panel_right)
                 }
             }
+            Internal::next_panel => {
+                if cc.areas.nb_pos > 1 {
+                    AppStateCmdResult::HandleInApp(Internal::next_panel)
+                } else {
+                    AppStateCmdResult::Keep
+                }
+            }
+            Internal::resize_panel => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg.map(|arg| arg.trim().parse::<i16>()) {
+                    Some(Ok(_)) if cc.areas.nb_pos < 2 => AppStateCmdResult::Keep,
+                    Some(Ok(delta)) => AppStateCmdResult::ResizePanel(delta),
+                    _ => AppStateCmdResult::DisplayError(
+                        "a number of cells is expected, eg `:resize_panel +5`".to_string()
+                    ),
+                }
+            }
+            Internal::tab_close | Internal::tab_new | Internal::tab_next => {
+                AppStateCmdResult::HandleInApp(internal_exec.internal)
+            }
+            Internal::toggle_link_panels => AppStateCmdResult::HandleInApp(Internal::toggle_link_panels),
+            Internal::broadcast_pattern | Internal::toggle_pattern_broadcast => {
+                AppStateCmdResult::HandleInApp(internal_exec.internal)
+            }
             Internal::print_path => {
                 print::print_path(self.selected_path(), con)?
             }
@@ -148,11 +237,61 @@ pub trait AppState {
                 print::print_relative_path(self.selected_path(), con)?
             }
             Internal::refresh => AppStateCmdResult::RefreshState { clear_cache: true },
+            Internal::reload_config => AppStateCmdResult::HandleInApp(Internal::reload_config),
             Internal::quit => AppStateCmdResult::Quit,
             _ => AppStateCmdResult::Keep,
         })
     }
 
+    /// execute the external verb, or first ask for a confirmation
+    /// if the verb requires one or if it acts on too many marked
+    /// entries
+    fn on_external(
+        &mut self,
+        w: &mut W,
+        verb: &Verb,
+        external: &ExternalExecution,
+        args: &Option<String>,
+        cc: &CmdContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let bulk = cc.con.bulk_limits.check(self.selection().marked);
+        if verb.needs_confirmation || bulk.is_some() {
+            Ok(AppStateCmdResult::NewState(Box::new(ConfirmState::new(
+                external.clone(),
+                self.selection(),
+                cc.other_path,
+                args,
+                bulk,
+            ))))
+        } else {
+            external.to_cmd_result(
+                w,
+                self.selection(),
+                cc.other_path,
+                args,
+                cc.panel_root.as_deref(),
+                cc.con,
+            )
+        }
+    }
+
+    /// build the commands of a sequence verb, to be executed
+    /// in order by the application
+    fn on_sequence(
+        &mut self,
+        seq_exec: &SequenceExecution,
+        args: &Option<String>,
+        cc: &CmdContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let sequence = seq_exec.to_sequence(self.selection(), cc.other_path, args);
+        // we check all the commands now so that a sequence isn't
+        // stopped in the middle
+        if let Err(e) = sequence.parse(cc.con) {
+            return Ok(AppStateCmdResult::DisplayError(e.to_string()));
+        }
+        Ok(AppStateCmdResult::ExecuteSequence { sequence })
+    }
+
     /// change the state, does no rendering
     fn on_command(
         &mut self,
@@ -164,7 +303,7 @@ pub trait AppState {
         let con = &cc.con;
         match cc.cmd {
             Command::Click(x, y) => self.on_click(*x, *y, screen, con),
-            Command::DoubleClick(x, y) => self.on_double_click(*x, *y, screen, con),
+            Command::DoubleClick(x, y) => self.on_double_click(w, *x, *y, screen, con),
             Command::PatternEdit { raw, expr } => {
                 match InputPattern::new(raw.clone(), expr, &cc.con) {
                     Ok(pattern) => self.on_pattern(pattern, con),
@@ -185,16 +324,25 @@ pub trait AppState {
                         cc,
                         screen,
                     ),
-                    VerbExecution::External(external) => external.to_cmd_result(
+                    VerbExecution::External(external) => self.on_external(
                         w,
-                        self.selection(),
-                        &cc.other_path,
+                        verb,
+                        external,
                         if let Some(inv) = &input_invocation {
                             &inv.args
                         } else {
                             &None
                         },
-                        con,
+                        cc,
+                    ),
+                    VerbExecution::Sequence(seq_exec) => self.on_sequence(
+                        seq_exec,
+                        if let Some(inv) = &input_invocation {
+                            &inv.args
+                        } else {
+                            &None
+                        },
+                        cc,
                     ),
                 }
             }
@@ -209,7 +357,10 @@ pub trait AppState {
                 cc,
                 screen,
             ),
-            Command::VerbInvocate(invocation) => match con.verb_store.search(&invocation.name) {
+            Command::VerbInvocate(invocation) => match con
+                .verb_store
+                .search_sel(&invocation.name, self.selection())
+            {
                 PrefixSearchResult::Match(_, verb) => {
                     if let Some(err) = verb.check_args(invocation, &cc.other_path) {
                         Ok(AppStateCmdResult::DisplayError(err))
@@ -224,17 +375,23 @@ pub trait AppState {
                                 screen,
                             ),
                             VerbExecution::External(external) => {
-                                external.to_cmd_result(
-                                    w,
-                                    self.selection(),
-                                    &cc.other_path,
-                                    &invocation.args,
-                                    con,
-                                )
+                                self.on_external(w, verb, external, &invocation.args, cc)
+                            }
+                            VerbExecution::Sequence(seq_exec) => {
+                                self.on_sequence(seq_exec, &invocation.args, cc)
                             }
                         }
                     }
                 }
+                PrefixSearchResult::NoMatch => Ok(
+                    // maybe the verb exists but doesn't apply to the selection
+                    match con.verb_store.search(&invocation.name) {
+                        PrefixSearchResult::Match(name, verb) => {
+                            AppStateCmdResult::DisplayError(verb.selection_condition_error(name))
+                        }
+                        _ => AppStateCmdResult::verb_not_found(&invocation.name),
+                    }
+                ),
                 _ => Ok(AppStateCmdResult::verb_not_found(&invocation.name)),
             },
             Command::None | Command::VerbEdit(_) => {
@@ -267,31 +424,91 @@ pub trait AppState {
             }
         } else {
             let path = self.selected_path();
-            if path.is_file() {
+            // every existing entry, special files included, can be
+            // previewed, at least with a metadata card
+            if path.symlink_metadata().is_ok() {
+                let mut state = PreviewState::new(
+                    path.to_path_buf(),
+                    InputPattern::none(),
+                    prefered_mode,
+                    cc.con,
+                );
+                if let Some(tree_options) = self.tree_options() {
+                    // a directory is previewed with the options of this tree
+                    state.set_selected_path(path.to_path_buf(), Some(tree_options), cc.con);
+                }
                 AppStateCmdResult::NewPanel {
-                    state: Box::new(PreviewState::new(
-                        path.to_path_buf(),
-                        InputPattern::none(),
-                        prefered_mode,
-                        &cc.con,
-                    )),
+                    state: Box::new(state),
                     purpose: PanelPurpose::Preview,
                     direction: HDir::Right,
                 }
             } else {
                 AppStateCmdResult::DisplayError(
-                    "only regular files can be previewed".to_string()
+                    "only regular files and directories can be previewed".to_string()
                 )
             }
         }
     }
 
+    fn get_type(&self) -> AppStateType {
+        AppStateType::Tree
+    }
+
     fn selected_path(&self) -> &Path;
 
     fn selection(&self) -> Selection<'_>;
 
+    /// a short description of the state, for the list of
+    /// states of the panel
+    fn label(&self) -> String {
+        self.selected_path()
+            .file_name()
+            .map_or_else(|| "/".to_string(), |name| name.to_string_lossy().to_string())
+    }
+
+    /// the root of the displayed tree, if the state displays one
+    fn tree_root(&self) -> Option<&Path> {
+        None
+    }
+
+    /// the options of the displayed tree, if the state displays one
+    fn tree_options(&self) -> Option<&TreeOptions> {
+        None
+    }
+
+    /// set the root of the tree of the panel linked to this one,
+    /// so that entries without counterpart can be highlighted
+    fn set_linked_root(&mut self, _root: Option<PathBuf>) {}
+
+    /// select the path if it's in the displayed tree, because the
+    /// selection moved to the same relative path in the linked panel
+    fn select_linked_path(&mut self, _path: &Path, _screen: &Screen) {}
+
+    /// return a new state displaying the same tree with the settings
+    /// of the given options, if the state displays a tree
+    fn with_tree_options(
+        &self,
+        _options: &TreeOptions,
+        _screen: &Screen,
+        _con: &AppContext,
+    ) -> Option<Box<dyn AppState>> {
+        None
+    }
+
+    /// change in place the settings of the options of the displayed
+    /// tree, if any, keeping its pattern. The state must be refreshed
+    /// for the trees to be built again with these settings
+    fn set_tree_settings(&mut self, _options: &TreeOptions) {}
+
     fn refresh(&mut self, screen: &Screen, con: &AppContext) -> Command;
 
+    /// update what's displayed of the paths, which an external command
+    /// may have changed, created or removed. By default the state is
+    /// totally refreshed
+    fn refresh_paths(&mut self, _paths: &[PathBuf], screen: &Screen, con: &AppContext) {
+        self.refresh(screen, con);
+    }
+
     fn do_pending_task(
         &mut self,
         _screen: &mut Screen,
@@ -306,6 +523,19 @@ pub trait AppState {
         None
     }
 
+    /// whether the state must be redrawn regularly, even without
+    /// user action, because it follows a changing file
+    fn is_following(&self) -> bool {
+        false
+    }
+
+    /// a text to display in the status line instead of the normal
+    /// one when no verb is being typed, for example the full path
+    /// of the selection when it's cropped in the state area
+    fn echo(&self) -> Option<String> {
+        None
+    }
+
     fn display(
         &mut self,
         w: &mut W,
@@ -324,7 +554,12 @@ pub trait AppState {
         String::new()
     }
 
-    fn set_selected_path(&mut self, _path: PathBuf, _con: &AppContext) {
+    fn set_selected_path(
+        &mut self,
+        _path: PathBuf,
+        _tree_options: Option<&TreeOptions>,
+        _con: &AppContext,
+    ) {
         // this function is useful for preview states
     }
 
@@ -343,7 +578,10 @@ pub trait AppState {
         con: &AppContext,
     ) -> Status {
         match cmd {
-            Command::PatternEdit{ .. } => self.no_verb_status(has_previous_state, con),
+            Command::PatternEdit { expr, .. } => match Pattern::new(expr, &con.search_modes) {
+                Ok(_) => self.no_verb_status(has_previous_state, con),
+                Err(e) => Status::new(e.to_string(), true),
+            },
             Command::VerbEdit(invocation) => {
                 if invocation.name.is_empty() {
                     Status::new(
@@ -351,12 +589,15 @@ pub trait AppState {
                         false,
                     )
                 } else {
-                    match con.verb_store.search(&invocation.name) {
-                        PrefixSearchResult::NoMatch => {
-                            Status::new("No matching verb (*?* for the list of verbs)", true)
-                        }
+                    let selection = self.selection();
+                    match con.verb_store.search_sel(&invocation.name, selection) {
+                        PrefixSearchResult::NoMatch => match con.verb_store.search(&invocation.name) {
+                            PrefixSearchResult::Match(name, verb) => {
+                                Status::new(verb.selection_condition_error(name), true)
+                            }
+                            _ => Status::new("No matching verb (*?* for the list of verbs)", true),
+                        },
                         PrefixSearchResult::Match(_, verb) => {
-                            let selection = self.selection();
                             verb.get_status(selection, other_path, invocation)
                         }
                         PrefixSearchResult::Matches(completions) => Status::new(
@@ -377,3 +618,32 @@ pub trait AppState {
         }
     }
 }
+
+/// copy the content of a text file to the system clipboard
+fn copy_content(path: &Path) -> Result<AppStateCmdResult, ProgramError> {
+    if !path.is_file() {
+        return Ok(AppStateCmdResult::DisplayError(
+            "only the content of regular files can be copied".to_string(),
+        ));
+    }
+    if fs::metadata(path)?.len() > MAX_COPIED_CONTENT_SIZE {
+        return Ok(AppStateCmdResult::DisplayError(format!(
+            "the file is too big to be copied ({} bytes max)",
+            MAX_COPIED_CONTENT_SIZE,
+        )));
+    }
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => {
+            return Ok(AppStateCmdResult::DisplayError(
+                "the file isn't a valid UTF-8 text".to_string(),
+            ));
+        }
+    };
+    cli_clipboard::set_contents(content)
+        .map_err(|_| ProgramError::ClipboardError)?;
+    Ok(AppStateCmdResult::DisplayMessage(format!(
+        "content of {} copied to the clipboard",
+        path.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string()),
+    )))
+}
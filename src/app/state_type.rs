@@ -1,5 +1,7 @@
 
-#[derive(Debug, Clone, Copy)]
+/// the kind of a state, which determines for example the
+/// history of patterns proposed in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AppStateType {
     Tree,
     Help,
@@ -0,0 +1,162 @@
+use {
+    super::{create, extract},
+    crossbeam::channel::{bounded, Receiver, RecvTimeoutError},
+    std::{
+        fs,
+        io::{self, Read, Seek, SeekFrom},
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    },
+};
+
+/// the progress of an archive job, shared between the thread
+/// doing it and the state displaying it
+#[derive(Debug, Default)]
+pub struct Progress {
+    bytes_done: AtomicU64,
+    bytes_total: AtomicU64,
+    entries_done: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    pub fn bytes_done(&self) -> u64 {
+        self.bytes_done.load(Ordering::Relaxed)
+    }
+    pub fn bytes_total(&self) -> u64 {
+        self.bytes_total.load(Ordering::Relaxed)
+    }
+    pub fn set_bytes_total(&self, total: u64) {
+        self.bytes_total.store(total, Ordering::Relaxed);
+    }
+    pub fn entries_done(&self) -> usize {
+        self.entries_done.load(Ordering::Relaxed)
+    }
+    pub fn add_entry(&self) {
+        self.entries_done.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn ratio(&self) -> f64 {
+        match self.bytes_total() {
+            0 => 0.0,
+            total => (self.bytes_done() as f64 / total as f64).min(1.0),
+        }
+    }
+    /// ask the job to stop, which it does on its next read
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// a reader counting the bytes it reads in the progress, and
+/// failing as soon as the job is cancelled
+pub struct ProgressReader<'p, R> {
+    inner: R,
+    progress: &'p Progress,
+}
+
+impl<'p, R> ProgressReader<'p, R> {
+    pub fn new(inner: R, progress: &'p Progress) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.progress.is_cancelled() {
+            return Err(io::Error::other("cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.progress.bytes_done.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+// needed by zip archives, whose index is at the end
+impl<R: Seek> Seek for ProgressReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// what an archive job does
+#[derive(Debug, Clone)]
+pub enum ArchiveTask {
+    Extract { archive: PathBuf, dst: PathBuf },
+    Create { srcs: Vec<PathBuf>, archive: PathBuf },
+}
+
+impl ArchiveTask {
+    /// do the task, returning the number of extracted or
+    /// archived entries
+    fn run(&self, progress: &Progress) -> io::Result<usize> {
+        match self {
+            Self::Extract { archive, dst } => extract(archive, dst, progress),
+            Self::Create { srcs, archive } => create(srcs, archive, progress),
+        }
+    }
+}
+
+/// an extraction or creation of archive, done in its own thread
+pub struct ArchiveJob {
+    pub task: ArchiveTask,
+    pub progress: Arc<Progress>,
+    receiver: Receiver<Result<usize, String>>,
+}
+
+impl ArchiveJob {
+    /// start the job in a new thread. In case of failure or
+    /// cancellation, what was written is removed
+    pub fn start(task: ArchiveTask) -> Self {
+        let progress = Arc::new(Progress::default());
+        let (sender, receiver) = bounded(1);
+        let thread_task = task.clone();
+        let thread_progress = Arc::clone(&progress);
+        thread::spawn(move || {
+            // what's written is removed only if it's ours
+            let written_existed = match &thread_task {
+                ArchiveTask::Extract { dst, .. } => dst.exists(),
+                ArchiveTask::Create { archive, .. } => archive.exists(),
+            };
+            let res = thread_task.run(&thread_progress);
+            if let Err(e) = &res {
+                warn!("archive job failed: {:?}", e);
+                if !written_existed {
+                    let _ = match &thread_task {
+                        ArchiveTask::Extract { dst, .. } => fs::remove_dir_all(dst),
+                        ArchiveTask::Create { archive, .. } => fs::remove_file(archive),
+                    };
+                }
+            }
+            let _ = sender.send(res.map_err(|e| e.to_string()));
+        });
+        Self {
+            task,
+            progress,
+            receiver,
+        }
+    }
+
+    /// wait at most for the given duration for the end of the job,
+    /// returning its result if it ended
+    pub fn wait(&self, timeout: Duration) -> Option<Result<usize, String>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(res) => Some(res),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err("the job crashed".to_string())),
+        }
+    }
+
+    /// stop the job, and wait for the cleaning to be done
+    pub fn cancel(&self) {
+        self.progress.cancel();
+        let _ = self.receiver.recv();
+    }
+}
@@ -0,0 +1,210 @@
+use {
+    super::{ArchiveJob, ArchiveTask},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{progress_bar, Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        task_sync::Dam,
+        verb::*,
+    },
+    std::{
+        path::Path,
+        time::{Duration, Instant},
+    },
+    termimad::{Area, FmtText, TextView},
+};
+
+/// how long we wait for the job before the progress is displayed again
+const DISPLAY_PERIOD: Duration = Duration::from_millis(200);
+
+/// how long we wait for the job between two checks of the events
+const POLL_PERIOD: Duration = Duration::from_millis(20);
+
+enum ArchiveStep {
+    Running(ArchiveJob),
+    Done(usize), // number of entries
+    Failed(String),
+}
+
+/// a modal state displaying the progress of an extraction or
+/// creation of archive, which can be cancelled with esc
+pub struct ArchiveState {
+    task: ArchiveTask,
+    step: ArchiveStep,
+}
+
+impl ArchiveState {
+    pub fn new(task: ArchiveTask) -> Self {
+        Self {
+            task: task.clone(),
+            step: ArchiveStep::Running(ArchiveJob::start(task)),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        let (what, done_verb) = match &self.task {
+            ArchiveTask::Extract { archive, dst } => (
+                format!(
+                    "**{}** is extracted into **{}**",
+                    archive.to_string_lossy(),
+                    dst.to_string_lossy(),
+                ),
+                "extracted",
+            ),
+            ArchiveTask::Create { srcs, archive } => (
+                format!(
+                    "{} is archived into **{}**",
+                    match srcs.len() {
+                        1 => format!("**{}**", srcs[0].to_string_lossy()),
+                        n => format!("{} entries", n),
+                    },
+                    archive.to_string_lossy(),
+                ),
+                "archived",
+            ),
+        };
+        match &self.step {
+            ArchiveStep::Running(job) => format!(
+                "\n{}.\n\n\
+                {} entries {} ({} / {})\n\n\
+                Hit *esc* to cancel",
+                what,
+                job.progress.entries_done(),
+                done_verb,
+                file_size::fit_4(job.progress.bytes_done()),
+                file_size::fit_4(job.progress.bytes_total()),
+            ),
+            ArchiveStep::Done(count) => format!("\n{}: {} entries {}", what, count, done_verb),
+            ArchiveStep::Failed(e) => format!("\n{}: failed\n\n`{}`", what, e),
+        }
+    }
+}
+
+impl AppState for ArchiveState {
+
+    fn selected_path(&self) -> &Path {
+        match &self.task {
+            ArchiveTask::Extract { dst, .. } => dst,
+            ArchiveTask::Create { archive, .. } => archive,
+        }
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: self.selected_path(),
+            line: 0,
+            stype: SelectionType::Any,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            ArchiveStep::Running(_) => AppStateCmdResult::Keep, // only esc cancels
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        if let ArchiveStep::Running(job) = &self.step {
+            if internal_exec.internal != Internal::back {
+                return Ok(AppStateCmdResult::Keep);
+            }
+            job.cancel();
+        }
+        Ok(AppStateCmdResult::PopStateAndRefresh { clear_cache: true })
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        match &self.step {
+            ArchiveStep::Running(_) => Some(match &self.task {
+                ArchiveTask::Extract { .. } => "extracting",
+                ArchiveTask::Create { .. } => "archiving",
+            }),
+            _ => None,
+        }
+    }
+
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        // we wait for the job until the user does something (for example
+        // hitting esc to cancel) or it's time to display the progress
+        let start = Instant::now();
+        while let ArchiveStep::Running(job) = &self.step {
+            if let Some(res) = job.wait(POLL_PERIOD) {
+                self.step = match res {
+                    Ok(count) => ArchiveStep::Done(count),
+                    Err(e) => ArchiveStep::Failed(e),
+                };
+                break;
+            }
+            if dam.has_event() || start.elapsed() > DISPLAY_PERIOD {
+                break;
+            }
+        }
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            ArchiveStep::Running(job) => format!(
+                "{} `{}` {:>3}% hit *esc* to cancel",
+                match &self.task {
+                    ArchiveTask::Extract { .. } => "Extracting",
+                    ArchiveTask::Create { .. } => "Archiving",
+                },
+                progress_bar(job.progress.ratio()),
+                (job.progress.ratio() * 100.0) as usize,
+            ),
+            _ => "Hit any key to go back".to_string(),
+        })
+    }
+}
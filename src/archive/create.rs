@@ -0,0 +1,158 @@
+use {
+    super::{ArchiveFormat, Progress, ProgressReader},
+    std::{
+        fs::{self, File, OpenOptions},
+        io::{self, BufWriter, Write},
+        path::{Path, PathBuf},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EntryKind {
+    Dir,
+    File,
+    Link,
+}
+
+/// an entry to write in the archive
+struct Entry {
+    path: PathBuf, // the path on disk
+    name: PathBuf, // the path in the archive
+    kind: EntryKind,
+}
+
+/// list the entry and, if it's a directory, its content (symlinks
+/// aren't followed), returning the total size of the files
+fn list_entries(
+    path: PathBuf,
+    name: PathBuf,
+    archive: &Path,
+    entries: &mut Vec<Entry>,
+) -> io::Result<u64> {
+    if path == archive {
+        return Ok(0); // the archive is being written in an archived directory
+    }
+    let md = fs::symlink_metadata(&path)?;
+    if md.file_type().is_symlink() {
+        entries.push(Entry { path, name, kind: EntryKind::Link });
+        return Ok(0);
+    }
+    if !md.is_dir() {
+        entries.push(Entry { path, name, kind: EntryKind::File });
+        return Ok(md.len());
+    }
+    let mut children: Vec<PathBuf> = fs::read_dir(&path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    children.sort();
+    entries.push(Entry { path, name: name.clone(), kind: EntryKind::Dir });
+    let mut size = 0;
+    for child in children {
+        if let Some(child_name) = child.file_name() {
+            let child_name = name.join(child_name);
+            size += list_entries(child, child_name, archive, entries)?;
+        }
+    }
+    Ok(size)
+}
+
+/// write the entries in a tar archive
+fn write_tar<W: Write>(writer: W, entries: &[Entry], progress: &Progress) -> io::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    for entry in entries {
+        match entry.kind {
+            EntryKind::Dir => builder.append_dir(&entry.name, &entry.path)?,
+            EntryKind::Link => builder.append_path_with_name(&entry.path, &entry.name)?,
+            EntryKind::File => {
+                let file = File::open(&entry.path)?;
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&file.metadata()?);
+                builder.append_data(&mut header, &entry.name, ProgressReader::new(file, progress))?;
+            }
+        }
+        progress.add_entry();
+    }
+    builder.into_inner()
+}
+
+/// the name of the entry in a zip archive, whose separator is
+/// always a slash
+fn zip_name(name: &Path) -> String {
+    name.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// write the entries in a zip archive. Symlinks aren't supported
+/// by zip and are skipped
+fn write_zip(file: File, entries: &[Entry], progress: &Progress) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for entry in entries {
+        match entry.kind {
+            EntryKind::Dir => {
+                zip.add_directory(zip_name(&entry.name), options)?;
+            }
+            EntryKind::Link => {
+                continue;
+            }
+            EntryKind::File => {
+                let file = File::open(&entry.path)?;
+                #[cfg(unix)]
+                let options = {
+                    use std::os::unix::fs::PermissionsExt;
+                    options.unix_permissions(file.metadata()?.permissions().mode())
+                };
+                zip.start_file(zip_name(&entry.name), options)?;
+                io::copy(&mut ProgressReader::new(file, progress), &mut zip)?;
+            }
+        }
+        progress.add_entry();
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// create an archive containing the sources, each one at the top
+/// of the archive. The format depends on the extension of the archive:
+/// zip, tar or tar.gz. An existing file isn't overwritten.
+///
+/// Return the number of archived entries.
+pub fn create(srcs: &[PathBuf], archive: &Path, progress: &Progress) -> io::Result<usize> {
+    let format = ArchiveFormat::of_path(archive);
+    match format {
+        Some(ArchiveFormat::Zip) | Some(ArchiveFormat::Tar) | Some(ArchiveFormat::TarGzip) => {}
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the archive name must end in .zip, .tar or .tar.gz",
+            ));
+        }
+    }
+    let mut entries = Vec::new();
+    let mut size = 0;
+    for src in srcs {
+        if let Some(name) = src.file_name() {
+            size += list_entries(src.clone(), PathBuf::from(name), archive, &mut entries)?;
+        }
+    }
+    progress.set_bytes_total(size);
+    let file = OpenOptions::new().write(true).create_new(true).open(archive)?;
+    match format {
+        Some(ArchiveFormat::Zip) => write_zip(file, &entries, progress)?,
+        Some(ArchiveFormat::TarGzip) => {
+            let encoder = flate2::write::GzEncoder::new(
+                BufWriter::new(file),
+                flate2::Compression::default(),
+            );
+            write_tar(encoder, &entries, progress)?.finish()?.flush()?;
+        }
+        _ => {
+            write_tar(BufWriter::new(file), &entries, progress)?.flush()?;
+        }
+    }
+    Ok(progress.entries_done())
+}
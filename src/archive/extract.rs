@@ -0,0 +1,154 @@
+use {
+    super::{tar_stream, ArchiveFormat, Progress, ProgressReader},
+    std::{
+        fs::{self, File, OpenOptions},
+        io::{self, BufReader, Read, Seek},
+        path::{Component, Path, PathBuf},
+    },
+    tar::Archive,
+};
+
+/// return the error telling an entry would overwrite a file
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.to_string_lossy()),
+    )
+}
+
+/// the directory an archive is extracted into when none is given:
+/// a sibling of the archive, named after it without its extensions
+pub fn default_destination(archive: &Path) -> PathBuf {
+    let name = archive
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+    let stem = name.split('.').next().filter(|stem| !stem.is_empty()).unwrap_or("extracted");
+    archive.with_file_name(stem)
+}
+
+/// the path a tar entry is written to by `unpack_in`, which ignores
+/// the root and prefix components of the path of the entry, or None
+/// when the entry is skipped because it would be outside dst
+fn unpacked_path(dst: &Path, entry_path: &Path) -> Option<PathBuf> {
+    let mut path = dst.to_path_buf();
+    for component in entry_path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+            Component::ParentDir => {
+                return None;
+            }
+            Component::Normal(part) => {
+                path.push(part);
+            }
+        }
+    }
+    Some(path)
+}
+
+/// extract the entries of the tar archive, read from the
+/// (already decompressed) stream
+fn extract_tar<R: Read>(reader: R, dst: &Path, progress: &Progress) -> io::Result<usize> {
+    let mut archive = Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // entries whose path would be outside dst are skipped by unpack_in
+        let path = match unpacked_path(dst, &entry.path()?) {
+            Some(path) => path,
+            None => {
+                debug!("tar entry {:?} skipped", entry.path());
+                continue;
+            }
+        };
+        if !entry.header().entry_type().is_dir() && fs::symlink_metadata(&path).is_ok() {
+            return Err(already_exists(&path));
+        }
+        if entry.unpack_in(dst)? {
+            progress.add_entry();
+        }
+    }
+    Ok(progress.entries_done())
+}
+
+/// extract the entries of the zip archive
+fn extract_zip<R: Read + Seek>(reader: R, dst: &Path, progress: &Progress) -> io::Result<usize> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx)?;
+        // entries whose path would be outside dst are skipped
+        let path = match entry.enclosed_name() {
+            Some(name) => dst.join(name),
+            None => {
+                debug!("zip entry {:?} skipped", entry.name());
+                continue;
+            }
+        };
+        if entry.name().ends_with('/') {
+            fs::create_dir_all(&path)?;
+        } else {
+            if fs::symlink_metadata(&path).is_ok() {
+                return Err(already_exists(&path));
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+            io::copy(&mut entry, &mut file)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Some(mode) = entry.unix_mode() {
+                    fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+                }
+            }
+        }
+        progress.add_entry();
+    }
+    Ok(progress.entries_done())
+}
+
+/// extract the archive into the dst directory, which is created if
+/// needed. Existing files aren't overwritten.
+///
+/// Return the number of extracted entries.
+pub fn extract(archive: &Path, dst: &Path, progress: &Progress) -> io::Result<usize> {
+    let format = ArchiveFormat::of_file(archive)
+        .filter(|format| format.is_extractable())
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "not an archive format broot can extract")
+        })?;
+    let file = File::open(archive)?;
+    // the progress is the part of the archive file which was read
+    progress.set_bytes_total(file.metadata()?.len());
+    let reader = ProgressReader::new(file, progress);
+    fs::create_dir_all(dst)?;
+    match format {
+        ArchiveFormat::Zip => extract_zip(BufReader::new(reader), dst, progress),
+        _ => extract_tar(tar_stream(format, reader)?, dst, progress),
+    }
+}
+
+#[cfg(test)]
+mod extract_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_unpacked_paths() {
+        let dst = Path::new("/tmp/dst");
+        assert_eq!(
+            unpacked_path(dst, Path::new("a/b.txt")),
+            Some(PathBuf::from("/tmp/dst/a/b.txt")),
+        );
+        // an absolute path is extracted below dst, not over the host file
+        assert_eq!(
+            unpacked_path(dst, Path::new("/etc/hosts")),
+            Some(PathBuf::from("/tmp/dst/etc/hosts")),
+        );
+        assert_eq!(
+            unpacked_path(dst, Path::new("./a.txt")),
+            Some(PathBuf::from("/tmp/dst/a.txt")),
+        );
+        assert_eq!(unpacked_path(dst, Path::new("a/../../b.txt")), None);
+    }
+}
@@ -0,0 +1,314 @@
+//! listing of the entries of archives, so that they can be
+//! previewed without being extracted, and extraction and
+//! creation of archives, done in a background thread.
+//!
+//! Tar archives (possibly compressed) and zip archives are
+//! handled. 7z archives can be listed, with the `7z` command.
+
+mod archive_job;
+mod archive_state;
+mod create;
+mod extract;
+
+pub use {
+    archive_job::{ArchiveJob, ArchiveTask, Progress, ProgressReader},
+    archive_state::ArchiveState,
+    create::create,
+    extract::{default_destination, extract},
+};
+
+use {
+    crate::{
+        magic,
+        task_sync::Dam,
+    },
+    std::{
+        fs::File,
+        io::{self, BufReader, Read},
+        path::Path,
+        process::Command,
+    },
+    tar::Archive,
+};
+
+/// the max number of entries read in an archive
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// an archive format broot knows how to list and extract
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarBzip2,
+    TarGzip,
+    TarXz,
+    TarZstd,
+    Zip,
+    SevenZip, // only listed
+}
+
+/// an entry of an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String, // the path in the archive, as written in it
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+impl ArchiveFormat {
+    /// determine the archive format from the extension of the file
+    pub fn of_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        let suffixes = [
+            (".tar", Self::Tar),
+            (".tar.bz2", Self::TarBzip2),
+            (".tbz2", Self::TarBzip2),
+            (".tar.gz", Self::TarGzip),
+            (".tgz", Self::TarGzip),
+            (".tar.xz", Self::TarXz),
+            (".txz", Self::TarXz),
+            (".tar.zst", Self::TarZstd),
+            (".zip", Self::Zip),
+            (".jar", Self::Zip),
+            (".7z", Self::SevenZip),
+        ];
+        suffixes
+            .iter()
+            .find(|(suffix, _)| name.ends_with(suffix))
+            .map(|&(_, format)| format)
+    }
+
+    /// determine the archive format from the mime type detected
+    /// from the content of the file. Compressed tar archives can't
+    /// be recognized this way.
+    pub fn of_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "application/x-tar" => Some(Self::Tar),
+            "application/zip" => Some(Self::Zip),
+            "application/x-7z-compressed" => Some(Self::SevenZip),
+            _ => None,
+        }
+    }
+
+    /// whether broot can extract the archives of this format
+    pub fn is_extractable(self) -> bool {
+        self != Self::SevenZip
+    }
+
+    /// determine the archive format from the extension of the
+    /// file or, failing that, from its content
+    pub fn of_file(path: &Path) -> Option<Self> {
+        Self::of_path(path).or_else(|| {
+            magic::mime_of_file(path).ok()
+                .flatten()
+                .and_then(Self::of_mime)
+        })
+    }
+}
+
+/// wrap the reader of a tar archive in the decompressor of its format
+fn tar_stream<'r, R: Read + 'r>(
+    format: ArchiveFormat,
+    reader: R,
+) -> io::Result<Box<dyn Read + 'r>> {
+    Ok(match format {
+        ArchiveFormat::TarBzip2 => Box::new(bzip2::read::BzDecoder::new(reader)),
+        ArchiveFormat::TarGzip => Box::new(flate2::read::GzDecoder::new(reader)),
+        ArchiveFormat::TarXz => Box::new(xz2::read::XzDecoder::new(reader)),
+        ArchiveFormat::TarZstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        _ => Box::new(BufReader::new(reader)),
+    })
+}
+
+/// list the entries of a tar archive, read from the
+/// (already decompressed) stream.
+/// Return None if interrupted by an event.
+fn tar_entries<R: Read>(reader: R, dam: &Dam) -> io::Result<Option<Vec<ArchiveEntry>>> {
+    let mut archive = Archive::new(reader);
+    let mut entries = Vec::new();
+    for entry in archive.entries()?.take(MAX_ENTRIES) {
+        if dam.has_event() {
+            return Ok(None);
+        }
+        let entry = entry?;
+        let header = entry.header();
+        entries.push(ArchiveEntry {
+            path: entry.path()?.to_string_lossy().to_string(),
+            size: header.size()?,
+            is_dir: header.entry_type().is_dir(),
+        });
+    }
+    Ok(Some(entries))
+}
+
+/// list the entries of a zip archive.
+/// Return None if interrupted by an event.
+fn zip_entries(file: File, dam: &Dam) -> io::Result<Option<Vec<ArchiveEntry>>> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))?;
+    let mut entries = Vec::new();
+    for idx in 0..archive.len().min(MAX_ENTRIES) {
+        if dam.has_event() {
+            return Ok(None);
+        }
+        let entry = archive.by_index(idx)?;
+        entries.push(ArchiveEntry {
+            path: entry.name().to_string(),
+            size: entry.size(),
+            is_dir: entry.name().ends_with('/'),
+        });
+    }
+    Ok(Some(entries))
+}
+
+/// parse the technical listing of a 7z archive, as given by
+/// `7z l -slt`, where each entry is a block of `key = value` lines
+fn parse_7z_listing(listing: &str) -> Vec<ArchiveEntry> {
+    let mut entries = Vec::new();
+    // the properties of the archive come before the separator
+    let lines = listing.lines().skip_while(|line| line.trim() != "----------").skip(1);
+    for line in lines {
+        let mut parts = line.splitn(2, " = ");
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => continue,
+        };
+        match key {
+            "Path" => {
+                if entries.len() == MAX_ENTRIES {
+                    break;
+                }
+                entries.push(ArchiveEntry {
+                    path: value.to_string(),
+                    size: 0,
+                    is_dir: false,
+                });
+            }
+            "Size" => {
+                if let Some(entry) = entries.last_mut() {
+                    entry.size = value.parse().unwrap_or(0);
+                }
+            }
+            "Folder" | "Attributes" => {
+                if let Some(entry) = entries.last_mut() {
+                    entry.is_dir |= value == "+" || value.starts_with('D');
+                }
+            }
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// list the entries of a 7z archive with the `7z` command, which
+/// only reads the headers, usually at the end of the archive
+fn sevenz_entries(path: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let output = Command::new("7z")
+        .arg("l")
+        .arg("-slt")
+        .arg("--") // so that the path can't be taken for an option
+        .arg(path)
+        .output()
+        .map_err(|e| io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("the 7z command is needed to list 7z archives: {}", e),
+        ))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(stderr.trim().to_string()));
+    }
+    Ok(parse_7z_listing(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// list the entries of the archive, in the order they're
+/// stored, up to MAX_ENTRIES.
+///
+/// Return None if interrupted by an event, the dam being checked
+/// between entries (the 7z command isn't interrupted).
+pub fn list_entries(
+    path: &Path,
+    format: ArchiveFormat,
+    dam: &Dam,
+) -> io::Result<Option<Vec<ArchiveEntry>>> {
+    match format {
+        ArchiveFormat::SevenZip => sevenz_entries(path).map(Some),
+        ArchiveFormat::Zip => zip_entries(File::open(path)?, dam),
+        _ => tar_entries(tar_stream(format, File::open(path)?)?, dam),
+    }
+}
+
+#[cfg(test)]
+mod archive_tests {
+
+    use {
+        super::*,
+        std::fs,
+        tempfile::TempDir,
+    };
+
+    /// create an archive of a small directory and list its entries
+    fn listed_paths(name: &str) -> Vec<String> {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("sub/notes.txt"), "some notes").unwrap();
+        let archive = dir.path().join(name);
+        create(&[src], &archive, &Progress::default()).unwrap();
+        let format = ArchiveFormat::of_file(&archive).unwrap();
+        let entries = list_entries(&archive, format, &Dam::unlimited()).unwrap().unwrap();
+        let file = entries.iter().find(|e| e.path.ends_with("notes.txt")).unwrap();
+        assert_eq!(file.size, 10);
+        assert!(!file.is_dir);
+        let mut paths: Vec<String> = entries.into_iter()
+            .map(|e| e.path.trim_end_matches('/').to_string())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn check_listings() {
+        let expected = vec!["src", "src/sub", "src/sub/notes.txt"];
+        assert_eq!(listed_paths("a.zip"), expected);
+        assert_eq!(listed_paths("a.tar"), expected);
+        assert_eq!(listed_paths("a.tar.gz"), expected);
+    }
+
+    #[test]
+    fn check_formats() {
+        assert_eq!(ArchiveFormat::of_path(Path::new("a.TGZ")), Some(ArchiveFormat::TarGzip));
+        assert_eq!(ArchiveFormat::of_path(Path::new("a.7z")), Some(ArchiveFormat::SevenZip));
+        assert_eq!(ArchiveFormat::of_path(Path::new("a.txt")), None);
+        assert!(!ArchiveFormat::SevenZip.is_extractable());
+    }
+
+    #[test]
+    fn check_7z_listing() {
+        let listing = "\
+7-Zip [64] 16.02
+
+Listing archive: a.7z
+
+--
+Path = a.7z
+Type = 7z
+Physical Size = 180
+
+----------
+Path = src
+Size = 0
+Folder = +
+Attributes = D_ drwxr-xr-x
+
+Path = src/notes.txt
+Size = 10
+Folder = -
+Attributes = A_ -rw-r--r--
+";
+        let entries = parse_7z_listing(listing);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "src");
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].path, "src/notes.txt");
+        assert_eq!(entries[1].size, 10);
+        assert!(!entries[1].is_dir);
+    }
+}
@@ -0,0 +1,170 @@
+use {
+    super::{synthetic_tree, Scenario},
+    crate::{
+        app::*,
+        browser::BrowserState,
+        command::Sequence,
+        conf::Conf,
+        display::{self, Areas, Screen},
+        errors::ProgramError,
+        skin::AppSkin,
+        task_sync::Dam,
+    },
+    std::{
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    },
+    tempfile::TempDir,
+};
+
+/// the dimensions of the (not displayed) screen, which are fixed
+/// so that the page based computations don't depend on the terminal
+const SCREEN_WIDTH: u16 = 120;
+const SCREEN_HEIGHT: u16 = 40;
+
+/// a measured step of the bench
+struct Phase {
+    name: String,
+    duration: Duration,
+    error: Option<String>,
+}
+
+/// a run of a scenario on a synthetic tree, with the timings
+/// of its phases
+pub struct BenchRun {
+    scenario: Scenario,
+    size: usize,
+    root: PathBuf,
+    _tree: TempDir, // the synthetic tree, removed with the run
+    phases: Vec<Phase>,
+}
+
+impl Phase {
+    fn new<S: Into<String>>(name: S, start: Instant) -> Self {
+        Self {
+            name: name.into(),
+            duration: start.elapsed(),
+            error: None,
+        }
+    }
+}
+
+impl BenchRun {
+    /// prepare the run, generating the synthetic tree of
+    /// the given number of files
+    pub fn new(scenario: Scenario, size: usize) -> Result<Self, ProgramError> {
+        let start = Instant::now();
+        let tree = synthetic_tree::generate(size)?;
+        let phase = Phase::new("generate the tree", start);
+        Ok(Self {
+            scenario,
+            size,
+            root: tree.path().to_path_buf(),
+            _tree: tree,
+            phases: vec![phase],
+        })
+    }
+
+    /// the root of the synthetic tree, which must be the
+    /// root given in launch arguments
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// replay the commands of the scenario in a panel which isn't
+    /// displayed, every command being followed by the pending tasks
+    /// it caused (searches, size computations, etc.), then print
+    /// the timings on stdout
+    pub fn run(mut self, con: &AppContext, conf: &Conf) -> Result<(), ProgramError> {
+        let mut w = display::writer();
+        let mut screen = Screen::new(con, conf)?;
+        screen.set_terminal_size(SCREEN_WIDTH, SCREEN_HEIGHT, con);
+        let skin = AppSkin::new(conf);
+        let mut dam = Dam::unlimited();
+
+        let start = Instant::now();
+        let state = BrowserState::new(
+            self.root.clone(),
+            con.launch_args.tree_options.clone(),
+            &screen,
+            con,
+            &dam,
+        )?
+        .ok_or_else(|| ProgramError::InternalError {
+            details: "initial tree building interrupted".to_string(),
+        })?;
+        let mut panel = Panel::new(
+            PanelId::from(0),
+            Box::new(state),
+            Areas::create(&mut Vec::new(), 0, &screen, &con.panels_layout, false)?,
+            con,
+        );
+        panel.do_pending_tasks(&mut screen, con, &mut dam)?;
+        self.phases.push(Phase::new("build the initial tree", start));
+
+        for line in &self.scenario.commands {
+            let start = Instant::now();
+            let mut error = None;
+            for (_, cmd) in Sequence::new_single(line.to_string()).parse(con)? {
+                let result = panel.apply_command(
+                    &mut w,
+                    &cmd,
+                    &None,
+                    &mut screen,
+                    &skin.focused,
+                    None,
+                    con,
+                )?;
+                match result {
+                    AppStateCmdResult::NewState(state) => {
+                        panel.push_state(state);
+                    }
+                    AppStateCmdResult::PopState | AppStateCmdResult::PopStateAndRefresh { .. } => {
+                        panel.remove_state();
+                        panel.mut_state().refresh(&screen, con);
+                    }
+                    AppStateCmdResult::RefreshState { .. } | AppStateCmdResult::RefreshPaths { .. } => {
+                        panel.mut_state().refresh(&screen, con);
+                    }
+                    AppStateCmdResult::DisplayError(e) => {
+                        error = Some(e);
+                    }
+                    _ => {}
+                }
+                panel.do_pending_tasks(&mut screen, con, &mut dam)?;
+            }
+            let mut phase = Phase::new(line.as_str(), start);
+            phase.error = error;
+            self.phases.push(phase);
+        }
+
+        self.print();
+        Ok(())
+    }
+
+    fn print(&self) {
+        println!(
+            "scenario {:?} on a tree of {} files in {:?}",
+            &self.scenario.name,
+            self.size,
+            &self.root,
+        );
+        let mut total = Duration::default();
+        for phase in &self.phases {
+            total += phase.duration;
+            print_duration(&phase.name, phase.duration);
+            if let Some(error) = &phase.error {
+                println!("    error: {}", error);
+            }
+        }
+        print_duration("total", total);
+    }
+}
+
+fn print_duration(name: &str, duration: Duration) {
+    println!(
+        "{:<40} {:>12.3} ms",
+        name,
+        duration.as_secs_f64() * 1000.0,
+    );
+}
@@ -0,0 +1,14 @@
+//! A benchmark mode, not meant for the normal user, replaying
+//! sequences of commands on a generated tree and reporting the
+//! duration of every phase, so that performance problems can be
+//! reproduced and bisected.
+
+mod bench_run;
+mod scenario;
+mod synthetic_tree;
+
+pub use {
+    bench_run::BenchRun,
+    scenario::Scenario,
+    synthetic_tree::DEFAULT_SIZE,
+};
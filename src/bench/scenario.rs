@@ -0,0 +1,49 @@
+use {
+    crate::errors::ProgramError,
+    std::{
+        fs,
+        path::Path,
+    },
+};
+
+/// the scenarios which don't need a file
+const BUILTIN_SCENARIOS: &[(&str, &[&str])] = &[
+    ("fuzzy", &["fi", "file_7", "fl17", ":total_search"]),
+    ("regex", &["/file_1.*7/", "/^dir_[0-3]$/", ":total_search"]),
+    ("content", &["c/needle", ":total_search"]),
+    ("toggles", &[":toggle_hidden", ":toggle_sizes", ":toggle_counts", ":toggle_dates"]),
+    ("focus", &[":focus dir_1", ":up_tree", "dir_3", ":toggle_flat"]),
+];
+
+/// a sequence of commands to replay, one command (which may be made
+/// of a pattern and a verb invocation, as typed) per line
+pub struct Scenario {
+    pub name: String,
+    pub commands: Vec<String>,
+}
+
+impl Scenario {
+    /// get the builtin scenario with this name or read the scenario
+    /// file at this path. Empty lines and lines starting with `#`
+    /// are ignored.
+    pub fn new(name: &str) -> Result<Self, ProgramError> {
+        let commands = match BUILTIN_SCENARIOS.iter().find(|(n, _)| *n == name) {
+            Some((_, commands)) => commands.iter().map(|s| s.to_string()).collect(),
+            None if !Path::new(name).is_file() => {
+                return Err(ProgramError::UnknownScenario {
+                    name: name.to_string(),
+                });
+            }
+            None => fs::read_to_string(name)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            commands,
+        })
+    }
+}
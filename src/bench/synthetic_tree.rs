@@ -0,0 +1,47 @@
+use {
+    std::{fs, io},
+    tempfile::TempDir,
+};
+
+/// number of files in the synthetic tree when not specified
+pub const DEFAULT_SIZE: usize = 10_000;
+
+/// number of files, and of subdirectories, in every directory
+const FANOUT: usize = 12;
+
+const EXTENSIONS: &[&str] = &["rs", "txt", "md", "json", "toml"];
+
+/// one file every NEEDLE_PERIOD contains the needle, so
+/// that content searches have some matches
+const NEEDLE_PERIOD: usize = 101;
+const NEEDLE: &str = "needle";
+
+/// generate a synthetic tree of the given number of files in
+/// a new temporary directory, removed when dropped.
+///
+/// The tree is always the same for a given size, so that timings
+/// can be compared between runs and versions of broot.
+pub fn generate(size: usize) -> io::Result<TempDir> {
+    let root = tempfile::Builder::new()
+        .prefix(&format!("broot-bench-{}-", size))
+        .tempdir()?;
+    for i in 0..size {
+        // the directory is made from the digits of i/FANOUT
+        // written in base FANOUT
+        let mut dir = root.path().to_path_buf();
+        let mut n = i / FANOUT;
+        while n > 0 {
+            dir.push(format!("dir_{}", n % FANOUT));
+            n /= FANOUT;
+        }
+        fs::create_dir_all(&dir)?;
+        let ext = EXTENSIONS[i % EXTENSIONS.len()];
+        let mut content = format!("synthetic file {}\n", i);
+        if i % NEEDLE_PERIOD == 0 {
+            content.push_str(NEEDLE);
+            content.push('\n');
+        }
+        fs::write(dir.join(format!("file_{}.{}", i, ext)), content)?;
+    }
+    Ok(root)
+}
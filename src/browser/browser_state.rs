@@ -1,29 +1,47 @@
 use {
     crate::{
         app::*,
+        archive::{self, ArchiveFormat, ArchiveState, ArchiveTask},
+        checksum::{self, Algorithm},
         command::{Command, TriggerType},
-        display::{DisplayableTree, Screen, W},
+        diff::DiffState,
+        display::{BranchGlyphs, DisplayableTree, Screen, W},
+        duplicates::DuplicatesState,
         errors::{ProgramError, TreeBuildError},
+        file_clipboard::{FileClipboard, FileClipboardMode},
+        file_sum,
         flag::Flag,
-        git,
+        git::{self, GitBlameState, GitIgnoreMode, GitLogState, GitReposState, GitRestoreState},
+        handlers,
+        immutable,
         launchable::Launchable,
+        list::ListState,
+        logs,
+        mover::{self, BulkRename, BulkRenameState, LinkKind, MovePlan, MoveState, ShredState},
+        oci::{self, ImageSource, ImageState},
         pattern::*,
         path,
+        permissions::PermissionAudit,
+        preview::PreviewState,
         path_anchor::PathAnchor,
         print,
         skin::PanelSkin,
         task_sync::Dam,
+        temp_text::TempText,
         tree::*,
         tree_build::TreeBuilder,
         verb::*,
     },
-    open,
+    regex::Regex,
     std::{
-        fs::OpenOptions,
-        io::Write,
+        collections::HashSet,
+        fs::{self, OpenOptions},
+        io::{self, Write},
         path::{Path, PathBuf},
+        sync::atomic::Ordering,
+        time::{Duration, SystemTime},
     },
-    termimad::Area,
+    termimad::{Area, FmtText, TextView},
 };
 
 /// An application state dedicated to displaying a tree.
@@ -33,8 +51,27 @@ pub struct BrowserState {
     pub filtered_tree: Option<Tree>,
     pub pending_pattern: InputPattern, // a pattern (or not) which has not yet be applied
     pub total_search_required: bool, // whether the pending pattern should be in total search mode
+    selection_cropped: bool, // whether the name of the selection didn't fit on last display
+    pub marked: Vec<PathBuf>, // paths on which verbs are to be executed, in marking order
+    linked_root: Option<PathBuf>, // root of the tree of the linked panel, if panels are linked
+    unpaired_lines: Option<UnpairedLines>, // computed at display, when there's a linked root
+    area_top: u16, // the first line of the area the tree was last displayed in
+    area_height: u16, // the height of the area the tree was last displayed in
 }
 
+/// the paths of the lines of a tree which have no counterpart in
+/// the tree of the linked panel, computed once per tree
+struct UnpairedLines {
+    linked_root: PathBuf,
+    build_time: SystemTime,
+    filtered: bool,
+    paths: HashSet<PathBuf>,
+}
+
+/// delay between two checks of the availability of a root
+/// which disappeared
+const ROOT_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
 impl BrowserState {
 
     /// build a new tree state if there's no error and there's no cancellation.
@@ -60,25 +97,178 @@ impl BrowserState {
             filtered_tree: None,
             pending_pattern,
             total_search_required: false,
+            selection_cropped: false,
+            marked: Vec::new(),
+            linked_root: None,
+            unpaired_lines: None,
+            area_top: screen.top,
+            area_height: screen.height.saturating_sub(2),
         }))
     }
 
+    /// build a state showing the same tree, with changed options
+    fn new_state_with_options(
+        &self,
+        screen: &Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        con: &AppContext,
+    ) -> Result<Option<BrowserState>, TreeBuildError> {
+        let tree = self.displayed_tree();
+        let mut options = tree.options.clone();
+        change_options(&mut options);
+        BrowserState::new(tree.root().clone(), options, screen, con, &Dam::unlimited())
+            .map(|state| state.map(|mut state| {
+                // marks are kept when only the display options change
+                state.marked = self.marked.clone();
+                state
+            }))
+    }
+
+    /// build a state with changed options, in the panel or in a new one.
+    /// When configured, the other panels get the same options.
     pub fn with_new_options(
         &self,
         screen: &Screen,
         change_options: &dyn Fn(&mut TreeOptions),
         in_new_panel: bool,
         con: &AppContext,
+    ) -> AppStateCmdResult {
+        let state = self.new_state_with_options(screen, change_options, con);
+        match AppStateCmdResult::from_optional_state(state, in_new_panel) {
+            AppStateCmdResult::NewState(state) if con.broadcast_tree_options => {
+                let mut options = self.displayed_tree().options.clone();
+                change_options(&mut options);
+                AppStateCmdResult::BroadcastTreeOptions {
+                    state: Some(state),
+                    options: Box::new(options),
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// show only the entries of the selected directory (or of the root)
+    /// failing the audit, or end the audit when one is running and
+    /// no spec is given
+    fn audit_permissions(
+        &self,
+        spec: Option<&String>,
+        screen: &Screen,
+        in_new_panel: bool,
+        con: &AppContext,
     ) -> AppStateCmdResult {
         let tree = self.displayed_tree();
-        let mut options = tree.options.clone();
-        change_options(&mut options);
+        let audit = match spec {
+            None if tree.options.permission_audit.is_some() => None,
+            None => Some(PermissionAudit::Parent),
+            Some(spec) => match spec.parse() {
+                Ok(audit) => Some(audit),
+                Err(e) => return AppStateCmdResult::DisplayError(e),
+            },
+        };
+        let line = tree.selected_line();
+        let root = if audit.is_some() && line.is_dir() {
+            line.path.clone()
+        } else {
+            tree.root().clone()
+        };
+        let mut options = tree.options.without_pattern();
+        options.permission_audit = audit;
         AppStateCmdResult::from_optional_state(
-            BrowserState::new(tree.root().clone(), options, screen, con, &Dam::unlimited()),
+            BrowserState::new(root, options, screen, con, &Dam::unlimited()),
+            in_new_panel,
+        )
+    }
+
+    /// show only the files having one of the given git states, or
+    /// stop filtering when no state is given and the tree is filtered
+    fn git_filter(
+        &self,
+        spec: Option<&String>,
+        screen: &Screen,
+        in_new_panel: bool,
+        con: &AppContext,
+    ) -> AppStateCmdResult {
+        let statuses = match spec.map(|spec| git::parse_status_filter(spec)) {
+            Some(Ok(statuses)) => Some(statuses),
+            Some(Err(e)) => return AppStateCmdResult::DisplayError(e),
+            None => None,
+        };
+        let filtering = statuses.is_some() || !self.displayed_tree().options.filter_by_git_status;
+        self.with_new_options(
+            screen,
+            &|o| {
+                o.filter_by_git_status = filtering;
+                o.git_status_filter = statuses;
+                if filtering {
+                    o.show_hidden = true;
+                }
+            },
             in_new_panel,
+            con,
         )
     }
 
+    /// mark the selection, or unmark it if it was already marked
+    fn toggle_mark(&mut self) -> AppStateCmdResult {
+        if self.displayed_tree().selection == 0 {
+            return AppStateCmdResult::DisplayError("the root can't be marked".to_string());
+        }
+        let path = self.selected_path().to_path_buf();
+        match self.marked.iter().position(|p| p == &path) {
+            Some(idx) => {
+                self.marked.remove(idx);
+            }
+            None => {
+                self.marked.push(path);
+            }
+        }
+        match self.marked.len() {
+            0 => AppStateCmdResult::Keep,
+            1 => AppStateCmdResult::DisplayMessage("1 marked entry".to_string()),
+            n => AppStateCmdResult::DisplayMessage(format!("{} marked entries", n)),
+        }
+    }
+
+    /// mark the entries of the tree whose path, relative to the root,
+    /// matches the glob, and select the first one. The pattern of
+    /// the tree isn't changed.
+    fn select_glob(&mut self, glob: &str, page_height: i32) -> AppStateCmdResult {
+        let glob_pattern = match glob::Pattern::new(glob) {
+            Ok(glob_pattern) => glob_pattern,
+            Err(e) => {
+                return AppStateCmdResult::DisplayError(format!("invalid glob: {}", e));
+            }
+        };
+        let tree = self.displayed_tree();
+        let root = tree.root();
+        let matching: Vec<PathBuf> = tree.lines[1..]
+            .iter()
+            .filter(|line| line.is_selectable())
+            .filter(|line| {
+                line.path
+                    .strip_prefix(root)
+                    .is_ok_and(|subpath| glob_pattern.matches_path(subpath))
+            })
+            .map(|line| line.path.clone())
+            .collect();
+        if matching.is_empty() {
+            return AppStateCmdResult::DisplayError(format!("no entry matches {:?}", glob));
+        }
+        let tree = self.displayed_tree_mut();
+        tree.try_select_path(&matching[0]);
+        tree.make_selection_visible(page_height);
+        for path in matching {
+            if !self.marked.contains(&path) {
+                self.marked.push(path);
+            }
+        }
+        match self.marked.len() {
+            1 => AppStateCmdResult::DisplayMessage("1 marked entry".to_string()),
+            n => AppStateCmdResult::DisplayMessage(format!("{} marked entries", n)),
+        }
+    }
+
     pub fn root(&self) -> &Path {
         self.tree.root()
     }
@@ -87,20 +277,585 @@ impl BrowserState {
         i32::from(screen.height) - 2
     }
 
+    /// the number of lines of the tree which are visible, root included,
+    /// which may be less than the page height when panels are stacked
+    /// or have a title
+    fn visible_height(&self) -> i32 {
+        i32::from(self.area_height)
+    }
+
     /// return a reference to the currently displayed tree, which
     /// is the filtered tree if there's one, the base tree if not.
     pub fn displayed_tree(&self) -> &Tree {
         self.filtered_tree.as_ref().unwrap_or(&self.tree)
     }
 
+    /// compute, unless it's already done for the displayed tree,
+    /// the lines which have no counterpart in the linked panel
+    fn update_unpaired_lines(&mut self) {
+        let linked_root = match &self.linked_root {
+            Some(linked_root) => linked_root,
+            None => {
+                self.unpaired_lines = None;
+                return;
+            }
+        };
+        let filtered = self.filtered_tree.is_some();
+        let tree = self.displayed_tree();
+        if let Some(ul) = &self.unpaired_lines {
+            if &ul.linked_root == linked_root && ul.build_time == tree.build_time && ul.filtered == filtered {
+                return;
+            }
+        }
+        let root = tree.root();
+        let paths = tree.lines.iter()
+            .filter(|line| line.path
+                .strip_prefix(root)
+                .is_ok_and(|subpath| !linked_root.join(subpath).exists()))
+            .map(|line| line.path.clone())
+            .collect();
+        let unpaired_lines = UnpairedLines {
+            linked_root: linked_root.clone(),
+            build_time: tree.build_time,
+            filtered,
+            paths,
+        };
+        self.unpaired_lines = Some(unpaired_lines);
+    }
+
     /// return a mutable reference to the currently displayed tree, which
     /// is the filtered tree if there's one, the base tree if not.
     pub fn displayed_tree_mut(&mut self) -> &mut Tree {
         self.filtered_tree.as_mut().unwrap_or(&mut self.tree)
     }
 
+    /// let the displayed tree adapt its columns to the width of the
+    /// panel, fetching the sums of files when they become needed
+    fn set_panel_width(&mut self, width: u16) {
+        let tree = self.displayed_tree_mut();
+        if tree.options.panel_width != Some(width) {
+            let needed_sum = tree.options.needs_sum();
+            tree.options.panel_width = Some(width);
+            if !needed_sum && tree.options.needs_sum() {
+                tree.fetch_regular_file_sums();
+            }
+        }
+    }
+
+    /// tell whether the root disappeared (for example a removable
+    /// media was unplugged or a network share dropped)
+    fn is_root_unavailable(&self) -> bool {
+        !self.tree.root().exists()
+    }
+
+    /// return the parent of the root, or the closest existing
+    /// ancestor when the root is unavailable
+    fn available_parent(&self) -> Option<PathBuf> {
+        let root = self.tree.root();
+        if self.is_root_unavailable() {
+            Some(path::closest_dir(root))
+        } else {
+            root.parent().map(Path::to_path_buf)
+        }
+    }
+
+    /// copy or move the selection to the destination, with a new state
+    /// when the transfer needs a confirmation or takes time. When it's
+    /// done immediately, the trees are updated in place.
+    fn transfer_selection(&mut self, dst: &Path, copy: bool, page_height: i32) -> AppStateCmdResult {
+        let src = self.selected_path().to_path_buf();
+        let plan = if copy {
+            MovePlan::copy(&src, dst)
+        } else {
+            MovePlan::new(&src, dst)
+        };
+        let dst = match &plan {
+            Ok(plan) => plan.dst.clone(),
+            Err(e) => return AppStateCmdResult::DisplayError(e.to_string()),
+        };
+        match plan.and_then(MoveState::new) {
+            Ok(Some(state)) => AppStateCmdResult::NewState(Box::new(state)),
+            Ok(None) => {
+                let mut trees = vec![&mut self.tree];
+                if let Some(filtered_tree) = &mut self.filtered_tree {
+                    trees.push(filtered_tree);
+                }
+                for tree in trees {
+                    if !copy {
+                        tree.remove_path(&src);
+                    }
+                    if tree.insert_path(&dst) {
+                        tree.make_selection_visible(page_height);
+                    }
+                }
+                AppStateCmdResult::DisplayMessage(format!(
+                    "*{}* {} to *{}*",
+                    src.to_string_lossy(),
+                    if copy { "copied" } else { "moved" },
+                    dst.to_string_lossy(),
+                ))
+            }
+            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+        }
+    }
+
+    /// copy or move the marked entries, or the selection when none is
+    /// marked, into the dst directory. The marks are then cleared.
+    /// When the marked entries exceed the bulk limits, the transfers
+    /// only start after the user's confirmation, and the marks are
+    /// kept (the ones of moved entries being forgotten on refresh).
+    fn transfer_to(&mut self, dst: &Path, copy: bool, con: &AppContext) -> AppStateCmdResult {
+        let srcs: Vec<&Path> = if self.marked.is_empty() {
+            vec![self.selected_path()]
+        } else {
+            self.marked.iter().map(PathBuf::as_path).collect()
+        };
+        let plans: io::Result<Vec<MovePlan>> = srcs
+            .into_iter()
+            .map(|src| {
+                if copy {
+                    MovePlan::copy(src, dst)
+                } else {
+                    MovePlan::new(src, dst)
+                }
+            })
+            .collect();
+        if let Some(bulk) = con.bulk_limits.check(&self.marked_or_selected_paths()) {
+            // the marks are kept, so that they're not lost on cancel
+            return match plans {
+                Ok(plans) => match MoveState::confirming(plans, bulk) {
+                    Some(state) => AppStateCmdResult::NewState(Box::new(state)),
+                    None => AppStateCmdResult::Keep,
+                },
+                Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+            };
+        }
+        match plans.and_then(MoveState::for_plans) {
+            Ok(state) => {
+                self.marked.clear();
+                match state {
+                    Some(state) => AppStateCmdResult::NewState(Box::new(state)),
+                    None => AppStateCmdResult::RefreshState { clear_cache: true },
+                }
+            }
+            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+        }
+    }
+
+    /// put the marked entries, or the selection, in the clipboard
+    fn put_in_clipboard(&self, mode: FileClipboardMode) -> Result<AppStateCmdResult, ProgramError> {
+        let paths = if self.marked.is_empty() {
+            vec![self.selected_path().to_path_buf()]
+        } else {
+            self.marked.clone()
+        };
+        let count = paths.len();
+        let in_system_clipboard = FileClipboard::new(mode, paths).write()?;
+        Ok(AppStateCmdResult::DisplayMessage(format!(
+            "{} {} put in the {}clipboard{}",
+            count,
+            if count > 1 { "entries" } else { "entry" },
+            if in_system_clipboard { "" } else { "broot " },
+            match mode {
+                FileClipboardMode::Copy => "",
+                FileClipboardMode::Cut => ", to be moved",
+            },
+        )))
+    }
+
+    /// set the access and modification times of the marked entries,
+    /// or of the selection, then update their lines
+    fn set_times(&mut self, arg: Option<&String>) -> AppStateCmdResult {
+        let time = match arg {
+            Some(arg) => match mover::parse_time(arg) {
+                Ok(time) => time,
+                Err(e) => return AppStateCmdResult::DisplayError(e),
+            },
+            None => mover::now(),
+        };
+        let paths = if self.marked.is_empty() {
+            vec![self.selected_path().to_path_buf()]
+        } else {
+            self.marked.clone()
+        };
+        for path in &paths {
+            if let Err(e) = mover::set_times(path, time) {
+                return AppStateCmdResult::DisplayError(
+                    format!("{}: {}", path.to_string_lossy(), e)
+                );
+            }
+        }
+        // the dates of the parent directories are computed again
+        file_sum::clear_cache();
+        self.tree.reload_metadata(&paths);
+        if let Some(tree) = self.filtered_tree.as_mut() {
+            tree.reload_metadata(&paths);
+        }
+        AppStateCmdResult::DisplayMessage(format!(
+            "times of {} {} set",
+            paths.len(),
+            if paths.len() > 1 { "entries" } else { "entry" },
+        ))
+    }
+
+    /// copy, or move if they were cut, the files of the clipboard
+    /// into the selected directory
+    fn paste_files(&mut self, con: &AppContext) -> AppStateCmdResult {
+        let clipboard = match FileClipboard::read() {
+            Some(clipboard) => clipboard,
+            None => return AppStateCmdResult::DisplayError(
+                "there's no file in the clipboard".to_string(),
+            ),
+        };
+        if let Some(missing) = clipboard.paths.iter().find(|path| !path.exists()) {
+            return AppStateCmdResult::DisplayError(format!(
+                "`{}` doesn't exist anymore",
+                missing.to_string_lossy(),
+            ));
+        }
+        let copy = clipboard.mode == FileClipboardMode::Copy;
+        let dst = path::closest_dir(self.selected_path());
+        let plans: io::Result<Vec<MovePlan>> = clipboard.paths
+            .iter()
+            .map(|src| {
+                if copy {
+                    MovePlan::copy(src, &dst)
+                } else {
+                    MovePlan::new(src, &dst)
+                }
+            })
+            .collect();
+        let plans = match plans {
+            Ok(plans) => plans,
+            Err(e) => return AppStateCmdResult::DisplayError(e.to_string()),
+        };
+        // cut files won't be at the same place anymore once moved, so
+        // the clipboard is emptied, but only when all moves succeeded
+        let prepare = |state: MoveState| {
+            let state = if copy { state } else { state.clearing_file_clipboard() };
+            AppStateCmdResult::NewState(Box::new(state))
+        };
+        if let Some(bulk) = con.bulk_limits.check(&clipboard.paths) {
+            return match MoveState::confirming(plans, bulk) {
+                Some(state) => prepare(state),
+                None => AppStateCmdResult::Keep,
+            };
+        }
+        match MoveState::for_plans(plans) {
+            Ok(Some(state)) => prepare(state),
+            Ok(None) => {
+                if !copy {
+                    FileClipboard::clear();
+                }
+                AppStateCmdResult::RefreshState { clear_cache: true }
+            }
+            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+        }
+    }
+
+    /// let the user edit the paths of the marked entries (or of the
+    /// ones of the filtered tree) in $EDITOR, then display the
+    /// renames for confirmation
+    fn bulk_rename(&mut self, w: &mut W, con: &AppContext) -> Result<AppStateCmdResult, ProgramError> {
+        let srcs: Vec<PathBuf> = if !self.marked.is_empty() {
+            self.marked.clone()
+        } else if let Some(filtered_tree) = &self.filtered_tree {
+            filtered_tree.lines
+                .iter()
+                .skip(1) // the root
+                .filter(|line| line.is_selectable())
+                .map(|line| line.path.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if srcs.is_empty() {
+            return Ok(AppStateCmdResult::DisplayError(
+                "mark the entries to rename, or filter the tree".to_string(),
+            ));
+        }
+        if std::env::var("EDITOR").is_err() {
+            return Ok(AppStateCmdResult::DisplayError(
+                "the $EDITOR environment variable must be set".to_string(),
+            ));
+        }
+        let root = self.tree.root().clone();
+        let file = tempfile::Builder::new()
+            .prefix("broot-bulk-rename-")
+            .suffix(".txt")
+            .tempfile()?
+            .into_temp_path(); // removed when dropped
+        if let Err(e) = mover::write_sources(&root, &srcs, &file) {
+            return Ok(AppStateCmdResult::DisplayError(e.to_string()));
+        }
+        let launchable = Launchable::program(
+            vec!["$EDITOR".to_string(), file.to_string_lossy().to_string()],
+            None,
+        )?;
+        let exit = launchable.execute_program(Some(w), con.launch_args.is_inline(), false);
+        let bulk = match exit {
+            Ok(exit) if exit.status.success() => {
+                BulkRename::from_edited_file(&root, &srcs, &file).map_err(|e| e.to_string())
+            }
+            Ok(_) => Err("the editor exited with an error".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        Ok(match bulk {
+            Ok(bulk) if bulk.is_empty() => AppStateCmdResult::DisplayMessage(
+                "no name was changed".to_string(),
+            ),
+            Ok(bulk) => {
+                self.marked.clear();
+                AppStateCmdResult::NewState(Box::new(BulkRenameState::new(bulk)))
+            }
+            Err(e) => AppStateCmdResult::DisplayError(e),
+        })
+    }
+
+    /// rename the selection, updating the trees in place when
+    /// there's no need for a confirmation
+    fn rename_selection(&mut self, new_name: &str) -> AppStateCmdResult {
+        let new_name = new_name.trim();
+        if new_name.is_empty() || new_name.contains('/') || new_name.contains(std::path::MAIN_SEPARATOR) {
+            return AppStateCmdResult::DisplayError(
+                "the new name must be a file name, not a path".to_string(),
+            );
+        }
+        let old_path = self.selected_path().to_path_buf();
+        let new_path = match old_path.parent() {
+            Some(parent) => parent.join(new_name),
+            None => {
+                return AppStateCmdResult::DisplayError("this can't be renamed".to_string());
+            }
+        };
+        match MovePlan::exact(&old_path, new_path.clone()).and_then(MoveState::new) {
+            Ok(Some(state)) => AppStateCmdResult::NewState(Box::new(state)),
+            Ok(None) => {
+                self.tree.rename_path(&old_path, &new_path);
+                if let Some(filtered_tree) = &mut self.filtered_tree {
+                    filtered_tree.rename_path(&old_path, &new_path);
+                }
+                AppStateCmdResult::DisplayMessage(format!("renamed to *{}*", new_name))
+            }
+            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+        }
+    }
+
+    /// create a link to the selection at the destination (or in it
+    /// when it's a directory), adding it to the trees when it's
+    /// inside, and refreshing the other panels
+    fn link_selection(&mut self, dst: &Path, kind: LinkKind, page_height: i32) -> AppStateCmdResult {
+        let link = match mover::create_link(self.selected_path(), dst, kind) {
+            Ok(link) => link,
+            Err(e) => return AppStateCmdResult::DisplayError(e.to_string()),
+        };
+        let mut trees = vec![&mut self.tree];
+        if let Some(filtered_tree) = &mut self.filtered_tree {
+            trees.push(filtered_tree);
+        }
+        let mut inserted = false;
+        for tree in trees {
+            if tree.insert_path(&link) {
+                tree.make_selection_visible(page_height);
+                inserted = true;
+            }
+        }
+        if inserted {
+            AppStateCmdResult::DisplayMessage(format!("created *{}*", link.to_string_lossy()))
+        } else {
+            // the link is probably in the tree of another panel
+            AppStateCmdResult::RefreshState { clear_cache: false }
+        }
+    }
+
+    /// compute the digests of the marked files, or of the selected one,
+    /// and copy them to the clipboard. When there are several files,
+    /// they're also shown in a new panel
+    fn hash_selection(
+        &self,
+        algorithm: Algorithm,
+        con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let paths: Vec<PathBuf> = if self.marked.is_empty() {
+            vec![self.selected_path().to_path_buf()]
+        } else {
+            self.marked.iter().filter(|p| p.is_file()).cloned().collect()
+        };
+        if paths.is_empty() || !paths[0].is_file() {
+            return Ok(AppStateCmdResult::DisplayError(
+                "select a file or mark the files to hash".to_string(),
+            ));
+        }
+        let root = self.tree.root().clone();
+        let name = format!("{}sums.txt", algorithm);
+        let temp_text = TempText::spawn(&name, move |output, stop| {
+            let digests = checksum::write_sums(&root, &paths, algorithm, output, stop)?;
+            // a clipboard failure mustn't hide the digests
+            if let Err(e) = cli_clipboard::set_contents(digests.join("\n")) {
+                warn!("clipboard error: {:?}", e);
+                writeln!(output, "\n(the digests couldn't be copied to the clipboard)")?;
+            }
+            Ok(())
+        })?;
+        Ok(AppStateCmdResult::NewPanel {
+            state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        })
+    }
+
+    /// compare the marked file with the selection, or both marked
+    /// files, or the selection with the selection of the other panel
+    fn diff_files(&self, other_path: &Option<PathBuf>) -> AppStateCmdResult {
+        let selected = self.selected_path();
+        let (left, right) = match (self.marked.as_slice(), other_path) {
+            ([marked], _) if marked != selected => (marked.as_path(), selected),
+            ([a, b], _) => (a.as_path(), b.as_path()),
+            ([], Some(other)) if other != selected => (selected, other.as_path()),
+            _ => {
+                return AppStateCmdResult::DisplayError(
+                    "mark a file to compare with the selection, or select one in another panel"
+                        .to_string(),
+                );
+            }
+        };
+        if !left.is_file() || !right.is_file() {
+            return AppStateCmdResult::DisplayError("only files can be compared".to_string());
+        }
+        match DiffState::new(left.to_path_buf(), right.to_path_buf()) {
+            Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+        }
+    }
+
+    /// check the files listed in the selected checksums file, showing
+    /// the report in a new panel
+    fn check_sums(&self, con: &AppContext) -> Result<AppStateCmdResult, ProgramError> {
+        let sums_path = self.selected_path().to_path_buf();
+        let temp_text = TempText::spawn("hash-check.txt", move |output, stop| {
+            match checksum::verify(&sums_path, stop) {
+                Ok(results) => write!(output, "{}", checksum::report(&results)),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(()),
+                Err(e) => writeln!(output, "not a valid checksums file: {}", e),
+            }
+        })?;
+        Ok(AppStateCmdResult::NewPanel {
+            state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        })
+    }
+
+    /// create a directory (with its missing parents) or an empty file
+    /// at a path relative to the selected directory, then add it to
+    /// the trees and select it
+    fn create_path(
+        &mut self,
+        arg: &str,
+        dir: bool,
+        page_height: i32,
+    ) -> AppStateCmdResult {
+        let path = path::path_from(self.selected_path(), PathAnchor::Directory, arg.trim());
+        if fs::symlink_metadata(&path).is_ok() {
+            return AppStateCmdResult::DisplayError(format!(
+                "`{}` already exists",
+                path.to_string_lossy(),
+            ));
+        }
+        let creation = if dir {
+            fs::create_dir_all(&path)
+        } else {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| OpenOptions::new().write(true).create_new(true).open(&path))
+                .map(|_| ())
+        };
+        if let Err(e) = creation {
+            return AppStateCmdResult::DisplayError(e.to_string());
+        }
+        if self.tree.insert_path(&path) {
+            self.tree.make_selection_visible(page_height);
+        }
+        if let Some(filtered_tree) = &mut self.filtered_tree {
+            if filtered_tree.insert_path(&path) {
+                filtered_tree.make_selection_visible(page_height);
+            }
+        }
+        AppStateCmdResult::DisplayMessage(format!("created *{}*", arg.trim()))
+    }
+
+    /// the entries a verb acts on: the marked ones, or the
+    /// selection when none is marked
+    fn marked_or_selected_paths(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            vec![self.selected_path().to_path_buf()]
+        } else {
+            self.marked.clone()
+        }
+    }
+
+    /// move the marked entries, or the selection, to the trash,
+    /// then remove them from the trees
+    fn trash_selection(&mut self, page_height: i32) -> AppStateCmdResult {
+        let paths = self.marked_or_selected_paths();
+        if paths.contains(self.tree.root()) {
+            return AppStateCmdResult::DisplayError(
+                "the root of the tree can't be removed".to_string(),
+            );
+        }
+        for path in &paths {
+            if let Err(e) = trash::delete(path) {
+                return AppStateCmdResult::DisplayError(e.to_string());
+            }
+            if self.tree.remove_path(path) {
+                self.tree.make_selection_visible(page_height);
+            }
+            if let Some(filtered_tree) = &mut self.filtered_tree {
+                if filtered_tree.remove_path(path) {
+                    filtered_tree.make_selection_visible(page_height);
+                }
+            }
+            self.marked.retain(|marked| marked != path);
+        }
+        AppStateCmdResult::DisplayMessage(match paths.as_slice() {
+            [path] => format!(
+                "*{}* moved to the trash",
+                path.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string()),
+            ),
+            _ => format!("{} entries moved to the trash", paths.len()),
+        })
+    }
+
+    fn display_unavailable_root(
+        &self,
+        w: &mut W,
+        screen: &Screen,
+        area: Area,
+        panel_skin: &PanelSkin,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &area)?;
+        let mut text_area = area.clone();
+        text_area.pad_for_max_width(120);
+        let md = format!(
+            "\n**The root is unavailable:**\n\n`{}`\n\n\
+            It may be on a removable media which was unplugged or on a network share \
+            which was dropped. It will be displayed again as soon as it comes back.\n\n\
+            Hit *F5* to retry now, or type `:up` to focus the closest available parent.",
+            self.tree.root().to_string_lossy(),
+        );
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
     pub fn open_selection_stay_in_broot(
         &mut self,
+        w: &mut W,
         screen: &mut Screen,
         con: &AppContext,
         in_new_panel: bool,
@@ -109,14 +864,8 @@ impl BrowserState {
         let tree = self.displayed_tree();
         let line = tree.selected_line();
         match &line.line_type {
-            TreeLineType::File => match open::that(&line.path) {
-                Ok(exit_status) => {
-                    info!("open returned with exit_status {:?}", exit_status);
-                    Ok(AppStateCmdResult::Keep)
-                }
-                Err(e) => Ok(AppStateCmdResult::DisplayError(format!("{:?}", e))),
-            },
-            TreeLineType::Dir | TreeLineType::SymLinkToDir(_) => {
+            TreeLineType::File => handlers::open_file_stay(w, &line.path, con),
+            TreeLineType::Dir | TreeLineType::Submodule | TreeLineType::SymLinkToDir(_) => {
                 let mut target = line.target();
                 if tree.selection == 0 {
                     // opening the root would be going to where we already are.
@@ -142,10 +891,11 @@ impl BrowserState {
                 ))
             }
             TreeLineType::SymLinkToFile(target) => {
-                let path = PathBuf::from(target);
-                open::that(&path)?;
-                Ok(AppStateCmdResult::Keep)
+                handlers::open_file_stay(w, Path::new(target), con)
             }
+            TreeLineType::Special(special_type) => Ok(AppStateCmdResult::DisplayError(
+                format!("a {} can't be opened", special_type),
+            )),
             _ => {
                 unreachable!();
             }
@@ -161,9 +911,9 @@ impl BrowserState {
         let line = tree.selected_line();
         match &line.line_type {
             TreeLineType::File => make_opener(line.path.clone(), line.is_exe(), con),
-            TreeLineType::Dir | TreeLineType::SymLinkToDir(_) => {
+            TreeLineType::Dir | TreeLineType::Submodule | TreeLineType::SymLinkToDir(_) => {
                 Ok(if con.launch_args.cmd_export_path.is_some() {
-                    CD.to_cmd_result(w, line.as_selection(), &None, &None, con)?
+                    CD.to_cmd_result(w, line.as_selection(), &None, &None, None, con)?
                 } else {
                     AppStateCmdResult::DisplayError(
                         "This feature needs broot to be launched with the `br` script".to_owned(),
@@ -177,6 +927,9 @@ impl BrowserState {
                     con,
                 )
             }
+            TreeLineType::Special(special_type) => Ok(AppStateCmdResult::DisplayError(
+                format!("a {} can't be opened", special_type),
+            )),
             _ => {
                 unreachable!();
             }
@@ -228,19 +981,23 @@ fn make_opener(
             )?)
         }
     } else {
-        AppStateCmdResult::from(Launchable::opener(path))
+        AppStateCmdResult::from(handlers::file_opener(path, con)?)
     })
 }
 
 impl AppState for BrowserState {
 
     fn get_pending_task(&self) -> Option<&'static str> {
-        if self.pending_pattern.is_some() {
+        if self.is_root_unavailable() {
+            Some("waiting for the root")
+        } else if self.pending_pattern.is_some() {
             Some("searching")
         } else if self.displayed_tree().has_dir_missing_sum() {
             Some("computing stats")
         } else if self.displayed_tree().is_missing_git_status_computation() {
             Some("computing git status")
+        } else if self.displayed_tree().is_missing_last_commits() {
+            Some("looking for last commits")
         } else {
             None
         }
@@ -253,7 +1010,63 @@ impl AppState for BrowserState {
 
 
     fn selection(&self) -> Selection<'_> {
-        self.displayed_tree().selected_line().as_selection()
+        Selection {
+            marked: &self.marked,
+            ..self.displayed_tree().selected_line().as_selection()
+        }
+    }
+
+    fn label(&self) -> String {
+        let tree = self.displayed_tree();
+        let root = tree.root().to_string_lossy();
+        if tree.options.pattern.is_some() {
+            format!("{} /{}", root, tree.options.pattern.raw)
+        } else {
+            root.to_string()
+        }
+    }
+
+    fn tree_root(&self) -> Option<&Path> {
+        Some(self.displayed_tree().root())
+    }
+
+    fn tree_options(&self) -> Option<&TreeOptions> {
+        Some(&self.displayed_tree().options)
+    }
+
+    fn set_linked_root(&mut self, root: Option<PathBuf>) {
+        self.linked_root = root;
+    }
+
+    fn select_linked_path(&mut self, path: &Path, _screen: &Screen) {
+        let page_height = self.visible_height();
+        let tree = self.displayed_tree_mut();
+        if tree.try_select_path(path) {
+            tree.make_selection_visible(page_height);
+        }
+    }
+
+    fn with_tree_options(
+        &self,
+        options: &TreeOptions,
+        screen: &Screen,
+        con: &AppContext,
+    ) -> Option<Box<dyn AppState>> {
+        match self.new_state_with_options(screen, &|o| o.set_settings_from(options), con) {
+            Ok(Some(state)) => Some(Box::new(state)),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("failed to apply the broadcasted options: {:?}", e);
+                None
+            }
+        }
+    }
+
+    fn set_tree_settings(&mut self, options: &TreeOptions) {
+        self.tree.options.set_settings_from(options);
+        if let Some(tree) = &mut self.filtered_tree {
+            tree.options.set_settings_from(options);
+        }
     }
 
     fn clear_pending(&mut self) {
@@ -267,19 +1080,21 @@ impl AppState for BrowserState {
         _screen: &mut Screen,
         _con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
-        self.displayed_tree_mut().try_select_y(y as i32);
+        let y = y as i32 - self.area_top as i32;
+        self.displayed_tree_mut().try_select_y(y);
         Ok(AppStateCmdResult::Keep)
     }
 
     fn on_double_click(
         &mut self,
+        w: &mut W,
         _x: u16,
         y: u16,
         screen: &mut Screen,
         con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
-        if self.displayed_tree().selection == y as usize {
-            self.open_selection_stay_in_broot(screen, con, false, false)
+        if y >= self.area_top && self.displayed_tree().selection == (y - self.area_top) as usize {
+            self.open_selection_stay_in_broot(w, screen, con, false, false)
         } else {
             // A double click always come after a simple click at
             // same position. If it's not the selected line, it means
@@ -310,7 +1125,7 @@ impl AppState for BrowserState {
         screen: &mut Screen,
     ) -> Result<AppStateCmdResult, ProgramError> {
         let con = &cc.con;
-        let page_height = BrowserState::page_height(screen);
+        let page_height = self.visible_height();
         let bang = input_invocation
             .map(|inv| inv.bang)
             .unwrap_or(internal_exec.bang);
@@ -345,9 +1160,427 @@ impl AppState for BrowserState {
                 con,
                 self.displayed_tree().options.clone(),
             ),
-            Internal::up_tree => match self.displayed_tree().root().parent() {
+            Internal::focus_in_panel => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                let direction = match arg.map(|arg| arg.trim()) {
+                    Some("left") => Some(HDir::Left),
+                    None | Some("") | Some("right") => Some(HDir::Right),
+                    _ => None,
+                };
+                match direction {
+                    Some(direction) => internal_focus::new_state_in_panel_on_path(
+                        self.selected_path().to_path_buf(),
+                        screen,
+                        self.displayed_tree().options.clone(),
+                        con,
+                        direction,
+                    ),
+                    None => AppStateCmdResult::DisplayError(
+                        "the direction must be `left` or `right`".to_string(),
+                    ),
+                }
+            }
+            Internal::branch_glyphs => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                let branch_glyphs = match arg {
+                    // without argument we cycle through the presets
+                    None => Ok(self.displayed_tree().options.branch_glyphs.next_preset()),
+                    Some(name) => BranchGlyphs::from_name(name),
+                };
+                match branch_glyphs {
+                    Ok(branch_glyphs) => self.with_new_options(
+                        screen,
+                        &|o| o.branch_glyphs = branch_glyphs.clone(),
+                        bang,
+                        con,
+                    ),
+                    Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+                }
+            }
+            Internal::browse_image => {
+                let tag = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                let selected_path = self.selected_path();
+                let source = if let Some(tag) = tag {
+                    ImageSource::Tag(tag.to_string())
+                } else if oci::is_image_tarball(selected_path) {
+                    ImageSource::Tarball(selected_path.to_path_buf())
+                } else {
+                    return Ok(AppStateCmdResult::DisplayError(
+                        "select an image tarball or give the tag of an image".to_string()
+                    ));
+                };
+                let options = self.displayed_tree().options.without_pattern();
+                AppStateCmdResult::NewState(Box::new(ImageState::new(source, options)?))
+            }
+            Internal::grep_logs => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg.map(|arg| Regex::new(arg)) {
+                    None => AppStateCmdResult::DisplayError(
+                        "a regular expression is expected, eg `:grep_logs error`".to_string()
+                    ),
+                    Some(Err(e)) => AppStateCmdResult::DisplayError(
+                        format!("invalid regular expression: {}", e)
+                    ),
+                    Some(Ok(regex)) => {
+                        let dir = path::closest_dir(self.selected_path());
+                        let temp_text = TempText::spawn("grep-logs.txt", move |output, stop| {
+                            let count = logs::grep(&dir, &regex, output, stop)?;
+                            if count == 0 && !stop.load(Ordering::Relaxed) {
+                                writeln!(output, "no matching line found in logs")?;
+                            }
+                            Ok(())
+                        })?;
+                        AppStateCmdResult::NewPanel {
+                            state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+                            purpose: PanelPurpose::None,
+                            direction: HDir::Right,
+                        }
+                    }
+                }
+            }
+            Internal::open_list => {
+                let name = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match name.and_then(|name| con.lists.iter().find(|list| &list.name == name)) {
+                    None => AppStateCmdResult::DisplayError(format!(
+                        "a list name is expected, one of {:?}",
+                        con.lists.iter().map(|list| &list.name).collect::<Vec<&String>>(),
+                    )),
+                    Some(list_conf) => {
+                        let dir = path::closest_dir(self.selected_path());
+                        match ListState::new(list_conf, dir) {
+                            Ok(state) if bang => AppStateCmdResult::NewPanel {
+                                state: Box::new(state),
+                                purpose: PanelPurpose::None,
+                                direction: HDir::Right,
+                            },
+                            Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+                            Err(e) => AppStateCmdResult::DisplayError(
+                                format!("list command failed: {}", e)
+                            ),
+                        }
+                    }
+                }
+            }
+            Internal::profile => {
+                let name = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match name.and_then(|name| con.profiles.get(name)) {
+                    None => {
+                        let mut names: Vec<&String> = con.profiles.keys().collect();
+                        names.sort();
+                        AppStateCmdResult::DisplayError(format!(
+                            "a profile name is expected, one of {:?}",
+                            names,
+                        ))
+                    }
+                    Some(flags) => {
+                        let mut options = self.displayed_tree().options.clone();
+                        match options.apply_flags(flags) {
+                            Ok(()) => self.with_new_options(
+                                screen,
+                                &|o| o.set_settings_from(&options),
+                                bang,
+                                con,
+                            ),
+                            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+                        }
+                    }
+                }
+            }
+            Internal::bulk_rename => self.bulk_rename(w, con)?,
+            Internal::extract => {
+                let archive = self.selected_path().to_path_buf();
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                let dst = match arg {
+                    Some(arg) => path::path_from(&archive, PathAnchor::Parent, arg),
+                    None => archive::default_destination(&archive),
+                };
+                if !ArchiveFormat::of_file(&archive).is_some_and(ArchiveFormat::is_extractable) {
+                    AppStateCmdResult::DisplayError(
+                        "the selection isn't an archive broot knows how to extract".to_string(),
+                    )
+                } else {
+                    let task = ArchiveTask::Extract { archive, dst };
+                    AppStateCmdResult::NewState(Box::new(ArchiveState::new(task)))
+                }
+            }
+            Internal::archive => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                let srcs = if self.marked.is_empty() {
+                    vec![self.selected_path().to_path_buf()]
+                } else {
+                    self.marked.clone()
+                };
+                match arg {
+                    Some(arg) => {
+                        let archive = path::path_from(self.selected_path(), PathAnchor::Parent, arg);
+                        if fs::symlink_metadata(&archive).is_ok() {
+                            AppStateCmdResult::DisplayError(format!(
+                                "`{}` already exists",
+                                archive.to_string_lossy(),
+                            ))
+                        } else {
+                            let task = ArchiveTask::Create { srcs, archive };
+                            AppStateCmdResult::NewState(Box::new(ArchiveState::new(task)))
+                        }
+                    }
+                    None => AppStateCmdResult::DisplayError(
+                        "the path of the archive must be given as argument, eg `:archive backup.tar.gz`"
+                            .to_string(),
+                    ),
+                }
+            }
+            Internal::hash => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg.map_or(Ok(Algorithm::Sha256), |arg| arg.parse()) {
+                    Ok(algorithm) => self.hash_selection(algorithm, con)?,
+                    Err(e) => AppStateCmdResult::DisplayError(e),
+                }
+            }
+            Internal::hash_check => self.check_sums(con)?,
+            Internal::set_times => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                self.set_times(arg)
+            }
+            Internal::diff => self.diff_files(cc.other_path),
+            Internal::file_copy => self.put_in_clipboard(FileClipboardMode::Copy)?,
+            Internal::file_cut => self.put_in_clipboard(FileClipboardMode::Cut)?,
+            Internal::file_paste => self.paste_files(con),
+            Internal::git_log => match GitLogState::new(self.selected_path().to_path_buf()) {
+                Ok(state) if bang => AppStateCmdResult::NewPanel {
+                    state: Box::new(state),
+                    purpose: PanelPurpose::None,
+                    direction: HDir::Right,
+                },
+                Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+                Err(e) => AppStateCmdResult::DisplayError(format!("git log failed: {}", e.message())),
+            },
+            Internal::git_blame => {
+                let line = self.displayed_tree().selected_line();
+                if !line.is_file() {
+                    return Ok(AppStateCmdResult::DisplayError(
+                        "git_blame only applies to files".to_string(),
+                    ));
+                }
+                let state = GitBlameState::new(line.path.clone());
+                if bang {
+                    AppStateCmdResult::NewPanel {
+                        state: Box::new(state),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    }
+                } else {
+                    AppStateCmdResult::NewState(Box::new(state))
+                }
+            }
+            Internal::git_repos => {
+                let tree = self.displayed_tree();
+                let state = GitReposState::new(tree.root().clone(), tree.options.clone());
+                if bang {
+                    AppStateCmdResult::NewPanel {
+                        state: Box::new(state),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    }
+                } else {
+                    AppStateCmdResult::NewState(Box::new(state))
+                }
+            }
+            Internal::git_restore => {
+                let line = self.displayed_tree().selected_line();
+                if !line.is_file() {
+                    return Ok(AppStateCmdResult::DisplayError(
+                        "git_restore only applies to files".to_string(),
+                    ));
+                }
+                AppStateCmdResult::NewState(Box::new(GitRestoreState::new(line.path.clone())))
+            }
+            Internal::find_duplicates => {
+                let tree = self.displayed_tree();
+                let state = DuplicatesState::new(tree.root().clone(), &tree.options);
+                if bang && cc.preview.is_none() {
+                    AppStateCmdResult::NewPanel {
+                        state: Box::new(state),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    }
+                } else {
+                    AppStateCmdResult::NewState(Box::new(state))
+                }
+            }
+            #[cfg(unix)]
+            Internal::chmod => match crate::permissions::ChmodState::new(self.selected_path().to_path_buf()) {
+                Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+                Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+            },
+            #[cfg(not(unix))]
+            Internal::chmod => AppStateCmdResult::DisplayError(
+                "permissions can't be edited on this platform".to_string(),
+            ),
+            Internal::copy_to | Internal::move_to => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg {
+                    Some(arg) => self.transfer_selection(
+                        &path::path_from(self.selected_path(), PathAnchor::Parent, arg),
+                        internal_exec.internal == Internal::copy_to,
+                        page_height,
+                    ),
+                    None => AppStateCmdResult::DisplayError(
+                        "the destination must be given as argument".to_string(),
+                    ),
+                }
+            }
+            Internal::symlink | Internal::hardlink => {
+                let kind = if internal_exec.internal == Internal::symlink {
+                    LinkKind::Symbolic
+                } else {
+                    LinkKind::Hard
+                };
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match (arg, &cc.other_path) {
+                    (Some(arg), _) => {
+                        let dst = path::path_from(self.selected_path(), PathAnchor::Parent, arg);
+                        self.link_selection(&dst, kind, page_height)
+                    }
+                    (None, Some(other_path)) => {
+                        self.link_selection(&path::closest_dir(other_path), kind, page_height)
+                    }
+                    (None, None) => AppStateCmdResult::DisplayError(
+                        "the destination must be given as argument, or be selected in another panel"
+                            .to_string(),
+                    ),
+                }
+            }
+            Internal::mkdir | Internal::touch => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg {
+                    Some(arg) => self.create_path(
+                        arg,
+                        internal_exec.internal == Internal::mkdir,
+                        page_height,
+                    ),
+                    None => AppStateCmdResult::DisplayError(
+                        "the path must be given as argument".to_string(),
+                    ),
+                }
+            }
+            Internal::copy_to_panel | Internal::move_to_panel => match &cc.other_path {
+                Some(other_path) => self.transfer_to(
+                    &path::closest_dir(other_path),
+                    internal_exec.internal == Internal::copy_to_panel,
+                    con,
+                ),
+                None => AppStateCmdResult::DisplayError(
+                    "this verb needs exactly two panels".to_string(),
+                ),
+            },
+            Internal::rename => {
+                let new_name = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match new_name {
+                    Some(new_name) => self.rename_selection(new_name),
+                    None => {
+                        // the input is pre-filled with the current name, so
+                        // that the user can edit it then hit enter
+                        let name = self
+                            .selected_path()
+                            .file_name()
+                            .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+                        AppStateCmdResult::SetInputInvocation(VerbInvocation::new(
+                            Internal::rename.name().to_string(),
+                            Some(name),
+                            false,
+                        ))
+                    }
+                }
+            }
+            Internal::toggle_mark => self.toggle_mark(),
+            Internal::broadcast_options => AppStateCmdResult::BroadcastTreeOptions {
+                state: None,
+                options: Box::new(self.displayed_tree().options.clone()),
+            },
+            Internal::clear_marks => {
+                self.marked.clear();
+                AppStateCmdResult::Keep
+            }
+            Internal::rm => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg.map(|arg| mover::parse_secure_option(arg, con.secure_delete_passes)) {
+                    None => self.trash_selection(page_height),
+                    Some(Ok(passes)) => {
+                        let paths = self.marked_or_selected_paths();
+                        if paths.contains(self.tree.root()) {
+                            AppStateCmdResult::DisplayError(
+                                "the root of the tree can't be removed".to_string(),
+                            )
+                        } else {
+                            match ShredState::new(paths, passes) {
+                                Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+                                Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+                            }
+                        }
+                    }
+                    Some(Err(e)) => AppStateCmdResult::DisplayError(e.to_string()),
+                }
+            }
+            Internal::rm_permanently => {
+                if self.marked_or_selected_paths().contains(self.tree.root()) {
+                    AppStateCmdResult::DisplayError(
+                        "the root of the tree can't be removed".to_string(),
+                    )
+                } else {
+                    let bulk = con.bulk_limits.check(&self.marked_or_selected_paths());
+                    AppStateCmdResult::NewState(Box::new(ConfirmState::removal(
+                        self.selection(),
+                        bulk,
+                    )))
+                }
+            }
+            Internal::resolve_chain => {
+                let chain = immutable::resolve_chain(self.selected_path());
+                if chain.len() < 2 {
+                    AppStateCmdResult::DisplayError("the selection isn't a link".to_string())
+                } else {
+                    let text = immutable::chain_text(&chain);
+                    let temp_text = TempText::write("resolve-chain.txt", &text)?;
+                    AppStateCmdResult::NewPanel {
+                        state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    }
+                }
+            }
+            Internal::up_tree => match self.available_parent() {
                 Some(path) => internal_focus::on_path(
-                    path.to_path_buf(),
+                    path,
                     screen,
                     self.displayed_tree().options.clone(),
                     bang,
@@ -355,15 +1588,20 @@ impl AppState for BrowserState {
                 ),
                 None => AppStateCmdResult::DisplayError("no parent found".to_string()),
             },
-            Internal::open_stay => self.open_selection_stay_in_broot(screen, con, bang, false)?,
-            Internal::open_stay_filter => self.open_selection_stay_in_broot(screen, con, bang, true)?,
+            Internal::open_stay => self.open_selection_stay_in_broot(w, screen, con, bang, false)?,
+            Internal::open_stay_filter => self.open_selection_stay_in_broot(w, screen, con, bang, true)?,
             Internal::open_leave => self.open_selection_quit_broot(w, con)?,
             Internal::line_down => {
-                self.displayed_tree_mut().move_selection(1, page_height);
+                let tree = self.displayed_tree_mut();
+                tree.move_selection(1, page_height);
+                // the selection may have been scrolled out of view
+                tree.make_selection_visible(page_height);
                 AppStateCmdResult::Keep
             }
             Internal::line_up => {
-                self.displayed_tree_mut().move_selection(-1, page_height);
+                let tree = self.displayed_tree_mut();
+                tree.move_selection(-1, page_height);
+                tree.make_selection_visible(page_height);
                 AppStateCmdResult::Keep
             }
             Internal::previous_match => {
@@ -377,14 +1615,14 @@ impl AppState for BrowserState {
             Internal::page_down => {
                 let tree = self.displayed_tree_mut();
                 if page_height < tree.lines.len() as i32 {
-                    tree.try_scroll(page_height, page_height);
+                    tree.try_scroll(page_height, page_height, con.page_selection);
                 }
                 AppStateCmdResult::Keep
             }
             Internal::page_up => {
                 let tree = self.displayed_tree_mut();
                 if page_height < tree.lines.len() as i32 {
-                    tree.try_scroll(-page_height, page_height);
+                    tree.try_scroll(-page_height, page_height, con.page_selection);
                 }
                 AppStateCmdResult::Keep
             }
@@ -444,12 +1682,30 @@ impl AppState for BrowserState {
             Internal::print_tree => {
                 print::print_tree(&self.displayed_tree(), screen, &cc.panel_skin, con)?
             }
+            Internal::scroll_down => {
+                self.displayed_tree_mut().scroll_view(1, page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::scroll_up => {
+                self.displayed_tree_mut().scroll_view(-1, page_height);
+                AppStateCmdResult::Keep
+            }
             Internal::select_first => {
                 self.displayed_tree_mut().try_select_first();
                 AppStateCmdResult::Keep
             }
+            Internal::select_glob => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                match arg {
+                    Some(glob) => self.select_glob(glob, page_height),
+                    None => AppStateCmdResult::DisplayError(
+                        "a glob is expected, eg `:select_glob *.log`".to_string()
+                    ),
+                }
+            }
             Internal::select_last => {
-                let page_height = BrowserState::page_height(screen);
                 self.displayed_tree_mut().try_select_last(page_height);
                 AppStateCmdResult::Keep
             }
@@ -546,11 +1802,39 @@ impl AppState for BrowserState {
             Internal::toggle_files => {
                 self.with_new_options(screen, &|o: &mut TreeOptions| o.only_folders ^= true, bang, con)
             }
+            Internal::toggle_flat => {
+                self.with_new_options(screen, &|o| o.flat ^= true, bang, con)
+            }
             Internal::toggle_hidden => {
                 self.with_new_options(screen, &|o| o.show_hidden ^= true, bang, con)
             }
+            Internal::cycle_git_ignore => {
+                self.with_new_options(screen, &|o| o.git_ignore_mode = o.git_ignore_mode.next(), bang, con)
+            }
             Internal::toggle_git_ignore => {
-                self.with_new_options(screen, &|o| o.respect_git_ignore ^= true, bang, con)
+                self.with_new_options(
+                    screen, &|o| {
+                        o.git_ignore_mode = if o.git_ignore_mode.hides_ignored() {
+                            GitIgnoreMode::Nothing
+                        } else {
+                            GitIgnoreMode::All
+                        };
+                    },
+                    bang,
+                    con,
+                )
+            }
+            Internal::toggle_ignore_files => {
+                self.with_new_options(screen, &|o| o.respect_ignore_files ^= true, bang, con)
+            }
+            Internal::toggle_broot_ignore => {
+                self.with_new_options(screen, &|o| o.respect_broot_ignore ^= true, bang, con)
+            }
+            Internal::toggle_git_author => {
+                self.with_new_options(screen, &|o| o.show_git_author ^= true, bang, con)
+            }
+            Internal::toggle_git_commit_date => {
+                self.with_new_options(screen, &|o| o.show_git_commit_date ^= true, bang, con)
             }
             Internal::toggle_git_file_info => {
                 self.with_new_options(screen, &|o| o.show_git_file_info ^= true, bang, con)
@@ -558,6 +1842,7 @@ impl AppState for BrowserState {
             Internal::toggle_git_status => {
                 self.with_new_options(
                     screen, &|o| {
+                        o.git_status_filter = None;
                         if o.filter_by_git_status {
                             o.filter_by_git_status = false;
                         } else {
@@ -567,6 +1852,21 @@ impl AppState for BrowserState {
                     }, bang, con
                 )
             }
+            Internal::git_filter => {
+                let spec = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                self.git_filter(spec, screen, bang, con)
+            }
+            Internal::audit_permissions => {
+                let spec = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                self.audit_permissions(spec, screen, bang, con)
+            }
+            Internal::toggle_owner_mismatch => {
+                self.with_new_options(screen, &|o| o.filter_by_owner_mismatch ^= true, bang, con)
+            }
             Internal::toggle_perm => {
                 self.with_new_options(screen, &|o| o.show_permissions ^= true, bang, con)
             }
@@ -610,6 +1910,19 @@ impl AppState for BrowserState {
         has_previous_state: bool,
         con: &AppContext,
     ) -> Status {
+        if self.is_root_unavailable() {
+            return Status::from_error(
+                "Root unavailable: *F5* to retry, *:up* to focus the closest available parent"
+            );
+        }
+        if let Some(deltas) = self.displayed_tree().refresh_deltas {
+            if deltas.removed > 0 && deltas.is_recent() {
+                return Status::new(
+                    format!("{} entries disappeared since the previous refresh", deltas.removed),
+                    false,
+                );
+            }
+        }
         let mut ssb = con.standard_status.builder(
             AppStateType::Tree,
             self.selection(),
@@ -629,7 +1942,13 @@ impl AppState for BrowserState {
         con: &AppContext,
         dam: &mut Dam,
     ) {
-        if self.pending_pattern.is_some() {
+        if self.is_root_unavailable() {
+            // the root may come back (a removable media plugged again)
+            if !dam.wait_for_event(ROOT_CHECK_PERIOD) && !self.is_root_unavailable() {
+                info!("root is available again");
+                self.refresh(screen, con);
+            }
+        } else if self.pending_pattern.is_some() {
             let pattern_str = self.pending_pattern.raw.clone();
             let mut options = self.tree.options.clone();
             options.pattern = self.pending_pattern.take();
@@ -651,13 +1970,15 @@ impl AppState for BrowserState {
             self.total_search_required = false;
             if let Some(ref mut ft) = filtered_tree {
                 ft.try_select_best_match();
-                ft.make_selection_visible(BrowserState::page_height(screen));
+                ft.make_selection_visible(self.visible_height());
                 self.filtered_tree = filtered_tree;
             }
         } else if self.displayed_tree().is_missing_git_status_computation() {
             let root_path = self.displayed_tree().root();
             let git_status = git::get_tree_status(root_path, dam);
             self.displayed_tree_mut().git_status = git_status;
+        } else if self.displayed_tree().is_missing_last_commits() {
+            self.displayed_tree_mut().fetch_last_commits(dam);
         } else {
             self.displayed_tree_mut().fetch_some_missing_dir_sum(dam);
         }
@@ -666,25 +1987,45 @@ impl AppState for BrowserState {
     fn display(
         &mut self,
         w: &mut W,
-        _screen: &Screen,
+        screen: &Screen,
         area: Area,
         panel_skin: &PanelSkin,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
+        if self.is_root_unavailable() {
+            return self.display_unavailable_root(w, screen, area, panel_skin);
+        }
+        self.set_panel_width(area.width);
+        self.update_unpaired_lines();
+        self.area_top = area.top;
+        self.area_height = area.height;
         let dp = DisplayableTree {
             tree: &self.displayed_tree(),
             skin: &panel_skin.styles,
             cols: &con.cols,
             show_selection_mark: con.show_selection_mark,
+            marked: &self.marked,
+            unpaired: self.unpaired_lines.as_ref().map(|ul| &ul.paths),
             ext_colors: &con.ext_colors,
             area,
             in_app: true,
         };
-        dp.write_on(w)
+        self.selection_cropped = dp.write_on_checking_crop(w)?;
+        Ok(())
+    }
+
+    fn echo(&self) -> Option<String> {
+        if self.selection_cropped {
+            Some(format!("`{}`", self.selected_path().to_string_lossy()))
+        } else {
+            None
+        }
     }
 
     fn refresh(&mut self, screen: &Screen, con: &AppContext) -> Command {
         let page_height = BrowserState::page_height(screen) as usize;
+        // the marked entries may have been moved or removed
+        self.marked.retain(|path| path.exists());
         // refresh the base tree
         if let Err(e) = self.tree.refresh(page_height, con) {
             warn!("refreshing base tree failed : {:?}", e);
@@ -703,6 +2044,18 @@ impl AppState for BrowserState {
         )
     }
 
+    fn refresh_paths(&mut self, paths: &[PathBuf], screen: &Screen, con: &AppContext) {
+        let page_height = BrowserState::page_height(screen);
+        let mut refreshed = self.tree.refresh_paths(paths, page_height);
+        if let Some(tree) = self.filtered_tree.as_mut() {
+            refreshed &= tree.refresh_paths(paths, page_height);
+        }
+        if !refreshed {
+            // too many changes, the trees are rebuilt
+            self.refresh(screen, con);
+        }
+    }
+
     fn get_flags(&self) -> Vec<Flag> {
         let options = &self.displayed_tree().options;
         vec![
@@ -712,7 +2065,11 @@ impl AppState for BrowserState {
             },
             Flag {
                 name: "gi",
-                value: if options.respect_git_ignore { "y" } else { "n" },
+                value: match options.git_ignore_mode {
+                    GitIgnoreMode::All => "y",
+                    GitIgnoreMode::RepoOnly => "repo",
+                    GitIgnoreMode::Nothing => "n",
+                },
             },
         ]
     }
@@ -0,0 +1,227 @@
+//! computation of the digests of files, and verification of
+//! files against a checksums file as written by `sha256sum`
+
+use {
+    md5::Md5,
+    sha1::Sha1,
+    sha2::{Digest, Sha256},
+    std::{
+        fmt,
+        fs::{self, File},
+        io::{self, Read, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_ref() {
+            "md5" => Ok(Self::Md5),
+            "sha1" => Ok(Self::Sha1),
+            "sha256" | "" => Ok(Self::Sha256),
+            _ => Err(format!("unknown algorithm {:?}, expected md5, sha1 or sha256", s)),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+        })
+    }
+}
+
+impl Algorithm {
+    /// guess the algorithm from the length of a hexadecimal digest
+    pub fn of_hex_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(Self::Md5),
+            40 => Some(Self::Sha1),
+            64 => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+}
+
+fn interrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "hashing interrupted")
+}
+
+fn hex_digest<D: Digest>(path: &Path, stop: &AtomicBool) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Err(interrupted());
+        }
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// compute the digest of the file, in lowercase hexadecimal.
+///
+/// Fail with an error of kind Interrupted when the stop flag is raised.
+pub fn digest(path: &Path, algorithm: Algorithm, stop: &AtomicBool) -> io::Result<String> {
+    match algorithm {
+        Algorithm::Md5 => hex_digest::<Md5>(path, stop),
+        Algorithm::Sha1 => hex_digest::<Sha1>(path, stop),
+        Algorithm::Sha256 => hex_digest::<Sha256>(path, stop),
+    }
+}
+
+/// compute the digests of the files and write them, one per line as
+/// soon as computed, in the format of `sha256sum`, with paths relative
+/// to root when they're inside.
+///
+/// Return the digests.
+pub fn write_sums<W: Write>(
+    root: &Path,
+    paths: &[PathBuf],
+    algorithm: Algorithm,
+    output: &mut W,
+    stop: &AtomicBool,
+) -> io::Result<Vec<String>> {
+    let mut digests = Vec::new();
+    for path in paths {
+        let name = path.strip_prefix(root).unwrap_or(path);
+        let digest = digest(path, algorithm, stop)?;
+        writeln!(output, "{}  {}", digest, name.to_string_lossy())?;
+        digests.push(digest);
+    }
+    Ok(digests)
+}
+
+/// the result of the verification of a file listed in a
+/// checksums file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verification {
+    Ok,
+    Failed,
+    Missing,
+}
+
+/// parse a line of a checksums file, like
+/// `e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  empty.txt`
+/// (a `*` before the name marks a file read in binary mode, which
+/// doesn't change anything for us)
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.trim_end().splitn(2, char::is_whitespace);
+    let hex = parts.next()?;
+    let name = parts.next()?.trim_start();
+    let name = name.strip_prefix('*').unwrap_or(name);
+    if name.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((hex, name))
+}
+
+/// check all the files listed in the checksums file, whose paths
+/// are relative to its directory
+pub fn verify(
+    sums_path: &Path,
+    stop: &AtomicBool,
+) -> io::Result<Vec<(PathBuf, Verification)>> {
+    let content = fs::read_to_string(sums_path)?;
+    let dir = sums_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut results = Vec::new();
+    for line in content.lines() {
+        let (hex, name) = match parse_line(line) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        let algorithm = match Algorithm::of_hex_len(hex.len()) {
+            Some(algorithm) => algorithm,
+            None => continue,
+        };
+        let path = dir.join(name);
+        let verification = match digest(&path, algorithm, stop) {
+            Ok(digest) if digest.eq_ignore_ascii_case(hex) => Verification::Ok,
+            Ok(_) => Verification::Failed,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Err(e),
+            Err(_) => Verification::Missing,
+        };
+        results.push((path, verification));
+    }
+    if results.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no checksum found in the file",
+        ));
+    }
+    Ok(results)
+}
+
+/// a human readable report of a verification: a summary, then
+/// the files, failures first
+pub fn report(results: &[(PathBuf, Verification)]) -> String {
+    let count = |wanted: &Verification| results.iter().filter(|(_, v)| v == wanted).count();
+    let mut s = format!(
+        "{} files checked: {} OK, {} FAILED, {} MISSING\n\n",
+        results.len(),
+        count(&Verification::Ok),
+        count(&Verification::Failed),
+        count(&Verification::Missing),
+    );
+    for wanted in &[Verification::Failed, Verification::Missing, Verification::Ok] {
+        for (path, verification) in results.iter().filter(|(_, v)| v == wanted) {
+            let label = match verification {
+                Verification::Ok => "OK",
+                Verification::Failed => "FAILED",
+                Verification::Missing => "MISSING",
+            };
+            s.push_str(&format!("{:8}{}\n", label, path.to_string_lossy()));
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod checksum_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_parse_line() {
+        assert_eq!(
+            parse_line("d41d8cd98f00b204e9800998ecf8427e  empty.txt"),
+            Some(("d41d8cd98f00b204e9800998ecf8427e", "empty.txt")),
+        );
+        assert_eq!(
+            parse_line("d41d8cd98f00b204e9800998ecf8427e *dir/a b.bin"),
+            Some(("d41d8cd98f00b204e9800998ecf8427e", "dir/a b.bin")),
+        );
+        assert_eq!(parse_line("# a comment"), None);
+        assert_eq!(parse_line(""), None);
+    }
+
+    #[test]
+    fn check_algorithm_parsing() {
+        assert_eq!("SHA1".parse::<Algorithm>(), Ok(Algorithm::Sha1));
+        assert_eq!("".parse::<Algorithm>(), Ok(Algorithm::Sha256));
+        assert!("crc32".parse::<Algorithm>().is_err());
+        assert_eq!(Algorithm::of_hex_len(32), Some(Algorithm::Md5));
+    }
+}
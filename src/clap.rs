@@ -50,6 +50,11 @@ pub fn clap_app() -> clap::App<'static, 'static> {
                 .long("git-status")
                 .help("Only show files having an interesting git status, including hidden ones"),
         )
+        .arg(
+            clap::Arg::with_name("owner-mismatch")
+                .long("owner-mismatch")
+                .help("Only show files whose owner differs from the owner of their parent (or from the expected owner)"),
+        )
         .arg(
             clap::Arg::with_name("hidden")
                 .short("h")
@@ -74,6 +79,31 @@ pub fn clap_app() -> clap::App<'static, 'static> {
                 .long("no-show-gitignored")
                 .help("Don't show gitignored files"),
         )
+        .arg(
+            clap::Arg::with_name("repo-gitignore-only")
+                .long("repo-gitignore-only")
+                .help("Only hide the files ignored by the rules of the repositories, not by the global excludes"),
+        )
+        .arg(
+            clap::Arg::with_name("ignore-files")
+                .long("ignore-files")
+                .help("Hide the files ignored by the .ignore and .fdignore files"),
+        )
+        .arg(
+            clap::Arg::with_name("no-ignore-files")
+                .long("no-ignore-files")
+                .help("Don't apply the rules of the .ignore and .fdignore files"),
+        )
+        .arg(
+            clap::Arg::with_name("broot-ignore")
+                .long("broot-ignore")
+                .help("Hide the files ignored by the .brootignore files"),
+        )
+        .arg(
+            clap::Arg::with_name("no-broot-ignore")
+                .long("no-broot-ignore")
+                .help("Don't apply the rules of the .brootignore files"),
+        )
         .arg(
             clap::Arg::with_name("permissions")
                 .short("p")
@@ -136,6 +166,16 @@ pub fn clap_app() -> clap::App<'static, 'static> {
                 .long("no-trim-root")
                 .help("Don't trim the root level, show a scrollbar"),
         )
+        .arg(
+            clap::Arg::with_name("flat")
+                .long("flat")
+                .help("Display search results as a flat list of paths ranked by score"),
+        )
+        .arg(
+            clap::Arg::with_name("no-flat")
+                .long("no-flat")
+                .help("Display search results as a tree"),
+        )
         // other options
         .arg(
             clap::Arg::with_name("cmd-export-path")
@@ -156,10 +196,19 @@ pub fn clap_app() -> clap::App<'static, 'static> {
                 .takes_value(true)
                 .help("Semicolon separated paths to specific config files"),
         )
+        .arg(
+            clap::Arg::with_name("verb")
+                .long("verb")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("definition")
+                .help("Define a verb for this session, as `name:execution` (e.g. `x:rg {pattern} {directory}`)"),
+        )
         .arg(
             clap::Arg::with_name("height")
                 .long("height")
-                .help("Height (if you don't want to fill the screen or for file export)")
+                .help("Height, broot being then displayed inline below the previous output (also used for file export)")
                 .takes_value(true),
         )
         .arg(
@@ -174,6 +223,32 @@ pub fn clap_app() -> clap::App<'static, 'static> {
                 .long("install")
                 .help("Install or reinstall the br shell function"),
         )
+        .arg(
+            clap::Arg::with_name("bench")
+                .long("bench")
+                .takes_value(true)
+                .value_name("scenario")
+                .hidden(true)
+                .help("Replay a scenario on a generated tree and print the timings"),
+        )
+        .arg(
+            clap::Arg::with_name("bench-size")
+                .long("bench-size")
+                .takes_value(true)
+                .value_name("files")
+                .hidden(true)
+                .help("Number of files of the tree generated for --bench"),
+        )
+        .arg(
+            clap::Arg::with_name("quiet")
+                .long("quiet")
+                .help("Don't display startup messages nor propose to install the br shell function"),
+        )
+        .arg(
+            clap::Arg::with_name("restore-session")
+                .long("restore-session")
+                .help("Reopen the panels which were open when broot was last quitted"),
+        )
         .arg(
             clap::Arg::with_name("no-style")
                 .long("no-style")
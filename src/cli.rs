@@ -3,15 +3,16 @@
 use {
     crate::{
         app::{App, AppContext},
+        bench::{self, BenchRun, Scenario},
         conf::Conf,
         display::{self, Screen},
-        errors::{ProgramError, TreeBuildError},
+        errors::{ConfError, ProgramError, TreeBuildError},
         launchable::Launchable,
         shell_install::{ShellInstall, ShellInstallState},
         tree::TreeOptions,
-        verb::VerbStore,
+        verb::{VerbConf, VerbStore},
     },
-    clap::{self, ArgMatches},
+    clap::ArgMatches,
     crossterm::{
         self,
         cursor,
@@ -76,13 +77,60 @@ pub struct AppLaunchArgs {
     pub cmd_export_path: Option<String>,  // where to write the produced command (if required with --outcmd)
     pub tree_options: TreeOptions,        // initial tree options
     pub commands: Option<String>,         // commands passed as cli argument, still unparsed
-    pub height: Option<u16>,              // an optional height to replace the screen's one
+    pub height: Option<u16>,              // an optional height, broot being then displayed inline
     pub no_style: bool,                   // whether to remove all styles (including colors)
+    pub quiet: bool,                      // whether to skip startup messages
+    pub restore_session: bool,            // whether to reopen the panels of the last session
+    pub conf_paths: Vec<PathBuf>,         // the configuration files, read again on changes
+    pub verb_definitions: Vec<String>,    // the verbs defined on launch, unparsed
 
     #[cfg(feature="client-server")]
     pub listen: Option<String>,
 }
 
+impl AppLaunchArgs {
+    /// tell whether broot is displayed in the normal screen buffer,
+    /// below the previous output, instead of in the alternate screen
+    pub fn is_inline(&self) -> bool {
+        self.height.is_some()
+    }
+    /// read the configuration files again, for their changes to be
+    /// applied without restarting broot
+    pub fn reread_conf(&self) -> Result<Conf, ProgramError> {
+        let mut conf = Conf::default();
+        for path in &self.conf_paths {
+            conf.read_file(path)?;
+        }
+        add_launch_verbs(&mut conf, &self.verb_definitions)?;
+        Ok(conf)
+    }
+}
+
+/// add the verbs defined on launch to the configuration, where they
+/// take precedence over the ones of the config file(s)
+fn add_launch_verbs(config: &mut Conf, verb_definitions: &[String]) -> Result<(), ConfError> {
+    let cli_verbs = verb_definitions
+        .iter()
+        .map(|definition| VerbConf::from_cli_definition(definition))
+        .collect::<Result<Vec<VerbConf>, ConfError>>()?;
+    config.verbs.splice(0..0, cli_verbs);
+    Ok(())
+}
+
+/// apply the display settings of the configuration to tree options
+pub fn apply_conf_to_tree_options(tree_options: &mut TreeOptions, config: &Conf) {
+    if let Some(format) = &config.date_time_format {
+        tree_options.set_date_time_format(format.clone());
+    }
+    if let Some(branch_glyphs) = &config.branch_glyphs {
+        tree_options.branch_glyphs = branch_glyphs.clone();
+    }
+    if let Some(b) = config.sum_immutable_stores {
+        tree_options.sum_immutable = b;
+    }
+    tree_options.responsive_cols = config.responsive_cols.clone();
+}
+
 #[cfg(not(windows))]
 fn canonicalize_root(root: &Path) -> io::Result<PathBuf> {
     root.canonicalize()
@@ -151,19 +199,9 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         .value_of("conf")
         .map(|s| s.split(';').map(PathBuf::from).collect());
 
-    // if we don't run on a specific config file, we check the
-    // configuration
-    if specific_conf.is_none() && install_args.install != Some(false) {
-        let mut shell_install = ShellInstall::new(install_args.install == Some(true));
-        shell_install.check()?;
-        if shell_install.should_quit {
-            return Ok(None);
-        }
-    }
-
     // read the configuration file(s): either the standard one
     // or the ones required by the launch args
-    let config = match &specific_conf {
+    let mut config = match &specific_conf {
         Some(conf_paths) => {
             let mut conf = Conf::default();
             for path in conf_paths {
@@ -174,21 +212,32 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         _ => Conf::from_default_location()?,
     };
 
-    // tree options are built from the default_flags
-    // found in the config file(s) (if any) then overriden
-    // by the cli args
-    let mut tree_options = TreeOptions::default();
-    if !config.default_flags.is_empty() {
-        let clap_app = crate::clap::clap_app().setting(clap::AppSettings::NoBinaryName);
-        let flags_args = format!("-{}", &config.default_flags);
-        let conf_matches = clap_app.get_matches_from(vec![&flags_args]);
-        tree_options.apply(&conf_matches);
-    }
-    tree_options.apply(&cli_matches);
-    if let Some(format) = &config.date_time_format {
-        tree_options.set_date_time_format(format.clone());
+    // startup messages and proposals may be disabled, either at
+    // launch or in configuration, so that they don't get in the
+    // way of scripts
+    let quiet = cli_matches.is_present("quiet") || config.quiet == Some(true);
+
+    // if we don't run on a specific config file, we check the
+    // shell function is installed (unless told not to ask)
+    let check_install = match install_args.install {
+        Some(install) => install,
+        None => !quiet,
+    };
+    if specific_conf.is_none() && check_install {
+        let mut shell_install = ShellInstall::new(install_args.install == Some(true));
+        shell_install.check()?;
+        if shell_install.should_quit {
+            return Ok(None);
+        }
     }
 
+    // verbs defined on launch take precedence over the ones of
+    // the config file(s)
+    let verb_definitions: Vec<String> = cli_matches
+        .values_of("verb")
+        .map_or_else(Vec::new, |definitions| definitions.map(str::to_string).collect());
+    add_launch_verbs(&mut config, &verb_definitions)?;
+
     // verb store is completed from the config file(s)
     let mut verb_store = VerbStore::default();
     verb_store.init(&config);
@@ -199,8 +248,42 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
     let commands = cli_matches.value_of("commands").map(str::to_string);
     let no_style = cli_matches.is_present("no-style");
     let height = cli_matches.value_of("height").and_then(|s| s.parse().ok());
+    // the session is restored when asked at launch, or in configuration
+    // when no root is explicitly given
+    let restore_session = cli_matches.is_present("restore-session")
+        || (config.restore_session == Some(true) && !cli_matches.is_present("ROOT"));
+
+    // in bench mode, the root is a generated tree
+    let bench_run = cli_matches
+        .value_of("bench")
+        .map(|name| {
+            let size = cli_matches
+                .value_of("bench-size")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(bench::DEFAULT_SIZE);
+            Scenario::new(name).and_then(|scenario| BenchRun::new(scenario, size))
+        })
+        .transpose()?;
+    let root = match &bench_run {
+        Some(bench_run) => bench_run.root().to_path_buf(),
+        None => get_root_path(&cli_matches)?,
+    };
 
-    let root = get_root_path(&cli_matches)?;
+    // tree options are built from the default_flags
+    // found in the config file(s) (if any), then from the
+    // flags of the rules matching the root, then overriden
+    // by the cli args
+    let mut tree_options = TreeOptions::default();
+    if !config.default_flags.is_empty() {
+        tree_options.apply_flags(&config.default_flags)?;
+    }
+    for (pattern, flags) in &config.root_flags {
+        if root.ancestors().any(|dir| pattern.matches_path(dir)) {
+            tree_options.apply_flags(flags)?;
+        }
+    }
+    tree_options.apply(&cli_matches);
+    apply_conf_to_tree_options(&mut tree_options, &config);
 
     #[cfg(feature="client-server")]
     if let Some(server_name) = cli_matches.value_of("send") {
@@ -230,28 +313,48 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         commands,
         height,
         no_style,
+        quiet,
+        restore_session,
+        conf_paths: specific_conf.unwrap_or_else(|| vec![Conf::default_location().to_path_buf()]),
+        verb_definitions,
 
         #[cfg(feature="client-server")]
         listen: cli_matches.value_of("listen").map(str::to_string),
     };
 
     let context = AppContext::from(launch_args, verb_store, &config);
+    if let Some(bench_run) = bench_run {
+        bench_run.run(&context, &config)?;
+        return Ok(None);
+    }
     let mut w = display::writer();
     let mut screen = Screen::new(&context, &config)?;
+    if context.launch_args.is_inline() {
+        screen.prepare_inline(&mut w)?;
+    }
     let app = App::new(&context, &screen)?;
-    w.queue(EnterAlternateScreen)?;
+    let inline = context.launch_args.is_inline();
+    if !inline {
+        w.queue(EnterAlternateScreen)?;
+    }
     w.queue(cursor::DisableBlinking)?;
     w.queue(cursor::Hide)?;
     if !config.disable_mouse_capture {
         w.queue(EnableMouseCapture)?;
     }
-    let r = app.run(&mut w, &mut screen, &context, &config);
+    // the context is given to the app, which replaces it when the
+    // configuration is reloaded
+    let r = app.run(&mut w, &mut screen, context, &config);
     if !config.disable_mouse_capture {
         w.queue(DisableMouseCapture)?;
     }
     w.queue(cursor::Show)?;
     w.queue(cursor::EnableBlinking)?;
-    w.queue(LeaveAlternateScreen)?;
+    if inline {
+        screen.clear_inline(&mut w)?;
+    } else {
+        w.queue(LeaveAlternateScreen)?;
+    }
     w.flush()?;
     r
 }
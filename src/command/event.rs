@@ -3,23 +3,33 @@ use {
     crate::{
         app::{
             AppContext,
+            AppStateType,
             Selection,
         },
         display::W,
         errors::ProgramError,
         keys,
         skin::PanelSkin,
-        verb::{Internal, Verb, VerbExecution},
+        verb::{Internal, PrefixSearchResult, Verb, VerbExecution, VerbInvocation},
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
     },
     termimad::{Area, Event, InputField},
 };
 
+
 /// wrap the input of a panel,
 /// receive events and make commands
 pub struct PanelInput {
     pub input_field: InputField,
     tab_cycle_count: usize,
     input_before_cycle: Option<String>,
+    history: InputHistory,
+    history_cycle: Option<(String, usize)>, // the input before cycling, and the number of steps back
+    history_cycled: bool, // whether the last event moved in the history
+    ghost: Option<String>, // the proposed completion, displayed after the content
 }
 
 impl PanelInput {
@@ -29,11 +39,16 @@ impl PanelInput {
             input_field: InputField::new(area),
             tab_cycle_count: 0,
             input_before_cycle: None,
+            history: InputHistory::default(),
+            history_cycle: None,
+            history_cycled: false,
+            ghost: None,
         }
     }
 
     pub fn set_content(&mut self, content: &str) {
         self.input_field.set_content(content);
+        self.ghost = None;
     }
 
     pub fn get_content(&self) -> String {
@@ -51,9 +66,106 @@ impl PanelInput {
         self.input_field.focused = active;
         self.input_field.area = area;
         self.input_field.display_on(w)?;
+        if active {
+            self.display_ghost(w, panel_skin)?;
+        }
+        Ok(())
+    }
+
+    /// write the proposed completion after the content, leaving
+    /// the cell of the cursor free
+    fn display_ghost(
+        &self,
+        w: &mut W,
+        panel_skin: &PanelSkin,
+    ) -> Result<(), ProgramError> {
+        if let Some(ghost) = &self.ghost {
+            let area = &self.input_field.area;
+            let start = self.input_field.get_content().chars().count() + 1;
+            let width = area.width as usize;
+            if start < width {
+                let ghost: String = ghost.chars().take(width - start).collect();
+                w.queue(cursor::MoveTo(area.left + start as u16, area.top))?;
+                panel_skin.styles.input_ghost.queue_str(w, &ghost)?;
+            }
+        }
         Ok(())
     }
 
+    /// replace the content with the previous entry of the history
+    /// among the ones starting with what was typed before cycling
+    fn history_prev(&mut self, state_type: AppStateType) -> bool {
+        let (before, steps) = self.history_cycle
+            .take()
+            .unwrap_or_else(|| (self.input_field.get_content(), 0));
+        let entry = self.history.completions_of(&before, state_type).nth(steps).cloned();
+        self.history_cycled = true;
+        if let Some(entry) = entry {
+            self.input_field.set_content(&entry);
+            self.history_cycle = Some((before, steps + 1));
+            true
+        } else {
+            if steps > 0 {
+                self.history_cycle = Some((before, steps));
+            }
+            false
+        }
+    }
+
+    /// replace the content with the next entry of the history, or
+    /// with what was typed before cycling
+    fn history_next(&mut self, state_type: AppStateType) -> bool {
+        self.history_cycled = true;
+        match self.history_cycle.take() {
+            Some((before, steps)) if steps > 1 => {
+                let entry = self.history.completions_of(&before, state_type).nth(steps - 2).cloned();
+                if let Some(entry) = entry {
+                    self.input_field.set_content(&entry);
+                }
+                self.history_cycle = Some((before, steps - 1));
+                true
+            }
+            Some((before, _)) => {
+                self.input_field.set_content(&before);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// compute the completion proposed for the current content: the
+    /// end of the last matching executed command or, when typing
+    /// a verb name, the end of the first matching verb
+    fn compute_ghost(
+        &self,
+        con: &AppContext,
+        sel: Selection<'_>,
+        state_type: AppStateType,
+    ) -> Option<String> {
+        let raw = self.input_field.get_content();
+        if raw.is_empty() || self.history_cycle.is_some() {
+            return None;
+        }
+        let entry = self.history.completions_of(&raw, state_type).next();
+        if let Some(entry) = entry {
+            return Some(entry[raw.len()..].to_string());
+        }
+        let parts = CommandParts::from(raw);
+        match &parts.verb_invocation {
+            Some(invocation) if !invocation.is_empty() && invocation.args.is_none() => {
+                let start = &invocation.name;
+                let name = match con.verb_store.search_sel(start, sel) {
+                    PrefixSearchResult::Match(name, _) => Some(name),
+                    PrefixSearchResult::Matches(names) => names.first().copied(),
+                    PrefixSearchResult::NoMatch => None,
+                };
+                name.filter(|name| name.len() > start.len() && name.starts_with(start.as_str()))
+                    .map(|name| name[start.len()..].to_string())
+            }
+            _ => None,
+        }
+    }
+
     /// consume the event to
     /// - maybe change the input
     /// - build a command
@@ -63,8 +175,14 @@ impl PanelInput {
         event: Event,
         con: &AppContext,
         sel: Selection<'_>,
+        state_type: AppStateType,
     ) -> Result<Command, ProgramError> {
-        let cmd = self.get_command(event, con, sel);
+        let cmd = self.get_command(event, con, sel, state_type);
+        if !self.history_cycled {
+            self.history_cycle = None;
+        }
+        self.history_cycled = false;
+        self.ghost = self.compute_ghost(con, sel, state_type);
         self.input_field.display_on(w)?;
         Ok(cmd)
     }
@@ -76,6 +194,7 @@ impl PanelInput {
         &mut self,
         verb: &Verb,
         _con: &AppContext,
+        state_type: AppStateType,
     ) -> bool {
         if let VerbExecution::Internal(internal_exec) = &verb.execution {
             match internal_exec.internal {
@@ -89,6 +208,13 @@ impl PanelInput {
                 Internal::input_go_word_right => self.input_field.move_word_right(),
                 Internal::input_go_to_start => self.input_field.move_to_start(),
                 Internal::input_go_to_end => self.input_field.move_to_end(),
+                Internal::input_history_next => self.history_next(state_type),
+                Internal::input_history_prev => self.history_prev(state_type),
+                Internal::input_set => {
+                    let content = internal_exec.arg.as_deref().unwrap_or("");
+                    self.input_field.set_content(content);
+                    true
+                }
                 _ => false,
             }
         } else {
@@ -104,6 +230,7 @@ impl PanelInput {
         event: Event,
         con: &AppContext,
         sel: Selection<'_>,
+        state_type: AppStateType,
     ) -> Command {
         match event {
             Event::Click(x, y, ..) => {
@@ -186,8 +313,23 @@ impl PanelInput {
                     self.input_before_cycle = None;
                 }
 
-                if key == keys::ENTER && parts.verb_invocation.is_some() {
-                    return Command::from_parts(parts, true);
+                // accepting the proposed completion, when the cursor is at the end
+                if key == keys::RIGHT {
+                    if let Some(ghost) = self.ghost.take() {
+                        if self.input_field.move_right() {
+                            return Command::from_raw(self.input_field.get_content(), false);
+                        }
+                        let raw = format!("{}{}", raw, ghost);
+                        self.input_field.set_content(&raw);
+                        return Command::from_raw(raw, false);
+                    }
+                }
+
+                if key == keys::ENTER {
+                    self.history.add(&raw, state_type);
+                    if parts.verb_invocation.is_some() {
+                        return Command::from_parts(parts, true);
+                    }
                 }
 
                 if key == keys::QUESTION && (raw.is_empty() || parts.verb_invocation.is_some()) {
@@ -203,10 +345,24 @@ impl PanelInput {
                 for (index, verb) in con.verb_store.verbs.iter().enumerate() {
                     for verb_key in &verb.keys {
                         if *verb_key == key {
-                            if self.handle_input_related_verb(verb, con) {
+                            if self.handle_input_related_verb(verb, con, state_type) {
                                 return Command::from_raw(self.input_field.get_content(), false);
                             }
-                            if sel.stype.respects(verb.selection_condition) {
+                            if verb.applies_to(sel) {
+                                if parts.verb_invocation.is_none() && verb.needs_args() {
+                                    if let Some(name) = verb.names.first() {
+                                        // the verb can't be executed yet: the input
+                                        // is prefilled so that the user types the
+                                        // arguments then hits enter
+                                        let raw = format!(
+                                            "{}{}",
+                                            &parts.raw_pattern,
+                                            VerbInvocation::new(name.as_str(), Some(""), false),
+                                        );
+                                        self.input_field.set_content(&raw);
+                                        return Command::from_raw(raw, false);
+                                    }
+                                }
                                 return Command::VerbTrigger {
                                     index,
                                     input_invocation: parts.verb_invocation,
@@ -0,0 +1,115 @@
+use {
+    super::CommandParts,
+    crate::app::AppStateType,
+    std::collections::HashMap,
+};
+
+/// max number of entries remembered in each list
+const MAX_HISTORY_LEN: usize = 100;
+
+/// the inputs executed in a panel, remembered so that they can be
+/// proposed again. The verb invocations and the patterns are kept
+/// in separate lists, and the patterns are kept by type of state,
+/// so that a search in a preview isn't proposed in a tree.
+#[derive(Debug, Default)]
+pub struct InputHistory {
+    verbs: Vec<String>,
+    patterns: HashMap<AppStateType, Vec<String>>,
+}
+
+/// tell whether the raw input must go in the verbs list
+fn is_verb_input(raw: &str) -> bool {
+    CommandParts::from(raw.to_string()).verb_invocation.is_some()
+}
+
+impl InputHistory {
+    /// remember the input, removing a previous occurrence
+    pub fn add(&mut self, raw: &str, state_type: AppStateType) {
+        if raw.is_empty() {
+            return;
+        }
+        let entries = if is_verb_input(raw) {
+            &mut self.verbs
+        } else {
+            self.patterns.entry(state_type).or_default()
+        };
+        entries.retain(|entry| entry != raw);
+        if entries.len() >= MAX_HISTORY_LEN {
+            entries.remove(0);
+        }
+        entries.push(raw.to_string());
+    }
+
+    /// return the entries of the list relevant for the given input,
+    /// most recent last
+    pub fn entries_for(&self, raw: &str, state_type: AppStateType) -> &[String] {
+        if is_verb_input(raw) {
+            &self.verbs
+        } else {
+            self.patterns
+                .get(&state_type)
+                .map(Vec::as_slice)
+                .unwrap_or(&[])
+        }
+    }
+
+    /// return the entries starting with the given input and longer,
+    /// most recent first
+    pub fn completions_of<'h>(
+        &'h self,
+        raw: &'h str,
+        state_type: AppStateType,
+    ) -> impl Iterator<Item = &'h String> {
+        self.entries_for(raw, state_type)
+            .iter()
+            .rev()
+            .filter(move |entry| entry.len() > raw.len() && entry.starts_with(raw))
+    }
+}
+
+#[cfg(test)]
+mod input_history_tests {
+    use super::*;
+    #[test]
+    fn check_separate_lists() {
+        let mut history = InputHistory::default();
+        history.add("toml", AppStateType::Tree);
+        history.add(":cd", AppStateType::Tree);
+        history.add("fn", AppStateType::Preview);
+        assert_eq!(history.entries_for("t", AppStateType::Tree), &["toml"]);
+        assert_eq!(history.entries_for(":c", AppStateType::Preview), &[":cd"]);
+        assert_eq!(history.entries_for("f", AppStateType::Preview), &["fn"]);
+        assert!(history.entries_for("f", AppStateType::Help).is_empty());
+    }
+    #[test]
+    fn check_no_duplicate_entries() {
+        let mut history = InputHistory::default();
+        history.add("", AppStateType::Tree);
+        history.add("a", AppStateType::Tree);
+        history.add("b", AppStateType::Tree);
+        history.add("a", AppStateType::Tree);
+        assert_eq!(history.entries_for("", AppStateType::Tree), &["b", "a"]);
+    }
+    #[test]
+    fn check_max_len() {
+        let mut history = InputHistory::default();
+        for i in 0..=MAX_HISTORY_LEN {
+            history.add(&i.to_string(), AppStateType::Tree);
+        }
+        let entries = history.entries_for("", AppStateType::Tree);
+        assert_eq!(entries.len(), MAX_HISTORY_LEN);
+        assert_eq!(entries[0], "1");
+    }
+    #[test]
+    fn check_completions() {
+        let mut history = InputHistory::default();
+        history.add("src", AppStateType::Tree);
+        history.add("s", AppStateType::Tree);
+        history.add("srcs", AppStateType::Tree);
+        history.add("target", AppStateType::Tree);
+        let completions: Vec<&String> = history
+            .completions_of("s", AppStateType::Tree)
+            .collect();
+        assert_eq!(completions, vec!["srcs", "src"]);
+    }
+}
@@ -1,6 +1,7 @@
 mod command;
 mod completion;
 mod event;
+mod input_history;
 mod parts;
 mod sequence;
 mod scroll;
@@ -10,6 +11,7 @@ pub use {
     command::Command,
     completion::Completions,
     event::PanelInput,
+    input_history::InputHistory,
     parts::CommandParts,
     sequence::Sequence,
     scroll::ScrollCommand,
@@ -27,6 +27,12 @@ impl fmt::Display for CommandParts {
 
 impl CommandParts {
 
+    /// parse the raw input into a pattern expression and an optional
+    /// verb invocation.
+    ///
+    /// This function is pure and accepts any string (it can't fail nor
+    /// panic), malformed patterns being detected when the pattern is
+    /// built.
     pub fn from(
         mut raw: String,
     ) -> Self {
@@ -48,8 +54,7 @@ impl CommandParts {
                     invocation_start_pos = Some(pos);
                     break;
                 }
-                if c == '/' {
-                    pt.mutate_or_create_atom(PatternParts::default).add_part();
+                if c == '/' && pt.mutate_or_create_atom(PatternParts::default).add_part() {
                     continue;
                 }
                 let allow_inter_pattern_token = match pt.current_atom() {
@@ -12,7 +12,7 @@ use {
 
 /// an unparsed sequence with its separator (which may be
 /// different from the one provided by local_separator())
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Sequence {
     pub separator: String,
     pub raw: String,
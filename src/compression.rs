@@ -0,0 +1,73 @@
+//! transparent decompression of the files whose extension
+//! denotes a compression format, so that they can be read
+//! (previewed, searched) like normal files.
+//!
+//! Only single-file compressions are handled: archives (like
+//! .tar.gz files) aren't decompressed.
+
+use {
+    std::{
+        fs::File,
+        io::{self, BufReader, Read},
+        path::Path,
+    },
+};
+
+/// a compression format broot knows how to read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Bzip2,
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+/// the max size of the decompressed content of a file
+/// which may be previewed
+pub const MAX_DECOMPRESSED_SIZE: usize = 50 * 1024 * 1024;
+
+impl Compression {
+    /// determine the compression format from the extension of the file
+    pub fn of_path(path: &Path) -> Option<Self> {
+        let is_tar = path.file_stem()
+            .and_then(|stem| Path::new(stem).extension())
+            .is_some_and(|ext| ext == "tar");
+        if is_tar {
+            return None;
+        }
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("bz2") => Some(Self::Bzip2),
+            Some("gz") => Some(Self::Gzip),
+            Some("xz") => Some(Self::Xz),
+            Some("zst") => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// open the file for reading, decompressing it on the fly
+/// if its extension denotes a known compression format
+pub fn open(path: &Path) -> io::Result<Box<dyn Read>> {
+    let file = File::open(path)?;
+    Ok(match Compression::of_path(path) {
+        Some(Compression::Bzip2) => Box::new(bzip2::read::BzDecoder::new(file)),
+        Some(Compression::Gzip) => Box::new(flate2::read::GzDecoder::new(file)),
+        Some(Compression::Xz) => Box::new(xz2::read::XzDecoder::new(file)),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(file)?),
+        None => Box::new(BufReader::new(file)),
+    })
+}
+
+/// read the decompressed content of a compressed file, up
+/// to max_size bytes.
+///
+/// Return None if the file isn't compressed (it should then
+/// be read normally).
+pub fn read_decompressed(path: &Path, max_size: usize) -> io::Result<Option<Vec<u8>>> {
+    if Compression::of_path(path).is_none() {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    open(path)?.take(max_size as u64).read_to_end(&mut bytes)?;
+    Ok(Some(bytes))
+}
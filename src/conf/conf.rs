@@ -7,13 +7,17 @@ use {
     },
     crate::{
         app::SelectionType,
-        display::{Col, Cols},
+        display::{BranchGlyphs, Col, Cols, PanelsDirection, ResponsiveCols},
         errors::ConfError,
+        handlers::{Handler, HandlerMap},
         keys,
+        list::ListConf,
+        path::path_from,
+        path_anchor::PathAnchor,
         pattern::{SearchModeMap, SearchModeMapEntry},
         skin::{ExtColorMap, SkinEntry},
         tree::*,
-        verb::VerbConf,
+        verb::{ExitAction, FileFilter, VerbConf, WorkingDir},
     },
     crossterm::style::Attribute,
     std::{
@@ -25,9 +29,11 @@ use {
 };
 
 /// The configuration read from conf.toml file(s)
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Conf {
     pub default_flags: String, // the flags to apply before cli ones
+    pub root_flags: Vec<(glob::Pattern, String)>, // flags applied when the root is in a matching dir
+    pub trusted_local_confs: Vec<glob::Pattern>, // dirs whose .broot.toml file may be applied
     pub date_time_format: Option<String>,
     pub verbs: Vec<VerbConf>,
     pub skin: HashMap<String, SkinEntry>,
@@ -36,9 +42,37 @@ pub struct Conf {
     pub disable_mouse_capture: bool,
     pub cols_order: Option<Cols>,
     pub show_selection_mark: Option<bool>,
+    pub show_line_numbers: Option<bool>,
+    pub max_preview_size: Option<u16>, // in megabytes
     pub ext_colors: ExtColorMap,
     pub syntax_theme: Option<String>,
     pub true_colors: Option<bool>,
+    pub kitty_graphics: Option<bool>,
+    pub sixel_graphics: Option<bool>,
+    pub branch_glyphs: Option<BranchGlyphs>,
+    pub expected_owner: Option<String>,
+    pub sum_immutable_stores: Option<bool>,
+    pub lists: Vec<ListConf>,
+    pub profiles: HashMap<String, String>,
+    pub handlers: HandlerMap,
+    pub quiet: Option<bool>,
+    pub show_refresh_deltas: Option<bool>,
+    pub page_selection: Option<PageSelection>,
+    pub panels_direction: Option<PanelsDirection>,
+    pub min_panel_width: Option<u16>,
+    pub min_panel_height: Option<u16>,
+    pub panel_titles: Option<bool>,
+    pub responsive_cols: ResponsiveCols,
+    pub commander: Option<bool>,
+    pub broadcast_tree_options: Option<bool>,
+    pub floating_panels: Option<bool>,
+    pub restore_session: Option<bool>,
+    pub bulk_confirm_count: Option<u16>,
+    pub bulk_confirm_gigabytes: Option<u16>,
+    pub secure_delete_passes: Option<u16>,
+    pub startup_messages: Vec<String>, // not read from file: notices to display at launch
+    pub files: Vec<PathBuf>, // not read from file: the files read, imported ones included
+    pub local_default_flags: String, // not read from file: the default flags of the local conf file
 }
 
 fn string_field(value: &Value, field_name: &str) -> Option<String> {
@@ -52,6 +86,29 @@ fn string_field(value: &Value, field_name: &str) -> Option<String> {
     None
 }
 
+fn string_array_field(value: &Value, field_name: &str) -> Option<Vec<String>> {
+    if let Value::Table(tbl) = value {
+        if let Some(Value::Array(arr)) = tbl.get(field_name) {
+            return arr
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_string()))
+                .collect();
+        }
+    }
+    None
+}
+
+fn u16_field(value: &Value, field_name: &str) -> Option<u16> {
+    if let Value::Table(tbl) = value {
+        if let Some(Value::Integer(i)) = tbl.get(field_name) {
+            if *i > 0 && *i <= i64::from(u16::MAX) {
+                return Some(*i as u16);
+            }
+        }
+    }
+    None
+}
+
 fn bool_field(value: &Value, field_name: &str) -> Option<bool> {
     if let Value::Table(tbl) = value {
         if let Some(Value::Boolean(b)) = tbl.get(field_name) {
@@ -74,21 +131,23 @@ impl Conf {
     /// Create it if it doesn't exist
     pub fn from_default_location() -> Result<Conf, ConfError> {
         let conf_filepath = Conf::default_location();
+        let mut conf = Conf::default();
         if !conf_filepath.exists() {
             Conf::write_sample(&conf_filepath)?;
-            println!(
-                "New Configuration file written in {}{:?}{}.",
-                Attribute::Bold,
-                &conf_filepath,
-                Attribute::Reset,
-            );
-            println!("You should have a look at it.");
+            conf.startup_messages.push(format!(
+                "New configuration file written in `{}`. You should have a look at it.",
+                conf_filepath.to_string_lossy(),
+            ));
         }
-        let mut conf = Conf::default();
         match conf.read_file(&conf_filepath) {
             Err(e) => {
-                println!("Failed to read configuration in {:?}.", &conf_filepath);
-                println!("Please delete or fix this file.");
+                eprintln!(
+                    "Failed to read configuration in {}{:?}{}.",
+                    Attribute::Bold,
+                    &conf_filepath,
+                    Attribute::Reset,
+                );
+                eprintln!("Please delete or fix this file.");
                 Err(e)
             }
             _ => Ok(conf),
@@ -105,7 +164,17 @@ impl Conf {
     /// read the configuration from a given path. Assume it exists.
     /// stderr is supposed to be a valid solution for displaying errors
     /// (i.e. this function is called before or after the terminal alternation)
+    ///
+    /// The files listed in `imports` are then read, in order, their
+    /// settings replacing the ones of the importing file. Their verbs
+    /// and special paths are put before the ones of the importing file
+    /// so that they take precedence too.
     pub fn read_file(&mut self, filepath: &Path) -> Result<(), ConfError> {
+        // canonical paths are stored so that cycles are detected
+        // whatever the way files are referenced
+        self.files.push(fs::canonicalize(filepath).unwrap_or_else(|_| filepath.to_path_buf()));
+        let verbs_start = self.verbs.len();
+        let special_paths_start = self.special_paths.len();
         let data = fs::read_to_string(filepath)?;
         let root: Value = data.parse::<Value>()?;
         // reading default flags
@@ -115,22 +184,79 @@ impl Conf {
             // (the last ones may reverse the first ones)
             self.default_flags.push_str(&s);
         }
+        // flags depending on the directory broot is launched in
+        if let Some(Value::Table(root_flags_tbl)) = &root.get("root-flags") {
+            for (glob, flags) in root_flags_tbl.iter() {
+                let flags = match flags.as_str() {
+                    Some(flags) => flags.to_string(),
+                    None => {
+                        return Err(ConfError::InvalidRootFlags { glob: glob.to_string() });
+                    }
+                };
+                // a glob starting with ~ is relative to the user home
+                let glob = if glob.starts_with('~') {
+                    path_from(filepath, PathAnchor::Parent, glob).to_string_lossy().to_string()
+                } else {
+                    glob.to_string()
+                };
+                let pattern = glob::Pattern::new(&glob)
+                    .map_err(|_| ConfError::InvalidRootFlags { glob })?;
+                self.root_flags.push((pattern, flags));
+            }
+        }
+        // the directories whose local configuration file is applied
+        if let Some(globs) = string_array_field(&root, "trusted_local_confs") {
+            for glob in globs {
+                let glob = if glob.starts_with('~') {
+                    path_from(filepath, PathAnchor::Parent, &glob).to_string_lossy().to_string()
+                } else {
+                    glob
+                };
+                let pattern = glob::Pattern::new(&glob)
+                    .map_err(|_| ConfError::InvalidTrustedLocalConf { glob })?;
+                self.trusted_local_confs.push(pattern);
+            }
+        }
         // date/time format
-        self.date_time_format = string_field(&root, "date_time_format");
+        if let Some(s) = string_field(&root, "date_time_format") {
+            self.date_time_format = Some(s);
+        }
         // reading the optional theme for syntect
-        self.syntax_theme = string_field(&root, "syntax_theme");
+        if let Some(s) = string_field(&root, "syntax_theme") {
+            self.syntax_theme = Some(s);
+        }
         // mouse capture
         if let Some(mouse_capture) = bool_field(&root, "capture_mouse") {
             self.disable_mouse_capture = !mouse_capture;
         }
         // cols order
-        self.cols_order = string_field(&root, "cols_order")
-            .map(|s| Col::parse_cols(&s))
-            .transpose()?;
+        if let Some(s) = string_field(&root, "cols_order") {
+            self.cols_order = Some(Col::parse_cols(&s)?);
+        }
+        // branch glyphs, either a preset or a custom set
+        if let Some(name) = string_field(&root, "branch_glyphs") {
+            self.branch_glyphs = Some(BranchGlyphs::from_name(&name)?);
+        }
+        if let Some(tbl) = root.get("branch-glyphs") {
+            let default = BranchGlyphs::default();
+            let glyphs = BranchGlyphs {
+                name: "custom".to_string(),
+                middle: string_field(tbl, "middle").unwrap_or(default.middle),
+                vertical: string_field(tbl, "vertical").unwrap_or(default.vertical),
+                last: string_field(tbl, "last").unwrap_or(default.last),
+                blank: string_field(tbl, "blank").unwrap_or(default.blank),
+                filling: default.filling,
+            };
+            glyphs.check()?;
+            self.branch_glyphs = Some(glyphs);
+        }
         // reading verbs
         if let Some(Value::Array(verbs_value)) = &root.get("verbs") {
             for verb_value in verbs_value.iter() {
-                let invocation = string_field(verb_value, "invocation");
+                let names = string_array_field(verb_value, "names").unwrap_or_default();
+                // without invocation, the first name is used as invocation
+                let invocation = string_field(verb_value, "invocation")
+                    .or_else(|| names.first().cloned());
                 let key = string_field(verb_value, "key")
                     .map(|s| keys::parse_key(&s))
                     .transpose()?;
@@ -141,7 +267,9 @@ impl Conf {
                         });
                     }
                 }
-                let execution = match string_field(verb_value, "execution") {
+                let execution = match string_field(verb_value, "execution")
+                    .or_else(|| string_array_field(verb_value, "cmds").map(|a| a.join(" && ")))
+                {
                     Some(s) => s,
                     None => {
                         eprintln!("Invalid [[verbs]] entry in configuration");
@@ -158,9 +286,17 @@ impl Conf {
                     );
                     continue;
                 }
+                let background = bool_field(verb_value, "async");
+                if background == Some(true) && from_shell == Some(true) {
+                    eprintln!("Invalid [[verbs]] entry in configuration");
+                    eprintln!(
+                        "You can't simultaneously have async=true and from_shell=true"
+                    );
+                    continue;
+                }
                 let selection_condition = match string_field(verb_value, "apply_to").as_deref() {
                     Some("file") => SelectionType::File,
-                    Some("directory") => SelectionType::Directory,
+                    Some("directory") | Some("dir") => SelectionType::Directory,
                     Some("any") => SelectionType::Any,
                     None => SelectionType::Any,
                     Some(s) => {
@@ -169,22 +305,110 @@ impl Conf {
                         continue;
                     }
                 };
-                let set_working_dir = bool_field(verb_value, "set_working_dir");
+                let file_filter = string_field(verb_value, "filter")
+                    .map(|s| vec![s])
+                    .or_else(|| string_array_field(verb_value, "filter"))
+                    .map(FileFilter::new)
+                    .transpose()?;
+                // set_working_dir is the older and less precise way
+                // to define the working dir
+                let working_dir = match string_field(verb_value, "working_dir") {
+                    Some(s) => Some(WorkingDir::from(&s)?),
+                    None => match bool_field(verb_value, "set_working_dir") {
+                        Some(true) => Some(WorkingDir::Directory),
+                        Some(false) => Some(WorkingDir::Unchanged),
+                        None => None,
+                    },
+                };
+                let on_success = string_field(verb_value, "on_success")
+                    .map(|s| ExitAction::from(&s))
+                    .transpose()?;
+                let on_failure = string_field(verb_value, "on_failure")
+                    .map(|s| ExitAction::from(&s))
+                    .transpose()?;
+                let confirm = bool_field(verb_value, "confirm");
                 let verb_conf = VerbConf {
                     invocation,
                     execution,
                     key,
                     shortcut: string_field(verb_value, "shortcut"),
+                    names,
                     description: string_field(verb_value, "description"),
                     from_shell,
                     leave_broot,
+                    background,
                     selection_condition,
-                    set_working_dir,
+                    file_filter,
+                    working_dir,
+                    on_success,
+                    on_failure,
+                    confirm,
                 };
 
                 self.verbs.push(verb_conf);
             }
         }
+        // reading the lists
+        if let Some(Value::Array(lists_value)) = &root.get("lists") {
+            for list_value in lists_value.iter() {
+                let (name, command) = match (
+                    string_field(list_value, "name"),
+                    string_field(list_value, "command"),
+                ) {
+                    (Some(name), Some(command)) => (name, command),
+                    _ => {
+                        eprintln!("Invalid [[lists]] entry in configuration");
+                        eprintln!("A list needs a name and a command");
+                        continue;
+                    }
+                };
+                let line_pattern = string_field(list_value, "line_pattern")
+                    .map(|s| regex::Regex::new(&s))
+                    .transpose()
+                    .map_err(|e| ConfError::InvalidListPattern {
+                        details: e.to_string(),
+                    })?;
+                self.lists.push(ListConf {
+                    name,
+                    command,
+                    line_pattern,
+                    enter: string_field(list_value, "enter"),
+                });
+            }
+        }
+        // reading the profiles, named sets of flags
+        if let Some(Value::Table(profiles_tbl)) = &root.get("profiles") {
+            for (name, flags) in profiles_tbl.iter() {
+                let flags = flags.as_str().ok_or_else(|| ConfError::InvalidProfile {
+                    name: name.to_string(),
+                })?;
+                self.profiles.insert(name.to_string(), flags.to_string());
+            }
+        }
+        // reading the handler chains
+        if let Some(Value::Array(handlers_value)) = &root.get("handlers") {
+            for handler_value in handlers_value.iter() {
+                let extensions = string_field(handler_value, "extensions")
+                    .map(|s| vec![s])
+                    .or_else(|| string_array_field(handler_value, "extensions"));
+                let (extensions, chain) = match (
+                    extensions,
+                    string_array_field(handler_value, "chain"),
+                ) {
+                    (Some(extensions), Some(chain)) => (extensions, chain),
+                    _ => {
+                        eprintln!("Invalid [[handlers]] entry in configuration");
+                        eprintln!("A handler chain needs extensions and a chain");
+                        continue;
+                    }
+                };
+                let chain = chain
+                    .iter()
+                    .map(|raw| Handler::from(raw))
+                    .collect::<Result<Vec<Handler>, ConfError>>()?;
+                self.handlers.set(&extensions, chain);
+            }
+        }
         // reading the skin
         if let Some(Value::Table(entries_tbl)) = &root.get("skin") {
             for (k, v) in entries_tbl.iter() {
@@ -242,16 +466,178 @@ impl Conf {
                 }
             }
         }
+        // reading the widths under which columns are hidden
+        // and over which they're shown
+        if let Some(Value::Table(responsive_tbl)) = &root.get("responsive-cols") {
+            for (k, v) in responsive_tbl.iter() {
+                let shown = match k.as_str() {
+                    "hidden-under" => false,
+                    "shown-over" => true,
+                    _ => {
+                        eprintln!("unexpected responsive-cols entry: {:?}", k);
+                        continue;
+                    }
+                };
+                if let Value::Table(widths_tbl) = v {
+                    for (name, width) in widths_tbl.iter() {
+                        let col = Col::from_name(name)?;
+                        let width = width.as_integer().unwrap_or(0);
+                        let width = width.max(0).min(i64::from(u16::MAX)) as u16;
+                        if shown {
+                            self.responsive_cols.set_shown_over(col, width);
+                        } else {
+                            self.responsive_cols.set_hidden_under(col, width);
+                        }
+                    }
+                }
+            }
+        }
         // true_colors ?
         if let Some(b) = bool_field(&root, "true_colors") {
             self.true_colors = Some(b);
         }
+        // kitty_graphics ?
+        if let Some(b) = bool_field(&root, "kitty_graphics") {
+            self.kitty_graphics = Some(b);
+        }
+        // sixel_graphics ?
+        if let Some(b) = bool_field(&root, "sixel_graphics") {
+            self.sixel_graphics = Some(b);
+        }
         // show selection mark
         if let Some(b) = bool_field(&root, "show_selection_mark") {
             self.show_selection_mark = Some(b);
         }
+        // show line numbers in text previews
+        if let Some(b) = bool_field(&root, "show_line_numbers") {
+            self.show_line_numbers = Some(b);
+        }
+        // how many megabytes of a file are read for a text preview
+        if let Some(size) = u16_field(&root, "max_preview_size") {
+            self.max_preview_size = Some(size);
+        }
+        // expected owner of files (the owner of the parent is used if none)
+        if let Some(s) = string_field(&root, "expected_owner") {
+            self.expected_owner = Some(s);
+        }
+        // whether sizes of dirs in immutable stores are computed
+        if let Some(b) = bool_field(&root, "sum_immutable_stores") {
+            self.sum_immutable_stores = Some(b);
+        }
+        // whether startup messages and the shell function install
+        // proposal are skipped
+        if let Some(b) = bool_field(&root, "quiet") {
+            self.quiet = Some(b);
+        }
+        // whether changes are shown after a refresh
+        if let Some(b) = bool_field(&root, "show_refresh_deltas") {
+            self.show_refresh_deltas = Some(b);
+        }
+        // how the selection moves when paging
+        if let Some(s) = string_field(&root, "page_selection") {
+            self.page_selection = Some(PageSelection::from(&s)?);
+        }
+        // whether two tree panels are always displayed
+        if let Some(b) = bool_field(&root, "commander") {
+            self.commander = Some(b);
+        }
+        // whether tree option changes apply to all panels
+        if let Some(b) = bool_field(&root, "broadcast_tree_options") {
+            self.broadcast_tree_options = Some(b);
+        }
+        // whether help and argument edition float over the other panels
+        if let Some(b) = bool_field(&root, "floating_panels") {
+            self.floating_panels = Some(b);
+        }
+        // whether the panels of the last session are reopened at launch
+        if let Some(b) = bool_field(&root, "restore_session") {
+            self.restore_session = Some(b);
+        }
+        // the limits over which acting on marked entries must be confirmed
+        if let Some(n) = u16_field(&root, "bulk_confirm_count") {
+            self.bulk_confirm_count = Some(n);
+        }
+        if let Some(n) = u16_field(&root, "bulk_confirm_gigabytes") {
+            self.bulk_confirm_gigabytes = Some(n);
+        }
+        // how many times :rm secure overwrites the files
+        if let Some(n) = u16_field(&root, "secure_delete_passes") {
+            if n < 1 {
+                return Err(ConfError::InvalidSecureDeletePasses { passes: n });
+            }
+            self.secure_delete_passes = Some(n);
+        }
+        // whether panels are side by side or stacked
+        if let Some(s) = string_field(&root, "panels_direction") {
+            self.panels_direction = Some(PanelsDirection::from(&s)?);
+        }
+        // the sizes under which a panel can't be opened
+        if let Some(w) = u16_field(&root, "min_panel_width") {
+            self.min_panel_width = Some(w);
+        }
+        if let Some(h) = u16_field(&root, "min_panel_height") {
+            self.min_panel_height = Some(h);
+        }
+        // whether panels have a title bar when there are several ones
+        if let Some(b) = bool_field(&root, "panel_titles") {
+            self.panel_titles = Some(b);
+        }
+        // the imported files, relative to this one, or absolute
+        let imports = string_field(&root, "imports")
+            .map(|s| vec![s])
+            .or_else(|| string_array_field(&root, "imports"))
+            .unwrap_or_default();
+        let own_verbs = self.verbs.len() - verbs_start;
+        let own_special_paths = self.special_paths.len() - special_paths_start;
+        for import in imports {
+            let path = path_from(filepath, PathAnchor::Parent, &import);
+            let canonical_path = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if self.files.contains(&canonical_path) {
+                warn!("{:?} is imported twice, probably in a cycle", path);
+                continue;
+            }
+            self.read_file(&path).map_err(|e| ConfError::InvalidImport {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        }
+        // the verbs and special paths of this file go after the imported ones
+        self.verbs[verbs_start..].rotate_left(own_verbs);
+        self.special_paths[special_paths_start..].rotate_left(own_special_paths);
 
         Ok(())
     }
 }
 
+
+#[cfg(test)]
+mod import_tests {
+
+    use {
+        super::*,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn check_imports() {
+        let dir = TempDir::new().unwrap();
+        let main = dir.path().join("conf.toml");
+        fs::write(
+            &main,
+            "imports = \"./sub/../verbs.toml\"\n[[verbs]]\ninvocation = \"main\"\nexecution = \"echo\"\n",
+        ).unwrap();
+        // verbs.toml imports conf.toml back, through another path
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(
+            dir.path().join("verbs.toml"),
+            "imports = \"sub/../conf.toml\"\n[[verbs]]\ninvocation = \"imported\"\nexecution = \"echo\"\n",
+        ).unwrap();
+        let mut conf = Conf::default();
+        conf.read_file(&main).unwrap();
+        assert_eq!(conf.files.len(), 2);
+        let invocations: Vec<&str> = conf.verbs.iter()
+            .filter_map(|v| v.invocation.as_deref())
+            .collect();
+        assert_eq!(invocations, vec!["imported", "main"]);
+    }
+}
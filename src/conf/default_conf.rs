@@ -27,6 +27,21 @@ pub const DEFAULT_CONF_FILE: &str = r#"
 #
 default_flags = ""
 
+###############################################################
+# Trusted local configurations
+# The .broot.toml file of a directory, which may define verbs,
+# is only applied when the directory matches one of those globs.
+#
+# trusted_local_confs = ["~/dev/*"]
+
+###############################################################
+# Flags depending on the launch directory
+# Those flags are applied after the default ones when broot is
+# launched in a directory, or below a directory, matching the glob.
+#
+# [root-flags]
+# "~/dotfiles" = "h"
+
 ###############################################################
 # Special paths
 # If some paths must be handled specially, uncomment (and change
@@ -36,6 +51,8 @@ default_flags = ""
 # "/media/slow-backup-disk" = "no-enter"
 # "/home/dys/useless" = "hide"
 # "/home/dys/my-link-I-want-to-explore" = "enter"
+# "**/node_modules" = "no-enter"
+# "**/.github" = "show"
 
 ###############################################################
 # Date/Time format
@@ -50,20 +67,128 @@ default_flags = ""
 #
 # show_selection_mark = true
 
+###############################################################
+# Whether to show line numbers in text previews. When not set,
+# they're shown only when the preview is wide enough or filtered
+#
+# show_line_numbers = true
+
+###############################################################
+# How many megabytes of a file are read for a text preview.
+# Use :preview_load_more to read the next part.
+#
+# max_preview_size = 10
+
+###############################################################
+# Whether to mark, for a few seconds after a refresh, the
+# entries which appeared or whose size changed
+#
+# show_refresh_deltas = true
+
+###############################################################
+# How the selection moves when scrolling by a page: "follow"
+# (moved only when it would go out of view), "screen" (keeps
+# its position on screen) or "line" (stays on its line)
+#
+# page_selection = "screen"
+
+###############################################################
+# Commander mode
+# If true, broot starts with two tree panels and keeps them open,
+# :copy_to_panel and :move_to_panel transferring the selection or
+# the marked entries from one to the other.
+#
+# commander = true
+
+###############################################################
+# Tree options scope
+# If true, changing a tree option (hidden files, sort, dates,
+# etc.) in a panel changes it in all panels.
+#
+# broadcast_tree_options = true
+
+###############################################################
+# Floating panels
+# If true, the help opened in a new panel and the panels opened
+# to edit a verb argument float over the other panels instead
+# of splitting the screen.
+#
+# floating_panels = true
+
+###############################################################
+# Session restoration
+# If true, broot launched without path reopens the tree panels
+# which were open when it was last quitted, as with the
+# --restore-session launch argument.
+#
+# restore_session = true
+
+###############################################################
+# Bulk confirmation
+# A verb acting on more marked entries than this count, or on
+# marked entries totalling more than this size, must be confirmed.
+#
+# bulk_confirm_count = 100
+# bulk_confirm_gigabytes = 10
+
+###############################################################
+# Secure deletion
+# How many times :rm secure overwrites the content of the files
+# before deleting them.
+#
+# secure_delete_passes = 3
+
+###############################################################
+# Panels layout
+# Panels are side by side ("columns") unless you stack them
+# ("rows"). A panel can't be opened when the panels wouldn't all
+# fit with the minimal width and height.
+#
+# panels_direction = "rows"
+# min_panel_width = 20
+# min_panel_height = 10
+#
+# When there are several panels, each one has a title bar
+# with its root, sort and flags, unless you remove them.
+#
+# panel_titles = false
+
+###############################################################
+# Expected owner
+# Files whose owner isn't the expected one are highlighted (and
+# can be filtered with `:toggle_owner_mismatch`). If not set, the
+# owner of the parent directory is expected.
+#
+# expected_owner = "dys"
+
 ###############################################################
 # Column order
-# cols_order, if specified, must be a permutation of "gbpdscn"
+# cols_order, if specified, must be a permutation of "gbpdtaschn"
 # where every char denotes a column:
 #  g : Git file info
 #  b : branch (shows the depth and parent in the tree)
 #  p : permissions (mode, user, group)
 #  d : last modification date
+#  t : date of the last commit changing the file
+#  a : author of the last commit changing the file
 #  s : size (with size bar when sorting)
 #  c : count, number of files in directories
+#  h : number of matching files in directories, when searching
 #  n : file name
 #
 # cols_order = "gbdscn"
 
+###############################################################
+# Responsive columns
+# Panel widths under which columns are hidden (by default the
+# dates under 80 chars and the permissions under 60), and over
+# which they're shown even when not enabled. Valid names are
+# date, permission, size and count.
+#
+# [responsive-cols]
+# hidden-under = { date = 80, permission = 60 }
+# shown-over = { count = 140, permission = 180 }
+
 ###############################################################
 # True Colors
 # If this parameter isn't set, broot tries to automatically
@@ -73,6 +198,85 @@ default_flags = ""
 # previewed images are too off.
 # true_colors = false
 
+###############################################################
+# Branch glyphs
+# The glyphs used to draw the tree branches may be chosen among
+# the "light" (default), "heavy", "ascii" and "none" sets. They
+# can be changed at runtime with the :branch_glyphs verb.
+#
+# branch_glyphs = "ascii"
+#
+# You may also define your own set of glyphs, each one being
+# 3 chars wide:
+#
+# [branch-glyphs]
+# middle = "├──"
+# vertical = "│  "
+# last = "└──"
+# blank = "   "
+
+###############################################################
+# Quiet startup
+# Uncomment to skip the startup messages and the proposal to
+# install the br shell function (same as --quiet)
+#
+# quiet = true
+
+###############################################################
+# Immutable stores
+# The sizes of the directories of immutable stores (like /nix/store)
+# aren't computed unless this is set to true. Use :resolve_chain to
+# see where a link into such a store leads.
+#
+# sum_immutable_stores = true
+
+###############################################################
+# Lists
+# A list is built from the output of a command, every line
+# being an entry on which verbs can be applied. It's opened
+# with `:open_list name`.
+#
+# [[lists]]
+# name = "todos"
+# command = "rg --vimgrep TODO"
+# line_pattern = "^(?P<path>[^:]+):(?P<line>\\d+):\\d+:(?P<text>.*)$"
+# enter = "edit"
+
+###############################################################
+# Profiles
+# Named sets of flags, written as in default_flags, applied to
+# the tree with `:profile name`.
+#
+# [profiles]
+# audit = "phd"
+# dev = "IS"
+
+###############################################################
+# Handlers
+# Ordered chains of handlers used, per extension, to preview and
+# open files. The handlers whose program isn't installed are
+# skipped.
+#
+# [[handlers]]
+# extensions = ["pdf"]
+# chain = ["convert: pdftotext {file} -", "exec: zathura {file}", "open"]
+
+###############################################################
+# Kitty Graphics
+# On terminals supporting the kitty graphics protocol (kitty,
+# wezterm), images are previewed in full resolution. If the
+# detection fails, you may uncomment this setting and set it
+# to true or false.
+# kitty_graphics = true
+
+###############################################################
+# Sixel Graphics
+# On terminals which don't support the kitty graphics protocol
+# but know the sixel graphics (foot, mlterm, mintty, etc.),
+# images are previewed as sixel. Same as for kitty, you may
+# uncomment this setting if the detection fails.
+# sixel_graphics = true
+
 ###############################################################
 # Verbs and shortcuts
 # You can define your own commands which would be applied to
@@ -90,6 +294,12 @@ default_flags = ""
 # invocation = "touch {new_file}"
 # execution = "touch {directory}/{new_file}"
 # leave_broot = false
+#
+# Exemple 3: running a long command in background (see its output with :jobs)
+# [[verbs]]
+# invocation = "rsync_to {destination}"
+# execution = "rsync -a {file} {destination}"
+# async = true
 
 # If $EDITOR isn't set on your computer, you should either set it using
 #  something similar to
@@ -105,6 +315,7 @@ default_flags = ""
 invocation = "edit"
 key = "F2"
 shortcut = "e"
+apply_to = "file"
 execution = "$EDITOR {file}"
 leave_broot = false
 
@@ -127,6 +338,7 @@ execution = "git difftool -y {file}"
 [[verbs]]
 name = "view"
 invocation = "view"
+apply_to = "file"
 execution = "$PAGER {file}"
 leave_broot = false
 
@@ -135,7 +347,7 @@ leave_broot = false
 # [[verbs]]
 # key = "ctrl-t"
 # execution = "$SHELL"
-# set_working_dir = true
+# working_dir = "directory"
 # leave_broot = false
 
 # A popular set of shorctuts for going up and down:
@@ -193,13 +405,18 @@ leave_broot = false
 # directory = "ansi(208) None Bold / ansi(172) None bold"
 # exe = "Cyan None"
 # link = "Magenta None"
+# special = "Yellow None"
 # pruning = "gray(12) None Italic"
+# dimmed = "gray(13) None Italic / gray(9) None Italic"
 # perm__ = "gray(5) None"
 # perm_r = "ansi(94) None"
 # perm_w = "ansi(132) None"
 # perm_x = "ansi(65) None"
 # owner = "ansi(138) None"
 # group = "ansi(131) None"
+# owner_mismatch = "ansi(202) None Underlined"
+# immutable = "None None Italic"
+# unpaired = "ansi(178) None Underlined"
 # count = "ansi(136) gray(3)"
 # dates = "ansi(66) None"
 # sparse = "ansi(214) None"
@@ -220,6 +437,7 @@ leave_broot = false
 # flag_label = "gray(15) None"
 # flag_value = "ansi(208) None Bold"
 # input = "White None / gray(15) gray(2)"
+# input_ghost = "gray(10) gray(2) Italic"
 # status_error = "gray(22) ansi(124)"
 # status_job = "ansi(220) gray(5)"
 # status_normal = "gray(20) gray(3) / gray(2) gray(2)"
@@ -231,6 +449,9 @@ leave_broot = false
 # purpose_italic = "ansi(178) gray(2)"
 # purpose_bold = "ansi(178) gray(2) Bold"
 # purpose_ellipsis = "gray(20) gray(2)"
+# panel_title = "gray(22) gray(5) Bold / gray(14) gray(3)"
+# tab = "gray(15) gray(2)"
+# tab_current = "gray(22) gray(5) Bold"
 # scrollbar_track = "gray(7) None / gray(4) None"
 # scrollbar_thumb = "gray(22) None / gray(14) None"
 # help_paragraph = "gray(20) None"
@@ -247,6 +468,8 @@ leave_broot = false
 # hex_ascii_whitespace = "ansi(143) None"
 # hex_ascii_other = "ansi(215) None"
 # hex_non_ascii = "ansi(167) None"
+# diff_added = "None ansi(22)"
+# diff_removed = "None ansi(52)"
 
 # You may find explanations and other skins on
 #  https://dystroy.org/broot/skins
@@ -0,0 +1,75 @@
+use {
+    super::Conf,
+    crate::errors::ConfError,
+    std::path::{Path, PathBuf},
+};
+
+/// name of the file which, in a directory, holds the settings
+/// specific to the tree below this directory
+pub const LOCAL_CONF_FILE_NAME: &str = ".broot.toml";
+
+/// return the local configuration file applying to the directory,
+/// that is the closest one in the directory or its ancestors
+pub fn find_local_conf(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(LOCAL_CONF_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+impl Conf {
+    /// read a local configuration file, to be added later
+    /// with add_local
+    pub fn read_local_file(filepath: &Path) -> Result<Conf, ConfError> {
+        let mut local = Conf::default();
+        local.read_file(filepath)?;
+        Ok(local)
+    }
+
+    /// tell whether the local configuration file is in a directory
+    /// matching one of the `trusted_local_confs` globs. As a local
+    /// file may define verbs, it's ignored in other directories.
+    pub fn trusts_local_file(&self, filepath: &Path) -> bool {
+        filepath.parent().is_some_and(|dir| {
+            self.trusted_local_confs.iter().any(|pattern| pattern.matches_path(dir))
+        })
+    }
+
+    /// add the settings of a local configuration to the ones
+    /// already read. The special paths and the verbs of the local
+    /// file take precedence, while its default flags are kept apart,
+    /// to be applied to the tree entering its directory
+    pub fn add_local(&mut self, local: &Conf) {
+        self.special_paths.splice(0..0, local.special_paths.iter().cloned());
+        self.verbs.splice(0..0, local.verbs.iter().cloned());
+        self.files.extend(local.files.iter().cloned());
+        self.local_default_flags = local.default_flags.clone();
+    }
+}
+
+#[cfg(test)]
+mod local_conf_tests {
+
+    use {
+        super::*,
+        std::fs,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn check_trust() {
+        let dir = TempDir::new().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        let local_file = project.join(LOCAL_CONF_FILE_NAME);
+        fs::write(&local_file, "default_flags = \"gh\"\n").unwrap();
+        assert_eq!(find_local_conf(&project.join("src")), Some(local_file.clone()));
+        let mut conf = Conf::default();
+        assert!(!conf.trusts_local_file(&local_file));
+        let glob = format!("{}/*", dir.path().to_string_lossy());
+        conf.trusted_local_confs.push(glob::Pattern::new(&glob).unwrap());
+        assert!(conf.trusts_local_file(&local_file));
+        let local = Conf::read_local_file(&local_file).unwrap();
+        conf.add_local(&local);
+        assert_eq!(conf.local_default_flags, "gh");
+    }
+}
@@ -7,9 +7,11 @@ use {
 
 mod conf;
 mod default_conf;
+mod local_conf;
 
 pub use {
     conf::Conf,
+    local_conf::{find_local_conf, LOCAL_CONF_FILE_NAME},
 };
 
 /// return the instance of ProjectDirs holding broot's specific paths
@@ -1,6 +1,5 @@
 
 use {
-    memmap::Mmap,
     phf::{phf_set, Set},
 };
 
@@ -73,7 +72,7 @@ static SIGNATURES_4: Set<[u8;4]> = phf_set! {
 ///
 /// If you feel this list should maybe be changed, contact
 /// me on miaou or raise an issue.
-pub fn is_known_binary(hay: &Mmap) -> bool {
+pub fn is_known_binary(hay: &[u8]) -> bool {
     if hay.len() < MIN_FILE_SIZE {
         return false;
     }
@@ -4,11 +4,12 @@
 
 use {
     super::*,
+    crate::compression::{self, Compression},
     memmap::Mmap,
     std::{
         convert::TryInto,
         fmt,
-        fs::File,
+        fs::{self, File},
         io,
         path::{Path},
     },
@@ -33,6 +34,12 @@ impl fmt::Debug for Needle {
     }
 }
 
+/// tell whether the path can be read as a regular file: FIFOs
+/// would block on opening and devices may be endless
+fn is_regular_file(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|md| md.is_file())
+}
+
 fn get_mmap<P: AsRef<Path>>(hay_path: P) -> io::Result<Mmap> {
     let file = File::open(hay_path.as_ref())?;
     let hay = unsafe { Mmap::map(&file)? };
@@ -53,12 +60,12 @@ impl Needle {
     }
 
     // no, it doesn't bring more than a few % in speed
-    fn find_naive_1(&self, hay: &Mmap) -> Option<usize> {
+    fn find_naive_1(&self, hay: &[u8]) -> Option<usize> {
         let n = self.bytes[0];
         hay.iter().position(|&b| b==n)
     }
 
-    fn find_naive_2(&self, mut pos: usize, hay: &Mmap) -> Option<usize> {
+    fn find_naive_2(&self, mut pos: usize, hay: &[u8]) -> Option<usize> {
         let max_pos = hay.len() - 2;
         let b0 = self.bytes[0];
         let b1 = self.bytes[1];
@@ -75,7 +82,7 @@ impl Needle {
         None
     }
 
-    fn find_naive_3(&self, mut pos: usize, hay: &Mmap) -> Option<usize> {
+    fn find_naive_3(&self, mut pos: usize, hay: &[u8]) -> Option<usize> {
         let max_pos = hay.len() - 3;
         let b0 = self.bytes[0];
         let b1 = self.bytes[1];
@@ -94,7 +101,7 @@ impl Needle {
         None
     }
 
-    fn find_naive_4(&self, mut pos: usize, hay: &Mmap) -> Option<usize> {
+    fn find_naive_4(&self, mut pos: usize, hay: &[u8]) -> Option<usize> {
         use std::mem::transmute;
         let max_pos = hay.len() - 4;
         unsafe {
@@ -109,7 +116,7 @@ impl Needle {
         None
     }
 
-    fn find_naive_6(&self, mut pos: usize, hay: &Mmap) -> Option<usize> {
+    fn find_naive_6(&self, mut pos: usize, hay: &[u8]) -> Option<usize> {
         let max_pos = hay.len() - 6;
         let b0 = self.bytes[0];
         let b1 = self.bytes[1];
@@ -134,7 +141,7 @@ impl Needle {
         None
     }
 
-    fn is_at_pos(&self, hay_stack: &Mmap, pos: usize) -> bool {
+    fn is_at_pos(&self, hay_stack: &[u8], pos: usize) -> bool {
         unsafe {
             for (i, b) in self.bytes.iter().enumerate() {
                 if hay_stack.get_unchecked(i+pos) != b {
@@ -145,7 +152,7 @@ impl Needle {
         true
     }
 
-    fn find_naive(&self, mut pos: usize, hay: &Mmap) -> Option<usize> {
+    fn find_naive(&self, mut pos: usize, hay: &[u8]) -> Option<usize> {
         let max_pos = hay.len() - self.bytes.len();
         while pos <= max_pos {
             if self.is_at_pos(&hay, pos) {
@@ -188,6 +195,15 @@ impl Needle {
             // TODO the Windows equivalent might be PrefetchVirtualMemory
         }
 
+        self.search_bytes(hay)
+    }
+
+    /// search the bytes (either a mem map or a decompressed content)
+    /// to find the first occurence of the needle
+    fn search_bytes(&self, hay: &[u8]) -> ContentSearchResult {
+        if hay.len() < self.bytes.len() {
+            return ContentSearchResult::NotFound;
+        }
         let pos = match self.bytes.len() {
             1 => self.find_naive_1(&hay),
             2 => self.find_naive_2(0, &hay),
@@ -204,6 +220,12 @@ impl Needle {
 
     /// determine whether the file contains the needle
     pub fn search<P: AsRef<Path>>(&self, hay_path: P) -> io::Result<ContentSearchResult> {
+        if !is_regular_file(hay_path.as_ref()) {
+            return Ok(ContentSearchResult::NotSuitable);
+        }
+        if Compression::of_path(hay_path.as_ref()).is_some() {
+            return self.search_compressed(hay_path.as_ref());
+        }
         if let Some(ext) = hay_path.as_ref().extension().and_then(|s| s.to_str()) {
             if extensions::is_known_binary(&ext) {
                 return Ok(ContentSearchResult::NotSuitable);
@@ -221,6 +243,19 @@ impl Needle {
         Ok(self.search_mmap(&hay))
     }
 
+    /// determine whether the decompressed content of the file contains
+    /// the needle
+    fn search_compressed(&self, hay_path: &Path) -> io::Result<ContentSearchResult> {
+        let hay = match compression::read_decompressed(hay_path, MAX_FILE_SIZE + 1)? {
+            Some(hay) => hay,
+            None => { return Ok(ContentSearchResult::NotSuitable); }
+        };
+        if hay.len() > MAX_FILE_SIZE || magic_numbers::is_known_binary(&hay) {
+            return Ok(ContentSearchResult::NotSuitable);
+        }
+        Ok(self.search_bytes(&hay))
+    }
+
     /// this is supposed to be called only when it's known that there's
     /// a match
     pub fn get_match<P: AsRef<Path>>(
@@ -228,6 +263,20 @@ impl Needle {
         hay_path: P,
         desired_len: usize,
     ) -> Option<ContentMatch> {
+        if !is_regular_file(hay_path.as_ref()) {
+            return None;
+        }
+        if Compression::of_path(hay_path.as_ref()).is_some() {
+            let hay = compression::read_decompressed(hay_path.as_ref(), MAX_FILE_SIZE)
+                .ok()
+                .flatten()?;
+            return match self.search_bytes(&hay) {
+                ContentSearchResult::Found { pos } => Some(ContentMatch::build(
+                    &hay, pos, self.as_str(), desired_len,
+                )),
+                _ => None,
+            };
+        }
         let hay = match get_mmap(hay_path) {
             Ok(hay) => hay,
             _ => { return None; }
@@ -0,0 +1,323 @@
+use {
+    super::{diff, Edit, MAX_DIFFERENCES},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::{Area, CompoundStyle},
+};
+
+/// the max size of the compared files
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// a line of the side by side display: the indexes of the lines
+/// of both files, when there's one
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    left: Option<usize>,
+    right: Option<usize>,
+    same: bool,
+}
+
+/// read the lines of a text file
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    if fs::metadata(path)?.len() > MAX_FILE_SIZE {
+        return Err(io::Error::other(
+            format!("{} is too big", path.to_string_lossy()),
+        ));
+    }
+    let content = fs::read_to_string(path).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} isn't a valid UTF-8 text", path.to_string_lossy()),
+        )
+    })?;
+    Ok(content.lines().map(String::from).collect())
+}
+
+/// build the rows from the edits, with the deleted lines
+/// facing the inserted ones which follow them
+fn rows(edits: &[Edit]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    for edit in edits.iter().map(Some).chain(std::iter::once(None)) {
+        match edit {
+            Some(Edit::Delete(i)) => deleted.push(*i),
+            Some(Edit::Insert(j)) => inserted.push(*j),
+            _ => {
+                for idx in 0..deleted.len().max(inserted.len()) {
+                    rows.push(Row {
+                        left: deleted.get(idx).copied(),
+                        right: inserted.get(idx).copied(),
+                        same: false,
+                    });
+                }
+                deleted.clear();
+                inserted.clear();
+                if let Some(&Edit::Equal(i, j)) = edit {
+                    rows.push(Row {
+                        left: Some(i),
+                        right: Some(j),
+                        same: true,
+                    });
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// an application state comparing two text files, displayed
+/// side by side with the differences colored
+pub struct DiffState {
+    left_path: PathBuf,
+    right_path: PathBuf,
+    left_lines: Vec<String>,
+    right_lines: Vec<String>,
+    rows: Vec<Row>,
+    scroll: usize,
+    page_height: usize,
+}
+
+impl DiffState {
+    pub fn new(left_path: PathBuf, right_path: PathBuf) -> io::Result<Self> {
+        let left_lines = read_lines(&left_path)?;
+        let right_lines = read_lines(&right_path)?;
+        let edits = diff(&left_lines, &right_lines).ok_or_else(|| {
+            io::Error::other(
+                format!("the files have more than {} differences", MAX_DIFFERENCES),
+            )
+        })?;
+        Ok(Self {
+            left_path,
+            right_path,
+            rows: rows(&edits),
+            left_lines,
+            right_lines,
+            scroll: 0,
+            page_height: 0,
+        })
+    }
+
+    /// the number of blocks of changed lines
+    fn changes_count(&self) -> usize {
+        (0..self.rows.len()).filter(|&idx| self.is_change_start(idx)).count()
+    }
+
+    fn is_change_start(&self, idx: usize) -> bool {
+        !self.rows[idx].same && (idx == 0 || self.rows[idx - 1].same)
+    }
+
+    /// the height of the area of the rows, below the title line
+    fn rows_height(&self) -> usize {
+        self.page_height.saturating_sub(1)
+    }
+
+    fn try_scroll(&mut self, dy: i32) {
+        let max_scroll = self.rows.len().saturating_sub(self.rows_height()) as i32;
+        self.scroll = (self.scroll as i32 + dy).max(0).min(max_scroll) as usize;
+    }
+
+    /// scroll to the next (or previous) change, keeping one
+    /// line of context above it
+    fn go_to_change(&mut self, forward: bool) {
+        let target = if forward {
+            (self.scroll + 2..self.rows.len()).find(|&idx| self.is_change_start(idx))
+        } else {
+            (0..=self.scroll).rev().find(|&idx| self.is_change_start(idx))
+        };
+        if let Some(idx) = target {
+            self.scroll = idx.saturating_sub(1);
+            self.try_scroll(0);
+        }
+    }
+}
+
+/// write one side of a row: the line number then the line
+fn write_side(
+    cw: &mut CropWriter<'_, W>,
+    lines: &[String],
+    idx: Option<usize>,
+    number_width: usize,
+    line_style: &CompoundStyle,
+    number_style: &CompoundStyle,
+) -> Result<(), ProgramError> {
+    match idx {
+        Some(idx) => {
+            cw.queue_g_string(number_style, format!("{:>w$} ", idx + 1, w = number_width))?;
+            cw.queue_str(line_style, &lines[idx])?;
+        }
+        None => {
+            cw.queue_g_string(number_style, format!("{:>w$} ", "", w = number_width))?;
+        }
+    }
+    cw.fill(line_style, LONG_SPACE)?;
+    Ok(())
+}
+
+impl AppState for DiffState {
+
+    fn selected_path(&self) -> &Path {
+        &self.right_path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.right_path,
+            line: 0,
+            stype: SelectionType::File,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.rows_height() as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down | Internal::scroll_down => {
+                self.try_scroll(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up | Internal::scroll_up => {
+                self.try_scroll(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.try_scroll(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.try_scroll(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.scroll = 0;
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.try_scroll(self.rows.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::next_match => {
+                self.go_to_change(true);
+                AppStateCmdResult::Keep
+            }
+            Internal::previous_match => {
+                self.go_to_change(false);
+                AppStateCmdResult::Keep
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.try_scroll(0);
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        let left_width = width.saturating_sub(1) / 2;
+        let right_width = width.saturating_sub(left_width + 1);
+        let number_width = self.left_lines.len().max(self.right_lines.len()).to_string().len();
+        let mut removed_style = styles.preview.clone();
+        if let Some(c) = styles.diff_removed.get_bg() {
+            removed_style.set_bg(c);
+        }
+        let mut added_style = styles.preview.clone();
+        if let Some(c) = styles.diff_added.get_bg() {
+            added_style.set_bg(c);
+        }
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            if y == 0 {
+                // the title line, with the paths of the files
+                let title_style = &styles.preview_line_number;
+                let mut cw = CropWriter::new(w, left_width);
+                cw.queue_str(title_style, &self.left_path.to_string_lossy())?;
+                cw.fill(title_style, LONG_SPACE)?;
+                title_style.queue(w, ' ')?;
+                let mut cw = CropWriter::new(w, right_width);
+                cw.queue_str(title_style, &self.right_path.to_string_lossy())?;
+                cw.fill(title_style, LONG_SPACE)?;
+                continue;
+            }
+            let row = self.rows.get(self.scroll + y as usize - 1);
+            let (left, right, same) = row.map_or((None, None, true), |r| (r.left, r.right, r.same));
+            let mut cw = CropWriter::new(w, left_width);
+            write_side(
+                &mut cw,
+                &self.left_lines,
+                left,
+                number_width,
+                if same || left.is_none() { &styles.preview } else { &removed_style },
+                &styles.preview_line_number,
+            )?;
+            styles.tree.queue(w, '│')?;
+            let mut cw = CropWriter::new(w, right_width);
+            write_side(
+                &mut cw,
+                &self.right_lines,
+                right,
+                number_width,
+                if same || right.is_none() { &styles.preview } else { &added_style },
+                &styles.preview_line_number,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match self.changes_count() {
+            0 => "The files have the same lines. Hit *esc* to go back".to_string(),
+            n => format!(
+                "{} change{}. Hit *tab* to go to the next one, *shift-tab* for the previous one",
+                n,
+                if n > 1 { "s" } else { "" },
+            ),
+        })
+    }
+}
@@ -0,0 +1,9 @@
+//! comparison of two text files, displayed side by side
+
+mod diff_state;
+mod myers;
+
+pub use {
+    diff_state::DiffState,
+    myers::{diff, Edit, MAX_DIFFERENCES},
+};
@@ -0,0 +1,135 @@
+//! a line diff, using the algorithm described by Eugene W. Myers
+//! in "An O(ND) Difference Algorithm and Its Variations"
+
+/// an operation transforming the first sequence into the second one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edit {
+    Equal(usize, usize), // index in a, index in b
+    Delete(usize),       // index in a
+    Insert(usize),       // index in b
+}
+
+/// the max number of differences we accept to compute, as the
+/// memory needed grows with its square
+pub const MAX_DIFFERENCES: usize = 2_000;
+
+/// compute the trace of the search of the shortest edit script:
+/// for each number d of differences, the furthest x reached on
+/// every diagonal k (from -d to d) before the step d
+fn shortest_edit_trace<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<Vec<isize>>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace = Vec::new();
+    for d in 0..=(max.min(MAX_DIFFERENCES) as isize) {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1] // move down: insertion
+            } else {
+                v[idx - 1] + 1 // move right: deletion
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                return Some(trace);
+            }
+            k += 2;
+        }
+    }
+    None
+}
+
+/// compute a minimal list of edits transforming a into b, or None
+/// if there are more than MAX_DIFFERENCES differences
+pub fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<Edit>> {
+    let trace = shortest_edit_trace(a, b)?;
+    let mut edits = Vec::new();
+    let mut x = a.len() as isize;
+    let mut y = b.len() as isize;
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        if d == 0 {
+            // only the common start remains
+            while x > 0 && y > 0 {
+                x -= 1;
+                y -= 1;
+                edits.push(Edit::Equal(x as usize, y as usize));
+            }
+            break;
+        }
+        // v holds the values of the diagonals -d to d
+        let at = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if x == prev_x {
+            edits.push(Edit::Insert(prev_y as usize));
+        } else {
+            edits.push(Edit::Delete(prev_x as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    Some(edits)
+}
+
+#[cfg(test)]
+mod myers_tests {
+
+    use super::*;
+
+    /// apply the edits to a, checking they produce b
+    fn check(a: &str, b: &str, differences: usize) {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let edits = diff(&a, &b).unwrap();
+        let mut result = Vec::new();
+        for edit in &edits {
+            match *edit {
+                Edit::Equal(i, j) => {
+                    assert_eq!(a[i], b[j]);
+                    result.push(a[i]);
+                }
+                Edit::Insert(j) => result.push(b[j]),
+                Edit::Delete(_) => {}
+            }
+        }
+        assert_eq!(result, b);
+        let count = edits
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Equal(..)))
+            .count();
+        assert_eq!(count, differences);
+    }
+
+    #[test]
+    fn check_diffs() {
+        check("", "", 0);
+        check("abc", "abc", 0);
+        check("", "abc", 3);
+        check("abc", "", 3);
+        check("abcabba", "cbabac", 5);
+        check("the cat", "the hat", 2);
+        check("xabc", "abcx", 2);
+    }
+}
@@ -1,5 +1,7 @@
 use {
     super::{
+        PanelsDirection,
+        PanelsLayout,
         Screen,
         WIDE_STATUS,
     },
@@ -15,17 +17,15 @@ use {
 /// there are, and their respective positions
 #[derive(Debug, Clone)]
 pub struct Areas {
+    pub title: Option<Area>,
     pub state: Area,
     pub status: Area,
     pub input: Area,
     pub purpose: Option<Area>,
-    pub pos_idx: usize, // from left to right
+    pub pos_idx: usize, // from left to right, or top to bottom
     pub nb_pos: usize, // number of displayed panels
 }
 
-const MINIMAL_PANEL_HEIGHT: u16 = 10;
-const MINIMAL_PANEL_WIDTH: u16 = 20;
-
 enum Slot<'a> {
     Panel(usize),
     New(&'a mut Areas),
@@ -38,12 +38,14 @@ impl Areas {
         present_panels: &mut [Panel],
         mut insertion_idx: usize,
         screen: &Screen,
+        layout: &PanelsLayout,
         with_preview: bool, // slightly larger last panel
     ) -> Result<Self, ProgramError> {
         if insertion_idx > present_panels.len() {
             insertion_idx = present_panels.len();
         }
         let mut areas = Areas {
+            title: None,
             state: Area::uninitialized(),
             status: Area::uninitialized(),
             input: Area::uninitialized(),
@@ -52,60 +54,108 @@ impl Areas {
             nb_pos: 1,
         };
         let mut slots = Vec::with_capacity(present_panels.len() + 1);
-        for i in 0..insertion_idx {
-            slots.push(Slot::Panel(i));
-        }
+        // floating panels aren't part of the layout
+        let laid_out = |(i, panel): (usize, &Panel)| {
+            if panel.floating_over.is_none() { Some(Slot::Panel(i)) } else { None }
+        };
+        slots.extend(present_panels.iter().enumerate().take(insertion_idx).filter_map(laid_out));
         slots.push(Slot::New(&mut areas));
-        for i in insertion_idx..present_panels.len() {
-            slots.push(Slot::Panel(i));
-        }
-        Self::compute_areas(present_panels, &mut slots, screen, with_preview)?;
+        slots.extend(present_panels.iter().enumerate().skip(insertion_idx).filter_map(laid_out));
+        Self::compute_areas(present_panels, &mut slots, screen, layout, with_preview)?;
         Ok(areas)
     }
 
     pub fn resize_all(
         panels: &mut [Panel],
         screen: &Screen,
+        layout: &PanelsLayout,
         with_preview: bool, // slightly larger last panel
     ) -> Result<(), ProgramError> {
         let mut slots = Vec::new();
-        for i in 0..panels.len() {
-            slots.push(Slot::Panel(i));
+        for (i, panel) in panels.iter_mut().enumerate() {
+            if panel.floating_over.is_some() {
+                panel.areas = Self::floating(screen);
+            } else {
+                slots.push(Slot::Panel(i));
+            }
+        }
+        Self::compute_areas(panels, &mut slots, screen, layout, with_preview)
+    }
+
+    /// compute the areas of a panel floating in the middle of the
+    /// screen, over the other panels
+    pub fn floating(screen: &Screen) -> Self {
+        let width = (screen.width * 3 / 4).max(screen.width.min(60));
+        let height = (screen.height * 3 / 4).max(screen.height.min(12));
+        let left = (screen.width - width) / 2;
+        let top = screen.top + (screen.height - height) / 2;
+        let mut input = Area::new(left, top + height - 1, width, 1);
+        if left + width == screen.width && height == screen.height {
+            // the char at the bottom right of the terminal should not be touched
+            input.width -= 1;
+        }
+        Areas {
+            title: None,
+            state: Area::new(left, top, width, height - 2),
+            status: Area::new(left, top + height - 2, width, 1),
+            input,
+            purpose: None,
+            pos_idx: 0,
+            nb_pos: 1,
         }
-        Self::compute_areas(panels, &mut slots, screen, with_preview)
     }
 
     fn compute_areas(
         panels: &mut [Panel],
         slots: &mut Vec<Slot>,
         screen: &Screen,
+        layout: &PanelsLayout,
         with_preview: bool, // slightly larger last panel
     ) -> Result<(), ProgramError> {
-        if screen.height < MINIMAL_PANEL_HEIGHT {
-            return Err(ProgramError::TerminalTooSmallError);
+        match layout.direction {
+            PanelsDirection::Columns => {
+                Self::compute_columns(panels, slots, screen, layout, with_preview)
+            }
+            PanelsDirection::Rows => {
+                Self::compute_rows(panels, slots, screen, layout, with_preview)
+            }
         }
-        let n = slots.len() as u16;
-        let mut panel_width = if with_preview {
-            3 * screen.width / (3 * n + 1)
-        } else {
-            screen.width / n
-        };
-        if panel_width < MINIMAL_PANEL_WIDTH {
+    }
+
+    /// compute the areas of panels placed side by side, sharing
+    /// the status line
+    fn compute_columns(
+        panels: &mut [Panel],
+        slots: &mut [Slot],
+        screen: &Screen,
+        layout: &PanelsLayout,
+        with_preview: bool,
+    ) -> Result<(), ProgramError> {
+        if screen.height < layout.min_panel_height {
             return Err(ProgramError::TerminalTooSmallError);
         }
-        let mut x = 0;
         let nb_pos = slots.len();
+        let with_titles = layout.titles && nb_pos > 1 && screen.height > 3;
+        let widths = layout
+            .sizes(screen.width, nb_pos, with_preview, layout.min_panel_width)
+            .ok_or(ProgramError::TerminalTooSmallError)?;
+        let mut x = 0;
+        let mut previous_width = 0;
         #[allow(clippy::needless_range_loop)]
         for slot_idx in 0..nb_pos {
-            if slot_idx==nb_pos-1 {
-                panel_width = screen.width - x;
-            }
+            let panel_width = widths[slot_idx];
             let areas: &mut Areas = match &mut slots[slot_idx] {
                 Slot::Panel(panel_idx) => &mut panels[*panel_idx].areas,
                 Slot::New(areas) => areas,
             };
-            let y = screen.height - 2;
-            areas.state = Area::new(x, 0, panel_width, y);
+            let y = screen.top + screen.height - 2;
+            if with_titles {
+                areas.title = Some(Area::new(x, screen.top, panel_width, 1));
+                areas.state = Area::new(x, screen.top + 1, panel_width, screen.height - 3);
+            } else {
+                areas.title = None;
+                areas.state = Area::new(x, screen.top, panel_width, screen.height - 2);
+            }
             areas.status = if WIDE_STATUS {
                 Area::new(0, y, screen.width, 1)
             } else {
@@ -119,7 +169,8 @@ impl Areas {
                 areas.input.width -= 1;
             }
             areas.purpose = if slot_idx > 0 {
-                let area_width = panel_width / 2;
+                // on the right of the input of the previous panel
+                let area_width = previous_width / 2;
                 Some(Area::new(x - area_width, y, area_width, 1))
             } else {
                 None
@@ -127,10 +178,86 @@ impl Areas {
             areas.pos_idx = slot_idx;
             areas.nb_pos = nb_pos;
             x += panel_width;
+            previous_width = panel_width;
         }
         Ok(())
     }
 
+    /// compute the areas of panels stacked from top to bottom, each
+    /// one with its input line. The status line is the last line of
+    /// the screen.
+    fn compute_rows(
+        panels: &mut [Panel],
+        slots: &mut [Slot],
+        screen: &Screen,
+        layout: &PanelsLayout,
+        with_preview: bool,
+    ) -> Result<(), ProgramError> {
+        if screen.width < layout.min_panel_width || screen.height < 3 {
+            return Err(ProgramError::TerminalTooSmallError);
+        }
+        let nb_pos = slots.len();
+        let available_height = screen.height - 1; // the status takes the last line
+        let heights = layout
+            .sizes(available_height, nb_pos, with_preview, layout.min_panel_height)
+            .ok_or(ProgramError::TerminalTooSmallError)?;
+        let status_y = screen.top + available_height;
+        let mut y = screen.top;
+        #[allow(clippy::needless_range_loop)]
+        for slot_idx in 0..nb_pos {
+            let panel_height = heights[slot_idx];
+            let areas: &mut Areas = match &mut slots[slot_idx] {
+                Slot::Panel(panel_idx) => &mut panels[*panel_idx].areas,
+                Slot::New(areas) => areas,
+            };
+            if layout.titles && nb_pos > 1 && panel_height > 2 {
+                areas.title = Some(Area::new(0, y, screen.width, 1));
+                areas.state = Area::new(0, y + 1, screen.width, panel_height - 2);
+            } else {
+                areas.title = None;
+                areas.state = Area::new(0, y, screen.width, panel_height - 1);
+            }
+            // the char at the bottom right of the terminal should not be touched
+            // so the status, being on the last line, is one char shorter
+            areas.status = Area::new(0, status_y, screen.width - 1, 1);
+            let input_y = y + panel_height - 1;
+            areas.input = Area::new(0, input_y, screen.width, 1);
+            areas.purpose = if slot_idx > 0 {
+                // on the right of the input of the previous panel
+                let area_width = screen.width / 2;
+                Some(Area::new(screen.width - area_width, y - 1, area_width, 1))
+            } else {
+                None
+            };
+            areas.pos_idx = slot_idx;
+            areas.nb_pos = nb_pos;
+            y += panel_height;
+        }
+        Ok(())
+    }
+
+    /// the first line of the panel, which is its title when it has one
+    pub fn top(&self) -> u16 {
+        self.title.as_ref().map_or(self.state.top, |title| title.top)
+    }
+
+    /// tell whether the point is in the panel having those areas
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.state.left
+            && x < self.state.left + self.state.width
+            && y >= self.top()
+            && y <= self.input.top
+    }
+
+    /// return the position of the start of the panel and its size,
+    /// in the direction of the layout
+    pub fn span(&self, direction: PanelsDirection) -> (u16, u16) {
+        match direction {
+            PanelsDirection::Columns => (self.state.left, self.state.width),
+            PanelsDirection::Rows => (self.top(), self.input.top + 1 - self.top()),
+        }
+    }
+
     pub fn is_first(&self) -> bool {
         self.pos_idx == 0
     }
@@ -0,0 +1,130 @@
+use {
+    super::{LONG_BRANCH, LONG_SPACE},
+    crate::errors::ConfError,
+    unicode_width::UnicodeWidthStr,
+};
+
+/// the width, in columns, of every glyph
+const GLYPH_WIDTH: usize = 3;
+
+lazy_static! {
+    static ref LONG_HEAVY_BRANCH: String = "━".repeat(LONG_SPACE.len());
+    static ref LONG_ASCII_BRANCH: String = "-".repeat(LONG_SPACE.len());
+}
+
+/// names of the predefined glyph sets, in the order they're
+/// cycled through
+pub static PRESET_NAMES: &[&str] = &["light", "heavy", "ascii", "none"];
+
+/// the strings used to draw the branches of the tree. Each
+/// of them is expected to be 3 chars wide.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchGlyphs {
+    pub name: String,
+    pub middle: String,   // last level of a child which isn't the last one
+    pub vertical: String, // a branch passing by
+    pub last: String,     // last level of the last child
+    pub blank: String,    // no branch at this level
+    pub filling: &'static str, // fills the void between the branch and the name
+}
+
+impl BranchGlyphs {
+    fn new(
+        name: &str,
+        middle: &str,
+        vertical: &str,
+        last: &str,
+        blank: &str,
+        filling: &'static str,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            middle: middle.to_string(),
+            vertical: vertical.to_string(),
+            last: last.to_string(),
+            blank: blank.to_string(),
+            filling,
+        }
+    }
+    pub fn light() -> Self {
+        Self::new("light", "├──", "│  ", "└──", "   ", LONG_BRANCH)
+    }
+    pub fn heavy() -> Self {
+        Self::new("heavy", "┣━━", "┃  ", "┗━━", "   ", &LONG_HEAVY_BRANCH)
+    }
+    pub fn ascii() -> Self {
+        Self::new("ascii", "|--", "|  ", "`--", "   ", &LONG_ASCII_BRANCH)
+    }
+    /// no branch, only the indentation
+    pub fn none() -> Self {
+        Self::new("none", "   ", "   ", "   ", "   ", LONG_SPACE)
+    }
+    pub fn from_name(name: &str) -> Result<Self, ConfError> {
+        match name {
+            "light" => Ok(Self::light()),
+            "heavy" => Ok(Self::heavy()),
+            "ascii" => Ok(Self::ascii()),
+            "none" => Ok(Self::none()),
+            _ => Err(ConfError::InvalidBranchGlyphs {
+                details: format!("unknown glyph set: {:?} (valid ones: {:?})", name, PRESET_NAMES),
+            }),
+        }
+    }
+    /// check that every glyph has the expected width, as the
+    /// alignment of the tree depends on it
+    pub fn check(&self) -> Result<(), ConfError> {
+        let glyphs = [
+            ("middle", &self.middle),
+            ("vertical", &self.vertical),
+            ("last", &self.last),
+            ("blank", &self.blank),
+        ];
+        for (name, glyph) in glyphs.iter() {
+            if UnicodeWidthStr::width(glyph.as_str()) != GLYPH_WIDTH {
+                return Err(ConfError::InvalidBranchGlyphs {
+                    details: format!(
+                        "{} glyph {:?} isn't {} columns wide",
+                        name, glyph, GLYPH_WIDTH,
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+    /// return the preset following this one (the first preset
+    /// if this one is custom)
+    pub fn next_preset(&self) -> Self {
+        let idx = PRESET_NAMES
+            .iter()
+            .position(|&name| name == self.name)
+            .map_or(0, |idx| (idx + 1) % PRESET_NAMES.len());
+        Self::from_name(PRESET_NAMES[idx]).unwrap() // can't fail: names are valid
+    }
+}
+
+impl Default for BranchGlyphs {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+#[cfg(test)]
+mod branch_glyphs_tests {
+    use super::*;
+    #[test]
+    fn check_glyph_widths() {
+        for name in PRESET_NAMES {
+            assert!(BranchGlyphs::from_name(name).unwrap().check().is_ok());
+        }
+        let mut glyphs = BranchGlyphs {
+            middle: "├─".to_string(),
+            ..BranchGlyphs::default()
+        };
+        assert!(glyphs.check().is_err());
+        glyphs.middle = "├──".to_string();
+        glyphs.blank = "\t".to_string();
+        assert!(glyphs.check().is_err());
+        glyphs.blank = "　 ".to_string(); // a wide space then a narrow one
+        assert!(glyphs.check().is_ok());
+    }
+}
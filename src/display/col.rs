@@ -5,7 +5,7 @@ use {
 };
 
 // number of columns in enum
-const COLS_COUNT: usize = 8;
+const COLS_COUNT: usize = 11;
 
 /// One of the "columns" of the tree view
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -25,12 +25,21 @@ pub enum Col {
     /// last modified date
     Date,
 
+    /// date of the last commit changing the file
+    GitCommitDate,
+
+    /// author of the last commit changing the file
+    GitAuthor,
+
     /// file size, including size bar in sort_by_size mode
     Size,
 
     /// number of files in the directory
     Count,
 
+    /// number of matching files in the directory, when searching
+    Matches,
+
     /// name of the file, or subpath if relevant due to filtering mode
     Name,
 }
@@ -42,8 +51,11 @@ impl Col {
             'g' => Self::Git,
             'b' => Self::Branch,
             'd' => Self::Date,
+            't' => Self::GitCommitDate,
+            'a' => Self::GitAuthor,
             's' => Self::Size,
             'c' => Self::Count,
+            'h' => Self::Matches,
             'n' => Self::Name,
             _ => {
                 return Err(ConfError::InvalidCols {
@@ -52,6 +64,28 @@ impl Col {
             }
         })
     }
+    /// parse the name of a column, as used in the responsive_cols
+    /// configuration
+    pub fn from_name(name: &str) -> Result<Self, ConfError> {
+        Ok(match name {
+            "mark" => Self::Mark,
+            "git" => Self::Git,
+            "branch" => Self::Branch,
+            "permission" | "permissions" => Self::Permission,
+            "date" | "dates" => Self::Date,
+            "git_commit_date" => Self::GitCommitDate,
+            "git_author" => Self::GitAuthor,
+            "size" | "sizes" => Self::Size,
+            "count" | "counts" => Self::Count,
+            "matches" => Self::Matches,
+            "name" => Self::Name,
+            _ => {
+                return Err(ConfError::InvalidCols {
+                    details: format!("column not recognized : {:?}", name),
+                });
+            }
+        })
+    }
     pub fn index_in(self, cols: &Cols) -> Option<usize> {
         for (idx, col) in cols.iter().enumerate() {
             if *col==self {
@@ -88,8 +122,11 @@ pub static DEFAULT_COLS: Cols = [
     Col::Git,
     Col::Size,
     Col::Count,
+    Col::Matches,
     Col::Permission,
     Col::Date,
+    Col::GitCommitDate,
+    Col::GitAuthor,
     Col::Branch,
     Col::Name,
 ];
@@ -4,7 +4,7 @@ use {
         Cols,
         CropWriter,
         GitStatusDisplay,
-        LONG_SPACE, LONG_BRANCH,
+        LONG_SPACE,
         MatchedString,
     },
     crate::{
@@ -14,7 +14,7 @@ use {
         pattern::PatternObject,
         skin::{ExtColorMap, StyleMap},
         task_sync::ComputationResult,
-        tree::{Tree, TreeLine, TreeLineType},
+        tree::{LineDelta, Tree, TreeLine, TreeLineType},
     },
     chrono::{Local, DateTime, TimeZone},
     crossterm::{
@@ -24,7 +24,11 @@ use {
     },
     file_size,
     git2::Status,
-    std::io::Write,
+    std::{
+        collections::HashSet,
+        io::Write,
+        path::PathBuf,
+    },
     termimad::{CompoundStyle, ProgressBar},
 };
 
@@ -42,6 +46,8 @@ pub struct DisplayableTree<'s, 't> {
     pub in_app: bool, // if true we show the selection and scrollbar
     pub cols: &'s Cols,
     pub show_selection_mark: bool,
+    pub marked: &'s [PathBuf], // paths marked by the user, shown in the mark column
+    pub unpaired: Option<&'s HashSet<PathBuf>>, // lines without counterpart in the linked panel
     pub ext_colors: &'s ExtColorMap,
 }
 
@@ -59,6 +65,8 @@ impl<'s, 't> DisplayableTree<'s, 't> {
             skin,
             cols,
             show_selection_mark: false,
+            marked: &[],
+            unpaired: None,
             ext_colors,
             area: termimad::Area {
                 left: 0,
@@ -76,7 +84,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         selected: bool,
     ) -> CompoundStyle {
         let style = match &line.line_type {
-            TreeLineType::Dir => &self.skin.directory,
+            TreeLineType::Dir | TreeLineType::Submodule => &self.skin.directory,
             TreeLineType::File => {
                 if line.is_exe() {
                     &self.skin.exe
@@ -85,12 +93,26 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                 }
             }
             TreeLineType::SymLinkToFile(_) | TreeLineType::SymLinkToDir(_) => &self.skin.link,
+            TreeLineType::Special(_) => &self.skin.special,
             TreeLineType::Pruning => &self.skin.pruning,
         };
         let mut style = style.clone();
         if let Some(ext_color) = line.extension().and_then(|ext| self.ext_colors.get(ext)) {
             style.set_fg(ext_color);
         }
+        if line.normally_hidden {
+            // hidden and gitignored entries, when shown, are dimmed
+            style.overwrite_with(&self.skin.dimmed);
+        }
+        if line.owner_mismatch || line.audit_failed {
+            style.overwrite_with(&self.skin.owner_mismatch);
+        }
+        if line.immutable {
+            style.overwrite_with(&self.skin.immutable);
+        }
+        if !self.has_counterpart(line) {
+            style.overwrite_with(&self.skin.unpaired);
+        }
         if selected {
             if let Some(c) = self.skin.selected_line.get_bg() {
                 style.set_bg(c);
@@ -99,6 +121,12 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         style
     }
 
+    /// tell whether the entry exists at the same relative path
+    /// in the linked panel (which is true when there's none)
+    fn has_counterpart(&self, line: &TreeLine) -> bool {
+        self.unpaired.is_none_or(|unpaired| !unpaired.contains(&line.path))
+    }
+
     fn write_line_count<'w, W: Write>(
         &self,
         cw: &mut CropWriter<'w, W>,
@@ -114,15 +142,34 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         })
     }
 
+    fn write_line_matches<'w, W: Write>(
+        &self,
+        cw: &mut CropWriter<'w, W>,
+        line: &TreeLine,
+        selected: bool,
+    ) -> Result<usize, termimad::Error> {
+        Ok(if line.is_dir() && line.nb_matches > 0 {
+            cond_bg!(count_style, self, selected, self.skin.count);
+            cw.queue_g_string(count_style, format!("{:>6}", line.nb_matches))?;
+            1
+        } else {
+            7
+        })
+    }
+
     fn write_line_selection_mark<'w, W: Write>(
         &self,
         cw: &mut CropWriter<'w, W>,
         style: &CompoundStyle,
         selected: bool,
+        marked: bool,
     ) -> Result<usize, termimad::Error> {
-        Ok(if selected {
+        Ok(if selected && self.show_selection_mark {
             cw.queue_char(&style, '▶')?;
             0
+        } else if marked {
+            cw.queue_char(style, '◆')?;
+            0
         } else {
             1
         })
@@ -211,6 +258,24 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         Ok(1)
     }
 
+    fn write_git_author<'w, W: Write>(
+        &self,
+        cw: &mut CropWriter<'w, W>,
+        line: &TreeLine,
+        author_len: usize,
+        selected: bool,
+    ) -> Result<usize, termimad::Error> {
+        Ok(if let ComputationResult::Done(commit) = &line.last_commit {
+            let author: String = commit.author.chars().take(author_len).collect();
+            let void_len = author_len - author.chars().count();
+            cond_bg!(author_style, self, selected, self.skin.owner);
+            cw.queue_g_string(author_style, author)?;
+            void_len + 1
+        } else {
+            author_len + 1
+        })
+    }
+
     fn write_branch<'w, W: Write>(
         &self,
         cw: &mut CropWriter<'w, W>,
@@ -218,22 +283,27 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         line: &TreeLine,
         selected: bool,
     ) -> Result<usize, ProgramError> {
+        if self.tree.options.is_flat() {
+            // there's no branch in a flat list
+            return Ok(0);
+        }
         cond_bg!(branch_style, self, selected, self.skin.tree);
+        let glyphs = &self.tree.options.branch_glyphs;
         let mut branch = String::new();
         for depth in 0..line.depth {
             branch.push_str(
                 if line.left_branchs[depth as usize] {
                     if self.tree.has_branch(line_index + 1, depth as usize) {
                         if depth == line.depth - 1 {
-                            "├──"
+                            &glyphs.middle
                         } else {
-                            "│  "
+                            &glyphs.vertical
                         }
                     } else {
-                        "└──"
+                        &glyphs.last
                     }
                 } else {
-                    "   "
+                    &glyphs.blank
                 },
             );
         }
@@ -244,6 +314,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
     }
 
     /// write the name or subpath, depending on the pattern_object
+    /// (in flat mode, the path to the name is always written)
     fn write_line_label<'w, W: Write>(
         &self,
         cw: &mut CropWriter<'w, W>,
@@ -256,6 +327,10 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         let label = if pattern_object.subpath {
             &line.subpath
         } else {
+            if self.tree.options.is_flat() && line.subpath.ends_with(&line.name) {
+                let parent_len = line.subpath.len() - line.name.len();
+                cw.queue_str(style, &line.subpath[..parent_len])?;
+            }
             &line.name
         };
         let name_match = self.tree.options.pattern.pattern.search_string(label);
@@ -272,6 +347,16 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                     cw.queue_str(style, " …")?;
                 }
             }
+            TreeLineType::Submodule => {
+                if line.unlisted > 0 {
+                    cw.queue_str(style, " …")?;
+                }
+                cond_bg!(submodule_style, self, selected, self.skin.git_branch);
+                match line.submodule_status {
+                    Some(status) => cw.queue_g_string(submodule_style, format!(" [submodule: {}]", status))?,
+                    None => cw.queue_str(submodule_style, " [submodule]")?,
+                }
+            }
             TreeLineType::SymLinkToFile(target) | TreeLineType::SymLinkToDir(target) => {
                 cw.queue_str(style, " -> ")?;
                 if line.has_error {
@@ -286,8 +371,22 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                     cw.queue_str(target_style, &target)?;
                 }
             }
+            TreeLineType::Special(special_type) => {
+                cw.queue_char(style, special_type.marker())?;
+            }
             _ => {}
         }
+        if let Some(delta) = line.delta {
+            if self.tree.refresh_deltas.is_some_and(|deltas| deltas.is_recent()) {
+                let delta_style = match delta {
+                    LineDelta::Added => &self.skin.git_status_new,
+                    LineDelta::Resized => &self.skin.git_status_modified,
+                };
+                cond_bg!(delta_style, self, selected, delta_style);
+                cw.queue_char(style, ' ')?;
+                cw.queue_char(delta_style, delta.marker())?;
+            }
+        }
         Ok(1)
     }
 
@@ -354,6 +453,12 @@ impl<'s, 't> DisplayableTree<'s, 't> {
 
     /// write the whole tree on the given `W`
     pub fn write_on<W: Write>(&self, f: &mut W) -> Result<(), ProgramError> {
+        self.write_on_checking_crop(f).map(|_| ())
+    }
+
+    /// write the whole tree on the given `W`, and return whether the
+    /// name of the selected line had to be cropped
+    pub fn write_on_checking_crop<W: Write>(&self, f: &mut W) -> Result<bool, ProgramError> {
         #[cfg(unix)]
         let perm_writer = super::PermWriter::for_tree(&self.skin, &self.tree);
 
@@ -370,16 +475,33 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         let mut cw = CropWriter::new(f, self.area.width as usize);
         let pattern_object = tree.options.pattern.pattern.object();
         self.write_root_line(&mut cw, self.in_app && tree.selection == 0)?;
+        let mut selection_cropped = self.in_app
+            && tree.selection == 0
+            && tree.lines[0].path.to_string_lossy().chars().count() > self.area.width as usize;
         f.queue(SetBackgroundColor(Color::Reset))?;
 
         // we compute the length of the dates, depending on the format
-        let date_len = if tree.options.show_dates {
+        let date_len = if tree.options.shows_dates() || tree.options.shows_git_commit_date() {
             let date_time: DateTime<Local> = Local::now();
             date_time.format(tree.options.date_time_format).to_string().len()
         } else {
             0 // we don't care
         };
 
+        // authors are cropped so that long names don't eat the line
+        let author_len = if tree.options.shows_git_author() {
+            tree.lines.iter()
+                .filter_map(|line| match &line.last_commit {
+                    ComputationResult::Done(commit) => Some(commit.author.chars().count()),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0)
+                .min(20)
+        } else {
+            0
+        };
+
         for y in 1..self.area.height {
             if self.in_app {
                 f.queue(cursor::MoveTo(self.area.left, y + self.area.top))?;
@@ -410,8 +532,9 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                 for col in self.cols {
                     let void_len = match col {
 
-                        Col::Mark if self.show_selection_mark => {
-                            self.write_line_selection_mark(cw, &label_style, selected)?
+                        Col::Mark if self.show_selection_mark || !self.marked.is_empty() => {
+                            let marked = self.marked.iter().any(|p| p == &line.path);
+                            self.write_line_selection_mark(cw, &label_style, selected, marked)?
                         }
 
                         Col::Git if !tree.git_status.is_none() => {
@@ -424,11 +547,11 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                         }
 
                         #[cfg(unix)]
-                        Col::Permission if tree.options.show_permissions => {
+                        Col::Permission if tree.options.shows_permissions() => {
                             perm_writer.write_permissions(cw, line, selected)?
                         }
 
-                        Col::Date if tree.options.show_dates => {
+                        Col::Date if tree.options.shows_dates() => {
                             if let Some(seconds) = line.sum.and_then(|sum| sum.to_valid_seconds()) {
                                 self.write_date(cw, seconds, selected)?
                             } else {
@@ -436,7 +559,19 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                             }
                         }
 
-                        Col::Size if tree.options.show_sizes => {
+                        Col::GitCommitDate if tree.options.shows_git_commit_date() => {
+                            if let ComputationResult::Done(commit) = &line.last_commit {
+                                self.write_date(cw, commit.time, selected)?
+                            } else {
+                                date_len + 1
+                            }
+                        }
+
+                        Col::GitAuthor if tree.options.shows_git_author() => {
+                            self.write_git_author(cw, line, author_len, selected)?
+                        }
+
+                        Col::Size if tree.options.shows_sizes() => {
                             if tree.options.sort.is_some() {
                                 // as soon as there's only one level displayed we can show the size bars
                                 self.write_line_size_with_bar(cw, line, &label_style, total_size, selected)?
@@ -445,13 +580,21 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                             }
                         }
 
-                        Col::Count if tree.options.show_counts => {
+                        Col::Count if tree.options.shows_counts() => {
                             self.write_line_count(cw, line, selected)?
                         }
 
+                        Col::Matches if tree.options.pattern.is_some() => {
+                            self.write_line_matches(cw, line, selected)?
+                        }
+
                         Col::Name => {
                             in_branch = false;
-                            self.write_line_label(cw, line, &label_style, pattern_object, selected)?
+                            let void_len = self.write_line_label(cw, line, &label_style, pattern_object, selected)?;
+                            if selected && cw.is_full() {
+                                selection_cropped = true;
+                            }
+                            void_len
                         }
 
                         _ => {
@@ -460,7 +603,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                     };
                     // void: intercol & replacing missing cells
                     let (void_base_style, void) = if in_branch && void_len > 2 {
-                        (&self.skin.tree, LONG_BRANCH)
+                        (&self.skin.tree, tree.options.branch_glyphs.filling)
                     } else {
                         (&self.skin.default, LONG_SPACE)
                     };
@@ -479,7 +622,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
             f.queue(SetBackgroundColor(Color::Reset))?;
             if self.in_app && y > 0 {
                 if let Some((sctop, scbottom)) = scrollbar {
-                    f.queue(cursor::MoveTo(self.area.left + self.area.width - 1, y))?;
+                    f.queue(cursor::MoveTo(self.area.left + self.area.width - 1, y + self.area.top))?;
                     let style = if sctop <= y && y <= scbottom {
                         &self.skin.scrollbar_thumb
                     } else {
@@ -492,7 +635,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         if !self.in_app {
             write!(f, "\r\n")?;
         }
-        Ok(())
+        Ok(selection_cropped)
     }
 }
 
@@ -12,10 +12,35 @@ pub struct GitStatusDisplay<'a, 's> {
     skin: &'s StyleMap,
     show_branch: bool,
     show_wide: bool,
+    show_sync: bool,
+    show_stashes: bool,
     show_stats: bool,
     pub width: usize,
 }
 
+/// the counts of commits ahead and behind the upstream, eg `↑2↓1`,
+/// empty when the branch is in sync
+fn sync_string(status: &TreeGitStatus) -> String {
+    let mut s = String::new();
+    if let Some((ahead, behind)) = status.ahead_behind {
+        if ahead > 0 {
+            s.push_str(&format!("↑{}", ahead));
+        }
+        if behind > 0 {
+            s.push_str(&format!("↓{}", behind));
+        }
+    }
+    s
+}
+
+fn stashes_string(status: &TreeGitStatus) -> String {
+    if status.stashes > 0 {
+        format!("stash:{}", status.stashes)
+    } else {
+        String::new()
+    }
+}
+
 impl<'a, 's> GitStatusDisplay<'a, 's> {
     pub fn from(status: &'a TreeGitStatus, skin: &'s StyleMap, available_width: usize) -> Self {
         let mut show_branch = false;
@@ -27,6 +52,19 @@ impl<'a, 's> GitStatusDisplay<'a, 's> {
                 show_branch = true;
             }
         }
+        // the sync and stashes counts are written after the branch
+        let mut show_sync = false;
+        let sync_width = sync_string(status).chars().count();
+        if show_branch && sync_width > 0 && width + sync_width + 1 < available_width {
+            width += sync_width + 1;
+            show_sync = true;
+        }
+        let mut show_stashes = false;
+        let stashes_width = stashes_string(status).len();
+        if show_branch && stashes_width > 0 && width + stashes_width + 1 < available_width {
+            width += stashes_width + 1;
+            show_stashes = true;
+        }
         let mut show_stats = false;
         let unstyled_stats = format!("+{}-{}", status.insertions, status.deletions);
         let stats_width = unstyled_stats.len();
@@ -42,6 +80,8 @@ impl<'a, 's> GitStatusDisplay<'a, 's> {
             status,
             skin,
             show_branch,
+            show_sync,
+            show_stashes,
             show_stats,
             show_wide,
             width,
@@ -66,6 +106,14 @@ impl<'a, 's> GitStatusDisplay<'a, 's> {
                 }
                 cw.queue_str(&branch_style, name)?;
                 cw.queue_char(&branch_style, ' ')?;
+                if self.show_sync {
+                    cw.queue_g_string(branch_style, sync_string(self.status))?;
+                    cw.queue_char(branch_style, ' ')?;
+                }
+                if self.show_stashes {
+                    cw.queue_g_string(branch_style, stashes_string(self.status))?;
+                    cw.queue_char(branch_style, ' ')?;
+                }
             }
         }
         if self.show_stats {
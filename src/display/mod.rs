@@ -21,6 +21,7 @@ macro_rules! cond_bg {
 }
 
 mod areas;
+mod branch_glyphs;
 mod col;
 mod crop_writer;
 mod displayable_tree;
@@ -28,6 +29,8 @@ pub mod flags_display;
 mod git_status_display;
 pub mod status_line;
 mod matched_string;
+mod panels_layout;
+mod responsive_cols;
 mod screen;
 
 #[cfg(unix)]
@@ -35,11 +38,14 @@ mod permissions;
 
 pub use {
     areas::Areas,
+    branch_glyphs::BranchGlyphs,
     col::{Col, Cols, DEFAULT_COLS},
     crop_writer::CropWriter,
     displayable_tree::DisplayableTree,
     git_status_display::GitStatusDisplay,
     matched_string::MatchedString,
+    panels_layout::{PanelsDirection, PanelsLayout},
+    responsive_cols::ResponsiveCols,
     screen::Screen,
 };
 use {
@@ -69,6 +75,19 @@ pub static LONG_BRANCH: &str = "────────────────
 /// of the terminal (over the other panels)
 pub const WIDE_STATUS: bool = true;
 
+/// width, in cells, of the progress bars of the status
+pub const PROGRESS_BAR_WIDTH: usize = 20;
+
+/// build a bar of PROGRESS_BAR_WIDTH cells filled up to the ratio
+pub fn progress_bar(ratio: f64) -> String {
+    let filled = ((ratio * PROGRESS_BAR_WIDTH as f64) as usize).min(PROGRESS_BAR_WIDTH);
+    format!(
+        "{}{}",
+        "█".repeat(filled),
+        "░".repeat(PROGRESS_BAR_WIDTH - filled),
+    )
+}
+
 /// the type used by all GUI writing functions
 pub type W = std::io::BufWriter<std::io::Stderr>;
 
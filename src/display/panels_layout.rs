@@ -0,0 +1,148 @@
+use {
+    crate::errors::ConfError,
+    std::collections::HashMap,
+};
+
+/// how panels are placed relatively to each other
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PanelsDirection {
+    /// side by side, from left to right
+    #[default]
+    Columns,
+    /// stacked, from top to bottom, each panel having
+    /// its own input line
+    Rows,
+}
+
+/// the configurable constraints of the layout of panels, and
+/// the relative sizes the user gave them during the session
+#[derive(Debug, Clone)]
+pub struct PanelsLayout {
+    pub direction: PanelsDirection,
+    pub min_panel_width: u16,
+    pub min_panel_height: u16,
+    pub titles: bool, // whether panels have a title bar when there are several ones
+    weights: HashMap<usize, Vec<u16>>, // by number of panels
+}
+
+impl PanelsDirection {
+    pub fn from(raw: &str) -> Result<Self, ConfError> {
+        match raw {
+            "columns" => Ok(Self::Columns),
+            "rows" => Ok(Self::Rows),
+            _ => Err(ConfError::InvalidPanelsDirection {
+                raw: raw.to_string(),
+            }),
+        }
+    }
+}
+
+impl Default for PanelsLayout {
+    fn default() -> Self {
+        Self {
+            direction: PanelsDirection::Columns,
+            min_panel_width: 20,
+            min_panel_height: 10,
+            titles: true,
+            weights: HashMap::new(),
+        }
+    }
+}
+
+/// the weights of panels when the user didn't resize them: all
+/// panels have the same size, except the preview which is a
+/// little larger
+fn default_weights(nb_panels: usize, with_preview: bool) -> Vec<u16> {
+    (0..nb_panels)
+        .map(|i| if with_preview && i + 1 == nb_panels { 4 } else { 3 })
+        .collect()
+}
+
+/// share the total size between panels according to their weights,
+/// the last panel getting the rest of the divisions. Return None
+/// if a panel would be smaller than min
+fn distribute(total: u16, weights: &[u16], min: u16) -> Option<Vec<u16>> {
+    let sum: u32 = weights.iter().map(|&w| w as u32).sum();
+    if weights.is_empty() || sum == 0 {
+        return None;
+    }
+    let mut sizes: Vec<u16> = weights
+        .iter()
+        .map(|&w| (total as u32 * w as u32 / sum) as u16)
+        .collect();
+    let last = sizes.len() - 1;
+    sizes[last] = total - sizes[..last].iter().sum::<u16>();
+    if sizes.iter().any(|&size| size < min) {
+        None
+    } else {
+        Some(sizes)
+    }
+}
+
+impl PanelsLayout {
+    /// compute the sizes (widths or heights, depending on the
+    /// direction) of panels sharing the total size, or None when
+    /// there's not enough space for the panels
+    pub fn sizes(
+        &self,
+        total: u16,
+        nb_panels: usize,
+        with_preview: bool,
+        min: u16,
+    ) -> Option<Vec<u16>> {
+        self.weights
+            .get(&nb_panels)
+            .and_then(|weights| distribute(total, weights, min))
+            .or_else(|| distribute(total, &default_weights(nb_panels, with_preview), min))
+    }
+
+    /// remember the sizes of the panels, so that their ratio is kept
+    /// when the screen is resized or when there's again the same
+    /// number of panels
+    pub fn set_sizes(&mut self, sizes: Vec<u16>) {
+        self.weights.insert(sizes.len(), sizes);
+    }
+
+    /// the minimal size of a panel in the direction of the layout
+    pub fn min_panel_size(&self) -> u16 {
+        match self.direction {
+            PanelsDirection::Columns => self.min_panel_width,
+            PanelsDirection::Rows => self.min_panel_height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod panels_layout_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_distribute() {
+        // the last panel gets the rest of the divisions
+        assert_eq!(distribute(100, &[1, 1, 1], 10), Some(vec![33, 33, 34]));
+        assert_eq!(distribute(100, &[3, 3, 4], 10), Some(vec![30, 30, 40]));
+        // the sum of the sizes is always the total
+        let sizes = distribute(97, &[5, 7, 11, 2], 1).unwrap();
+        assert_eq!(sizes.iter().sum::<u16>(), 97);
+        // a panel would be too small
+        assert_eq!(distribute(100, &[1, 9], 20), None);
+        assert_eq!(distribute(100, &[], 10), None);
+        assert_eq!(distribute(100, &[0, 0], 10), None);
+    }
+
+    #[test]
+    fn check_kept_sizes() {
+        let mut layout = PanelsLayout::default();
+        assert_eq!(layout.sizes(70, 2, false, 20), Some(vec![35, 35]));
+        // the sizes set by the user are kept as a ratio
+        layout.set_sizes(vec![40, 60]);
+        assert_eq!(layout.sizes(200, 2, false, 20), Some(vec![80, 120]));
+        // unless a panel would be too small
+        layout.set_sizes(vec![10, 90]);
+        assert_eq!(layout.sizes(100, 2, false, 20), Some(vec![50, 50]));
+        // other numbers of panels have their own sizes
+        assert_eq!(layout.sizes(100, 3, true, 20), Some(vec![30, 30, 40]));
+        assert_eq!(layout.sizes(50, 3, true, 20), None);
+    }
+}
@@ -134,7 +134,7 @@ impl<'s> PermWriter<'s> {
 fn user_group_max_lengths(tree: &Tree) -> (usize, usize) {
     let mut max_user_len = 0;
     let mut max_group_len = 0;
-    if tree.options.show_permissions {
+    if tree.options.shows_permissions() {
         for i in 1..tree.lines.len() {
             let line = &tree.lines[i];
             let user = permissions::user_name(line.metadata.uid());
@@ -0,0 +1,53 @@
+use {
+    super::Col,
+};
+
+/// the panel widths under which optional columns are hidden, and
+/// over which they're shown even when not enabled in the tree options
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponsiveCols {
+    hidden_under: Vec<(Col, u16)>,
+    shown_over: Vec<(Col, u16)>,
+}
+
+impl Default for ResponsiveCols {
+    /// by default, dates are dropped first, then permissions,
+    /// and nothing is added on wide panels
+    fn default() -> Self {
+        Self {
+            hidden_under: vec![(Col::Date, 80), (Col::Permission, 60)],
+            shown_over: Vec::new(),
+        }
+    }
+}
+
+fn set_breakpoint(breakpoints: &mut Vec<(Col, u16)>, col: Col, width: u16) {
+    breakpoints.retain(|&(c, _)| c != col);
+    if width > 0 {
+        breakpoints.push((col, width));
+    }
+}
+
+impl ResponsiveCols {
+    /// set the width under which the column is hidden (0
+    /// meaning it's never hidden)
+    pub fn set_hidden_under(&mut self, col: Col, width: u16) {
+        set_breakpoint(&mut self.hidden_under, col, width);
+    }
+
+    /// set the width from which the column is always shown (0
+    /// meaning it's only shown when enabled)
+    pub fn set_shown_over(&mut self, col: Col, width: u16) {
+        set_breakpoint(&mut self.shown_over, col, width);
+    }
+
+    /// tell whether the column is displayed in a panel of the given
+    /// width, `enabled` being its state in the tree options
+    pub fn is_shown(&self, col: Col, enabled: bool, width: u16) -> bool {
+        if self.hidden_under.iter().any(|&(c, w)| c == col && width < w) {
+            false
+        } else {
+            enabled || self.shown_over.iter().any(|&(c, w)| c == col && width >= w)
+        }
+    }
+}
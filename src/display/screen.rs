@@ -7,9 +7,11 @@ use {
     },
     crossterm::{
         cursor,
+        style::Print,
         terminal::{Clear, ClearType},
         QueueableCommand,
     },
+    std::io::Write,
     super::W,
     termimad::Area,
 };
@@ -17,6 +19,12 @@ use {
 pub struct Screen {
     pub width: u16,
     pub height: u16,
+    /// the first terminal line broot draws panels on (not 0 when broot
+    /// is displayed inline, below the previous output of the shell, or
+    /// when there's a tab bar)
+    pub top: u16,
+    /// whether the line above the panels is used by the tab bar
+    tab_bar: bool,
 }
 
 impl Screen {
@@ -24,6 +32,8 @@ impl Screen {
         let mut screen = Screen {
             width: 0,
             height: 0,
+            top: 0,
+            tab_bar: false,
         };
         screen.read_size(con)?;
         Ok(screen)
@@ -34,12 +44,55 @@ impl Screen {
         if let Some(h) = con.launch_args.height {
             self.height = h;
         }
+        if self.tab_bar {
+            self.height -= 1;
+        }
+    }
+    /// keep the first line for the tab bar, or give it back to panels
+    pub fn set_tab_bar(&mut self, shown: bool) {
+        if shown && !self.tab_bar {
+            self.top += 1;
+            self.height -= 1;
+        } else if !shown && self.tab_bar {
+            self.top -= 1;
+            self.height += 1;
+        }
+        self.tab_bar = shown;
+    }
+    /// the line of the tab bar, if it's shown
+    pub fn tab_bar_y(&self) -> Option<u16> {
+        if self.tab_bar {
+            Some(self.top - 1)
+        } else {
+            None
+        }
     }
     pub fn read_size(&mut self, con: &AppContext) -> Result<(), ProgramError> {
         let (w, h) = termimad::terminal_size();
         self.set_terminal_size(w, h, con);
         Ok(())
     }
+    /// make room for broot below the cursor, in the normal screen
+    /// buffer, and remember where this room starts.
+    /// Must be called instead of entering the alternate screen.
+    pub fn prepare_inline(&mut self, w: &mut W) -> Result<(), ProgramError> {
+        let (_, terminal_height) = termimad::terminal_size();
+        self.height = self.height.min(terminal_height);
+        for _ in 1..self.height {
+            w.queue(Print('\n'))?;
+        }
+        w.flush()?;
+        let (_, y) = cursor::position()?;
+        self.top = (y + 1).saturating_sub(self.height);
+        Ok(())
+    }
+    /// clear the lines used by broot when displayed inline
+    /// and leave the cursor at the start of this room
+    pub fn clear_inline(&self, w: &mut W) -> Result<(), ProgramError> {
+        self.goto(w, 0, self.top)?;
+        w.queue(Clear(ClearType::FromCursorDown))?;
+        Ok(())
+    }
     /// move the cursor to x,y
     pub fn goto(&self, w: &mut W, x: u16, y: u16) -> Result<(), ProgramError> {
         w.queue(cursor::MoveTo(x, y))?;
@@ -68,7 +121,7 @@ impl Screen {
         w: &mut W,
         panel_skin: &PanelSkin,
     ) -> Result<(), ProgramError> {
-        self.goto(w, self.width, self.height)?;
+        self.goto(w, self.width, self.top + self.height)?;
         panel_skin.styles.default.queue(w, ' ')?;
         Ok(())
     }
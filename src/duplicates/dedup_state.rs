@@ -0,0 +1,202 @@
+use {
+    super::same_content,
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        mover::free_path,
+        skin::PanelSkin,
+        verb::*,
+    },
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::{Area, FmtText, TextView},
+};
+
+enum DedupStep {
+    /// the copies are listed, waiting for the user's confirmation
+    Confirm,
+    Done(usize), // number of removed or linked copies
+    Failed(Vec<String>),
+}
+
+/// a modal state asking for the confirmation before keeping only
+/// one copy of identical files, either moving the other ones to
+/// the trash or replacing them with hard links
+pub struct DedupState {
+    kept: PathBuf,
+    others: Vec<PathBuf>,
+    link: bool,
+    step: DedupStep,
+}
+
+/// replace the file at path with a hard link to target. The link
+/// is created with a temporary name first so that the file isn't
+/// lost if the link can't be made (for example across devices)
+fn replace_with_link(target: &Path, path: &Path) -> io::Result<()> {
+    let tmp = free_path(&path.with_file_name(".broot-dedup-link"));
+    fs::hard_link(target, &tmp)?;
+    fs::rename(&tmp, path)
+}
+
+impl DedupState {
+    pub fn new(kept: PathBuf, others: Vec<PathBuf>, link: bool) -> Self {
+        Self {
+            kept,
+            others,
+            link,
+            step: DedupStep::Confirm,
+        }
+    }
+
+    fn apply(&self) -> DedupStep {
+        let mut done = 0;
+        let mut errors = Vec::new();
+        for path in &self.others {
+            let res = match same_content(&self.kept, path) {
+                Ok(true) if self.link => replace_with_link(&self.kept, path),
+                Ok(true) => trash::delete(path)
+                    .map_err(|e| io::Error::other(e.to_string())),
+                Ok(false) => Err(io::Error::other("content changed")),
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(()) => done += 1,
+                Err(e) => errors.push(format!("{}: {}", path.to_string_lossy(), e)),
+            }
+        }
+        if errors.is_empty() {
+            DedupStep::Done(done)
+        } else {
+            DedupStep::Failed(errors)
+        }
+    }
+
+    fn action(&self) -> &'static str {
+        if self.link {
+            "replaced with hard links to it"
+        } else {
+            "moved to the trash"
+        }
+    }
+
+    fn markdown(&self) -> String {
+        match &self.step {
+            DedupStep::Confirm => {
+                let mut md = format!(
+                    "\nOnly `{}` will be kept. The other copies will be {}:\n\n",
+                    self.kept.to_string_lossy(),
+                    self.action(),
+                );
+                for path in &self.others {
+                    md.push_str(&format!("* `{}`\n", path.to_string_lossy()));
+                }
+                md.push_str("\nType *y* to confirm, any other key to cancel.");
+                md
+            }
+            DedupStep::Done(count) => format!("\n{} copies were {}", count, self.action()),
+            DedupStep::Failed(errors) => {
+                let mut md = "\nSome copies couldn't be processed:\n\n".to_string();
+                for e in errors {
+                    md.push_str(&format!("* `{}`\n", e));
+                }
+                md
+            }
+        }
+    }
+}
+
+impl AppState for DedupState {
+
+    fn selected_path(&self) -> &Path {
+        &self.kept
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.kept,
+            line: 0,
+            stype: SelectionType::File,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            DedupStep::Confirm => match cc.cmd {
+                Command::PatternEdit { raw, .. } if raw == "y" || raw == "Y" => {
+                    self.step = self.apply();
+                    AppStateCmdResult::Keep
+                }
+                _ => AppStateCmdResult::PopState, // any other key cancels
+            },
+            // the list of duplicates is refreshed to forget the handled copies
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: false },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        _internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match self.step {
+            DedupStep::Confirm => AppStateCmdResult::PopState,
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: false },
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            DedupStep::Confirm => format!(
+                "Keep only one copy, the {} other ones being {} ? *y*/*N*",
+                self.others.len(),
+                self.action(),
+            ),
+            _ => "Hit any key to go back".to_string(),
+        })
+    }
+}
@@ -0,0 +1,335 @@
+use {
+    super::{DedupState, DuplicateFinder, DuplicateGroup},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        handlers,
+        skin::PanelSkin,
+        task_sync::Dam,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    },
+    termimad::Area,
+};
+
+/// how long the search goes on before the progress is displayed again
+const DISPLAY_PERIOD: Duration = Duration::from_millis(200);
+
+/// a displayed line
+#[derive(Debug, Clone, Copy)]
+enum Line {
+    Group(usize),        // index of the group
+    Copy(usize, usize),  // index of the group, index of the path in the group
+}
+
+/// an application state searching the files with identical content
+/// under a directory, then displaying them by groups, with verbs to
+/// keep only one copy of a group
+pub struct DuplicatesState {
+    root: PathBuf,
+    finder: Option<DuplicateFinder>, // None once the search is done
+    groups: Vec<DuplicateGroup>,
+    lines: Vec<Line>,
+    selection_idx: usize, // index in lines, always on a copy when there's one
+    scroll: usize,
+    page_height: usize,
+}
+
+impl DuplicatesState {
+    pub fn new(root: PathBuf, options: &TreeOptions) -> Self {
+        Self {
+            finder: Some(DuplicateFinder::new(&root, options)),
+            root,
+            groups: Vec::new(),
+            lines: Vec::new(),
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+        }
+    }
+
+    fn compute_lines(&mut self) {
+        self.lines.clear();
+        for (group_idx, group) in self.groups.iter().enumerate() {
+            self.lines.push(Line::Group(group_idx));
+            for path_idx in 0..group.paths.len() {
+                self.lines.push(Line::Copy(group_idx, path_idx));
+            }
+        }
+        self.selection_idx = self.selection_idx.min(self.lines.len().max(1) - 1);
+        if let Some(Line::Group(_)) = self.lines.get(self.selection_idx) {
+            self.selection_idx += 1;
+        }
+        self.ensure_selection_is_visible();
+    }
+
+    fn selected_copy(&self) -> Option<(usize, &Path)> {
+        match self.lines.get(self.selection_idx) {
+            Some(&Line::Copy(group_idx, path_idx)) => {
+                Some((group_idx, &self.groups[group_idx].paths[path_idx]))
+            }
+            _ => None,
+        }
+    }
+
+    /// move the selection by dy copies, skipping the group headers
+    fn move_selection(&mut self, dy: i32) {
+        let copies: Vec<usize> = self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| match line {
+                Line::Copy(..) => true,
+                Line::Group(_) => false,
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        if copies.is_empty() {
+            return;
+        }
+        let current = copies.iter().position(|&idx| idx == self.selection_idx).unwrap_or(0) as i32;
+        let new = (current + dy).max(0).min(copies.len() as i32 - 1);
+        self.selection_idx = copies[new as usize];
+        self.ensure_selection_is_visible();
+    }
+
+    fn ensure_selection_is_visible(&mut self) {
+        // the header of the group is kept visible with its first copy
+        let top = self.selection_idx.saturating_sub(1);
+        if top < self.scroll {
+            self.scroll = top;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    /// ask for the confirmation of keeping only the selected copy of
+    /// its group, either removing the other ones (to the trash) or
+    /// replacing them with hard links
+    fn dedup_selected_group(&self, link: bool) -> AppStateCmdResult {
+        match self.selected_copy() {
+            Some((group_idx, kept)) => {
+                let others = self.groups[group_idx]
+                    .paths
+                    .iter()
+                    .filter(|&path| path != kept)
+                    .cloned()
+                    .collect();
+                AppStateCmdResult::NewState(Box::new(
+                    DedupState::new(kept.to_path_buf(), others, link),
+                ))
+            }
+            None => AppStateCmdResult::DisplayError("no selected copy".to_string()),
+        }
+    }
+}
+
+impl AppState for DuplicatesState {
+
+    fn selected_path(&self) -> &Path {
+        self.selected_copy().map_or(&self.root, |(_, path)| path)
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        match self.selected_copy() {
+            Some((_, path)) => Selection {
+                path,
+                line: 0,
+                stype: SelectionType::File,
+                marked: &[],
+            },
+            None => Selection {
+                path: &self.root,
+                line: 0,
+                stype: SelectionType::Directory,
+                marked: &[],
+            },
+        }
+    }
+
+    /// forget the copies which were handled, for example after
+    /// a confirmed :remove_duplicates
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        for group in &mut self.groups {
+            group.forget_handled_copies();
+        }
+        self.groups.retain(|group| group.paths.len() > 1);
+        self.compute_lines();
+        Command::empty()
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        if self.finder.is_some() {
+            Some("searching duplicates")
+        } else {
+            None
+        }
+    }
+
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        let start = Instant::now();
+        if let Some(finder) = &mut self.finder {
+            while !finder.is_done() {
+                finder.step();
+                if dam.has_event() || start.elapsed() > DISPLAY_PERIOD {
+                    return;
+                }
+            }
+            self.groups = finder.groups();
+            self.finder = None;
+            self.compute_lines();
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.move_selection(-(self.lines.len() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.move_selection(self.lines.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay => match self.selected_copy() {
+                Some((_, path)) => handlers::open_file_stay(w, path, cc.con)?,
+                None => AppStateCmdResult::Keep,
+            },
+            Internal::remove_duplicates => self.dedup_selected_group(false),
+            Internal::link_duplicates => self.dedup_selected_group(true),
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            let idx = self.scroll + y as usize;
+            if let Some(finder) = &self.finder {
+                if y == 0 {
+                    cw.queue_g_string(&styles.dates, finder.progress())?;
+                }
+                cw.fill(&styles.default, LONG_SPACE)?;
+                continue;
+            }
+            match self.lines.get(idx) {
+                Some(&Line::Group(group_idx)) => {
+                    let group = &self.groups[group_idx];
+                    cw.queue_g_string(
+                        &styles.dates,
+                        format!(
+                            "{} copies of {} ({} wasted)",
+                            group.paths.len(),
+                            file_size::fit_4(group.size),
+                            file_size::fit_4(group.wasted()),
+                        ),
+                    )?;
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                }
+                Some(&Line::Copy(group_idx, path_idx)) => {
+                    let selected = idx == self.selection_idx;
+                    let mut path_style = styles.file.clone();
+                    let mut text_style = styles.default.clone();
+                    if selected {
+                        if let Some(c) = styles.selected_line.get_bg() {
+                            path_style.set_bg(c);
+                            text_style.set_bg(c);
+                        }
+                    }
+                    if con.show_selection_mark {
+                        cw.queue_char(&text_style, if selected { '▶' } else { ' ' })?;
+                    }
+                    let path = &self.groups[group_idx].paths[path_idx];
+                    let path = path.strip_prefix(&self.root).unwrap_or(path);
+                    cw.queue_str(&text_style, "  ")?;
+                    cw.queue_g_string(&path_style, path.to_string_lossy().to_string())?;
+                    cw.fill(&text_style, LONG_SPACE)?;
+                }
+                None => {
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.finder.is_some() {
+            Status::from_message("Searching files with identical content... hit *esc* to cancel")
+        } else if self.groups.is_empty() {
+            Status::from_message("No duplicate file found. Hit *esc* to go back")
+        } else {
+            let wasted: u64 = self.groups.iter().map(DuplicateGroup::wasted).sum();
+            Status::from_message(format!(
+                "{} groups of identical files, {} wasted. \
+                Use *:remove_duplicates* or *:link_duplicates* to keep only the selected copy",
+                self.groups.len(),
+                file_size::fit_4(wasted),
+            ))
+        }
+    }
+}
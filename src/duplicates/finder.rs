@@ -0,0 +1,337 @@
+use {
+    crate::{
+        git::{GitIgnoreChain, GitIgnorer, IgnoreStatus},
+        pattern::{Candidate, Pattern},
+        tree::TreeOptions,
+    },
+    std::{
+        collections::{hash_map::DefaultHasher, HashMap, HashSet},
+        fs::{self, File},
+        hash::Hasher,
+        io::{self, Read},
+        path::{Path, PathBuf},
+    },
+};
+
+/// files with the same content
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub size: u64, // size of one file
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// the space which would be freed by keeping only one copy
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+    /// forget the copies which were removed, or replaced with
+    /// hard links to another copy of the group
+    pub fn forget_handled_copies(&mut self) {
+        let mut ids = HashSet::new();
+        self.paths.retain(|path| match fs::symlink_metadata(path) {
+            Ok(md) => file_id(&md).is_none_or(|id| ids.insert(id)),
+            Err(_) => false,
+        });
+    }
+}
+
+/// the (device, inode) identifying the file, shared by hard links
+#[cfg(unix)]
+fn file_id(md: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((md.dev(), md.ino()))
+}
+#[cfg(not(unix))]
+fn file_id(_md: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// the current step of the search
+enum Step {
+    /// the directories are read to find the files and their sizes
+    Listing,
+    /// the files having the same size than another one are hashed
+    Hashing,
+    Done,
+}
+
+/// a search of the files with identical content under a directory,
+/// done in small steps so that it can be interrupted and its
+/// progress displayed.
+///
+/// Files are first grouped by size, then the ones sharing their
+/// size with another file are hashed.
+///
+/// Like in the tree, hidden and gitignored files are skipped
+/// according to the options, and only the files matching the
+/// pattern are considered.
+pub struct DuplicateFinder {
+    root: PathBuf,
+    show_hidden: bool,
+    pattern: Pattern,
+    git_ignorer: GitIgnorer,
+    step: Step,
+    dirs: Vec<(PathBuf, GitIgnoreChain)>, // directories still to read
+    by_size: HashMap<u64, Vec<PathBuf>>,
+    candidates: Vec<(u64, PathBuf)>, // files to hash
+    hashed_count: usize,
+    by_hash: HashMap<(u64, u64), Vec<PathBuf>>,
+    file_ids: HashSet<(u64, u64)>, // (device, inode) of the listed files
+    pub files_count: usize,
+}
+
+/// compute a hash of the whole content of the file
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(hasher.finish());
+        }
+        hasher.write(&buffer[..n]);
+    }
+}
+
+/// check byte per byte that both files have the same content,
+/// to be sure before removing one of them
+pub fn same_content(a: &Path, b: &Path) -> io::Result<bool> {
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    let mut a = File::open(a)?;
+    let mut b = File::open(b)?;
+    let mut buffer_a = vec![0; 64 * 1024];
+    let mut buffer_b = vec![0; 64 * 1024];
+    loop {
+        let n = a.read(&mut buffer_a)?;
+        if n == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buffer_b[..n])?;
+        if buffer_a[..n] != buffer_b[..n] {
+            return Ok(false);
+        }
+    }
+}
+
+impl DuplicateFinder {
+    pub fn new(root: &Path, options: &TreeOptions) -> Self {
+        let mut git_ignorer = GitIgnorer::new(
+            options.git_ignore_mode,
+            options.respect_ignore_files,
+            options.respect_broot_ignore,
+        );
+        let root_chain = git_ignorer.root_chain(root);
+        Self {
+            root: root.to_path_buf(),
+            show_hidden: options.show_hidden,
+            pattern: options.pattern.pattern.clone(),
+            git_ignorer,
+            step: Step::Listing,
+            dirs: vec![(root.to_path_buf(), root_chain)],
+            by_size: HashMap::new(),
+            candidates: Vec::new(),
+            hashed_count: 0,
+            by_hash: HashMap::new(),
+            file_ids: HashSet::new(),
+            files_count: 0,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self.step, Step::Done)
+    }
+
+    /// a short description of the progress of the search
+    pub fn progress(&self) -> String {
+        match self.step {
+            Step::Listing => format!("listing files: {} found", self.files_count),
+            Step::Hashing => format!(
+                "comparing files: {} / {}",
+                self.hashed_count,
+                self.candidates.len(),
+            ),
+            Step::Done => "done".to_string(),
+        }
+    }
+
+    /// do a small part of the search: read a directory or
+    /// hash a file
+    pub fn step(&mut self) {
+        match self.step {
+            Step::Listing => {
+                match self.dirs.pop() {
+                    Some((dir, chain)) => self.read_dir(&dir, &chain),
+                    None => {
+                        // only the files whose size isn't unique must be hashed
+                        for (size, paths) in self.by_size.drain() {
+                            if paths.len() > 1 {
+                                self.candidates.extend(paths.into_iter().map(|p| (size, p)));
+                            }
+                        }
+                        self.step = Step::Hashing;
+                    }
+                }
+            }
+            Step::Hashing => {
+                match self.candidates.get(self.hashed_count) {
+                    Some((size, path)) => {
+                        match hash_file(path) {
+                            Ok(hash) => {
+                                self.by_hash
+                                    .entry((*size, hash))
+                                    .or_default()
+                                    .push(path.clone());
+                            }
+                            Err(e) => {
+                                debug!("can't hash {:?}: {:?}", path, e);
+                            }
+                        }
+                        self.hashed_count += 1;
+                    }
+                    None => {
+                        self.step = Step::Done;
+                    }
+                }
+            }
+            Step::Done => {}
+        }
+    }
+
+    fn read_dir(&mut self, dir: &Path, chain: &GitIgnoreChain) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("can't read {:?}: {:?}", dir, e);
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if !self.show_hidden && name.starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            // symlinks aren't followed
+            let md = match fs::symlink_metadata(&path) {
+                Ok(md) => md,
+                Err(_) => continue,
+            };
+            if self.git_ignorer.status(chain, &path, &name, md.is_dir()) == IgnoreStatus::Hidden {
+                continue;
+            }
+            if md.is_dir() {
+                let chain = self.git_ignorer.deeper_chain(chain, &path);
+                self.dirs.push((path, chain));
+            } else if md.is_file()
+                && md.len() > 0
+                && self.matches_pattern(&path, &name)
+                && self.is_new_file(&md)
+            {
+                self.files_count += 1;
+                self.by_size.entry(md.len()).or_default().push(path);
+            }
+        }
+    }
+
+    fn matches_pattern(&self, path: &Path, name: &str) -> bool {
+        let subpath = path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy();
+        let candidate = Candidate {
+            path,
+            subpath: &subpath,
+            name,
+            regular_file: true,
+        };
+        self.pattern.score_of(candidate).is_some()
+    }
+
+    /// tell whether the file wasn't already listed under another
+    /// name, as hard links to the same file aren't duplicates
+    fn is_new_file(&mut self, md: &fs::Metadata) -> bool {
+        file_id(md).is_none_or(|id| self.file_ids.insert(id))
+    }
+
+    /// return the groups of files with the same content, the
+    /// ones wasting the most space first
+    pub fn groups(&self) -> Vec<DuplicateGroup> {
+        let mut groups: Vec<DuplicateGroup> = self
+            .by_hash
+            .iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(&(size, _), paths)| {
+                let mut paths = paths.clone();
+                paths.sort();
+                DuplicateGroup { size, paths }
+            })
+            .collect();
+        groups.sort_by(|a, b| b.wasted().cmp(&a.wasted()).then_with(|| a.paths.cmp(&b.paths)));
+        groups
+    }
+}
+
+#[cfg(test)]
+mod finder_tests {
+
+    use {
+        super::*,
+        crate::pattern::RegexPattern,
+        tempfile::TempDir,
+    };
+
+    fn find(root: &Path, options: &TreeOptions) -> Vec<Vec<String>> {
+        let mut finder = DuplicateFinder::new(root, options);
+        while !finder.is_done() {
+            finder.step();
+        }
+        finder
+            .groups()
+            .iter()
+            .map(|group| {
+                group.paths
+                    .iter()
+                    .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_groups() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        for (name, content) in &[
+            ("a.txt", "same"),
+            ("b.md", "same"),
+            ("sub/c.txt", "same"),
+            ("d.txt", "other"),
+            ("e.txt", "longer content"),
+            ("f.txt", "longer content"),
+            (".hidden.txt", "same"),
+            ("ignored.txt", "same"),
+            (".ignore", "ignored.txt"),
+        ] {
+            fs::write(root.join(name), content).unwrap();
+        }
+        let mut options = TreeOptions::default();
+        assert_eq!(
+            find(root, &options),
+            vec![
+                vec!["e.txt".to_string(), "f.txt".to_string()],
+                vec!["a.txt".to_string(), "b.md".to_string(), "sub/c.txt".to_string()],
+            ],
+        );
+        options.pattern.pattern = Pattern::NameRegex(RegexPattern::from("txt$", "").unwrap());
+        assert_eq!(
+            find(root, &options),
+            vec![
+                vec!["e.txt".to_string(), "f.txt".to_string()],
+                vec!["a.txt".to_string(), "sub/c.txt".to_string()],
+            ],
+        );
+    }
+}
@@ -0,0 +1,12 @@
+//! Search of the files with identical content in a directory, and
+//! removal of the redundant copies.
+
+mod dedup_state;
+mod duplicates_state;
+mod finder;
+
+pub use {
+    dedup_state::DedupState,
+    duplicates_state::DuplicatesState,
+    finder::{same_content, DuplicateFinder, DuplicateGroup},
+};
@@ -19,7 +19,9 @@ custom_error! {pub ProgramError
     UnmatchingVerbArgs {name: String} = "No matching argument found for verb {:?}",
     TreeBuild {source: TreeBuildError} = "{}",
     LaunchError {program: String, source: io::Error} = "Unable to launch {program}: {source}",
+    MissingWorkingDir {path: String} = "The working dir doesn't exist: {path}",
     UnknowShell {shell: String} = "Unknown shell: {shell}",
+    UnknownScenario {name: String} = "Unknown scenario: {name} (neither a builtin one nor a file)",
     InternalError {details: String} = "Internal error: {details}", // should not happen
     TerminalTooSmallError = "Terminal too small", // unable to open panel or app
     InvalidGlobError {pattern: String} = "Invalid glob: {pattern}",
@@ -43,7 +45,24 @@ custom_error! {pub ConfError
     InvalidKey {raw: String}                        = "not a valid key: {}",
     ReservedKey {key: String}                       = "reserved key: {}",
     UnexpectedInternalArg {invocation: String}      = "unexpected argument for internal: {}",
+    UnknownVerbGroup {group: String}                = "unknown group in verb execution: {group}",
+    InvalidVerbSequence {sequence: String}          = "invalid verb sequence: {sequence}",
     InvalidCols {details: String}                   = "invalid cols definition: {}",
+    InvalidBranchGlyphs {details: String}           = "invalid branch glyphs: {details}",
+    InvalidListPattern {details: String}            = "invalid line pattern in list: {details}",
+    InvalidVerbFilter {filter: String}              = "invalid verb filter: {filter:?}",
+    AskWithArgs {invocation: String}                = "ask groups can't be used with invocation arguments: {invocation}",
+    InvalidWorkingDir {working_dir: String}         = "invalid working dir (not an existing absolute path nor a known value): {working_dir:?}",
+    InvalidHandler {handler: String}                = "invalid handler: {handler:?}",
+    InvalidExitAction {action: String}              = "invalid exit action: {action:?}",
+    InvalidPageSelection {raw: String}              = "invalid page selection (expected follow, screen or line): {raw:?}",
+    InvalidPanelsDirection {raw: String}            = "invalid panels direction (expected columns or rows): {raw:?}",
+    InvalidImport {path: String, details: String}   = "invalid import of {path:?}: {details}",
+    InvalidDefaultFlags {flags: String}             = "invalid default flags: {flags:?}",
+    InvalidProfile {name: String}                   = "invalid profile {name:?} (expected a string of flags)",
+    InvalidRootFlags {glob: String}                 = "invalid root flags rule {glob:?} (expected a glob and a string of flags)",
+    InvalidTrustedLocalConf {glob: String}          = "invalid glob in trusted_local_confs: {glob:?}",
+    InvalidSecureDeletePasses {passes: u16}         = "invalid secure_delete_passes: {passes} (expected at least 1)",
 }
 
 // error which can be raised when parsing a pattern the user typed
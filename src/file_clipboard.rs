@@ -0,0 +1,152 @@
+//! cut, copy and paste of files through the system clipboard, where
+//! they're written as file URIs, one per line (the text/uri-list
+//! format), so that they can be pasted in another broot or in a
+//! graphical file manager.
+//!
+//! As the system clipboard only holds text, whether the files were
+//! cut is stored in a file of broot's data directory, which also
+//! serves as clipboard when the system one isn't available.
+
+use {
+    crate::{
+        conf,
+        path::{percent_decode, percent_encode},
+    },
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileClipboardMode {
+    Copy,
+    Cut, // the files are moved when pasted
+}
+
+/// files put in the clipboard to be pasted elsewhere
+#[derive(Debug, Clone)]
+pub struct FileClipboard {
+    pub mode: FileClipboardMode,
+    pub paths: Vec<PathBuf>,
+}
+
+/// the file keeping the content of the clipboard, and whether
+/// it was cut
+fn store_path() -> PathBuf {
+    conf::app_dirs().data_dir().join("file-clipboard")
+}
+
+/// the URI of the file, which must be absolute
+pub fn file_uri(path: &Path) -> String {
+    format!("file://{}", percent_encode(path))
+}
+
+/// read the paths of a text/uri-list. Absolute paths, as given
+/// by some tools, are accepted too. Other lines are ignored.
+pub fn parse_uri_list(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            if let Some(uri_path) = line.strip_prefix("file://") {
+                // the host part, usually empty, is skipped
+                uri_path.find('/').map(|idx| percent_decode(&uri_path[idx..]))
+            } else if line.starts_with('/') {
+                Some(PathBuf::from(line))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+impl FileClipboard {
+    pub fn new(mode: FileClipboardMode, paths: Vec<PathBuf>) -> Self {
+        Self { mode, paths }
+    }
+
+    fn uri_list(&self) -> String {
+        self.paths
+            .iter()
+            .map(|path| file_uri(path))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// put the files in the system clipboard and in broot's store.
+    ///
+    /// Return false if the system clipboard couldn't be written, in
+    /// which case only other broot instances can paste them
+    pub fn write(&self) -> io::Result<bool> {
+        let path = store_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mode = match self.mode {
+            FileClipboardMode::Copy => "copy",
+            FileClipboardMode::Cut => "cut",
+        };
+        fs::write(&path, format!("{}\n{}\n", mode, self.uri_list()))?;
+        Ok(cli_clipboard::set_contents(self.uri_list()).is_ok())
+    }
+
+    /// read broot's store
+    fn read_store() -> Option<Self> {
+        let content = fs::read_to_string(store_path()).ok()?;
+        let mode = match content.lines().next()? {
+            "copy" => FileClipboardMode::Copy,
+            "cut" => FileClipboardMode::Cut,
+            _ => return None,
+        };
+        Some(Self::new(mode, parse_uri_list(&content)))
+    }
+
+    /// read the files of the clipboard. When the system clipboard
+    /// holds the files of broot's store, they're taken with their
+    /// mode. Other files of the system clipboard are copied.
+    pub fn read() -> Option<Self> {
+        let stored = Self::read_store();
+        let text = match cli_clipboard::get_contents() {
+            Ok(text) => text,
+            Err(_) => {
+                // the system clipboard isn't available
+                return stored;
+            }
+        };
+        let paths = parse_uri_list(&text);
+        if paths.is_empty() {
+            return None;
+        }
+        match stored {
+            Some(stored) if stored.paths == paths => Some(stored),
+            _ => Some(Self::new(FileClipboardMode::Copy, paths)),
+        }
+    }
+
+    /// empty the clipboard, which must be done once cut files
+    /// were pasted, as they're not at their old path anymore
+    pub fn clear() {
+        let _ = fs::remove_file(store_path());
+        let _ = cli_clipboard::set_contents(String::new());
+    }
+}
+
+#[cfg(test)]
+mod file_clipboard_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_parse_uri_list() {
+        let text = "# a comment\nfile:///home/me/a%20b.txt\nfile://localhost/tmp/c\n/var/log\nnot a path";
+        assert_eq!(
+            parse_uri_list(text),
+            vec![
+                PathBuf::from("/home/me/a b.txt"),
+                PathBuf::from("/tmp/c"),
+                PathBuf::from("/var/log"),
+            ],
+        );
+    }
+}
@@ -78,6 +78,11 @@ pub fn compute_dir_sum(path: &Path, dam: &Dam) -> Option<FileSum> {
                                     // processing
                                     busy.fetch_add(1, Ordering::Relaxed);
                                     dirs_sender.send(Some(e.path())).unwrap();
+                                } else if !md.is_file() && !md.file_type().is_symlink() {
+                                    // devices, sockets and FIFOs are counted but
+                                    // their size, if any, isn't stored on disk
+                                    thread_sum.incr();
+                                    continue;
                                 } else {
 
                                     #[cfg(unix)]
@@ -141,6 +146,10 @@ pub fn compute_dir_sum(path: &Path, dam: &Dam) -> Option<FileSum> {
 /// compute the sum for a regular file (not a folder)
 pub fn compute_file_sum(path: &Path) -> FileSum {
     match fs::metadata(path) {
+        Ok(md) if !md.is_file() && !md.is_dir() => {
+            // a device, socket or FIFO: its size isn't stored on disk
+            FileSum::new(0, false, 1, extract_seconds(&md))
+        }
         Ok(md) => {
             let seconds = extract_seconds(&md);
 
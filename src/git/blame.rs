@@ -0,0 +1,73 @@
+use {
+    super::log::open_repo,
+    std::{
+        fs,
+        path::Path,
+    },
+};
+
+/// a line of a blamed file, with the last commit which changed it
+#[derive(Debug, Clone)]
+pub struct BlameLine {
+    pub short_id: String,
+    pub author: String,
+    pub time: i64, // seconds since epoch
+    pub content: String,
+}
+
+/// the number of lines last changed by an author
+#[derive(Debug, Clone)]
+pub struct AuthorContribution {
+    pub author: String,
+    pub lines: usize,
+}
+
+/// the blame of a file, as it is in the head commit
+#[derive(Debug, Clone)]
+pub struct FileBlame {
+    pub lines: Vec<BlameLine>,
+    pub contributions: Vec<AuthorContribution>, // the biggest first
+    pub modified: bool, // whether the file has uncommitted changes
+}
+
+/// compute the blame of the file as it is in the head commit
+pub fn blame(path: &Path) -> Result<FileBlame, git2::Error> {
+    let (repo, relative_path) = open_repo(path)?;
+    let entry = repo.head()?.peel_to_tree()?.get_path(&relative_path)?;
+    let blob = repo.find_blob(entry.id())?;
+    let content = blob.content();
+    let modified = fs::read(path).map_or(true, |current| current != content);
+    let blame = repo.blame_file(&relative_path, None)?;
+    let mut lines = Vec::new();
+    for (idx, content) in String::from_utf8_lossy(content).lines().enumerate() {
+        let hunk = match blame.get_line(idx + 1) {
+            Some(hunk) => hunk,
+            None => {
+                break; // shouldn't happen as the blame is of the same content
+            }
+        };
+        let signature = hunk.final_signature();
+        lines.push(BlameLine {
+            short_id: hunk.final_commit_id().to_string()[..7].to_string(),
+            author: signature.name().unwrap_or("").to_string(),
+            time: signature.when().seconds(),
+            content: content.replace('\t', "    "),
+        });
+    }
+    let mut contributions: Vec<AuthorContribution> = Vec::new();
+    for line in &lines {
+        match contributions.iter_mut().find(|c| c.author == line.author) {
+            Some(contribution) => contribution.lines += 1,
+            None => contributions.push(AuthorContribution {
+                author: line.author.clone(),
+                lines: 1,
+            }),
+        }
+    }
+    contributions.sort_by_key(|contribution| std::cmp::Reverse(contribution.lines));
+    Ok(FileBlame {
+        lines,
+        contributions,
+        modified,
+    })
+}
@@ -0,0 +1,254 @@
+use {
+    super::blame::{blame, FileBlame},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        task_sync::{ComputationResult, Dam},
+        verb::*,
+    },
+    chrono::{Local, TimeZone},
+    crossbeam::channel::{bounded, Receiver},
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        path::{Path, PathBuf},
+        thread,
+    },
+    termimad::Area,
+};
+
+/// lines changed more recently than that (in seconds) are highlighted
+const RECENT_CHANGE: i64 = 30 * 24 * 60 * 60;
+
+/// the width of the bars of the author contributions
+const BAR_WIDTH: usize = 20;
+
+/// an application state showing the blame of a file: how many lines
+/// each author last changed, then every line with its last commit.
+/// The blame is computed in a background thread.
+pub struct GitBlameState {
+    path: PathBuf,
+    receiver: Option<Receiver<ComputationResult<Result<FileBlame, String>>>>,
+    blame: Option<Result<FileBlame, String>>, // None while computing
+    scroll: usize,
+    page_height: usize,
+}
+
+impl GitBlameState {
+    pub fn new(path: PathBuf) -> Self {
+        let (sender, receiver) = bounded(1);
+        let blamed_path = path.clone();
+        thread::spawn(move || {
+            let res = time!(Debug, "blame", blame(&blamed_path))
+                .map_err(|e| e.message().to_string());
+            if sender.send(ComputationResult::Done(res)).is_err() {
+                debug!("no channel at end of blame");
+            }
+        });
+        Self {
+            path,
+            receiver: Some(receiver),
+            blame: None,
+            scroll: 0,
+            page_height: 0,
+        }
+    }
+
+    /// the number of displayed rows: one per author, a separating
+    /// row, then one per line of the file
+    fn rows_count(&self) -> usize {
+        match &self.blame {
+            Some(Ok(blame)) => blame.contributions.len() + 1 + blame.lines.len(),
+            _ => 0,
+        }
+    }
+
+    fn try_scroll(&mut self, dy: i32) {
+        let max = self.rows_count().saturating_sub(self.page_height) as i32;
+        self.scroll = (self.scroll as i32 + dy).max(0).min(max) as usize;
+    }
+}
+
+impl AppState for GitBlameState {
+
+    fn selected_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.path,
+            line: 0,
+            stype: SelectionType::File,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        if self.receiver.is_some() {
+            Some("computing blame")
+        } else {
+            None
+        }
+    }
+
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        if let Some(receiver) = &self.receiver {
+            // the computation goes on in background when the dam interrupts the wait
+            if let ComputationResult::Done(blame) = dam.select(receiver.clone()) {
+                self.blame = Some(blame);
+                self.receiver = None;
+            }
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.try_scroll(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.try_scroll(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.try_scroll(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.try_scroll(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.try_scroll(-(self.rows_count() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.try_scroll(self.rows_count() as i32);
+                AppStateCmdResult::Keep
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        self.page_height = state_area.height as usize;
+        self.try_scroll(0);
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        let blame = match &self.blame {
+            Some(Ok(blame)) => Some(blame),
+            _ => None,
+        };
+        let author_width = blame
+            .and_then(|blame| blame.contributions.iter().map(|c| c.author.chars().count()).max())
+            .unwrap_or(0)
+            .min(20);
+        let total_lines = blame.map_or(0, |blame| blame.lines.len()).max(1);
+        let line_number_width = total_lines.to_string().len();
+        let now = Local::now().timestamp();
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            let idx = self.scroll + y as usize;
+            if let Some(blame) = blame {
+                let nb_contributions = blame.contributions.len();
+                if let Some(contribution) = blame.contributions.get(idx) {
+                    let author: String = contribution.author.chars().take(author_width).collect();
+                    cw.queue_g_string(&styles.owner, format!("{:<w$} ", author, w = author_width))?;
+                    let percent = 100 * contribution.lines / total_lines;
+                    cw.queue_g_string(
+                        &styles.count,
+                        format!("{:>6} lines {:>3}% ", contribution.lines, percent),
+                    )?;
+                    let bar_len = (BAR_WIDTH * contribution.lines / total_lines).max(1);
+                    cw.queue_g_string(&styles.git_insertions, "▮".repeat(bar_len))?;
+                } else if let Some(line) = idx
+                    .checked_sub(nb_contributions + 1)
+                    .and_then(|line_idx| blame.lines.get(line_idx))
+                {
+                    let line_number = idx - nb_contributions;
+                    cw.queue_g_string(
+                        &styles.preview_line_number,
+                        format!("{:>w$}", line_number, w = line_number_width),
+                    )?;
+                    cw.queue_char(&styles.default, ' ')?;
+                    cw.queue_g_string(&styles.git_branch, format!("{} ", line.short_id))?;
+                    let date_style = if now - line.time < RECENT_CHANGE {
+                        &styles.git_status_modified
+                    } else {
+                        &styles.dates
+                    };
+                    let date = Local.timestamp(line.time, 0).format("%Y-%m-%d");
+                    cw.queue_g_string(date_style, format!("{} ", date))?;
+                    let author: String = line.author.chars().take(author_width).collect();
+                    cw.queue_g_string(&styles.owner, format!("{:<w$} ", author, w = author_width))?;
+                    cw.queue_str(&styles.tree, "│ ")?;
+                    cw.queue_str(&styles.default, &line.content)?;
+                }
+            }
+            cw.fill(&styles.default, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        let name = self.path.file_name().map_or_else(
+            || self.path.to_string_lossy(),
+            |name| name.to_string_lossy(),
+        );
+        match &self.blame {
+            None => Status::from_message(format!("Computing the blame of *{}*...", name)),
+            Some(Err(e)) => Status::from_error(format!("No blame for {}: {}", name, e)),
+            Some(Ok(blame)) => Status::from_message(format!(
+                "*{}* has {} lines, last changed by {} authors{}",
+                name,
+                blame.lines.len(),
+                blame.contributions.len(),
+                if blame.modified { " (uncommitted changes aren't blamed)" } else { "" },
+            )),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use {
+    git2::{DiffFormat, DiffOptions, Repository},
+    std::path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffLineKind {
+    Header, // file header, hunk header, or "no newline" notice
+    Addition,
+    Deletion,
+    Context,
+}
+
+/// a line of a unified diff
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String, // without the '+', '-' or ' ' prefix
+}
+
+/// compute the unified diff between the version of the file
+/// in HEAD and the one in the working directory (including the
+/// staged changes). The returned vec is empty when the file
+/// isn't modified.
+pub fn file_diff(path: &Path) -> Result<Vec<DiffLine>, git2::Error> {
+    let as_git_error = |e: std::io::Error| git2::Error::from_str(&e.to_string());
+    let path = path.canonicalize().map_err(as_git_error)?;
+    let repo = Repository::discover(&path)?;
+    let workdir = repo.workdir()
+        .ok_or_else(|| git2::Error::from_str("no working directory"))?
+        .canonicalize()
+        .map_err(as_git_error)?;
+    let relative_path = path.strip_prefix(&workdir)
+        .map_err(|_| git2::Error::from_str("file not in the working directory"))?;
+    let head_tree = repo.head()?.peel_to_tree()?;
+    let mut options = DiffOptions::new();
+    options.pathspec(relative_path);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut options))?;
+    let mut lines = Vec::new();
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        let kind = match line.origin() {
+            '+' => DiffLineKind::Addition,
+            '-' => DiffLineKind::Deletion,
+            ' ' => DiffLineKind::Context,
+            _ => DiffLineKind::Header,
+        };
+        // file headers span several lines
+        for content in String::from_utf8_lossy(line.content()).lines() {
+            lines.push(DiffLine {
+                kind,
+                content: content.to_string(),
+            });
+        }
+        true
+    })?;
+    Ok(lines)
+}
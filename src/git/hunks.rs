@@ -0,0 +1,234 @@
+use {
+    super::{
+        diff::{DiffLine, DiffLineKind},
+        log::open_repo,
+    },
+    git2::{Diff, DiffFormat, DiffOptions},
+    std::path::Path,
+};
+
+/// a hunk of the changes of a file, either staged (between HEAD
+/// and the index) or not (between the index and the working directory)
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    pub staged: bool,
+    pub header: String, // eg "@@ -12,7 +12,9 @@ fn main() {"
+    pub lines: Vec<DiffLine>,
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+    old_content: Vec<u8>, // the lines of the old side, context included
+    new_content: Vec<u8>, // the lines of the new side, context included
+}
+
+/// read the hunks of a diff, which is about a single file
+fn read_hunks(diff: &Diff<'_>, staged: bool, hunks: &mut Vec<Hunk>) -> Result<(), git2::Error> {
+    diff.print(DiffFormat::Patch, |_delta, hunk, line| {
+        if line.origin() == 'H' {
+            if let Some(hunk) = hunk {
+                hunks.push(Hunk {
+                    staged,
+                    header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                    lines: Vec::new(),
+                    old_start: hunk.old_start() as usize,
+                    old_lines: hunk.old_lines() as usize,
+                    new_start: hunk.new_start() as usize,
+                    new_lines: hunk.new_lines() as usize,
+                    old_content: Vec::new(),
+                    new_content: Vec::new(),
+                });
+            }
+            return true;
+        }
+        let current = match hunks.last_mut() {
+            Some(current) if hunk.is_some() => current,
+            _ => {
+                return true; // file header
+            }
+        };
+        let kind = match line.origin() {
+            '+' => {
+                current.new_content.extend_from_slice(line.content());
+                DiffLineKind::Addition
+            }
+            '-' => {
+                current.old_content.extend_from_slice(line.content());
+                DiffLineKind::Deletion
+            }
+            ' ' => {
+                current.old_content.extend_from_slice(line.content());
+                current.new_content.extend_from_slice(line.content());
+                DiffLineKind::Context
+            }
+            _ => DiffLineKind::Header, // "no newline at end of file"
+        };
+        current.lines.push(DiffLine {
+            kind,
+            content: String::from_utf8_lossy(line.content()).trim_matches('\n').to_string(),
+        });
+        true
+    })
+}
+
+/// compute the hunks of the file, the staged ones first. The returned
+/// vec is empty when the file has no local modification
+pub fn file_hunks(path: &Path) -> Result<Vec<Hunk>, git2::Error> {
+    let (repo, relative_path) = open_repo(path)?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut hunks = Vec::new();
+    let mut options = DiffOptions::new();
+    options.pathspec(relative_path.as_path());
+    let staged_diff = repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))?;
+    read_hunks(&staged_diff, true, &mut hunks)?;
+    let mut options = DiffOptions::new();
+    options
+        .pathspec(relative_path.as_path())
+        .include_untracked(true)
+        .show_untracked_content(true);
+    let unstaged_diff = repo.diff_index_to_workdir(None, Some(&mut options))?;
+    read_hunks(&unstaged_diff, false, &mut hunks)?;
+    Ok(hunks)
+}
+
+/// replace `len` lines of the content, starting at the 1-based line
+/// `start` (when `len` is 0, the new lines are inserted after `start`)
+fn splice_lines(content: &[u8], start: usize, len: usize, replacement: &[u8]) -> Vec<u8> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.iter().enumerate().filter(|&(_, &b)| b == b'\n').map(|(i, _)| i + 1))
+        .collect();
+    let first = if len == 0 { start } else { start - 1 };
+    let offset = |line: usize| line_starts.get(line).map_or(content.len(), |&o| o.min(content.len()));
+    let (from, to) = (offset(first), offset(first + len));
+    let mut spliced = Vec::with_capacity(content.len() + replacement.len());
+    spliced.extend_from_slice(&content[..from]);
+    spliced.extend_from_slice(replacement);
+    spliced.extend_from_slice(&content[to..]);
+    spliced
+}
+
+/// stage the hunk if it's not staged, unstage it if it's staged, by
+/// changing the version of the file in the index
+pub fn toggle_hunk_stage(path: &Path, hunk: &Hunk) -> Result<(), git2::Error> {
+    let (repo, relative_path) = open_repo(path)?;
+    let mut index = repo.index()?;
+    // adding the file to the index would silently resolve the conflict
+    if (1..=3).any(|stage| index.get_path(&relative_path, stage).is_some()) {
+        return Err(git2::Error::from_str("the file is conflicted: resolve the conflict first"));
+    }
+    match index.get_path(&relative_path, 0) {
+        None if hunk.staged => {
+            return Err(git2::Error::from_str("a file deletion can't be unstaged by hunk"));
+        }
+        None => {
+            // an untracked file, whose only hunk is the whole file
+            index.add_path(&relative_path)?;
+        }
+        Some(_) if !hunk.staged && !path.exists() => {
+            index.remove_path(&relative_path)?;
+        }
+        Some(entry) => {
+            let in_head = repo.head().ok()
+                .and_then(|head| head.peel_to_tree().ok())
+                .is_some_and(|tree| tree.get_path(&relative_path).is_ok());
+            if hunk.staged && !in_head {
+                // the addition of the file is unstaged
+                index.remove_path(&relative_path)?;
+            } else {
+                let blob = repo.find_blob(entry.id)?;
+                let content = if hunk.staged {
+                    splice_lines(blob.content(), hunk.new_start, hunk.new_lines, &hunk.old_content)
+                } else {
+                    splice_lines(blob.content(), hunk.old_start, hunk.old_lines, &hunk.new_content)
+                };
+                index.add_frombuffer(&entry, &content)?;
+            }
+        }
+    }
+    index.write()
+}
+
+#[cfg(test)]
+mod hunks_tests {
+
+    use {
+        super::*,
+        git2::{Repository, Signature},
+        std::{fs, path::PathBuf},
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_splice_lines() {
+        let content = b"a\nb\nc\nd\n";
+        assert_eq!(splice_lines(content, 2, 2, b"x\n"), b"a\nx\nd\n".to_vec());
+        assert_eq!(splice_lines(content, 2, 0, b"x\n"), b"a\nb\nx\nc\nd\n".to_vec());
+        assert_eq!(splice_lines(content, 0, 0, b"x\n"), b"x\na\nb\nc\nd\n".to_vec());
+        assert_eq!(splice_lines(content, 4, 1, b"e"), b"a\nb\nc\ne".to_vec());
+        assert_eq!(splice_lines(b"", 0, 0, b"x\n"), b"x\n".to_vec());
+    }
+
+    /// create a repository with a committed file, and return it
+    /// with the path of the file
+    fn repo_with_file(dir: &Path, content: &str) -> (Repository, PathBuf) {
+        let repo = Repository::init(dir).unwrap();
+        let path = dir.join("a.txt");
+        fs::write(&path, content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        let tree_id = index.write_tree().unwrap();
+        {
+            let tree = repo.find_tree(tree_id).unwrap();
+            let signature = Signature::now("test", "test@example.com").unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[]).unwrap();
+        }
+        (repo, path)
+    }
+
+    #[test]
+    fn check_hunk_staging() {
+        let dir = TempDir::new().unwrap();
+        let (_repo, path) = repo_with_file(dir.path(), "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n");
+        fs::write(&path, "a\nB\nc\nd\ne\nf\ng\nh\ni\nJ\n").unwrap();
+        let hunks = file_hunks(&path).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks.iter().all(|hunk| !hunk.staged));
+        // staging the second hunk
+        toggle_hunk_stage(&path, &hunks[1]).unwrap();
+        let hunks = file_hunks(&path).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks[0].staged);
+        assert!(hunks[0].lines.iter().any(|line| line.content == "J"));
+        assert!(!hunks[1].staged);
+        assert!(hunks[1].lines.iter().any(|line| line.content == "B"));
+        // then unstaging it
+        toggle_hunk_stage(&path, &hunks[0]).unwrap();
+        let hunks = file_hunks(&path).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks.iter().all(|hunk| !hunk.staged));
+    }
+
+    #[test]
+    fn check_conflicted_file_isnt_staged() {
+        let dir = TempDir::new().unwrap();
+        let (repo, path) = repo_with_file(dir.path(), "a\n");
+        fs::write(&path, "b\n").unwrap();
+        let hunks = file_hunks(&path).unwrap();
+        // the file is made conflicted, its stage 0 entry being
+        // replaced with the entries of the three stages
+        let mut index = repo.index().unwrap();
+        let entries: Vec<_> = (1..=3)
+            .map(|_| index.get_path(Path::new("a.txt"), 0).unwrap())
+            .collect();
+        index.remove_path(Path::new("a.txt")).unwrap();
+        for (mut entry, stage) in entries.into_iter().zip(1..=3) {
+            entry.flags |= stage << 12;
+            index.add(&entry).unwrap();
+        }
+        index.write().unwrap();
+        assert!(toggle_hunk_stage(&path, &hunks[0]).is_err());
+        let index = repo.index().unwrap();
+        assert!(index.has_conflicts());
+    }
+}
@@ -1,13 +1,14 @@
-//! Implements parsing and applying .gitignore files.
+//! Implements parsing and applying .gitignore files, and the
+//! other ignore files using the same syntax.
 
 use {
     git2,
     glob,
     id_arena::{Arena, Id},
     std::{
-        fs::File,
+        fs::{self, File},
         io::{BufRead, BufReader, Result},
-        path::Path,
+        path::{Path, PathBuf},
     },
 };
 
@@ -15,6 +16,61 @@ pub fn is_repo(root: &Path) -> bool {
     root.join(".git").exists()
 }
 
+/// return the git directory of the repository, which is either its
+/// .git directory or, for submodules and worktrees, the directory
+/// pointed to by its .git file
+fn git_dir(repo: &Path) -> Option<PathBuf> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let line = content.lines().find(|line| line.starts_with("gitdir:"))?;
+    Some(repo.join(line["gitdir:".len()..].trim()))
+}
+
+/// which gitignore rules hide files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GitIgnoreMode {
+    /// the rules of the .gitignore files, of the .git/info/exclude
+    /// files, and of the global excludes file
+    All,
+    /// only the rules of the repositories: their .gitignore files
+    /// and .git/info/exclude files
+    RepoOnly,
+    /// nothing is hidden
+    Nothing,
+}
+
+impl GitIgnoreMode {
+    /// the mode following this one in the cycle of :cycle_git_ignore
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::RepoOnly,
+            Self::RepoOnly => Self::Nothing,
+            Self::Nothing => Self::All,
+        }
+    }
+    pub fn hides_ignored(self) -> bool {
+        self != Self::Nothing
+    }
+}
+
+/// the ignore files of the ripgrep and fd conventions, which apply
+/// whether the directory is in a git repository or not
+const TOOL_IGNORE_FILE_NAMES: &[&str] = &[".ignore", ".fdignore"];
+
+/// the ignore file specific to broot
+const BROOT_IGNORE_FILE_NAME: &str = ".brootignore";
+
+/// how an entry is affected by the ignore rules
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IgnoreStatus {
+    NotIgnored,
+    Dimmed, // ignored by rules which don't hide files
+    Hidden,
+}
+
 /// a simple rule of a gitignore file
 #[derive(Clone)]
 struct GitIgnoreRule {
@@ -70,21 +126,30 @@ impl GitIgnoreRule {
 #[derive(Clone)]
 pub struct GitIgnoreFile {
     rules: Vec<GitIgnoreRule>,
+    hides: bool, // whether the ignored entries are hidden or just dimmed
 }
 impl GitIgnoreFile {
     pub fn new(path: &Path) -> Result<GitIgnoreFile> {
+        GitIgnoreFile::relative_to(path, path.parent().unwrap())
+    }
+    fn hiding(mut self, hides: bool) -> Self {
+        self.hides = hides;
+        self
+    }
+    /// read an ignore file whose anchored patterns (starting
+    /// with a `/`) are relative to the given directory
+    pub fn relative_to(path: &Path, dir: &Path) -> Result<GitIgnoreFile> {
         let f = File::open(path)?;
-        let parent = path.parent().unwrap();
         let mut rules: Vec<GitIgnoreRule> = Vec::new();
         for line in BufReader::new(f).lines() {
-            if let Some(rule) = GitIgnoreRule::from(&line?, &parent) {
+            if let Some(rule) = GitIgnoreRule::from(&line?, dir) {
                 rules.push(rule);
             }
         }
         // the last rule applicable to a path is the right one. So
         // we reverse the list to easily iterate from the last one to the first one
         rules.reverse();
-        Ok(GitIgnoreFile { rules })
+        Ok(GitIgnoreFile { rules, hides: true })
     }
 }
 
@@ -115,33 +180,79 @@ impl GitIgnoreChain {
 pub struct GitIgnorer {
     files: Arena<GitIgnoreFile>,
     global_chain: GitIgnoreChain,
+    hides_git_ignored: bool,
+    other_file_names: Vec<&'static str>, // the ignore files read besides the .gitignore ones
 }
 
-impl Default for GitIgnorer {
-    fn default() -> Self {
+impl GitIgnorer {
+    /// build an ignorer applying the gitignore rules of the mode. When
+    /// the mode is `Nothing`, all gitignore rules are applied as ignored
+    /// files are still dimmed.
+    ///
+    /// The `.ignore` and `.fdignore` files, and the `.brootignore` ones,
+    /// are only read when asked, and then always hide files.
+    pub fn new(mode: GitIgnoreMode, tool_ignore_files: bool, broot_ignore_file: bool) -> Self {
         let mut files = Arena::new();
         let mut global_chain = GitIgnoreChain::default();
         lazy_static! {
             static ref GLOBAL_GI: Option<GitIgnoreFile> = find_global_ignore();
         }
-        if let Some(gif) = &*GLOBAL_GI {
-            global_chain.push(files.alloc(gif.clone()));
+        if mode != GitIgnoreMode::RepoOnly {
+            if let Some(gif) = &*GLOBAL_GI {
+                global_chain.push(files.alloc(gif.clone().hiding(mode.hides_ignored())));
+            }
+        }
+        let mut other_file_names = Vec::new();
+        if tool_ignore_files {
+            other_file_names.extend(TOOL_IGNORE_FILE_NAMES);
+        }
+        if broot_ignore_file {
+            other_file_names.push(BROOT_IGNORE_FILE_NAME);
         }
         Self {
             files,
             global_chain,
+            hides_git_ignored: mode.hides_ignored(),
+            other_file_names,
         }
     }
-}
-impl GitIgnorer {
-    pub fn root_chain(&mut self, mut dir: &Path) -> GitIgnoreChain {
+    /// read the ignore files of the directory, the ones with
+    /// the highest priority last
+    fn dir_files(&self, dir: &Path) -> Vec<GitIgnoreFile> {
+        let mut dir_files = Vec::new();
+        if let Ok(gif) = GitIgnoreFile::new(&dir.join(".gitignore")) {
+            dir_files.push(gif.hiding(self.hides_git_ignored));
+        }
+        for name in &self.other_file_names {
+            if let Ok(gif) = GitIgnoreFile::new(&dir.join(name)) {
+                dir_files.push(gif);
+            }
+        }
+        dir_files
+    }
+    /// return the global chain completed with the .git/info/exclude
+    /// file of the repository
+    fn repo_chain(&mut self, repo: &Path) -> GitIgnoreChain {
         let mut chain = self.global_chain.clone();
-        loop {
-            let ignore_file = dir.join(".gitignore");
-            if let Ok(gif) = GitIgnoreFile::new(&ignore_file) {
-                chain.push(self.files.alloc(gif));
+        if let Some(git_dir) = git_dir(repo) {
+            let exclude_file = git_dir.join("info/exclude");
+            if let Ok(gif) = GitIgnoreFile::relative_to(&exclude_file, repo) {
+                chain.push(self.files.alloc(gif.hiding(self.hides_git_ignored)));
             }
+        }
+        chain
+    }
+    pub fn root_chain(&mut self, mut dir: &Path) -> GitIgnoreChain {
+        // the files are found from the deepest one but the
+        // deepest ones must be at the end of the chain
+        let mut ignore_files = Vec::new();
+        let mut repo = None;
+        loop {
+            let mut dir_files = self.dir_files(dir);
+            dir_files.reverse();
+            ignore_files.append(&mut dir_files);
             if is_repo(dir) {
+                repo = Some(dir);
                 break;
             }
             if let Some(parent) = dir.parent() {
@@ -150,6 +261,13 @@ impl GitIgnorer {
                 break;
             }
         }
+        let mut chain = match repo {
+            Some(repo) => self.repo_chain(repo),
+            None => self.global_chain.clone(),
+        };
+        for gif in ignore_files.into_iter().rev() {
+            chain.push(self.files.alloc(gif));
+        }
         chain
     }
     pub fn deeper_chain(&mut self, parent_chain: &GitIgnoreChain, dir: &Path) -> GitIgnoreChain {
@@ -158,23 +276,22 @@ impl GitIgnorer {
         // we don't want the .gitignore files of super repositories
         // (see https://github.com/Canop/broot/issues/160)
         let mut chain = if is_repo(dir) {
-            self.global_chain.clone()
+            self.repo_chain(dir)
         } else {
             parent_chain.clone()
         };
-        let ignore_file = dir.join(".gitignore");
-        if let Ok(gif) = GitIgnoreFile::new(&ignore_file) {
+        for gif in self.dir_files(dir) {
             chain.push(self.files.alloc(gif));
         }
         chain
     }
-    pub fn accepts(
+    pub fn status(
         &self,
         chain: &GitIgnoreChain,
         path: &Path,
         filename: &str,
         directory: bool,
-    ) -> bool {
+    ) -> IgnoreStatus {
         // we start with deeper files: deeper rules have a bigger priority
         for id in chain.file_ids.iter().rev() {
             let file = &self.files[*id];
@@ -188,10 +305,16 @@ impl GitIgnorer {
                     rule.pattern.matches_path_with(path, rule.pattern_options)
                 };
                 if ok {
-                    return rule.ok;
+                    return if rule.ok {
+                        IgnoreStatus::NotIgnored
+                    } else if file.hides {
+                        IgnoreStatus::Hidden
+                    } else {
+                        IgnoreStatus::Dimmed
+                    };
                 }
             }
         }
-        true
+        IgnoreStatus::NotIgnored
     }
 }
@@ -0,0 +1,168 @@
+use {
+    crate::task_sync::Dam,
+    git2::{DiffOptions, Oid, Repository},
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+/// the max number of commits walked when looking for the last
+/// commits of files, as files may be untouched for a long time
+/// in big repositories
+const MAX_WALKED_COMMITS: usize = 10_000;
+
+/// the last commit which changed a file or a file of a directory
+#[derive(Debug, Clone)]
+pub struct LastCommit {
+    pub time: i64, // seconds since epoch
+    pub author: String,
+}
+
+type LastCommits = HashMap<PathBuf, Option<LastCommit>>;
+
+lazy_static! {
+    // the key is the working directory of the repository and its head,
+    // the paths of the values are relative to this working directory
+    static ref LC_CACHE_MX: Mutex<HashMap<(PathBuf, Oid), LastCommits>> =
+        Mutex::new(HashMap::new());
+}
+
+/// walk the history from the head, the most recent commits first, and
+/// fill the last commits of the paths, which are relative to the working
+/// directory. Return false if the dam interrupted the walk.
+fn find_last_commits(
+    repo: &Repository,
+    paths: &[PathBuf],
+    found: &mut LastCommits,
+    dam: &Dam,
+) -> Result<bool, git2::Error> {
+    let mut remaining: Vec<&PathBuf> = paths.iter().collect();
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    for id in revwalk.take(MAX_WALKED_COMMITS) {
+        if remaining.is_empty() {
+            break;
+        }
+        if dam.has_event() {
+            return Ok(false);
+        }
+        let commit = repo.find_commit(id?)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let mut options = DiffOptions::new();
+        if remaining.iter().all(|path| !path.as_os_str().is_empty()) {
+            for path in &remaining {
+                options.pathspec(path.as_path());
+            }
+        }
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+        if diff.deltas().len() == 0 {
+            continue;
+        }
+        let last_commit = LastCommit {
+            time: commit.time().seconds(),
+            author: commit.author().name().unwrap_or("").to_string(),
+        };
+        remaining.retain(|path| {
+            let changed = diff.deltas().any(|delta| {
+                delta.new_file().path().into_iter()
+                    .chain(delta.old_file().path())
+                    .any(|changed_path| changed_path.starts_with(path))
+            });
+            if changed {
+                found.insert(path.to_path_buf(), Some(last_commit.clone()));
+            }
+            !changed
+        });
+    }
+    for path in remaining {
+        found.insert(path.clone(), None);
+    }
+    Ok(true)
+}
+
+/// get the last commits of the paths, in the same order, with a
+/// single walk of the history for all the paths which weren't
+/// already looked up with the same head.
+///
+/// Return None when the dam interrupted the computation (the
+/// commits found before being kept for the next call) and a
+/// vec of None when the paths aren't in a git repository.
+pub fn get_last_commits(
+    root_path: &Path,
+    paths: &[&Path],
+    dam: &Dam,
+) -> Option<Vec<Option<LastCommit>>> {
+    let not_in_repo = || Some(vec![None; paths.len()]);
+    let repo = match Repository::discover(root_path) {
+        Ok(repo) => repo,
+        Err(_) => return not_in_repo(),
+    };
+    let head = match repo.head().ok().and_then(|head| head.target()) {
+        Some(head) => head,
+        None => return not_in_repo(),
+    };
+    let workdir = match repo.workdir().and_then(|workdir| workdir.canonicalize().ok()) {
+        Some(workdir) => workdir,
+        None => return not_in_repo(),
+    };
+    // paths out of the repository, or unreadable, get no commit
+    let relative_paths: Vec<Option<PathBuf>> = paths
+        .iter()
+        .map(|path| {
+            path.canonicalize()
+                .ok()
+                .and_then(|path| path.strip_prefix(&workdir).ok().map(Path::to_path_buf))
+        })
+        .collect();
+    let key = (workdir, head);
+    let mut found = LC_CACHE_MX
+        .lock()
+        .unwrap()
+        .remove(&key)
+        .unwrap_or_default();
+    let mut missing: Vec<PathBuf> = relative_paths
+        .iter()
+        .flatten()
+        .filter(|path| !found.contains_key(*path))
+        .cloned()
+        .collect();
+    missing.sort();
+    missing.dedup();
+    let complete = if missing.is_empty() {
+        true
+    } else {
+        let walk = time!(
+            Debug,
+            "find_last_commits",
+            find_last_commits(&repo, &missing, &mut found, dam),
+        );
+        walk.unwrap_or_else(|e| {
+            warn!("error while looking for last commits: {:?}", e);
+            for path in missing {
+                found.entry(path).or_insert(None);
+            }
+            true
+        })
+    };
+    let last_commits = if complete {
+        Some(
+            relative_paths
+                .iter()
+                .map(|path| path.as_ref().and_then(|path| found.get(path).cloned().flatten()))
+                .collect(),
+        )
+    } else {
+        None
+    };
+    let mut cache = LC_CACHE_MX.lock().unwrap();
+    // the commits found with a previous head are obsolete
+    cache.retain(|(workdir, _), _| workdir != &key.0);
+    cache.insert(key, found);
+    last_commits
+}
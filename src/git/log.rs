@@ -0,0 +1,148 @@
+use {
+    crate::task_sync::Dam,
+    chrono::{Local, TimeZone},
+    git2::{Commit, DiffFormat, DiffOptions, Oid, Repository},
+    std::path::{Path, PathBuf},
+};
+
+/// the max number of commits listed by a git log
+pub const MAX_COMMITS: usize = 2_000;
+
+/// the max number of commits examined by a git log, as the history
+/// of big repositories would be too long to walk
+const MAX_WALKED_COMMITS: usize = 10_000;
+
+/// a commit of a git log
+#[derive(Debug, Clone)]
+pub struct LogCommit {
+    pub id: Oid,
+    pub short_id: String,
+    pub time: i64, // seconds since epoch
+    pub author: String,
+    pub summary: String,
+}
+
+/// open the repository containing the path, and return it with
+/// the path relative to its working directory
+pub fn open_repo(path: &Path) -> Result<(Repository, PathBuf), git2::Error> {
+    let as_git_error = |e: std::io::Error| git2::Error::from_str(&e.to_string());
+    let path = path.canonicalize().map_err(as_git_error)?;
+    let repo = Repository::discover(&path)?;
+    let workdir = repo.workdir()
+        .ok_or_else(|| git2::Error::from_str("no working directory"))?
+        .canonicalize()
+        .map_err(as_git_error)?;
+    let relative_path = path.strip_prefix(&workdir)
+        .map_err(|_| git2::Error::from_str("not in the working directory"))?
+        .to_path_buf();
+    Ok((repo, relative_path))
+}
+
+/// an incremental walk of the history of the current branch, looking
+/// for the commits changing the file or a file of the directory
+pub struct LogWalk {
+    repo: Repository,
+    relative_path: PathBuf,
+    ids: Vec<Oid>, // the commits to examine, the most recent first
+    next: usize, // the index in ids of the next commit to examine
+}
+
+impl LogWalk {
+    pub fn new(path: &Path) -> Result<Self, git2::Error> {
+        let (repo, relative_path) = open_repo(path)?;
+        let ids = {
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push_head()?;
+            revwalk.take(MAX_WALKED_COMMITS).collect::<Result<Vec<Oid>, git2::Error>>()?
+        };
+        Ok(Self {
+            repo,
+            relative_path,
+            ids,
+            next: 0,
+        })
+    }
+
+    /// tell whether there's no commit left to examine
+    pub fn is_done(&self) -> bool {
+        self.next >= self.ids.len()
+    }
+
+    /// tell whether the commit changes the path. Merge commits
+    /// are compared to their first parent.
+    fn changes_path(&self, commit: &Commit<'_>) -> Result<bool, git2::Error> {
+        if self.relative_path.as_os_str().is_empty() {
+            return Ok(true);
+        }
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let mut options = DiffOptions::new();
+        options.pathspec(self.relative_path.as_path());
+        let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+        Ok(diff.deltas().len() > 0)
+    }
+
+    /// examine the next commits, adding the ones changing the path,
+    /// until the dam gets an event or the walk is done
+    pub fn step(&mut self, commits: &mut Vec<LogCommit>, dam: &Dam) -> Result<(), git2::Error> {
+        while !self.is_done() {
+            if commits.len() >= MAX_COMMITS {
+                self.next = self.ids.len();
+                break;
+            }
+            if dam.has_event() {
+                break;
+            }
+            let commit = self.repo.find_commit(self.ids[self.next])?;
+            self.next += 1;
+            if !self.changes_path(&commit)? {
+                continue;
+            }
+            let id = commit.id();
+            commits.push(LogCommit {
+                id,
+                short_id: id.to_string()[..7].to_string(),
+                time: commit.time().seconds(),
+                author: commit.author().name().unwrap_or("").to_string(),
+                summary: commit.summary().unwrap_or("").to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// write the commit, with its message and its changes, in the
+/// format of `git show`
+pub fn show_commit(path: &Path, id: Oid) -> Result<String, git2::Error> {
+    let (repo, _) = open_repo(path)?;
+    let commit = repo.find_commit(id)?;
+    let author = commit.author();
+    let mut text = format!(
+        "commit {}\nAuthor: {} <{}>\nDate:   {}\n\n",
+        id,
+        author.name().unwrap_or(""),
+        author.email().unwrap_or(""),
+        Local.timestamp(commit.time().seconds(), 0).format("%Y-%m-%d %H:%M:%S"),
+    );
+    for line in commit.message().unwrap_or("").lines() {
+        text.push_str(&format!("    {}\n", line));
+    }
+    text.push('\n');
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parents().next() {
+        Some(parent) => Some(parent.tree()?),
+        None => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        if let '+' | '-' | ' ' = line.origin() {
+            text.push(line.origin());
+        }
+        text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(text)
+}
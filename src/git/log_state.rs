@@ -0,0 +1,296 @@
+use {
+    super::log::{show_commit, LogCommit, LogWalk, MAX_COMMITS},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        pattern::InputPattern,
+        preview::PreviewState,
+        skin::PanelSkin,
+        task_sync::Dam,
+        temp_text::TempText,
+        verb::*,
+    },
+    chrono::{Local, TimeZone},
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::path::{Path, PathBuf},
+    termimad::Area,
+};
+
+/// an application state listing the commits which changed
+/// the file or directory
+pub struct GitLogState {
+    path: PathBuf,
+    commits: Vec<LogCommit>,
+    walk: Option<LogWalk>, // the walk of the history, while not done
+    filtered: Vec<usize>, // indexes of the commits matching the pattern
+    pattern: InputPattern,
+    selection_idx: usize, // index in filtered
+    scroll: usize,
+    page_height: usize,
+}
+
+impl GitLogState {
+    pub fn new(path: PathBuf) -> Result<Self, git2::Error> {
+        let walk = LogWalk::new(&path)?;
+        Ok(Self {
+            path,
+            commits: Vec::new(),
+            walk: Some(walk),
+            filtered: Vec::new(),
+            pattern: InputPattern::none(),
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+        })
+    }
+
+    fn selected_commit(&self) -> Option<&LogCommit> {
+        self.filtered
+            .get(self.selection_idx)
+            .map(|&idx| &self.commits[idx])
+    }
+
+    fn move_selection(&mut self, dy: i32) {
+        if self.filtered.is_empty() {
+            return;
+        }
+        let max = self.filtered.len() as i32 - 1;
+        self.selection_idx = (self.selection_idx as i32 + dy).max(0).min(max) as usize;
+        self.ensure_selection_is_visible();
+    }
+
+    fn ensure_selection_is_visible(&mut self) {
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    fn apply_pattern(&mut self) {
+        self.filter();
+        self.selection_idx = 0;
+        self.scroll = 0;
+    }
+
+    /// compute the indexes of the commits matching the pattern
+    fn filter(&mut self) {
+        let pattern = &self.pattern.pattern;
+        self.filtered = self
+            .commits
+            .iter()
+            .enumerate()
+            .filter(|(_, commit)| {
+                pattern.is_none()
+                    || pattern.score_of_string(&commit.short_id).is_some()
+                    || pattern.score_of_string(&commit.author).is_some()
+                    || pattern.score_of_string(&commit.summary).is_some()
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+    }
+
+    /// show the changes of the selected commit in a new panel
+    fn show_selected_commit(&self, con: &AppContext) -> Result<AppStateCmdResult, ProgramError> {
+        let commit = match self.selected_commit() {
+            Some(commit) => commit,
+            None => return Ok(AppStateCmdResult::DisplayError("no selected commit".to_string())),
+        };
+        let text = match show_commit(&self.path, commit.id) {
+            Ok(text) => text,
+            Err(e) => return Ok(AppStateCmdResult::DisplayError(e.to_string())),
+        };
+        // the extension makes the diff highlighted in the preview
+        let name = format!("commit-{}.diff", commit.short_id);
+        let temp_text = TempText::write(&name, &text)?;
+        Ok(AppStateCmdResult::NewPanel {
+            state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        })
+    }
+}
+
+impl AppState for GitLogState {
+
+    fn selected_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.path,
+            line: 0,
+            stype: if self.path.is_dir() { SelectionType::Directory } else { SelectionType::File },
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        self.walk.as_ref().map(|_| "walking the git history")
+    }
+
+    /// go on walking the history, the new commits being
+    /// added at the end
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        if let Some(walk) = &mut self.walk {
+            let done = match walk.step(&mut self.commits, dam) {
+                Ok(()) => walk.is_done(),
+                Err(e) => {
+                    warn!("error while walking the git history: {:?}", e);
+                    true
+                }
+            };
+            if done {
+                self.walk = None;
+            }
+            self.filter();
+        }
+    }
+
+    fn on_pattern(
+        &mut self,
+        pat: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = pat;
+        self.apply_pattern();
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.move_selection(-(self.filtered.len() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.move_selection(self.filtered.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay | Internal::git_show => self.show_selected_commit(cc.con)?,
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        let author_width = self.commits.iter().map(|c| c.author.chars().count()).max().unwrap_or(0).min(20);
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            let idx = self.scroll + y as usize;
+            let commit = match self.filtered.get(idx) {
+                Some(&commit_idx) => &self.commits[commit_idx],
+                None => {
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                    continue;
+                }
+            };
+            let selected = idx == self.selection_idx;
+            let mut id_style = styles.git_branch.clone();
+            let mut date_style = styles.dates.clone();
+            let mut author_style = styles.owner.clone();
+            let mut summary_style = styles.default.clone();
+            if selected {
+                if let Some(c) = styles.selected_line.get_bg() {
+                    id_style.set_bg(c);
+                    date_style.set_bg(c);
+                    author_style.set_bg(c);
+                    summary_style.set_bg(c);
+                }
+            }
+            if con.show_selection_mark {
+                cw.queue_char(&summary_style, if selected { '▶' } else { ' ' })?;
+            }
+            cw.queue_g_string(&id_style, format!("{} ", commit.short_id))?;
+            let date = Local.timestamp(commit.time, 0).format("%Y-%m-%d");
+            cw.queue_g_string(&date_style, format!("{} ", date))?;
+            let author: String = commit.author.chars().take(author_width).collect();
+            cw.queue_g_string(&author_style, format!("{:<w$} ", author, w = author_width))?;
+            cw.queue_str(&summary_style, &commit.summary)?;
+            cw.fill(&summary_style, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(format!(
+            "*{}* was changed by {} commits{}. Hit *enter* to see the changes of the selected one",
+            self.path.file_name().map_or_else(
+                || self.path.to_string_lossy(),
+                |name| name.to_string_lossy(),
+            ),
+            self.filtered.len(),
+            if self.walk.is_some() {
+                " (so far)"
+            } else if self.commits.len() == MAX_COMMITS {
+                " (at least)"
+            } else {
+                ""
+            },
+        ))
+    }
+}
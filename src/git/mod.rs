@@ -1,14 +1,44 @@
+mod blame;
+mod blame_state;
+mod diff;
+mod hunks;
 mod ignore;
+mod last_commit;
+mod log;
+mod log_state;
+mod repos;
+mod repos_state;
+mod restore;
+mod restore_state;
 mod status;
 mod status_computer;
 
 pub use {
-    ignore::{GitIgnoreChain, GitIgnorer},
-    status::{LineGitStatus, LineStatusComputer, TreeGitStatus},
+    blame_state::GitBlameState,
+    diff::{file_diff, DiffLine, DiffLineKind},
+    hunks::{file_hunks, toggle_hunk_stage, Hunk},
+    ignore::{GitIgnoreChain, GitIgnoreMode, GitIgnorer, IgnoreStatus},
+    last_commit::{get_last_commits, LastCommit},
+    log_state::GitLogState,
+    repos_state::GitReposState,
+    restore_state::GitRestoreState,
+    status::{parse_status_filter, LineGitStatus, LineStatusComputer, SubmoduleStatus, TreeGitStatus},
     status_computer::{clear_status_computer_cache, get_tree_status},
 };
 
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// tell whether the directory is the root of an initialized git
+/// submodule, whose .git file points into the git directory of its
+/// superproject (the .git file of a linked worktree points elsewhere)
+pub fn is_submodule_root(dir: &Path) -> bool {
+    fs::read_to_string(dir.join(".git")).is_ok_and(|content| {
+        content.starts_with("gitdir:") && content.contains("/modules/")
+    })
+}
 
 /// return the closest parent (or self) containing a
 /// .git file
@@ -0,0 +1,130 @@
+use {
+    super::{ignore::is_repo, status::ahead_behind},
+    git2::{Repository, Status},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// a git repository found by a repo finder
+#[derive(Debug, Clone)]
+pub struct RepoInfo {
+    pub path: PathBuf,
+    pub branch: Option<String>, // None when the head is detached
+    pub changes: usize, // number of files with uncommitted changes
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+impl RepoInfo {
+    fn of(path: PathBuf) -> Option<Self> {
+        let repo = Repository::open(&path).ok()?;
+        let branch = repo.head().ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(String::from));
+        let changes = repo.statuses(None).map_or(0, |statuses| {
+            statuses.iter().filter(|entry| !entry.status().contains(Status::IGNORED)).count()
+        });
+        Some(Self {
+            path,
+            branch,
+            changes,
+            ahead_behind: ahead_behind(&repo),
+        })
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.changes > 0
+    }
+}
+
+/// a search of the git repositories under a directory, done one
+/// directory at a time so that it can be interrupted.
+///
+/// The content of the found repositories isn't searched, apart
+/// from the root one.
+pub struct RepoFinder {
+    root: PathBuf,
+    show_hidden: bool,
+    dirs: Vec<PathBuf>, // directories still to read
+    pub repos: Vec<RepoInfo>,
+    pub dirs_count: usize,
+}
+
+impl RepoFinder {
+    /// prepare the search, which is done by the calls to step
+    pub fn new(root: &Path, show_hidden: bool) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            show_hidden,
+            dirs: vec![root.to_path_buf()],
+            repos: Vec::new(),
+            dirs_count: 0,
+        }
+    }
+    pub fn is_done(&self) -> bool {
+        self.dirs.is_empty()
+    }
+    fn read_dir(&mut self, dir: &Path) {
+        self.dirs_count += 1;
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("can't read {:?}: {:?}", dir, e);
+                return;
+            }
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let is_dir = entry.file_type().is_ok_and(|ft| ft.is_dir());
+            if !is_dir {
+                continue; // links aren't followed
+            }
+            if !self.show_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            self.dirs.push(entry.path());
+        }
+    }
+    /// handle one directory: either it's a repository or its
+    /// children are queued (the root being searched in both cases)
+    pub fn step(&mut self) {
+        if let Some(dir) = self.dirs.pop() {
+            let is_root = dir == self.root;
+            if is_root {
+                self.read_dir(&dir);
+            }
+            if is_repo(&dir) {
+                self.repos.extend(RepoInfo::of(dir));
+            } else if !is_root {
+                self.read_dir(&dir);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod repos_tests {
+
+    use {
+        super::*,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn check_repo_finder() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        Repository::init(root).unwrap();
+        Repository::init(root.join("libs/a")).unwrap();
+        Repository::init(root.join("libs/a/inner")).unwrap();
+        Repository::init(root.join(".hidden/b")).unwrap();
+        let mut finder = RepoFinder::new(root, false);
+        assert!(finder.repos.is_empty()); // nothing is done before the first step
+        while !finder.is_done() {
+            finder.step();
+        }
+        let mut paths: Vec<&Path> = finder.repos.iter().map(|r| r.path.as_path()).collect();
+        paths.sort();
+        // the content of a found repository, apart from the root, isn't searched
+        assert_eq!(paths, vec![root.to_path_buf(), root.join("libs/a")]);
+    }
+}
@@ -0,0 +1,342 @@
+use {
+    super::repos::{RepoFinder, RepoInfo},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        pattern::InputPattern,
+        skin::PanelSkin,
+        task_sync::Dam,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    },
+    termimad::Area,
+};
+
+/// how long the search goes on before the progress is displayed again
+const DISPLAY_PERIOD: Duration = Duration::from_millis(200);
+
+/// an application state searching the git repositories under a
+/// directory, then listing them with their branch and state, the
+/// ones with uncommitted changes first
+pub struct GitReposState {
+    root: PathBuf,
+    tree_options: TreeOptions, // the options of the trees opened on repositories
+    finder: Option<RepoFinder>, // None once the search is done
+    repos: Vec<RepoInfo>,
+    filtered: Vec<usize>, // indexes of the repositories matching the pattern
+    pattern: InputPattern,
+    selection_idx: usize, // index in filtered
+    scroll: usize,
+    page_height: usize,
+}
+
+impl GitReposState {
+    pub fn new(root: PathBuf, tree_options: TreeOptions) -> Self {
+        let finder = RepoFinder::new(&root, tree_options.show_hidden);
+        Self {
+            root,
+            tree_options: tree_options.without_pattern(),
+            finder: Some(finder),
+            repos: Vec::new(),
+            filtered: Vec::new(),
+            pattern: InputPattern::none(),
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+        }
+    }
+
+    fn selected_repo(&self) -> Option<&RepoInfo> {
+        self.filtered
+            .get(self.selection_idx)
+            .map(|&idx| &self.repos[idx])
+    }
+
+    fn move_selection(&mut self, dy: i32) {
+        if self.filtered.is_empty() {
+            return;
+        }
+        let max = self.filtered.len() as i32 - 1;
+        self.selection_idx = (self.selection_idx as i32 + dy).max(0).min(max) as usize;
+        self.ensure_selection_is_visible();
+    }
+
+    fn ensure_selection_is_visible(&mut self) {
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    fn displayed_path<'s>(&'s self, repo: &'s RepoInfo) -> &'s Path {
+        repo.path.strip_prefix(&self.root)
+            .ok()
+            .filter(|path| !path.as_os_str().is_empty())
+            .unwrap_or(&repo.path)
+    }
+
+    fn apply_pattern(&mut self) {
+        let pattern = &self.pattern.pattern;
+        self.filtered = self
+            .repos
+            .iter()
+            .enumerate()
+            .filter(|(_, repo)| {
+                pattern.is_none()
+                    || pattern.score_of_string(&self.displayed_path(repo).to_string_lossy()).is_some()
+                    || repo.branch.as_ref().is_some_and(|b| pattern.score_of_string(b).is_some())
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.selection_idx = 0;
+        self.scroll = 0;
+    }
+}
+
+impl AppState for GitReposState {
+
+    fn selected_path(&self) -> &Path {
+        self.selected_repo().map_or(&self.root, |repo| &repo.path)
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: self.selected_path(),
+            line: 0,
+            stype: SelectionType::Directory,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        if self.finder.is_some() {
+            Some("searching git repositories")
+        } else {
+            None
+        }
+    }
+
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        let start = Instant::now();
+        if let Some(finder) = &mut self.finder {
+            // the status of a repository is computed at each step, so
+            // the dam is checked before each one
+            while !finder.is_done() {
+                if dam.has_event() {
+                    return;
+                }
+                finder.step();
+                if start.elapsed() > DISPLAY_PERIOD {
+                    return;
+                }
+            }
+            self.repos = std::mem::take(&mut finder.repos);
+            self.repos.sort_by(|a, b| {
+                b.is_dirty().cmp(&a.is_dirty()).then_with(|| a.path.cmp(&b.path))
+            });
+            self.finder = None;
+            self.apply_pattern();
+        }
+    }
+
+    fn on_pattern(
+        &mut self,
+        pat: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = pat;
+        self.apply_pattern();
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        let bang = input_invocation
+            .map(|inv| inv.bang)
+            .unwrap_or(internal_exec.bang);
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.move_selection(-(self.filtered.len() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.move_selection(self.filtered.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay => match self.selected_repo() {
+                Some(repo) => internal_focus::on_path(
+                    repo.path.clone(),
+                    screen,
+                    self.tree_options.clone(),
+                    bang,
+                    cc.con,
+                ),
+                None => AppStateCmdResult::Keep,
+            },
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        let path_width = self.repos.iter()
+            .map(|repo| self.displayed_path(repo).to_string_lossy().chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(width / 2);
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            if let Some(finder) = &self.finder {
+                if y == 0 {
+                    cw.queue_g_string(
+                        &styles.dates,
+                        format!(
+                            "{} repositories found in {} directories",
+                            finder.repos.len(),
+                            finder.dirs_count,
+                        ),
+                    )?;
+                }
+                cw.fill(&styles.default, LONG_SPACE)?;
+                continue;
+            }
+            let idx = self.scroll + y as usize;
+            let repo = match self.filtered.get(idx) {
+                Some(&repo_idx) => &self.repos[repo_idx],
+                None => {
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                    continue;
+                }
+            };
+            let selected = idx == self.selection_idx;
+            let mut path_style = styles.directory.clone();
+            let mut branch_style = styles.git_branch.clone();
+            let mut state_style = if repo.is_dirty() {
+                styles.git_status_modified.clone()
+            } else {
+                styles.git_status_current.clone()
+            };
+            let mut text_style = styles.default.clone();
+            if selected {
+                if let Some(c) = styles.selected_line.get_bg() {
+                    path_style.set_bg(c);
+                    branch_style.set_bg(c);
+                    state_style.set_bg(c);
+                    text_style.set_bg(c);
+                }
+            }
+            if con.show_selection_mark {
+                cw.queue_char(&text_style, if selected { '▶' } else { ' ' })?;
+            }
+            let path = self.displayed_path(repo).to_string_lossy().to_string();
+            let path_len = path.chars().count();
+            cw.queue_g_string(&path_style, path)?;
+            cw.queue_g_string(
+                &text_style,
+                " ".repeat(path_width.saturating_sub(path_len) + 1),
+            )?;
+            cw.queue_g_string(
+                &branch_style,
+                repo.branch.clone().unwrap_or_else(|| "(detached)".to_string()),
+            )?;
+            if let Some((ahead, behind)) = repo.ahead_behind {
+                if ahead > 0 {
+                    cw.queue_g_string(&branch_style, format!(" ↑{}", ahead))?;
+                }
+                if behind > 0 {
+                    cw.queue_g_string(&branch_style, format!(" ↓{}", behind))?;
+                }
+            }
+            cw.queue_char(&text_style, ' ')?;
+            if repo.is_dirty() {
+                cw.queue_g_string(&state_style, format!("{} changed files", repo.changes))?;
+            } else {
+                cw.queue_str(&state_style, "clean")?;
+            }
+            cw.fill(&text_style, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.finder.is_some() {
+            Status::from_message("Searching git repositories... hit *esc* to cancel")
+        } else if self.repos.is_empty() {
+            Status::from_message("No git repository found. Hit *esc* to go back")
+        } else {
+            Status::from_message(format!(
+                "{} repositories, {} with uncommitted changes. Hit *enter* to browse the selected one",
+                self.repos.len(),
+                self.repos.iter().filter(|repo| repo.is_dirty()).count(),
+            ))
+        }
+    }
+}
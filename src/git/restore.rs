@@ -0,0 +1,67 @@
+use {
+    super::log::open_repo,
+    std::{
+        fs,
+        path::Path,
+    },
+};
+
+/// the git file mode of symbolic links
+const LINK_MODE: i32 = 0o120_000;
+
+/// discard the local modifications of the file, staged or not, by
+/// writing its version of the head commit.
+///
+/// The blob is written rather than checked out because the checkout
+/// of git2 0.11 reads the path as a pathspec, and would restore all
+/// the files matching a name like `*.rs`.
+pub fn restore_file(path: &Path) -> Result<(), git2::Error> {
+    let as_git_error = |e: std::io::Error| git2::Error::from_str(&e.to_string());
+    let (repo, relative_path) = open_repo(path)?;
+    // an untracked file would just be left as is
+    let entry = repo.head()?.peel_to_tree()?.get_path(&relative_path)?;
+    let blob = repo.find_blob(entry.id())?;
+    let path = repo.workdir()
+        .ok_or_else(|| git2::Error::from_str("no working directory"))?
+        .join(&relative_path);
+    // the file is removed first so that a link isn't followed
+    if fs::symlink_metadata(&path).is_ok() {
+        fs::remove_file(&path).map_err(as_git_error)?;
+    }
+    if entry.filemode() == LINK_MODE {
+        write_link(&path, blob.content()).map_err(as_git_error)?;
+    } else {
+        fs::write(&path, blob.content()).map_err(as_git_error)?;
+        set_mode(&path, entry.filemode()).map_err(as_git_error)?;
+    }
+    // the staged modifications are discarded too, the file in the
+    // working directory being now the one of the head
+    let mut index = repo.index()?;
+    index.add_path(&relative_path)?;
+    index.write()
+}
+
+#[cfg(unix)]
+fn write_link(path: &Path, target: &[u8]) -> std::io::Result<()> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    std::os::unix::fs::symlink(OsStr::from_bytes(target), path)
+}
+
+#[cfg(not(unix))]
+fn write_link(path: &Path, target: &[u8]) -> std::io::Result<()> {
+    // without symbolic links, git writes the target in a plain file
+    fs::write(path, target)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: i32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(if mode & 0o111 != 0 { 0o755 } else { 0o644 });
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: i32) -> std::io::Result<()> {
+    Ok(())
+}
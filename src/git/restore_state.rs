@@ -0,0 +1,170 @@
+use {
+    super::{
+        diff::{file_diff, DiffLineKind},
+        restore::restore_file,
+    },
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        verb::*,
+    },
+    std::path::{Path, PathBuf},
+    termimad::{Area, FmtText, TextView},
+};
+
+enum GitRestoreStep {
+    /// the changes are summarized, waiting for the user's confirmation
+    Confirm {
+        insertions: usize,
+        deletions: usize,
+    },
+    Done,
+    Failed(String),
+}
+
+/// a modal state asking for the confirmation before discarding the
+/// local modifications of a file, then restoring it from the head
+pub struct GitRestoreState {
+    path: PathBuf,
+    step: GitRestoreStep,
+}
+
+impl GitRestoreState {
+    pub fn new(path: PathBuf) -> Self {
+        let step = match file_diff(&path) {
+            Ok(lines) if lines.is_empty() => {
+                GitRestoreStep::Failed("the file has no local modification".to_string())
+            }
+            Ok(lines) => GitRestoreStep::Confirm {
+                insertions: lines.iter().filter(|l| l.kind == DiffLineKind::Addition).count(),
+                deletions: lines.iter().filter(|l| l.kind == DiffLineKind::Deletion).count(),
+            },
+            Err(e) => GitRestoreStep::Failed(e.message().to_string()),
+        };
+        Self { path, step }
+    }
+
+    fn name(&self) -> String {
+        self.path.file_name().map_or_else(
+            || self.path.to_string_lossy().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        )
+    }
+
+    fn markdown(&self) -> String {
+        match &self.step {
+            GitRestoreStep::Confirm { insertions, deletions } => format!(
+                "\nThe local modifications of `{}` will be discarded:\n\n\
+                * {} added lines\n* {} removed lines\n\n\
+                The file will be restored as it is in the head commit.\n\n\
+                Type *y* to discard them, any other key to cancel.",
+                self.path.to_string_lossy(),
+                insertions,
+                deletions,
+            ),
+            GitRestoreStep::Done => format!("\n`{}` was restored", self.name()),
+            GitRestoreStep::Failed(e) => format!(
+                "\n`{}` can't be restored:\n\n`{}`",
+                self.name(),
+                e,
+            ),
+        }
+    }
+}
+
+impl AppState for GitRestoreState {
+
+    fn selected_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.path,
+            line: 0,
+            stype: SelectionType::File,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            GitRestoreStep::Confirm { .. } => match cc.cmd {
+                Command::PatternEdit { raw, .. } if raw == "y" || raw == "Y" => {
+                    self.step = match restore_file(&self.path) {
+                        Ok(()) => GitRestoreStep::Done,
+                        Err(e) => GitRestoreStep::Failed(e.message().to_string()),
+                    };
+                    AppStateCmdResult::Keep
+                }
+                _ => AppStateCmdResult::PopState, // any other key cancels
+            },
+            // the tree and the preview are refreshed for the git
+            // marker and the diff to be up to date
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        _internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match self.step {
+            GitRestoreStep::Confirm { .. } => AppStateCmdResult::PopState,
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            GitRestoreStep::Confirm { .. } => format!(
+                "Discard the modifications of {} ? *y*/*N*",
+                self.name(),
+            ),
+            _ => "Hit any key to go back".to_string(),
+        })
+    }
+}
@@ -1,7 +1,9 @@
 use {
-    git2::{self, Repository, Status},
+    crate::task_sync::Dam,
+    git2::{self, Repository, Status, Submodule},
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
+        fmt,
         path::{Path, PathBuf},
     },
 };
@@ -10,6 +12,15 @@ const INTERESTING: Status = Status::from_bits_truncate(
     Status::WT_NEW.bits() | Status::CONFLICTED.bits() | Status::WT_MODIFIED.bits(),
 );
 
+/// the changes added to the index
+const STAGED: Status = Status::from_bits_truncate(
+    Status::INDEX_NEW.bits()
+        | Status::INDEX_MODIFIED.bits()
+        | Status::INDEX_DELETED.bits()
+        | Status::INDEX_RENAMED.bits()
+        | Status::INDEX_TYPECHANGE.bits(),
+);
+
 // if I add nothing, I'll remove this useless struct
 // and only use git2.Status
 #[derive(Debug, Clone, Copy)]
@@ -28,37 +39,165 @@ impl LineGitStatus {
     }
 }
 
+/// the state of a submodule, as seen from its superproject
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubmoduleStatus {
+    Uninitialized,
+    Clean,
+    NewCommits, // its head isn't the commit recorded in the superproject
+    Modified,   // it has uncommitted changes
+}
+
+impl SubmoduleStatus {
+    fn of(submodule: &Submodule<'_>) -> Self {
+        match submodule.open() {
+            Err(_) => Self::Uninitialized,
+            Ok(repo) => {
+                let modified = repo.statuses(None).is_ok_and(|statuses| {
+                    statuses.iter().any(|entry| entry.status().intersects(INTERESTING | STAGED))
+                });
+                if modified {
+                    Self::Modified
+                } else if submodule.head_id() != submodule.workdir_id() {
+                    Self::NewCommits
+                } else {
+                    Self::Clean
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for SubmoduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Uninitialized => "uninitialized",
+            Self::Clean => "clean",
+            Self::NewCommits => "new commits",
+            Self::Modified => "modified",
+        })
+    }
+}
+
 pub struct LineStatusComputer {
-    interesting_statuses: HashMap<PathBuf, Status>,
+    git_dir: PathBuf, // the repository isn't kept as it can't be shared between threads
+    repo_path: PathBuf,
+    statuses: HashMap<PathBuf, Status>, // the interesting or staged ones
+    submodule_paths: HashSet<PathBuf>,
+    submodules: HashMap<PathBuf, SubmoduleStatus>, // only the computed ones
 }
 impl LineStatusComputer {
     pub fn from(repo: Repository) -> Self {
         let repo_path = repo.path().parent().unwrap().to_path_buf();
-        let mut interesting_statuses = HashMap::new();
-        if let Ok(statuses) = &repo.statuses(None) {
-            for entry in statuses.iter() {
+        let mut statuses = HashMap::new();
+        if let Ok(repo_statuses) = &repo.statuses(None) {
+            for entry in repo_statuses.iter() {
                 let status = entry.status();
-                if status.intersects(INTERESTING) {
+                // staged changes are kept too, for the filters
+                if status.intersects(INTERESTING | STAGED) {
                     if let Some(path) = entry.path() {
                         let path = repo_path.join(path);
-                        interesting_statuses.insert(path, status);
+                        statuses.insert(path, status);
                     }
                 }
             }
         } else {
             debug!("get statuses failed");
         }
+        // the statuses of the submodules, which need opening their
+        // repositories, are computed later, and only for the displayed ones
+        let submodule_paths = repo.submodules().map_or_else(
+            |_| HashSet::new(),
+            |submodules| submodules.iter().map(|s| repo_path.join(s.path())).collect(),
+        );
         Self {
-            interesting_statuses,
+            git_dir: repo.path().to_path_buf(),
+            repo_path,
+            statuses,
+            submodule_paths,
+            submodules: HashMap::new(),
+        }
+    }
+    /// compute the statuses of the submodules found among the given
+    /// paths. Return false if interrupted by an event.
+    pub fn compute_submodule_statuses<'p>(
+        &mut self,
+        paths: impl Iterator<Item = &'p Path>,
+        dam: &Dam,
+    ) -> bool {
+        let wanted: HashSet<&Path> = paths
+            .filter(|path| self.submodule_paths.contains(*path) && !self.submodules.contains_key(*path))
+            .collect();
+        if wanted.is_empty() {
+            return true;
+        }
+        let repo = match Repository::open(&self.git_dir) {
+            Ok(repo) => repo,
+            Err(_) => return true,
+        };
+        let repo_submodules = match repo.submodules() {
+            Ok(repo_submodules) => repo_submodules,
+            Err(_) => return true,
+        };
+        for submodule in &repo_submodules {
+            let path = self.repo_path.join(submodule.path());
+            if !wanted.contains(path.as_path()) {
+                continue;
+            }
+            if dam.has_event() {
+                return false;
+            }
+            let status = time!(Debug, "submodule status", SubmoduleStatus::of(submodule));
+            self.submodules.insert(path, status);
         }
+        true
     }
     pub fn line_status(&self, path: &Path) -> Option<LineGitStatus> {
-        self.interesting_statuses
+        self.statuses
             .get(path)
+            .filter(|status| status.intersects(INTERESTING))
             .map(|&status| LineGitStatus { status })
     }
+    pub fn submodule_status(&self, path: &Path) -> Option<SubmoduleStatus> {
+        self.submodules.get(path).copied()
+    }
     pub fn is_interesting(&self, path: &Path) -> bool {
-        self.interesting_statuses.contains_key(path)
+        self.has_status(path, INTERESTING)
+    }
+    /// tell whether the file has one of the given statuses
+    pub fn has_status(&self, path: &Path, statuses: Status) -> bool {
+        self.statuses
+            .get(path)
+            .is_some_and(|status| status.intersects(statuses))
+    }
+}
+
+/// parse the states given to :git_filter, eg `conflicted`
+/// or `modified,untracked`, into a set of statuses
+pub fn parse_status_filter(s: &str) -> Result<Status, String> {
+    let mut statuses = Status::empty();
+    let names = s
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty());
+    for name in names {
+        statuses |= match name {
+            // the modification may be staged or not
+            "modified" => Status::WT_MODIFIED | Status::INDEX_MODIFIED,
+            "new" | "untracked" => Status::WT_NEW,
+            "staged" => STAGED,
+            "conflicted" => Status::CONFLICTED,
+            _ => {
+                return Err(format!(
+                    "Unknown git state: {:?} (expected `modified`, `untracked`, `staged` or `conflicted`)",
+                    name,
+                ));
+            }
+        };
+    }
+    if statuses.is_empty() {
+        Err("No git state given".to_string())
+    } else {
+        Ok(statuses)
     }
 }
 
@@ -68,6 +207,20 @@ pub struct TreeGitStatus {
     pub current_branch_name: Option<String>,
     pub insertions: usize,
     pub deletions: usize,
+    pub ahead_behind: Option<(usize, usize)>, // commits ahead and behind the upstream, if any
+    pub stashes: usize,
+}
+
+/// count the commits of the current branch which aren't in its
+/// upstream, and the ones of the upstream which aren't in the branch
+pub fn ahead_behind(repo: &Repository) -> Option<(usize, usize)> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None; // detached head
+    }
+    let local = head.target()?;
+    let upstream = git2::Branch::wrap(head).upstream().ok()?.get().target()?;
+    repo.graph_ahead_behind(local, upstream).ok()
 }
 
 impl TreeGitStatus {
@@ -91,10 +244,33 @@ impl TreeGitStatus {
                 return None;
             }
         };
+        // each stash is an entry of the reflog of refs/stash
+        let stashes = repo.reflog("refs/stash").map_or(0, |reflog| reflog.len());
         Some(Self {
             current_branch_name,
             insertions: stats.insertions(),
             deletions: stats.deletions(),
+            ahead_behind: ahead_behind(repo),
+            stashes,
         })
     }
 }
+
+#[cfg(test)]
+mod status_filter_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_parse_status_filter() {
+        let modified = parse_status_filter("modified").unwrap();
+        assert!(modified.contains(Status::WT_MODIFIED));
+        assert!(modified.contains(Status::INDEX_MODIFIED));
+        assert!(!modified.intersects(Status::WT_NEW));
+        let statuses = parse_status_filter("staged, untracked").unwrap();
+        assert!(statuses.contains(Status::INDEX_NEW | Status::INDEX_RENAMED | Status::WT_NEW));
+        assert!(!statuses.intersects(Status::WT_MODIFIED));
+        assert!(parse_status_filter("modified,ignored").is_err());
+        assert!(parse_status_filter(" , ").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! per-extension chains of handlers, defining how files are
+//! previewed and opened.
+//!
+//! A chain is an ordered list of handlers. When previewing a file,
+//! the first handler of its chain able to build a preview is used.
+//! When opening it, the first opening handler is used. The handlers
+//! whose command isn't available on the machine are removed on launch
+//! so that the next ones act as fallbacks.
+
+use {
+    crate::{
+        app::{AppContext, AppStateCmdResult},
+        display::W,
+        errors::{ConfError, ProgramError},
+        launchable::Launchable,
+        preview::PreviewMode,
+    },
+    std::{
+        collections::HashMap,
+        env,
+        io,
+        path::{Path, PathBuf},
+        process::Command,
+    },
+};
+
+/// an external command, whose `{file}` tokens are replaced
+/// with the path of the handled file
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandlerCommand {
+    tokens: Vec<String>,
+}
+
+/// a way to preview or to open a file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Handler {
+    /// preview in broot with the given mode (`"image"`, `"text"`,
+    /// `"hex"`, `"markdown"`, `"structured"` or `"diff"`)
+    Preview(PreviewMode),
+
+    /// preview the output of a command, as an image if it can be
+    /// decoded as one, as text otherwise (`"convert: <command>"`)
+    Convert(HandlerCommand),
+
+    /// open with the default application of the system (`"open"`)
+    SystemOpen,
+
+    /// open with a command (`"exec: <command>"`)
+    Exec(HandlerCommand),
+}
+
+/// the handler chains, by lowercased extension
+#[derive(Debug, Clone, Default)]
+pub struct HandlerMap {
+    chains: HashMap<String, Vec<Handler>>,
+}
+
+/// tell whether the executable can be found, either directly
+/// or in one of the directories of the PATH
+fn is_executable_available(exe: &str) -> bool {
+    if let Some(var) = exe.strip_prefix('$') {
+        // it will be replaced with the value of the env variable
+        return env::var_os(var).is_some();
+    }
+    let exe_path = Path::new(exe);
+    if exe_path.components().count() > 1 {
+        return exe_path.is_file();
+    }
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| {
+            dir.join(exe).is_file()
+                || (cfg!(windows) && dir.join(format!("{}.exe", exe)).is_file())
+        })
+    })
+}
+
+impl HandlerCommand {
+    fn from(raw: &str) -> Option<Self> {
+        let tokens: Vec<String> = raw.split_whitespace().map(String::from).collect();
+        if tokens.is_empty() {
+            None
+        } else {
+            Some(Self { tokens })
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        is_executable_available(&self.tokens[0])
+    }
+
+    /// the executable and its arguments for the given file
+    pub fn parts(&self, path: &Path) -> Vec<String> {
+        let path = path.to_string_lossy();
+        self.tokens
+            .iter()
+            .map(|token| token.replace("{file}", &path))
+            .collect()
+    }
+
+    /// run the command on the file and return what it wrote on stdout
+    pub fn output(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let parts = self.parts(path);
+        let output = Command::new(&parts[0]).args(&parts[1..]).output()?;
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))
+        }
+    }
+}
+
+impl Handler {
+    pub fn from(raw: &str) -> Result<Self, ConfError> {
+        let raw = raw.trim();
+        let command = |prefix: &str| {
+            raw.strip_prefix(prefix).and_then(HandlerCommand::from)
+        };
+        match raw {
+            "image" => Ok(Self::Preview(PreviewMode::Image)),
+            "text" => Ok(Self::Preview(PreviewMode::Text)),
+            "hex" => Ok(Self::Preview(PreviewMode::Hex)),
+            "markdown" => Ok(Self::Preview(PreviewMode::Markdown)),
+            "structured" => Ok(Self::Preview(PreviewMode::Structured)),
+            "diff" => Ok(Self::Preview(PreviewMode::Diff)),
+            "open" => Ok(Self::SystemOpen),
+            _ => {
+                if let Some(c) = command("convert:") {
+                    Ok(Self::Convert(c))
+                } else if let Some(c) = command("exec:") {
+                    Ok(Self::Exec(c))
+                } else {
+                    Err(ConfError::InvalidHandler {
+                        handler: raw.to_string(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// tell whether the handler can be used on this machine
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::Convert(command) | Self::Exec(command) => command.is_available(),
+            _ => true,
+        }
+    }
+
+    pub fn is_previewer(&self) -> bool {
+        matches!(self, Self::Preview(_) | Self::Convert(_))
+    }
+
+    pub fn is_opener(&self) -> bool {
+        matches!(self, Self::SystemOpen | Self::Exec(_))
+    }
+}
+
+impl HandlerMap {
+    /// set the chain of the given extensions, replacing the
+    /// previously defined ones
+    pub fn set(&mut self, extensions: &[String], chain: Vec<Handler>) {
+        for ext in extensions {
+            let ext = ext.trim_start_matches('.').to_lowercase();
+            self.chains.insert(ext, chain.clone());
+        }
+    }
+
+    /// remove the handlers which can't be used on this machine
+    pub fn keep_available(&mut self) {
+        for (ext, chain) in self.chains.iter_mut() {
+            chain.retain(|handler| {
+                let available = handler.is_available();
+                if !available {
+                    info!("handler {:?} of extension {:?} isn't available", handler, ext);
+                }
+                available
+            });
+        }
+    }
+
+    /// return the chain of the file, which may be empty
+    pub fn chain(&self, path: &Path) -> &[Handler] {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.chains.get(&ext.to_lowercase()))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// return the handlers which may be used to preview
+    /// the file, in order of preference
+    pub fn previewers<'m>(&'m self, path: &Path) -> impl Iterator<Item = &'m Handler> {
+        self.chain(path).iter().filter(|handler| handler.is_previewer())
+    }
+
+    /// return the handler to use to open the file, if one
+    /// was configured
+    pub fn opener(&self, path: &Path) -> Option<&Handler> {
+        self.chain(path).iter().find(|handler| handler.is_opener())
+    }
+}
+
+/// open the file with the opener of its chain, or with the default
+/// application of the system, broot staying open
+pub fn open_file_stay(
+    w: &mut W,
+    path: &Path,
+    con: &AppContext,
+) -> Result<AppStateCmdResult, ProgramError> {
+    match con.handlers.opener(path) {
+        Some(Handler::Exec(command)) => {
+            let launchable = Launchable::program(command.parts(path), None)?;
+            Ok(match launchable.execute(Some(w), con.launch_args.is_inline()) {
+                Ok(()) => AppStateCmdResult::RefreshState { clear_cache: true },
+                Err(e) => {
+                    warn!("opener failed : {:?}", e);
+                    AppStateCmdResult::DisplayError(e.to_string())
+                }
+            })
+        }
+        _ => Ok(match open::that(path) {
+            Ok(exit_status) => {
+                info!("open returned with exit_status {:?}", exit_status);
+                AppStateCmdResult::Keep
+            }
+            Err(e) => AppStateCmdResult::DisplayError(format!("{:?}", e)),
+        }),
+    }
+}
+
+/// build the launchable opening the file with the opener of its
+/// chain, or with the default application of the system, after
+/// broot quits
+pub fn file_opener(
+    path: PathBuf,
+    con: &AppContext,
+) -> Result<Launchable, ProgramError> {
+    Ok(match con.handlers.opener(&path) {
+        Some(Handler::Exec(command)) => Launchable::program(command.parts(&path), None)?,
+        _ => Launchable::opener(path),
+    })
+}
@@ -1,6 +1,7 @@
 use {
     crate::app::AppContext,
     minimad::{Text, TextTemplate},
+    std::path::Path,
 };
 
 static MD: &str = r#"
@@ -49,8 +50,11 @@ Flags are displayed at bottom right:
 * `gi:y`, `gi:n` : whether gitignore rules are active or not
 "#;
 
-/// build the markdown which will be displayed in the help page
-pub fn build_text(con: &AppContext) -> Text<'_> {
+/// build the markdown which will be displayed in the help page.
+///
+/// The verbs whose file filter doesn't accept the given path
+/// aren't listed.
+pub fn build_text<'c>(con: &'c AppContext, applied_path: &Path) -> Text<'c> {
     lazy_static! {
         // this doesn't really matter, only half a ms is spared
         static ref TEMPLATE: TextTemplate<'static> = TextTemplate::from(MD);
@@ -60,6 +64,11 @@ pub fn build_text(con: &AppContext) -> Text<'_> {
         .set("version", env!("CARGO_PKG_VERSION"))
         .set("config-path", &con.config_path);
     for verb in &con.verb_store.verbs {
+        if let Some(filter) = &verb.file_filter {
+            if !filter.accepts(applied_path) {
+                continue;
+            }
+        }
         let sub = expander
             .sub("verb-rows")
             .set(
@@ -70,14 +79,7 @@ pub fn build_text(con: &AppContext) -> Text<'_> {
                     ""
                 },
             )
-            .set(
-                "shortcut",
-                if let Some(shortcut) = verb.names.get(1) {
-                    &shortcut
-                } else {
-                    ""
-                },
-            )
+            .set("shortcut", &verb.aliases_desc)
             .set("key", &verb.keys_desc);
         if verb.description.code {
             sub.set("description", "");
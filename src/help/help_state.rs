@@ -6,11 +6,11 @@ use {
         conf::Conf,
         display::{Screen, W},
         errors::ProgramError,
-        launchable::Launchable,
+        handlers,
         skin::PanelSkin,
         verb::*,
     },
-    std::path::Path,
+    std::path::{Path, PathBuf},
     termimad::{Area, FmtText, TextView},
 };
 
@@ -19,21 +19,31 @@ pub struct HelpState {
     pub scroll: i32, // scroll position
     pub text_area: Area,
     dirty: bool, // background must be cleared
+    applied_path: PathBuf, // the selection when help was asked, for verb filters
 }
 
 impl HelpState {
-    pub fn new(_screen: &Screen, _con: &AppContext) -> HelpState {
+    pub fn new(_screen: &Screen, _con: &AppContext, sel: Selection<'_>) -> HelpState {
         let text_area = Area::uninitialized(); // will be fixed at drawing time
         HelpState {
             text_area,
             scroll: 0,
             dirty: true,
+            applied_path: sel.path.to_path_buf(),
         }
     }
 }
 
 impl AppState for HelpState {
 
+    fn get_type(&self) -> AppStateType {
+        AppStateType::Help
+    }
+
+    fn label(&self) -> String {
+        "help".to_string()
+    }
+
     fn selected_path(&self) -> &Path {
         Conf::default_location()
     }
@@ -43,6 +53,7 @@ impl AppState for HelpState {
             path: Conf::default_location(),
             stype: SelectionType::File,
             line: 0,
+            marked: &[],
         }
     }
 
@@ -70,7 +81,7 @@ impl AppState for HelpState {
             screen.clear_area_to_right(w, &state_area)?;
             self.dirty = false;
         }
-        let text = help_content::build_text(con);
+        let text = help_content::build_text(con, &self.applied_path);
         let fmt_text = FmtText::from_text(
             &panel_skin.help_skin,
             text,
@@ -111,17 +122,12 @@ impl AppState for HelpState {
                 self.scroll -= 1;
                 AppStateCmdResult::Keep
             }
-            open_stay => match open::that(&Conf::default_location()) {
-                Ok(exit_status) => {
-                    info!("open returned with exit_status {:?}", exit_status);
-                    AppStateCmdResult::Keep
-                }
-                Err(e) => AppStateCmdResult::DisplayError(format!("{:?}", e)),
-            },
+            open_stay => handlers::open_file_stay(w, Conf::default_location(), cc.con)?,
             open_leave => {
-                AppStateCmdResult::from(Launchable::opener(
-                    Conf::default_location().to_path_buf()
-                ))
+                AppStateCmdResult::from(handlers::file_opener(
+                    Conf::default_location().to_path_buf(),
+                    cc.con,
+                )?)
             }
             page_down => {
                 self.scroll += self.text_area.height as i32;
@@ -132,6 +138,7 @@ impl AppState for HelpState {
                 AppStateCmdResult::Keep
             }
             toggle_dates | toggle_files | toggle_hidden | toggle_git_ignore
+            | toggle_ignore_files | toggle_broot_ignore
             | toggle_git_file_info | toggle_git_status | toggle_perm | toggle_sizes
             | toggle_trim_root => AppStateCmdResult::PopStateAndReapply,
             _ => self.on_internal_generic(
@@ -1,5 +1,9 @@
 use {
-    super::double_line::DoubleLine,
+    super::{
+        double_line::DoubleLine,
+        kitty,
+        sixel,
+    },
     crate::{
         app::AppContext,
         display::{fill_bg, Screen, W},
@@ -36,7 +40,8 @@ impl ImageView {
             Debug,
             "decode image",
             path,
-            Reader::open(&path)?.decode()?
+            // the format is guessed from the content, not the extension
+            Reader::open(path)?.with_guessed_format()?.decode()?
         );
         let (width, height) = img.dimensions();
         debug!("image dimensions: {},{}", width, height);
@@ -44,6 +49,14 @@ impl ImageView {
             img,
         })
     }
+    /// build an image view from the encoded content of an image,
+    /// for example the output of a converting command
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        let img = image::load_from_memory(bytes)?;
+        Ok(Self {
+            img,
+        })
+    }
     pub fn display(
         &mut self,
         w: &mut W,
@@ -52,6 +65,12 @@ impl ImageView {
         area: &Area,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
+        if con.kitty_graphics {
+            return self.display_with_graphics(w, panel_skin, area, kitty::print_image);
+        }
+        if con.sixel_graphics {
+            return self.display_with_graphics(w, panel_skin, area, sixel::print_image);
+        }
         let img = time!(
             Debug,
             "resize image",
@@ -94,6 +113,39 @@ impl ImageView {
         }
         Ok(())
     }
+    /// display the image with a graphics protocol (kitty or sixel),
+    /// centered in the area and preserving its ratio (assuming cells
+    /// are twice as high as wide)
+    fn display_with_graphics(
+        &mut self,
+        w: &mut W,
+        panel_skin: &PanelSkin,
+        area: &Area,
+        print_image: fn(&mut W, &DynamicImage, u16, u16) -> Result<(), ProgramError>,
+    ) -> Result<(), ProgramError> {
+        let styles = &panel_skin.styles;
+        let bg = styles.preview.get_bg()
+            .or_else(|| styles.default.get_bg())
+            .unwrap_or(Color::AnsiValue(238));
+        w.queue(SetBackgroundColor(bg))?;
+        for y in area.top..area.top+area.height {
+            w.queue(cursor::MoveTo(area.left, y))?;
+            fill_bg(w, area.width as usize, bg)?;
+        }
+        let (img_width, img_height) = self.img.dimensions();
+        if img_width == 0 || img_height == 0 {
+            return Ok(());
+        }
+        let ratio = f64::min(
+            area.width as f64 / img_width as f64,
+            (area.height * 2) as f64 / img_height as f64,
+        );
+        let cols = ((img_width as f64 * ratio) as u16).max(1).min(area.width);
+        let rows = ((img_height as f64 * ratio / 2.0).ceil() as u16).max(1).min(area.height);
+        let left = area.left + (area.width - cols) / 2;
+        w.queue(cursor::MoveTo(left, area.top))?;
+        print_image(w, &self.img, cols, rows)
+    }
     pub fn display_info(
         &mut self,
         w: &mut W,
@@ -0,0 +1,92 @@
+//! Support of the kitty terminal graphics protocol, which lets
+//! broot display real images instead of colored half-chars.
+//!
+//! See https://sw.kovidgoyal.net/kitty/graphics-protocol.html
+//!
+//! Images are displayed on a layer which isn't cleared by
+//! writing chars, so they must be explicitly deleted before
+//! the panels are redrawn.
+
+use {
+    crate::{
+        display::W,
+        errors::ProgramError,
+    },
+    crossterm::{
+        style::Print,
+        QueueableCommand,
+    },
+    image::{
+        DynamicImage,
+        GenericImageView,
+        imageops::FilterType,
+    },
+};
+
+/// size of the chunks of base64 data sent to the terminal
+const CHUNK_SIZE: usize = 4096;
+
+/// assumed size of a cell in pixels, used to limit the size
+/// of the transmitted image (the terminal scales it anyway)
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+
+/// try to determine whether the terminal supports the kitty
+/// graphics protocol. As this detection isn't perfectly reliable,
+/// the conf's `kitty_graphics` setting has precedence.
+pub fn is_kitty_graphics_protocol_supported() -> bool {
+    if std::env::var("KITTY_WINDOW_ID").is_ok() {
+        return true;
+    }
+    if let Ok(term) = std::env::var("TERM") {
+        if term == "xterm-kitty" {
+            return true;
+        }
+    }
+    if let Ok(term_program) = std::env::var("TERM_PROGRAM") {
+        if term_program == "WezTerm" {
+            return true;
+        }
+    }
+    false
+}
+
+/// delete all the images displayed with the kitty protocol
+pub fn delete_images(w: &mut W) -> Result<(), ProgramError> {
+    w.queue(Print("\u{1b}_Ga=d,q=2\u{1b}\\"))?;
+    Ok(())
+}
+
+/// display the image at the cursor position, scaled to fill
+/// the given number of cells
+pub fn print_image(
+    w: &mut W,
+    img: &DynamicImage,
+    cols: u16,
+    rows: u16,
+) -> Result<(), ProgramError> {
+    let img = img.resize(
+        cols as u32 * CELL_WIDTH,
+        rows as u32 * CELL_HEIGHT,
+        FilterType::Triangle,
+    );
+    let (width, height) = img.dimensions();
+    let encoded = base64::encode(img.to_rgba().into_raw());
+    let mut chunks = encoded.as_bytes().chunks(CHUNK_SIZE).peekable();
+    let mut first = true;
+    while let Some(chunk) = chunks.next() {
+        let m = if chunks.peek().is_some() { 1 } else { 0 };
+        // the chunks are base64 thus ASCII
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if first {
+            w.queue(Print(format!(
+                "\u{1b}_Ga=T,f=32,s={},v={},c={},r={},C=1,q=2,m={};{}\u{1b}\\",
+                width, height, cols, rows, m, chunk,
+            )))?;
+            first = false;
+        } else {
+            w.queue(Print(format!("\u{1b}_Gm={};{}\u{1b}\\", m, chunk)))?;
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,8 @@
 
 mod double_line;
 mod image_view;
+pub mod kitty;
+pub mod sixel;
 
 pub use {
     image_view::ImageView,
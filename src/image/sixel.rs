@@ -0,0 +1,186 @@
+//! Support of the sixel graphics, understood by terminals which
+//! don't know the kitty protocol, like foot, mlterm or mintty.
+//!
+//! See https://vt100.net/docs/vt3xx-gp/chapter14.html
+//!
+//! Contrary to the kitty images, sixel images are drawn in the
+//! cells, so they're erased by the drawing of chars.
+
+use {
+    crate::{
+        display::W,
+        errors::ProgramError,
+    },
+    crossterm::{
+        style::Print,
+        QueueableCommand,
+    },
+    image::{
+        DynamicImage,
+        imageops::FilterType,
+    },
+    std::fmt::Write,
+};
+
+/// assumed size of a cell in pixels. As a sixel image isn't
+/// scaled by the terminal, it's a lower bound of the usual sizes,
+/// so that the image doesn't overflow the area (and doesn't make
+/// the screen scroll when at the bottom)
+const CELL_WIDTH: u32 = 6;
+const CELL_HEIGHT: u32 = 12;
+
+/// number of levels of each channel in the palette, which
+/// is a color cube
+const LEVELS: u32 = 6;
+
+/// try to determine whether the terminal supports sixel graphics.
+/// As this detection only relies on the environment, the conf's
+/// `sixel_graphics` setting has precedence.
+pub fn is_sixel_supported() -> bool {
+    if let Ok(term) = std::env::var("TERM") {
+        if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.starts_with("yaft")
+        {
+            return true;
+        }
+    }
+    if let Ok(term_program) = std::env::var("TERM_PROGRAM") {
+        if term_program == "mintty" {
+            return true;
+        }
+    }
+    false
+}
+
+/// the index in the palette of the color closest to the given one
+fn palette_index(r: u8, g: u8, b: u8) -> usize {
+    let level = |c: u8| (c as u32 * (LEVELS - 1) + 127) / 255;
+    ((level(r) * LEVELS + level(g)) * LEVELS + level(b)) as usize
+}
+
+/// append to the sixel data a run of the same sixel char
+fn push_run(data: &mut String, sixel: u8, count: usize) {
+    let c = (b'?' + sixel) as char;
+    if count > 3 {
+        let _ = write!(data, "!{}{}", count, c);
+    } else {
+        for _ in 0..count {
+            data.push(c);
+        }
+    }
+}
+
+/// encode the image as a sixel sequence. Transparent pixels
+/// aren't drawn.
+pub fn encode(img: &DynamicImage) -> String {
+    let img = img.to_rgba();
+    let (width, height) = img.dimensions();
+    let colors_count = (LEVELS * LEVELS * LEVELS) as usize;
+    // the palette index of every pixel, None when transparent
+    let indexes: Vec<Option<usize>> = img.pixels()
+        .map(|p| if p[3] < 128 { None } else { Some(palette_index(p[0], p[1], p[2])) })
+        .collect();
+    let mut data = String::new();
+    // P2=1 leaves the transparent pixels untouched
+    let _ = write!(data, "\u{1b}P0;1;0q\"1;1;{};{}", width, height);
+    for idx in 0..colors_count as u32 {
+        let percent = |level: u32| level * 100 / (LEVELS - 1);
+        let _ = write!(
+            data,
+            "#{};2;{};{};{}",
+            idx,
+            percent(idx / (LEVELS * LEVELS)),
+            percent(idx / LEVELS % LEVELS),
+            percent(idx % LEVELS),
+        );
+    }
+    let width = width as usize;
+    let height = height as usize;
+    for band_top in (0..height).step_by(6) {
+        let band_height = (height - band_top).min(6);
+        let mut used = vec![false; colors_count];
+        for y in band_top..band_top + band_height {
+            for idx in indexes[y * width..(y + 1) * width].iter().flatten() {
+                used[*idx] = true;
+            }
+        }
+        let mut first_color = true;
+        for (color, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            if !first_color {
+                data.push('$'); // back to the start of the band
+            }
+            first_color = false;
+            let _ = write!(data, "#{}", color);
+            let mut run: Option<(u8, usize)> = None;
+            for x in 0..width {
+                let mut sixel = 0;
+                for dy in 0..band_height {
+                    if indexes[(band_top + dy) * width + x] == Some(color) {
+                        sixel |= 1 << dy;
+                    }
+                }
+                run = match run {
+                    Some((s, count)) if s == sixel => Some((s, count + 1)),
+                    Some((s, count)) => {
+                        push_run(&mut data, s, count);
+                        Some((sixel, 1))
+                    }
+                    None => Some((sixel, 1)),
+                };
+            }
+            if let Some((s, count)) = run {
+                push_run(&mut data, s, count);
+            }
+        }
+        data.push('-'); // next band
+    }
+    data.push_str("\u{1b}\\");
+    data
+}
+
+/// display the image at the cursor position, scaled to fit
+/// in the given number of cells
+pub fn print_image(
+    w: &mut W,
+    img: &DynamicImage,
+    cols: u16,
+    rows: u16,
+) -> Result<(), ProgramError> {
+    let img = img.resize(
+        cols as u32 * CELL_WIDTH,
+        rows as u32 * CELL_HEIGHT,
+        FilterType::Triangle,
+    );
+    w.queue(Print(encode(&img)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod sixel_tests {
+
+    use {
+        super::*,
+        image::{Rgba, RgbaImage},
+    };
+
+    #[test]
+    fn check_encoding() {
+        // a red line over a transparent one, then a white line
+        let mut img = RgbaImage::new(5, 3);
+        for x in 0..5 {
+            img.put_pixel(x, 0, Rgba([255, 0, 0, 255]));
+            img.put_pixel(x, 2, Rgba([255, 255, 255, 255]));
+        }
+        let sixel = encode(&DynamicImage::ImageRgba8(img));
+        assert!(sixel.starts_with("\u{1b}P0;1;0q\"1;1;5;3#0;2;0;0;0"));
+        let red = palette_index(255, 0, 0);
+        let white = palette_index(255, 255, 255);
+        assert_eq!(red, 180);
+        assert_eq!(white, 215);
+        // the red pixels are the first row of the band (sixel 1, char '@'),
+        // the white ones the third (sixel 4, char 'C')
+        assert!(sixel.ends_with("#180!5@$#215!5C-\u{1b}\\"));
+    }
+}
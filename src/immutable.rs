@@ -0,0 +1,78 @@
+//! awareness of the immutable stores (the nix and guix stores,
+//! ostree deployments) whose content can't be modified and which
+//! are usually reached through chains of symlinks
+
+use {
+    crate::path,
+    std::{
+        fmt::Write,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// roots of the known immutable stores
+static STORE_ROOTS: &[&str] = &[
+    "/nix/store",
+    "/gnu/store",
+    "/ostree/deploy",
+    "/sysroot/ostree",
+];
+
+/// max number of links followed when resolving a chain, to
+/// protect against loops
+const MAX_CHAIN_LENGTH: usize = 40;
+
+/// tell whether the path is in an immutable store
+pub fn is_immutable(path: &Path) -> bool {
+    STORE_ROOTS.iter().any(|root| path.starts_with(root))
+}
+
+/// follow the symlinks starting at the given path, and return
+/// all the paths of the chain, the given one included, the last
+/// one being the real path.
+pub fn resolve_chain(path: &Path) -> Vec<PathBuf> {
+    let mut chain = vec![path.to_path_buf()];
+    let mut current = path.to_path_buf();
+    while chain.len() <= MAX_CHAIN_LENGTH {
+        let target = match fs::read_link(&current) {
+            Ok(target) => target,
+            Err(_) => break, // not a link
+        };
+        let target = match current.parent() {
+            Some(parent) if target.is_relative() => path::normalize_path(parent.join(target)),
+            _ => target,
+        };
+        let is_loop = chain.contains(&target);
+        chain.push(target.clone());
+        if is_loop {
+            return chain;
+        }
+        current = target;
+    }
+    // the final path may still be behind a link, in one of its ancestors
+    if let Ok(canonical) = fs::canonicalize(&current) {
+        if canonical != current {
+            chain.push(canonical);
+        }
+    }
+    chain
+}
+
+/// make the text showing the chain, one path per line, flagging
+/// the paths which are in an immutable store or don't exist
+pub fn chain_text(chain: &[PathBuf]) -> String {
+    let mut text = String::new();
+    for (idx, path) in chain.iter().enumerate() {
+        let prefix = if idx == 0 { "  " } else { "→ " };
+        let flag = if fs::symlink_metadata(path).is_err() {
+            " (missing)"
+        } else if is_immutable(path) {
+            " (immutable)"
+        } else {
+            ""
+        };
+        let _ = writeln!(text, "{}{}{}", prefix, path.to_string_lossy(), flag);
+    }
+    text
+}
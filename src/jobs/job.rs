@@ -0,0 +1,130 @@
+use {
+    std::{
+        io::{self, BufRead, BufReader, Read},
+        path::PathBuf,
+        process::{Command, Stdio},
+        sync::Mutex,
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// where a job is in its life
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Finished(Option<i32>), // the exit code, if the process wasn't killed by a signal
+    Failed(String),        // the process couldn't be waited for
+}
+
+/// a command executed in background
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: usize, // starts at 1
+    pub command: String,
+    pub start: Instant,
+    pub duration: Option<Duration>, // None while the job is running
+    pub status: JobStatus,
+    pub output: String, // stdout and stderr, interleaved
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<Vec<Job>> = Mutex::new(Vec::new());
+}
+
+impl Job {
+    pub fn is_success(&self) -> bool {
+        self.status == JobStatus::Finished(Some(0))
+    }
+}
+
+fn with_job<F: FnOnce(&mut Job)>(id: usize, f: F) {
+    if let Ok(mut jobs) = JOBS.lock() {
+        if let Some(job) = jobs.get_mut(id - 1) {
+            f(job);
+        }
+    }
+}
+
+/// append the lines read from the stream to the output of the job
+fn capture<R: Read>(id: usize, reader: R) {
+    for line in BufReader::new(reader).lines() {
+        match line {
+            Ok(line) => with_job(id, |job| {
+                job.output.push_str(&line);
+                job.output.push('\n');
+            }),
+            Err(e) => {
+                warn!("error while reading output of job {}: {:?}", id, e);
+                break;
+            }
+        }
+    }
+}
+
+/// start the program in a new process whose output is captured,
+/// and return the id of the job
+pub fn launch(
+    exe: String,
+    args: Vec<String>,
+    working_dir: Option<PathBuf>,
+) -> io::Result<usize> {
+    let mut command = Command::new(&exe);
+    command
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(working_dir) = working_dir {
+        command.current_dir(working_dir);
+    }
+    let mut child = command.spawn()?;
+    let id = {
+        let mut jobs = JOBS.lock().unwrap();
+        let id = jobs.len() + 1;
+        let mut command = exe;
+        for arg in &args {
+            command.push(' ');
+            command.push_str(arg);
+        }
+        jobs.push(Job {
+            id,
+            command,
+            start: Instant::now(),
+            duration: None,
+            status: JobStatus::Running,
+            output: String::new(),
+        });
+        id
+    };
+    let stdout = child.stdout.take();
+    let stderr_thread = child
+        .stderr
+        .take()
+        .map(|stderr| thread::spawn(move || capture(id, stderr)));
+    thread::spawn(move || {
+        if let Some(stdout) = stdout {
+            capture(id, stdout);
+        }
+        if let Some(stderr_thread) = stderr_thread {
+            let _ = stderr_thread.join();
+        }
+        let status = match child.wait() {
+            Ok(exit_status) => JobStatus::Finished(exit_status.code()),
+            Err(e) => JobStatus::Failed(e.to_string()),
+        };
+        info!("job {} ended with status {:?}", id, &status);
+        with_job(id, |job| {
+            job.duration = Some(job.start.elapsed());
+            job.status = status;
+        });
+    });
+    Ok(id)
+}
+
+/// return a copy of all the jobs, the most recent first
+pub fn jobs() -> Vec<Job> {
+    JOBS.lock()
+        .map(|jobs| jobs.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
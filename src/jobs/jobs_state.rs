@@ -0,0 +1,219 @@
+use {
+    super::{jobs, Job, JobStatus},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        preview::PreviewState,
+        skin::PanelSkin,
+        temp_text::TempText,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::path::{Path, PathBuf},
+    termimad::Area,
+};
+
+/// an application state listing the jobs launched in background,
+/// the most recent first, and giving access to their output
+pub struct JobsState {
+    jobs: Vec<Job>,
+    dir: PathBuf, // the selected directory when the state was opened
+    selection_idx: usize,
+    scroll: usize,
+    page_height: usize,
+}
+
+impl JobsState {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            jobs: jobs(),
+            dir,
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+        }
+    }
+
+    fn move_selection(&mut self, dy: i32) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let max = self.jobs.len() as i32 - 1;
+        self.selection_idx = (self.selection_idx as i32 + dy).max(0).min(max) as usize;
+        self.ensure_selection_is_visible();
+    }
+
+    fn ensure_selection_is_visible(&mut self) {
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    /// write the output of the selected job in a temporary file and
+    /// open it in a new panel
+    fn open_output(&self, con: &AppContext) -> Result<AppStateCmdResult, ProgramError> {
+        let job = match self.jobs.get(self.selection_idx) {
+            Some(job) => job,
+            None => return Ok(AppStateCmdResult::DisplayError("no job".to_string())),
+        };
+        let name = format!("job-{}.txt", job.id);
+        let temp_text = TempText::write(&name, &job.output)?;
+        Ok(AppStateCmdResult::NewPanel {
+            state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        })
+    }
+}
+
+impl AppState for JobsState {
+
+    fn selected_path(&self) -> &Path {
+        &self.dir
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.dir,
+            line: 0,
+            stype: SelectionType::Directory,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        self.jobs = jobs();
+        if self.selection_idx >= self.jobs.len() {
+            self.selection_idx = 0;
+        }
+        Command::empty()
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay => self.open_output(cc.con)?,
+            Internal::refresh => {
+                self.refresh(screen, cc.con);
+                AppStateCmdResult::Keep
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        // the jobs may have progressed since the last display
+        self.jobs = jobs();
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            let idx = self.scroll + y as usize;
+            let job = match self.jobs.get(idx) {
+                Some(job) => job,
+                None => {
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                    continue;
+                }
+            };
+            let selected = idx == self.selection_idx;
+            let (glyph, mut status_style) = match &job.status {
+                JobStatus::Running => ('…', styles.dates.clone()),
+                _ if job.is_success() => ('✓', styles.char_match.clone()),
+                _ => ('✗', styles.file_error.clone()),
+            };
+            let mut text_style = styles.default.clone();
+            let mut info_style = styles.dates.clone();
+            if selected {
+                if let Some(c) = styles.selected_line.get_bg() {
+                    status_style.set_bg(c);
+                    text_style.set_bg(c);
+                    info_style.set_bg(c);
+                }
+            }
+            if con.show_selection_mark {
+                cw.queue_char(&text_style, if selected { '▶' } else { ' ' })?;
+            }
+            cw.queue_char(&status_style, glyph)?;
+            cw.queue_g_string(&info_style, format!(" #{} ", job.id))?;
+            let info = match &job.status {
+                JobStatus::Running => format!("running for {}s", job.start.elapsed().as_secs()),
+                JobStatus::Finished(Some(code)) => format!(
+                    "exit code {} after {}s",
+                    code,
+                    job.duration.map_or(0, |d| d.as_secs()),
+                ),
+                JobStatus::Finished(None) => "killed".to_string(),
+                JobStatus::Failed(e) => e.to_string(),
+            };
+            cw.queue_g_string(&info_style, format!("{:<24}", info))?;
+            cw.queue_str(&text_style, &job.command)?;
+            cw.fill(&text_style, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.jobs.is_empty() {
+            Status::from_message("No job was launched. Hit *esc* to go back")
+        } else {
+            Status::from_message(
+                "Hit *enter* to see the output of the selected job, *F5* to refresh, *esc* to go back",
+            )
+        }
+    }
+}
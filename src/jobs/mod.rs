@@ -0,0 +1,11 @@
+//! Jobs are external commands executed in background, without
+//! leaving or blocking broot. Their output is captured so that
+//! it can be read in the jobs state.
+
+mod job;
+mod jobs_state;
+
+pub use {
+    job::{jobs, launch, Job, JobStatus},
+    jobs_state::JobsState,
+};
@@ -21,12 +21,15 @@ macro_rules! const_key {
 }
 
 // we define a few constants which make it easier to check key events
+const_key!(ALT_DOWN, Down, KeyModifiers::ALT);
 const_key!(ALT_ENTER, Enter, KeyModifiers::ALT);
+const_key!(ALT_UP, Up, KeyModifiers::ALT);
 const_key!(ENTER, Enter);
 const_key!(BACKSPACE, Backspace);
 const_key!(BACK_TAB, BackTab);
 const_key!(DELETE, Delete);
 const_key!(DOWN, Down);
+const_key!(CTRL_DOWN, Down, KeyModifiers::CONTROL);
 const_key!(PAGE_DOWN, PageDown);
 const_key!(END, End);
 const_key!(ESC, Esc);
@@ -37,6 +40,7 @@ const_key!(RIGHT, Right);
 const_key!(SPACE, Char(' '));
 const_key!(TAB, Tab);
 const_key!(UP, Up);
+const_key!(CTRL_UP, Up, KeyModifiers::CONTROL);
 const_key!(PAGE_UP, PageUp);
 const_key!(F1, F(1));
 const_key!(F2, F(2));
@@ -23,10 +23,17 @@ use {
         env,
         io::{self, Write},
         path::PathBuf,
-        process::Command,
+        process::{Command, ExitStatus, Stdio},
     },
 };
 
+/// how an external program exited
+#[derive(Debug)]
+pub struct ProgramExit {
+    pub status: ExitStatus,
+    pub stderr: Option<String>, // only when it was captured
+}
+
 /// description of a possible launch of an external program
 /// A launchable can only be executed on end of life of broot.
 #[derive(Debug)]
@@ -113,7 +120,15 @@ impl Launchable {
         }
     }
 
-    pub fn execute(&self, mut w: Option<&mut W>) -> Result<(), ProgramError> {
+    /// execute the launchable.
+    ///
+    /// `inline` tells whether broot is displayed in the normal
+    /// screen buffer instead of the alternate screen
+    pub fn execute(
+        &self,
+        w: Option<&mut W>,
+        inline: bool,
+    ) -> Result<(), ProgramError> {
         match self {
             Launchable::Printer { to_print } => {
                 println!("{}", to_print);
@@ -123,47 +138,93 @@ impl Launchable {
                 let dp = DisplayableTree::out_of_app(&tree, &skin, &cols, &ext_colors, *width);
                 dp.write_on(&mut std::io::stdout())
             }
+            Launchable::Program { .. } => {
+                self.execute_program(w, inline, false)?;
+                Ok(())
+            }
+            Launchable::SystemOpen { path } => {
+                open::that(path)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// execute the launchable, which must be a program, and return
+    /// how it exited. When `capture_stderr` is true, what the program
+    /// writes on stderr is returned instead of being displayed.
+    pub fn execute_program(
+        &self,
+        mut w: Option<&mut W>,
+        inline: bool,
+        capture_stderr: bool,
+    ) -> Result<ProgramExit, ProgramError> {
+        match self {
             Launchable::Program { working_dir, exe, args } => {
+                if let Some(working_dir) = working_dir {
+                    // checked before leaving the alternate screen, as
+                    // the program can't be started
+                    if !working_dir.is_dir() {
+                        return Err(ProgramError::MissingWorkingDir {
+                            path: working_dir.to_string_lossy().to_string(),
+                        });
+                    }
+                }
                 // we restore the normal terminal in case the executable
                 // is a terminal application, and we'll switch back to
                 // broot's alternate terminal when we're back to broot
                 // (and this part of the code should be cleaned...)
+                // When broot is inline, it's the other way around: the
+                // program gets the alternate screen, which preserves the
+                // lines of broot
                 if let Some(ref mut w) = &mut w {
                     w.queue(cursor::Show).unwrap();
                     w.queue(cursor::EnableBlinking).unwrap();
-                    w.queue(LeaveAlternateScreen).unwrap();
+                    if inline {
+                        w.queue(EnterAlternateScreen).unwrap();
+                    } else {
+                        w.queue(LeaveAlternateScreen).unwrap();
+                    }
                     terminal::disable_raw_mode().unwrap();
                     w.flush().unwrap();
                 }
-                let mut old_working_dir = None;
+                let mut command = Command::new(exe);
+                command.args(args.iter());
                 if let Some(working_dir) = working_dir {
-                    old_working_dir = std::env::current_dir().ok();
-                    std::env::set_current_dir(working_dir).unwrap();
+                    command.current_dir(working_dir);
                 }
-                Command::new(&exe)
-                    .args(args.iter())
+                if capture_stderr {
+                    command.stderr(Stdio::piped());
+                }
+                let output = command
                     .spawn()
-                    .and_then(|mut p| p.wait())
+                    .and_then(|p| p.wait_with_output())
                     .map_err(|source| ProgramError::LaunchError {
                         program: exe.clone(),
                         source,
                     })?;
                 if let Some(ref mut w) = &mut w {
                     terminal::enable_raw_mode().unwrap();
-                    w.queue(EnterAlternateScreen).unwrap();
+                    if inline {
+                        w.queue(LeaveAlternateScreen).unwrap();
+                    } else {
+                        w.queue(EnterAlternateScreen).unwrap();
+                    }
                     w.queue(cursor::DisableBlinking).unwrap();
                     w.queue(cursor::Hide).unwrap();
                     w.flush().unwrap();
                 }
-                if let Some(old_working_dir) = old_working_dir {
-                    std::env::set_current_dir(old_working_dir).unwrap();
-                }
-                Ok(())
-            }
-            Launchable::SystemOpen { path } => {
-                open::that(&path)?;
-                Ok(())
+                Ok(ProgramExit {
+                    status: output.status,
+                    stderr: if capture_stderr {
+                        Some(String::from_utf8_lossy(&output.stderr).to_string())
+                    } else {
+                        None
+                    },
+                })
             }
+            _ => Err(ProgramError::InternalError {
+                details: "only programs can be executed this way".to_string(),
+            }),
         }
     }
 }
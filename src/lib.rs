@@ -18,27 +18,42 @@ mod time;
 
 #[macro_use]
 pub mod display;
+pub mod diff;
+pub mod duplicates;
 
 pub mod app;
+pub mod archive;
+pub mod bench;
 pub mod browser;
+pub mod checksum;
 pub mod clap;
 pub mod cli;
 pub mod command;
+pub mod compression;
 pub mod conf;
 pub mod content_search;
 pub mod errors;
+pub mod file_clipboard;
 pub mod file_sum;
 pub mod flag;
 pub mod git;
+pub mod handlers;
 pub mod hex;
 pub mod help;
 pub mod keys;
 pub mod image;
+pub mod jobs;
+pub mod immutable;
 pub mod launchable;
+pub mod list;
+pub mod logs;
+pub mod magic;
+pub mod mover;
 
 #[cfg(feature="client-server")]
 pub mod net;
 
+pub mod oci;
 pub mod path;
 pub mod path_anchor;
 pub mod pattern;
@@ -49,6 +64,8 @@ pub mod shell_install;
 pub mod skin;
 pub mod syntactic;
 pub mod task_sync;
+pub mod temp_text;
+pub mod trash_can;
 pub mod tree;
 pub mod tree_build;
 pub mod verb;
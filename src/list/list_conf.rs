@@ -0,0 +1,22 @@
+use {
+    regex::Regex,
+};
+
+/// the definition of a list, as read in the `[[lists]]`
+/// sections of the configuration
+#[derive(Debug, Clone)]
+pub struct ListConf {
+    /// the name given to `:open_list`
+    pub name: String,
+
+    /// the command whose output lines make the entries
+    pub command: String,
+
+    /// a regex parsing the lines, with a `path` group and
+    /// optionally `line` and `text` groups. When there's no
+    /// pattern, every line is a path
+    pub line_pattern: Option<Regex>,
+
+    /// the name of the verb executed on *enter*
+    pub enter: Option<String>,
+}
@@ -0,0 +1,332 @@
+use {
+    super::ListConf,
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        handlers,
+        pattern::InputPattern,
+        skin::PanelSkin,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+        process::Command as Process,
+    },
+    termimad::Area,
+};
+
+/// an entry of a list, built from a line of the command output
+#[derive(Debug, Clone)]
+pub struct ListEntry {
+    pub path: PathBuf,
+    pub display_path: String, // the path as written by the command
+    pub line: LineNumber,     // 0 if the command didn't give one
+    pub text: String,
+    pub stype: SelectionType,
+}
+
+impl ListEntry {
+    fn parse(raw: &str, conf: &ListConf, dir: &Path) -> Option<Self> {
+        let (display_path, line, text) = match &conf.line_pattern {
+            Some(regex) => {
+                let caps = regex.captures(raw)?;
+                (
+                    caps.name("path")?.as_str().to_string(),
+                    caps.name("line").and_then(|c| c.as_str().parse().ok()).unwrap_or(0),
+                    caps.name("text").map_or_else(String::new, |c| c.as_str().trim().to_string()),
+                )
+            }
+            None => (raw.to_string(), 0, String::new()),
+        };
+        if display_path.is_empty() {
+            return None;
+        }
+        let path = dir.join(&display_path);
+        let stype = if path.is_dir() {
+            SelectionType::Directory
+        } else {
+            SelectionType::File
+        };
+        Some(Self {
+            path,
+            display_path,
+            line,
+            text,
+            stype,
+        })
+    }
+}
+
+/// an application state displaying the entries parsed from
+/// the output of a command defined in configuration
+pub struct ListState {
+    name: String,
+    dir: PathBuf, // the directory in which the command was run
+    entries: Vec<ListEntry>,
+    filtered: Vec<usize>, // indexes of the entries matching the pattern
+    pattern: InputPattern,
+    selection_idx: usize, // index in filtered
+    scroll: usize,
+    page_height: usize,
+    enter: Option<String>,
+}
+
+impl ListState {
+    /// run the command of the list in the given directory
+    /// and build the state from its output
+    pub fn new(conf: &ListConf, dir: PathBuf) -> io::Result<ListState> {
+        let output = Process::new("sh")
+            .arg("-c")
+            .arg(&conf.command)
+            .current_dir(&dir)
+            .output()?;
+        if !output.status.success() && output.stdout.is_empty() {
+            return Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        let entries: Vec<ListEntry> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|raw| ListEntry::parse(raw, conf, &dir))
+            .collect();
+        let filtered = (0..entries.len()).collect();
+        Ok(ListState {
+            name: conf.name.clone(),
+            dir,
+            entries,
+            filtered,
+            pattern: InputPattern::none(),
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+            enter: conf.enter.clone(),
+        })
+    }
+
+    fn selected_entry(&self) -> Option<&ListEntry> {
+        self.filtered
+            .get(self.selection_idx)
+            .map(|&idx| &self.entries[idx])
+    }
+
+    fn move_selection(&mut self, dy: i32) {
+        if self.filtered.is_empty() {
+            return;
+        }
+        let max = self.filtered.len() as i32 - 1;
+        self.selection_idx = (self.selection_idx as i32 + dy).max(0).min(max) as usize;
+        self.ensure_selection_is_visible();
+    }
+
+    fn ensure_selection_is_visible(&mut self) {
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    fn apply_pattern(&mut self) {
+        let pattern = &self.pattern.pattern;
+        self.filtered = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                pattern.is_none()
+                    || pattern.score_of_string(&entry.display_path).is_some()
+                    || pattern.score_of_string(&entry.text).is_some()
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.selection_idx = 0;
+        self.scroll = 0;
+    }
+
+    /// execute the verb defined in the list conf for *enter*
+    fn execute_enter_verb(
+        &mut self,
+        w: &mut W,
+        verb_name: &str,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        match cc.con.verb_store.search_sel(verb_name, self.selection()) {
+            PrefixSearchResult::Match(_, verb) => match &verb.execution {
+                VerbExecution::Internal(internal_exec) if internal_exec.internal != Internal::open_stay => {
+                    self.on_internal(w, internal_exec, None, TriggerType::Other, cc, screen)
+                }
+                VerbExecution::External(external) => self.on_external(w, verb, external, &None, cc),
+                _ => handlers::open_file_stay(w, self.selected_path(), cc.con),
+            },
+            _ => Ok(AppStateCmdResult::verb_not_found(verb_name)),
+        }
+    }
+}
+
+impl AppState for ListState {
+
+    fn selected_path(&self) -> &Path {
+        self.selected_entry().map_or(&self.dir, |entry| &entry.path)
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        match self.selected_entry() {
+            Some(entry) => Selection {
+                path: &entry.path,
+                line: entry.line,
+                stype: entry.stype,
+                marked: &[],
+            },
+            None => Selection {
+                path: &self.dir,
+                line: 0,
+                stype: SelectionType::Directory,
+                marked: &[],
+            },
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pat: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = pat;
+        self.apply_pattern();
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.move_selection(-(self.filtered.len() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.move_selection(self.filtered.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay => match self.enter.clone() {
+                Some(verb_name) => self.execute_enter_verb(w, &verb_name, cc, screen)?,
+                None => handlers::open_file_stay(w, self.selected_path(), cc.con)?,
+            },
+            Internal::open_leave => {
+                AppStateCmdResult::from(handlers::file_opener(
+                    self.selected_path().to_path_buf(),
+                    cc.con,
+                )?)
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            let idx = self.scroll + y as usize;
+            let entry = match self.filtered.get(idx) {
+                Some(&entry_idx) => &self.entries[entry_idx],
+                None => {
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                    continue;
+                }
+            };
+            let selected = idx == self.selection_idx;
+            let mut path_style = match entry.stype {
+                SelectionType::Directory => styles.directory.clone(),
+                _ => styles.file.clone(),
+            };
+            let mut line_style = styles.dates.clone();
+            let mut text_style = styles.default.clone();
+            if selected {
+                if let Some(c) = styles.selected_line.get_bg() {
+                    path_style.set_bg(c);
+                    line_style.set_bg(c);
+                    text_style.set_bg(c);
+                }
+            }
+            if con.show_selection_mark {
+                cw.queue_char(&text_style, if selected { '▶' } else { ' ' })?;
+            }
+            cw.queue_str(&path_style, &entry.display_path)?;
+            if entry.line > 0 {
+                cw.queue_g_string(&line_style, format!(":{}", entry.line))?;
+            }
+            if !entry.text.is_empty() {
+                cw.queue_g_string(&text_style, format!("  {}", entry.text))?;
+            }
+            cw.fill(&text_style, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(format!(
+            "*{}*: {} entries. Hit *enter* to open the selection, *esc* to go back",
+            self.name,
+            self.filtered.len(),
+        ))
+    }
+}
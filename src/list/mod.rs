@@ -0,0 +1,11 @@
+//! Lists are states defined in configuration: a command is run
+//! and its output lines are parsed into entries which can be
+//! selected and on which verbs can be applied.
+
+mod list_conf;
+mod list_state;
+
+pub use {
+    list_conf::ListConf,
+    list_state::{ListEntry, ListState},
+};
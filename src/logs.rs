@@ -0,0 +1,138 @@
+//! helpers for the exploration of log directories (like /var/log),
+//! where logs are often rotated and compressed
+
+use {
+    crate::{
+        compression::{self, Compression},
+    },
+    regex::Regex,
+    std::{
+        fs,
+        io::{self, BufRead, BufReader, Write},
+        path::Path,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// tell whether the file looks like a log, maybe a rotated
+/// and compressed one (for example `syslog.2.gz`)
+pub fn is_log_file(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+    let name = if Compression::of_path(path).is_some() {
+        match name.rfind('.') {
+            Some(idx) => &name[..idx],
+            None => name,
+        }
+    } else {
+        name
+    };
+    // the rotation suffixes, like `.2` or `-20200815`, are removed
+    let name = regex!(r"([.-]\d+)+$").replace(name, "");
+    if regex!(r"\.log$").is_match(&name) {
+        return true;
+    }
+    // in a log directory, files without extension (like `syslog`
+    // or `messages`) are logs too
+    !name.contains('.')
+        && path
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name == "log" || name == "logs")
+}
+
+/// search the lines matching the regex in all the logs found
+/// in the directory (and its subdirectories), decompressing them
+/// if necessary, and write them in the output, each line
+/// prefixed with its path and line number.
+///
+/// The search ends early when the stop flag is raised.
+/// Return the number of matching lines.
+pub fn grep<W: Write>(
+    dir: &Path,
+    regex: &Regex,
+    output: &mut W,
+    stop: &AtomicBool,
+) -> io::Result<usize> {
+    let mut log_paths = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("can't read {:?} : {:?}", &dir, e);
+                continue;
+            }
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(ft) if ft.is_dir() => dirs.push(path),
+                Ok(ft) if ft.is_file() && is_log_file(&path) => log_paths.push(path),
+                _ => {}
+            }
+        }
+    }
+    log_paths.sort();
+    let mut count = 0;
+    let mut buf = Vec::new();
+    for path in &log_paths {
+        let mut reader = match compression::open(path) {
+            Ok(reader) => BufReader::new(reader),
+            Err(e) => {
+                debug!("can't open {:?} : {:?}", path, e);
+                continue;
+            }
+        };
+        let mut idx = 0;
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    debug!("can't read {:?} : {:?}", path, e); // badly compressed
+                    break;
+                }
+            }
+            idx += 1;
+            // lines which aren't valid UTF8 are searched too
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(&['\n', '\r'][..]);
+            if regex.is_match(line) {
+                writeln!(output, "{}:{}: {}", path.to_string_lossy(), idx, line)?;
+                count += 1;
+            }
+            if idx % 10_000 == 0 && stop.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+        if stop.load(Ordering::Relaxed) {
+            info!("log grepping stopped");
+            break;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod logs_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_log_files() {
+        assert!(is_log_file(Path::new("/home/me/app.log")));
+        assert!(is_log_file(Path::new("/home/me/app.log.2")));
+        assert!(is_log_file(Path::new("/home/me/app.log.3.gz")));
+        assert!(is_log_file(Path::new("/var/log/syslog")));
+        assert!(is_log_file(Path::new("/var/log/syslog.2.gz")));
+        assert!(is_log_file(Path::new("/var/log/messages-20200815")));
+        assert!(!is_log_file(Path::new("/home/me/syslog")));
+        assert!(!is_log_file(Path::new("/var/log/lastlog.db")));
+        assert!(!is_log_file(Path::new("/var/log/journal.conf")));
+    }
+}
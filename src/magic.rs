@@ -0,0 +1,110 @@
+//! detection of the type of a file from its first bytes,
+//! so that a file with a wrong extension (or none) is still
+//! previewed in the right mode.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+/// enough bytes to recognize all the known signatures (the
+/// tar one is at offset 257)
+const HEADER_LEN: usize = 262;
+
+/// the known signatures: offset, bytes, and mime type.
+/// Signatures too short to be reliable, like the "BM" of
+/// bitmaps, aren't listed as they'd catch text files
+static SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"II*\0", "image/tiff"),
+    (0, b"MM\0*", "image/tiff"),
+    (0, b"\0\0\x01\0", "image/x-icon"),
+    (8, b"WEBP", "image/webp"),
+    (8, b"WAVE", "audio/wav"),
+    (8, b"AVI ", "video/x-msvideo"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"\x1aE\xdf\xa3", "video/x-matroska"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"PK\x05\x06", "application/zip"), // empty zip
+    (257, b"ustar", "application/x-tar"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xfd7zXZ\0", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"Rar!\x1a\x07", "application/vnd.rar"),
+    (0, b"\x7fELF", "application/x-executable"),
+    (0, b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"SQLite format 3\0", "application/vnd.sqlite3"),
+];
+
+/// return the mime type of the content starting with the
+/// given bytes, if it's a known binary format
+pub fn mime_of_bytes(bytes: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(offset, signature, _)| {
+            bytes.len() >= offset + signature.len()
+                && &bytes[*offset..offset + signature.len()] == *signature
+        })
+        .map(|&(_, _, mime)| mime)
+}
+
+/// return the mime type of the file, if its first bytes
+/// are the signature of a known binary format
+pub fn mime_of_file(path: &Path) -> io::Result<Option<&'static str>> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    File::open(path)?
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut bytes)?;
+    Ok(mime_of_bytes(&bytes))
+}
+
+/// return the mime type of the file, detected from its content
+/// when possible, guessed from its extension otherwise
+pub fn mime_type(path: &Path) -> Option<String> {
+    mime_of_file(path)
+        .ok()
+        .flatten()
+        .map(str::to_string)
+        .or_else(|| mime_guess::from_path(path).first_raw().map(str::to_string))
+}
+
+/// tell whether the mime type is the one of a content which
+/// can't be read as text
+pub fn is_binary(mime: &str) -> bool {
+    !mime.starts_with("text/")
+}
+
+#[cfg(test)]
+mod magic_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_known_signatures() {
+        assert_eq!(mime_of_bytes(b"\x89PNG\r\n\x1a\n and the rest"), Some("image/png"));
+        assert_eq!(mime_of_bytes(b"%PDF-1.4"), Some("application/pdf"));
+        assert_eq!(mime_of_bytes(b"\x7fELF\x02\x01"), Some("application/x-executable"));
+        let mut tar_header = vec![0; 262];
+        tar_header[257..262].copy_from_slice(b"ustar");
+        assert_eq!(mime_of_bytes(&tar_header), Some("application/x-tar"));
+    }
+
+    #[test]
+    fn check_text_isnt_recognized() {
+        assert_eq!(mime_of_bytes(b"fn main() {}"), None);
+        assert_eq!(mime_of_bytes(b""), None);
+        assert_eq!(mime_of_bytes(b"B"), None);
+    }
+}
@@ -44,7 +44,7 @@ fn main() {
     configure_log();
     match cli::run() {
         Ok(Some(launchable)) => {
-            if let Err(e) = launchable.execute(None) {
+            if let Err(e) = launchable.execute(None, false) {
                 warn!("Failed to launch {:?}", &launchable);
                 warn!("Error: {:?}", e);
                 eprintln!("{}", e);
@@ -0,0 +1,222 @@
+use {
+    super::move_plan::free_path,
+    std::{
+        collections::HashSet,
+        fs, io,
+        path::{Path, PathBuf},
+    },
+};
+
+fn other_error<S: Into<String>>(txt: S) -> io::Error {
+    io::Error::other(txt.into())
+}
+
+/// the renaming of several files, whose new paths are written by
+/// the user in a text file, one per line, relative to a root
+#[derive(Debug, Clone)]
+pub struct BulkRename {
+    pub root: PathBuf,
+    pub renames: Vec<(PathBuf, PathBuf)>, // (source, destination), only the changed ones
+}
+
+/// return the path relative to the root, as written in the file
+/// given to the editor
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// write the file the user will edit: the paths of the sources,
+/// relative to the root, one per line.
+///
+/// Fail when a path contains a line break, as it couldn't be
+/// read back.
+pub fn write_sources(root: &Path, srcs: &[PathBuf], file: &Path) -> io::Result<()> {
+    let mut content = String::new();
+    for src in srcs {
+        let path = relative_path(root, src);
+        if path.contains(&['\n', '\r'][..]) {
+            return Err(other_error(format!(
+                "{:?} contains a line break and can't be renamed this way",
+                path,
+            )));
+        }
+        content.push_str(&path);
+        content.push('\n');
+    }
+    fs::write(file, content)
+}
+
+impl BulkRename {
+    /// read the file edited by the user and check the renames
+    /// can be done: no missing line, no collision between the new
+    /// paths or with existing files, no nested entries
+    pub fn from_edited_file(root: &Path, srcs: &[PathBuf], file: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(file)?;
+        let lines: Vec<&str> = content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.len() != srcs.len() {
+            return Err(other_error(format!(
+                "{} lines were expected, {} were found. Lines can't be added or removed",
+                srcs.len(),
+                lines.len(),
+            )));
+        }
+        let renames: Vec<(PathBuf, PathBuf)> = srcs
+            .iter()
+            .zip(lines)
+            .map(|(src, line)| (src.clone(), root.join(line)))
+            .filter(|(src, dst)| src != dst)
+            .collect();
+        let sources: HashSet<&Path> = renames.iter().map(|(src, _)| src.as_path()).collect();
+        let mut destinations = HashSet::new();
+        for (src, dst) in &renames {
+            if !destinations.insert(dst.as_path()) {
+                return Err(other_error(format!(
+                    "several entries would be renamed {:?}",
+                    relative_path(root, dst),
+                )));
+            }
+            if fs::symlink_metadata(dst).is_ok() && !sources.contains(dst.as_path()) {
+                return Err(other_error(format!(
+                    "{:?} already exists",
+                    relative_path(root, dst),
+                )));
+            }
+            if sources.iter().any(|other| other != src && src.starts_with(other)) {
+                return Err(other_error(format!(
+                    "{:?} can't be renamed with its parent",
+                    relative_path(root, src),
+                )));
+            }
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+            renames,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty()
+    }
+
+    /// do the renames, going through temporary names so that
+    /// swaps and chains of renames are possible.
+    ///
+    /// On failure, the entries already renamed are given back
+    /// their initial names.
+    pub fn apply(&self) -> io::Result<()> {
+        let mut tmp_paths = Vec::with_capacity(self.renames.len());
+        for (src, _) in &self.renames {
+            let tmp = free_path(&src.with_file_name(".broot-bulk-rename"));
+            if let Err(e) = fs::rename(src, &tmp) {
+                self.roll_back(&tmp_paths, 0);
+                return Err(e);
+            }
+            tmp_paths.push(tmp);
+        }
+        for (idx, (tmp, (_, dst))) in tmp_paths.iter().zip(&self.renames).enumerate() {
+            let renamed = dst
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::rename(tmp, dst));
+            if let Err(e) = renamed {
+                self.roll_back(&tmp_paths, idx);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// after a failure, move back to their sources the entries
+    /// which were moved to temporary paths, the `done` first ones
+    /// having already reached their destination
+    fn roll_back(&self, tmp_paths: &[PathBuf], done: usize) {
+        let entries = || tmp_paths.iter().zip(&self.renames);
+        for (tmp, (_, dst)) in entries().take(done) {
+            if let Err(e) = fs::rename(dst, tmp) {
+                warn!("can't move back {:?} : {:?}", dst, e);
+            }
+        }
+        for (tmp, (src, _)) in entries() {
+            if let Err(e) = fs::rename(tmp, src) {
+                warn!("can't move back {:?} : {:?}", tmp, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod bulk_rename_tests {
+
+    use {
+        super::*,
+        tempfile::TempDir,
+    };
+
+    fn edited(root: &Path, srcs: &[PathBuf], content: &str) -> io::Result<BulkRename> {
+        let file = root.join("edited.txt");
+        fs::write(&file, content)?;
+        BulkRename::from_edited_file(root, srcs, &file)
+    }
+
+    fn touch(root: &Path, names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| {
+                let path = root.join(name);
+                fs::write(&path, name).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_changed_lines_only() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let srcs = touch(root, &["a", "b", "c"]);
+        let bulk = edited(root, &srcs, "a\nd\nc\n").unwrap();
+        assert_eq!(bulk.renames, vec![(srcs[1].clone(), root.join("d"))]);
+        let bulk = edited(root, &srcs, "a\nb\nc\n").unwrap();
+        assert!(bulk.is_empty());
+    }
+
+    #[test]
+    fn check_rejected_edits() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let srcs = touch(root, &["a", "b"]);
+        fs::write(root.join("x"), "x").unwrap();
+        // a removed line
+        assert!(edited(root, &srcs, "a\n").is_err());
+        // two entries with the same new name
+        assert!(edited(root, &srcs, "c\nc\n").is_err());
+        // an entry given the name of a file which isn't renamed
+        assert!(edited(root, &srcs, "x\nb\n").is_err());
+    }
+
+    #[test]
+    fn check_swap() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let srcs = touch(root, &["a", "b"]);
+        let bulk = edited(root, &srcs, "b\na\n").unwrap();
+        bulk.apply().unwrap();
+        assert_eq!(fs::read_to_string(root.join("a")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(root.join("b")).unwrap(), "a");
+    }
+
+    #[test]
+    fn check_line_break_in_name() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let srcs = touch(root, &["a\nb"]);
+        assert!(write_sources(root, &srcs, &root.join("sources.txt")).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use {
+    super::{bulk_rename::relative_path, BulkRename},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        verb::*,
+    },
+    std::path::Path,
+    termimad::{Area, FmtText, TextView},
+};
+
+enum BulkRenameStep {
+    /// the renames are listed, waiting for the user's confirmation
+    Confirm,
+    Done,
+    Failed(String),
+}
+
+/// a modal state listing the renames written by the user in
+/// the editor, and doing them once confirmed
+pub struct BulkRenameState {
+    bulk: BulkRename,
+    step: BulkRenameStep,
+    scroll: i32,
+}
+
+impl BulkRenameState {
+    pub fn new(bulk: BulkRename) -> Self {
+        Self {
+            bulk,
+            step: BulkRenameStep::Confirm,
+            scroll: 0,
+        }
+    }
+
+    fn on_internal_scroll(&mut self, internal: Internal) {
+        self.scroll += match internal {
+            Internal::line_down => 1,
+            Internal::line_up => -1,
+            Internal::page_down => 10,
+            Internal::page_up => -10,
+            _ => 0,
+        };
+    }
+
+    fn markdown(&self) -> String {
+        match &self.step {
+            BulkRenameStep::Confirm => {
+                let mut md = format!(
+                    "\n{} entries will be renamed:\n\n",
+                    self.bulk.renames.len(),
+                );
+                for (src, dst) in &self.bulk.renames {
+                    md.push_str(&format!(
+                        "* `{}` → **{}**\n",
+                        relative_path(&self.bulk.root, src),
+                        relative_path(&self.bulk.root, dst),
+                    ));
+                }
+                md.push_str("\nType *y* to apply, any other key to cancel.");
+                md
+            }
+            BulkRenameStep::Done => format!("\n{} entries were renamed", self.bulk.renames.len()),
+            BulkRenameStep::Failed(e) => format!("\nThe renaming failed and was undone:\n\n`{}`", e),
+        }
+    }
+}
+
+impl AppState for BulkRenameState {
+
+    fn selected_path(&self) -> &Path {
+        &self.bulk.root
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.bulk.root,
+            line: 0,
+            stype: SelectionType::Directory,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            BulkRenameStep::Confirm => match cc.cmd {
+                Command::PatternEdit { raw, .. } if raw == "y" || raw == "Y" => {
+                    self.step = match self.bulk.apply() {
+                        Ok(()) => BulkRenameStep::Done,
+                        Err(e) => BulkRenameStep::Failed(e.to_string()),
+                    };
+                    self.scroll = 0;
+                    AppStateCmdResult::Keep
+                }
+                cmd => match scroll_internal(cmd, cc.con) {
+                    Some(internal) => {
+                        self.on_internal_scroll(internal);
+                        AppStateCmdResult::Keep
+                    }
+                    None => AppStateCmdResult::PopState, // any other key cancels
+                },
+            },
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(if is_scroll(internal_exec.internal) {
+            self.on_internal_scroll(internal_exec.internal);
+            AppStateCmdResult::Keep
+        } else {
+            match self.step {
+                BulkRenameStep::Confirm => AppStateCmdResult::PopState,
+                _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+            }
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let mut text_view = TextView::from(&text_area, &fmt_text);
+        self.scroll = text_view.set_scroll(self.scroll);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            BulkRenameStep::Confirm => format!(
+                "Rename {} entries ? *y*/*N*",
+                self.bulk.renames.len(),
+            ),
+            _ => "Hit any key to go back".to_string(),
+        })
+    }
+}
+
+fn is_scroll(internal: Internal) -> bool {
+    matches!(
+        internal,
+        Internal::line_down | Internal::line_up | Internal::page_down | Internal::page_up
+    )
+}
+
+/// return the scrolling internal the command is, directly
+/// or by a key, if any
+fn scroll_internal(cmd: &Command, con: &AppContext) -> Option<Internal> {
+    let internal = match cmd {
+        Command::Internal { internal, .. } => *internal,
+        Command::VerbTrigger { index, .. } => match &con.verb_store.verbs[*index].execution {
+            VerbExecution::Internal(internal_exec) => internal_exec.internal,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if is_scroll(internal) {
+        Some(internal)
+    } else {
+        None
+    }
+}
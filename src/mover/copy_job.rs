@@ -0,0 +1,219 @@
+use {
+    super::move_plan::remove_path,
+    std::{
+        fs::{self, File},
+        io::{self, Read, Write},
+        path::{Path, PathBuf},
+    },
+};
+
+/// size of the parts in which files are copied, so that the copy
+/// of a big file can be followed and interrupted
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// the copy of a file tree, done one entry at a time so that the
+/// progress can be displayed and the copy interrupted
+#[derive(Debug)]
+pub struct CopyJob {
+    src: PathBuf,
+    dst: PathBuf,
+    remove_source: bool, // true when the copy is part of a move
+    entries: Vec<(PathBuf, PathBuf)>, // (source, destination), parents first
+    special_files: Vec<PathBuf>, // fifos, sockets and devices, which aren't copied
+    done: usize,
+    current_file: Option<(File, File)>, // the file being copied, if any
+    buffer: Vec<u8>, // allocated on the first file chunk, then reused
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// list the entries to copy, and the special files which can't be
+/// (reading a fifo would block until something writes in it)
+fn list_entries(
+    src: &Path,
+    dst: &Path,
+    entries: &mut Vec<(PathBuf, PathBuf)>,
+    special_files: &mut Vec<PathBuf>,
+    bytes_total: &mut u64,
+) -> io::Result<()> {
+    let md = fs::symlink_metadata(src)?;
+    let file_type = md.file_type();
+    if !file_type.is_dir() && !file_type.is_file() && !file_type.is_symlink() {
+        special_files.push(src.to_path_buf());
+        return Ok(());
+    }
+    entries.push((src.to_path_buf(), dst.to_path_buf()));
+    if file_type.is_dir() {
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            list_entries(
+                &entry.path(),
+                &dst.join(entry.file_name()),
+                entries,
+                special_files,
+                bytes_total,
+            )?;
+        }
+    } else if file_type.is_file() {
+        *bytes_total += md.len();
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::copy(src, dst).map(|_| ())
+}
+
+impl CopyJob {
+    pub fn new(src: &Path, dst: &Path, remove_source: bool) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        let mut special_files = Vec::new();
+        let mut bytes_total = 0;
+        list_entries(src, dst, &mut entries, &mut special_files, &mut bytes_total)?;
+        Ok(Self {
+            src: src.to_path_buf(),
+            dst: dst.to_path_buf(),
+            remove_source,
+            entries,
+            special_files,
+            done: 0,
+            current_file: None,
+            buffer: Vec::new(),
+            bytes_done: 0,
+            bytes_total,
+        })
+    }
+
+    /// the fifos, sockets and devices found in the source, which
+    /// aren't copied
+    pub fn special_files(&self) -> &[PathBuf] {
+        &self.special_files
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done >= self.entries.len()
+    }
+
+    /// return the number of copied entries and the total number
+    pub fn progress(&self) -> (usize, usize) {
+        (self.done, self.entries.len())
+    }
+
+    /// return the part of the job already done, between 0 and 1,
+    /// counted in bytes when there are some
+    pub fn ratio(&self) -> f64 {
+        if self.bytes_total > 0 {
+            self.bytes_done as f64 / self.bytes_total as f64
+        } else if !self.entries.is_empty() {
+            self.done as f64 / self.entries.len() as f64
+        } else {
+            1.0
+        }
+    }
+
+    /// copy the next entry, or the next chunk of the file
+    /// being copied
+    pub fn step(&mut self) -> io::Result<()> {
+        if let Some((reader, writer)) = &mut self.current_file {
+            if self.buffer.is_empty() {
+                self.buffer = vec![0; CHUNK_SIZE];
+            }
+            let n = reader.read(&mut self.buffer)?;
+            if n > 0 {
+                writer.write_all(&self.buffer[..n])?;
+                self.bytes_done += n as u64;
+                return Ok(());
+            }
+            // the file is completely copied
+            self.current_file = None;
+            let (src, dst) = &self.entries[self.done];
+            fs::set_permissions(dst, fs::metadata(src)?.permissions())?;
+            self.done += 1;
+            return Ok(());
+        }
+        let (src, dst) = match self.entries.get(self.done) {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+        let md = fs::symlink_metadata(src)?;
+        let file_type = md.file_type();
+        if file_type.is_dir() {
+            fs::create_dir(dst)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(src, dst)?;
+        } else if !file_type.is_file() {
+            // the file was replaced with a special one since the listing
+            self.special_files.push(src.clone());
+        } else {
+            let reader = File::open(src)?;
+            let writer = fs::OpenOptions::new().write(true).create_new(true).open(dst)?;
+            self.current_file = Some((reader, writer));
+            return Ok(());
+        }
+        self.done += 1;
+        Ok(())
+    }
+
+    /// once all entries are copied, give the directories their
+    /// permissions (they may be read-only) and remove the source
+    /// if it's a move, unless it holds special files which weren't
+    /// copied
+    pub fn finish(&self) -> io::Result<()> {
+        for (src, dst) in self.entries.iter().rev() {
+            let md = fs::symlink_metadata(src)?;
+            if md.is_dir() {
+                fs::set_permissions(dst, md.permissions())?;
+            }
+        }
+        if self.remove_source && self.special_files.is_empty() {
+            remove_path(&self.src)?;
+        }
+        Ok(())
+    }
+
+    /// remove what was copied, leaving the source untouched
+    pub fn cancel(&mut self) -> io::Result<()> {
+        let started = self.done > 0 || self.current_file.is_some();
+        self.current_file = None; // closing the files before removing them
+        if started {
+            remove_path(&self.dst)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod copy_job_tests {
+
+    use {
+        super::*,
+        std::os::unix::net::UnixListener,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn check_special_files_are_skipped() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        let socket = src.join("socket");
+        let _listener = UnixListener::bind(&socket).unwrap();
+        let dst = dir.path().join("dst");
+        let mut job = CopyJob::new(&src, &dst, true).unwrap();
+        assert_eq!(job.special_files(), &[socket]);
+        while !job.is_finished() {
+            job.step().unwrap();
+        }
+        job.finish().unwrap();
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "a");
+        assert!(!dst.join("socket").exists());
+        // the source of the move is kept, as the socket wasn't moved
+        assert!(src.join("a.txt").exists());
+    }
+}
@@ -0,0 +1,59 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// the kind of a link to create
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkKind {
+    Symbolic,
+    Hard,
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// create a link to src at dst. When dst is an existing directory,
+/// the link is created inside it, with the name of src.
+/// Return the path of the link.
+pub fn create_link(src: &Path, dst: &Path, kind: LinkKind) -> io::Result<PathBuf> {
+    let mut dst = dst.to_path_buf();
+    if dst.is_dir() {
+        if let Some(name) = src.file_name() {
+            dst = dst.join(name);
+        }
+    }
+    if fs::symlink_metadata(&dst).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{:?} already exists", dst),
+        ));
+    }
+    match kind {
+        LinkKind::Symbolic => {
+            // the target is made absolute so that the link works
+            // wherever it's created
+            let target = src.canonicalize()?;
+            symlink(&target, &dst)?;
+        }
+        LinkKind::Hard => {
+            if src.is_dir() {
+                return Err(io::Error::other(
+                    "a directory can't be hard linked",
+                ));
+            }
+            fs::hard_link(src, &dst)?;
+        }
+    }
+    Ok(dst)
+}
@@ -0,0 +1,29 @@
+//! Moves and renames done by broot itself rather than by `mv`, so
+//! that the problematic cases (destination overwrite, case-only
+//! rename on a case-insensitive filesystem, move to another device)
+//! are detected and handled with the user's consent. Several files
+//! can also be renamed at once by editing their names in `$EDITOR`,
+//! links can be created, files can be overwritten before their
+//! deletion, and their times can be set.
+
+mod bulk_rename;
+mod bulk_rename_state;
+mod copy_job;
+mod link;
+mod move_plan;
+mod move_state;
+mod shred;
+mod shred_state;
+mod times;
+
+pub use {
+    bulk_rename::{write_sources, BulkRename},
+    bulk_rename_state::BulkRenameState,
+    copy_job::CopyJob,
+    link::{create_link, LinkKind},
+    move_plan::{free_path, remove_path, MoveKind, MovePlan},
+    move_state::MoveState,
+    shred::parse_secure_option,
+    shred_state::ShredState,
+    times::{now, parse_time, set_times},
+};
@@ -0,0 +1,313 @@
+use {
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// how a move must be done
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveKind {
+    /// a simple rename, the destination being on the same device
+    Rename,
+
+    /// the destination is the same file under another name (usually
+    /// a name differing only by the case, on a case-insensitive
+    /// filesystem), so a direct rename would do nothing: we rename
+    /// through a temporary name
+    CaseOnlyRename,
+
+    /// the destination is on another device: the files must be
+    /// copied then the source removed
+    CrossDevice,
+
+    /// not a move but a copy: the source is kept
+    Copy,
+}
+
+/// the analysis of a move or a copy, done before any modification
+#[derive(Debug, Clone)]
+pub struct MovePlan {
+    pub src: PathBuf,
+    pub dst: PathBuf,
+    pub kind: MoveKind,
+    replaced: Option<PathBuf>, // where the overwritten destination was set aside
+}
+
+fn other_error<S: Into<String>>(txt: S) -> io::Error {
+    io::Error::other(txt.into())
+}
+
+/// tell whether both paths lead to the same file (without following
+/// the last link)
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+#[cfg(not(unix))]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    b.exists() && a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+/// tell whether the destination would be on another device
+#[cfg(unix)]
+fn is_on_other_device(src: &Path, dst: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let src_dev = match fs::symlink_metadata(src) {
+        Ok(md) => md.dev(),
+        Err(_) => return false,
+    };
+    // the destination doesn't exist yet, we look for its closest ancestor
+    let mut ancestor = dst.parent();
+    while let Some(dir) = ancestor {
+        if let Ok(md) = fs::metadata(dir) {
+            return md.dev() != src_dev;
+        }
+        ancestor = dir.parent();
+    }
+    false
+}
+#[cfg(not(unix))]
+fn is_on_other_device(_src: &Path, _dst: &Path) -> bool {
+    false
+}
+
+/// tell whether the error is the one returned by a rename to
+/// another device (which may happen even when the device ids
+/// are the same, for example with bind mounts)
+pub fn is_cross_device_error(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        e.raw_os_error() == Some(libc::EXDEV)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+/// remove a file or a directory with its content
+pub fn remove_path(path: &Path) -> io::Result<()> {
+    let md = fs::symlink_metadata(path)?;
+    if md.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// return the first path which doesn't exist, among the given one
+/// and the ones made by adding a number to its stem, like
+/// `report (2).txt`
+pub fn free_path(path: &Path) -> PathBuf {
+    if fs::symlink_metadata(path).is_err() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map_or_else(String::new, |s| s.to_string_lossy().to_string());
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+    let mut i = 2;
+    loop {
+        let name = match &extension {
+            Some(extension) => format!("{} ({}).{}", stem, i, extension),
+            None => format!("{} ({})", stem, i),
+        };
+        let candidate = path.with_file_name(name);
+        if fs::symlink_metadata(&candidate).is_err() {
+            return candidate;
+        }
+        i += 1;
+    }
+}
+
+impl MovePlan {
+    /// analyze the move of src to dst. When dst is an existing
+    /// directory, the source is moved inside it, like with `mv`.
+    pub fn new(src: &Path, dst: &Path) -> io::Result<Self> {
+        let mut dst = dst.to_path_buf();
+        if dst.is_dir() && !is_same_file(src, &dst) {
+            if let Some(name) = src.file_name() {
+                dst = dst.join(name);
+            }
+        }
+        Self::exact(src, dst)
+    }
+
+    /// analyze the copy of src to dst, with the same rules than
+    /// a move regarding existing directories
+    pub fn copy(src: &Path, dst: &Path) -> io::Result<Self> {
+        let mut plan = Self::new(src, dst)?;
+        if plan.kind == MoveKind::CaseOnlyRename {
+            return Err(other_error("the source and the destination are the same"));
+        }
+        plan.kind = MoveKind::Copy;
+        Ok(plan)
+    }
+
+    /// tell whether the source is kept
+    pub fn is_copy(&self) -> bool {
+        self.kind == MoveKind::Copy
+    }
+
+    /// analyze the move of src to dst, dst being the new path of
+    /// the file even when it's an existing directory (which is
+    /// what's expected for a rename)
+    pub fn exact(src: &Path, dst: PathBuf) -> io::Result<Self> {
+        let src_md = fs::symlink_metadata(src)?;
+        if src == dst {
+            return Err(other_error("the source and the destination are the same"));
+        }
+        if src_md.is_dir() && dst.starts_with(src) {
+            return Err(other_error("a directory can't be moved inside itself"));
+        }
+        let kind = if is_same_file(src, &dst) {
+            MoveKind::CaseOnlyRename
+        } else if is_on_other_device(src, &dst) {
+            MoveKind::CrossDevice
+        } else {
+            MoveKind::Rename
+        };
+        Ok(Self {
+            src: src.to_path_buf(),
+            dst,
+            kind,
+            replaced: None,
+        })
+    }
+
+    /// whether the move would replace an existing file
+    pub fn overwrites(&self) -> bool {
+        self.kind != MoveKind::CaseOnlyRename && fs::symlink_metadata(&self.dst).is_ok()
+    }
+
+    /// change the destination for a path which doesn't exist yet
+    pub fn keep_both(&mut self) {
+        self.dst = free_path(&self.dst);
+    }
+
+    /// rename the file or directory which would be replaced, so
+    /// that it can be put back if the transfer fails. It must be
+    /// removed with forget_replaced once the transfer is done.
+    pub fn set_aside_destination(&mut self) -> io::Result<()> {
+        let name = self.dst.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string());
+        let tmp = free_path(&self.dst.with_file_name(format!(".broot-replaced-{}", name)));
+        fs::rename(&self.dst, &tmp)?;
+        self.replaced = Some(tmp);
+        Ok(())
+    }
+
+    /// remove the destination which was set aside, now that the
+    /// transfer succeeded
+    pub fn forget_replaced(&mut self) -> io::Result<()> {
+        match self.replaced.take() {
+            Some(replaced) => remove_path(&replaced).map_err(|e| {
+                other_error(format!("{:?} couldn't be removed: {}", replaced, e))
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// put back the destination which was set aside, after a failed
+    /// or cancelled transfer. If the transfer left a file at the
+    /// destination, the old one is kept under its temporary name.
+    pub fn restore_replaced(&mut self) -> io::Result<()> {
+        match self.replaced.take() {
+            Some(replaced) if fs::symlink_metadata(&self.dst).is_ok() => Err(other_error(
+                format!("the replaced file was kept as {:?}", replaced),
+            )),
+            Some(replaced) => fs::rename(&replaced, &self.dst),
+            None => Ok(()),
+        }
+    }
+
+    /// do the move when it's neither a cross-device one nor a copy. The
+    /// destination, if any, must have been set aside before.
+    pub fn rename(&self) -> io::Result<()> {
+        match self.kind {
+            MoveKind::CaseOnlyRename => {
+                let tmp = free_path(&self.src.with_file_name(".broot-rename"));
+                fs::rename(&self.src, &tmp)?;
+                fs::rename(&tmp, &self.dst)
+            }
+            _ => fs::rename(&self.src, &self.dst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod move_plan_tests {
+
+    use {
+        super::*,
+        tempfile::TempDir,
+    };
+
+    fn write(path: &Path, content: &str) {
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn check_plans() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        let sub = dir.path().join("sub");
+        write(&file, "a");
+        fs::create_dir(&sub).unwrap();
+        // moving into an existing directory keeps the name
+        let plan = MovePlan::new(&file, &sub).unwrap();
+        assert_eq!(plan.dst, sub.join("a.txt"));
+        assert_eq!(plan.kind, MoveKind::Rename);
+        assert!(!plan.overwrites());
+        let plan = MovePlan::copy(&file, &sub).unwrap();
+        assert!(plan.is_copy());
+        // but a rename gives the exact destination
+        let plan = MovePlan::exact(&file, sub.clone()).unwrap();
+        assert_eq!(plan.dst, sub);
+        assert!(plan.overwrites());
+        assert!(MovePlan::exact(&file, file.clone()).is_err());
+        assert!(MovePlan::new(&sub, &sub.join("deeper")).is_err());
+        assert!(MovePlan::new(&dir.path().join("missing"), &sub).is_err());
+    }
+
+    #[test]
+    fn check_free_path() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("report.txt");
+        assert_eq!(free_path(&file), file);
+        write(&file, "1");
+        write(&dir.path().join("report (2).txt"), "2");
+        assert_eq!(free_path(&file), dir.path().join("report (3).txt"));
+        let mut plan = MovePlan::exact(&dir.path().join("report (2).txt"), file.clone()).unwrap();
+        plan.keep_both();
+        assert_eq!(plan.dst, dir.path().join("report (3).txt"));
+    }
+
+    #[test]
+    fn check_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("new.txt");
+        let dst = dir.path().join("old.txt");
+        // a failed transfer puts back the replaced file
+        write(&src, "new");
+        write(&dst, "old");
+        let mut plan = MovePlan::exact(&src, dst.clone()).unwrap();
+        plan.set_aside_destination().unwrap();
+        assert!(!dst.exists());
+        plan.restore_replaced().unwrap();
+        assert_eq!(read(&dst), "old");
+        // a successful one removes it
+        plan.set_aside_destination().unwrap();
+        plan.rename().unwrap();
+        plan.forget_replaced().unwrap();
+        assert_eq!(read(&dst), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
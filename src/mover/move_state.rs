@@ -0,0 +1,514 @@
+use {
+    super::{CopyJob, MoveKind, MovePlan},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{progress_bar, Screen, W},
+        errors::ProgramError,
+        file_clipboard::FileClipboard,
+        skin::PanelSkin,
+        task_sync::Dam,
+        verb::*,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    },
+    termimad::{Area, FmtText, TextView},
+};
+
+/// how long the copy goes on before the progress is displayed again
+const DISPLAY_PERIOD: Duration = Duration::from_millis(200);
+
+enum MoveStep {
+    /// the marked entries exceed the bulk limits, we wait for
+    /// the user to confirm before starting
+    AskBulk(BulkSummary),
+    /// the destination exists, we wait for the user to choose
+    /// between overwriting it, skipping the transfer, keeping both,
+    /// or cancelling
+    AskOverwrite,
+    /// files are being copied to another device
+    Copying(CopyJob),
+    Done,
+    Failed(String),
+}
+
+/// what to do when the destination of a transfer exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CollisionChoice {
+    Overwrite,
+    Skip,
+    KeepBoth,
+}
+
+/// a modal state handling the moves which can't be done with a
+/// simple rename: the ones needing a confirmation and the
+/// cross-device ones, whose progress is displayed. It also
+/// handles copies, and several transfers done in sequence
+/// (when entries are marked).
+pub struct MoveState {
+    plan: MovePlan,
+    next_plans: Vec<MovePlan>, // the transfers to do after this one, last first
+    done_count: usize, // number of finished transfers, before the current one
+    skipped_count: usize, // number of transfers skipped on a collision
+    special_files: Vec<PathBuf>, // fifos, sockets and devices which weren't copied
+    collision_choice: Option<CollisionChoice>, // the choice made for all collisions, if any
+    clear_file_clipboard: bool, // whether the transfers are a paste of cut files
+    step: MoveStep,
+}
+
+impl MoveState {
+    /// return the state to push when the move needs the user's
+    /// attention, or do the move and return None
+    pub fn new(plan: MovePlan) -> io::Result<Option<Self>> {
+        Self::for_plans(vec![plan])
+    }
+
+    /// do the transfers one after the other, returning the state
+    /// to push as soon as one of them needs the user's attention,
+    /// or None when all were done
+    pub fn for_plans(plans: Vec<MovePlan>) -> io::Result<Option<Self>> {
+        let mut state = match Self::waiting(plans, MoveStep::Done) {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+        state.begin()?;
+        Ok(match state.step {
+            MoveStep::Done => None,
+            _ => Some(state),
+        })
+    }
+
+    /// return the state asking the user to confirm the transfers
+    /// of too many entries, which are started only after
+    pub fn confirming(plans: Vec<MovePlan>, bulk: BulkSummary) -> Option<Self> {
+        Self::waiting(plans, MoveStep::AskBulk(bulk))
+    }
+
+    fn waiting(mut plans: Vec<MovePlan>, step: MoveStep) -> Option<Self> {
+        plans.reverse();
+        plans.pop().map(|plan| Self {
+            plan,
+            next_plans: plans,
+            done_count: 0,
+            skipped_count: 0,
+            special_files: Vec::new(),
+            collision_choice: None,
+            clear_file_clipboard: false,
+            step,
+        })
+    }
+
+    /// make the state empty the file clipboard once all transfers
+    /// are done, the cut files not being at their old path anymore
+    pub fn clearing_file_clipboard(mut self) -> Self {
+        self.clear_file_clipboard = true;
+        self
+    }
+
+    /// called when there's no transfer left to start
+    fn on_last_plan(&mut self) {
+        if let MoveStep::Done = self.step {
+            if self.clear_file_clipboard {
+                FileClipboard::clear();
+            }
+        }
+    }
+
+    /// start the current transfer, and the next ones while they're
+    /// done immediately, stopping on the first one which needs the
+    /// user's attention
+    fn begin(&mut self) -> io::Result<()> {
+        loop {
+            if self.plan.overwrites() {
+                match self.collision_choice {
+                    Some(choice) => self.resolve(choice)?,
+                    None => {
+                        self.step = MoveStep::AskOverwrite;
+                        return Ok(());
+                    }
+                }
+            } else {
+                self.start()?;
+            }
+            if !self.next_plan() {
+                self.on_last_plan();
+                return Ok(());
+            }
+        }
+    }
+
+    /// handle the collision of the current transfer according
+    /// to the choice, then start it unless it's skipped
+    fn resolve(&mut self, choice: CollisionChoice) -> io::Result<()> {
+        match choice {
+            CollisionChoice::Overwrite => {
+                self.plan.set_aside_destination()?;
+            }
+            CollisionChoice::KeepBoth => {
+                self.plan.keep_both();
+            }
+            CollisionChoice::Skip => {
+                self.skipped_count += 1;
+                self.step = MoveStep::Done;
+                return Ok(());
+            }
+        }
+        self.start()
+    }
+
+    /// if the current transfer is done and there's another one
+    /// to do, make it the current one and return true
+    fn next_plan(&mut self) -> bool {
+        if let MoveStep::Done = self.step {
+            if let Some(plan) = self.next_plans.pop() {
+                self.plan = plan;
+                self.done_count += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// go on with the next transfers, if any, once the
+    /// current one is done
+    fn go_on(&mut self) {
+        if self.next_plan() {
+            if let Err(e) = self.begin() {
+                self.fail(e);
+            }
+        } else {
+            self.on_last_plan();
+        }
+    }
+
+    /// stop on the failure of the current transfer, putting back
+    /// the destination it was to replace
+    fn fail(&mut self, e: io::Error) {
+        let mut message = e.to_string();
+        if let Err(e) = self.plan.restore_replaced() {
+            message = format!("{}\n\n{}", message, e);
+        }
+        self.step = MoveStep::Failed(message);
+    }
+
+    /// do the move, or start the copy when it can't be a rename
+    fn start(&mut self) -> io::Result<()> {
+        if self.plan.kind != MoveKind::CrossDevice && !self.plan.is_copy() {
+            match self.plan.rename() {
+                Err(e) if super::move_plan::is_cross_device_error(&e) => {
+                    self.plan.kind = MoveKind::CrossDevice;
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+                Ok(()) => {
+                    self.step = MoveStep::Done;
+                    return self.plan.forget_replaced();
+                }
+            }
+        }
+        let remove_source = !self.plan.is_copy();
+        self.step = MoveStep::Copying(CopyJob::new(&self.plan.src, &self.plan.dst, remove_source)?);
+        Ok(())
+    }
+
+    /// start the transfers the user confirmed
+    fn on_bulk_confirmed(&mut self) -> AppStateCmdResult {
+        match self.begin() {
+            Ok(()) => match self.step {
+                MoveStep::Done => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+                _ => AppStateCmdResult::Keep,
+            },
+            Err(e) => {
+                self.fail(e);
+                AppStateCmdResult::Keep
+            }
+        }
+    }
+
+    /// handle the key typed by the user on a collision: lowercase
+    /// for this transfer, uppercase for all the remaining ones
+    fn on_choice(&mut self, key: &str) -> AppStateCmdResult {
+        let choice = match key {
+            "o" | "O" => CollisionChoice::Overwrite,
+            "s" | "S" => CollisionChoice::Skip,
+            "k" | "K" => CollisionChoice::KeepBoth,
+            _ => {
+                return AppStateCmdResult::PopStateAndRefresh { clear_cache: false };
+            }
+        };
+        if key.chars().all(char::is_uppercase) {
+            self.collision_choice = Some(choice);
+        }
+        match self.resolve(choice) {
+            Ok(()) => {
+                self.go_on();
+                match self.step {
+                    MoveStep::Done => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+                    _ => AppStateCmdResult::Keep,
+                }
+            }
+            Err(e) => {
+                self.fail(e);
+                AppStateCmdResult::Keep
+            }
+        }
+    }
+
+    /// the participle describing the transfer
+    fn done_verb(&self) -> &'static str {
+        if self.plan.is_copy() {
+            "copied"
+        } else {
+            "moved"
+        }
+    }
+
+    /// the list of the special files which weren't copied, if any
+    fn special_files_markdown(&self) -> String {
+        if self.special_files.is_empty() {
+            return String::new();
+        }
+        let mut md = format!(
+            "\n\n{} special files (fifos, sockets or devices) weren't copied{}:\n",
+            self.special_files.len(),
+            if self.plan.is_copy() { "" } else { ", so their sources weren't removed" },
+        );
+        for path in &self.special_files {
+            md.push_str(&format!("\n* {}", path.to_string_lossy()));
+        }
+        md
+    }
+
+    fn markdown(&self) -> String {
+        let src = self.plan.src.to_string_lossy();
+        let dst = self.plan.dst.to_string_lossy();
+        let total_count = self.done_count + 1 + self.next_plans.len();
+        let count_info = if total_count > 1 {
+            format!("\n\nEntry {} / {}", self.done_count + 1, total_count)
+        } else {
+            String::new()
+        };
+        match &self.step {
+            MoveStep::AskBulk(bulk) => format!(
+                "\n{} will be {} to **{}**.\n\n\
+                Type *y* to confirm, any other key to cancel.",
+                bulk,
+                self.done_verb(),
+                self.plan.dst.parent().unwrap_or(self.plan.dst.as_path()).to_string_lossy(),
+            ),
+            MoveStep::AskOverwrite => format!(
+                "\n**{}** already exists.\n\n\
+                * type *o* to overwrite it\n\
+                * type *s* to skip this entry\n\
+                * type *k* to keep both, writing **{}**\n\
+                * type any other key to cancel{}{}",
+                dst,
+                super::move_plan::free_path(&self.plan.dst).to_string_lossy(),
+                if self.next_plans.is_empty() {
+                    ""
+                } else {
+                    "\n\nType *O*, *S* or *K* to do the same for all the following collisions."
+                },
+                count_info,
+            ),
+            MoveStep::Copying(job) => {
+                let (done, total) = job.progress();
+                let intro = if self.plan.is_copy() {
+                    format!("**{}** is copied to **{}**.", src, dst)
+                } else {
+                    format!(
+                        "**{}** is on another device than **{}**: files are copied then removed.",
+                        dst,
+                        src,
+                    )
+                };
+                format!(
+                    "\n{}\n\n\
+                    Copied {} / {} files ({} / {}){}\n\n\
+                    Hit *esc* to cancel",
+                    intro,
+                    done,
+                    total,
+                    file_size::fit_4(job.bytes_done),
+                    file_size::fit_4(job.bytes_total),
+                    count_info,
+                )
+            }
+            MoveStep::Done if total_count > 1 && self.skipped_count > 0 => format!(
+                "\n{} entries were {}, {} skipped{}",
+                total_count - self.skipped_count,
+                self.done_verb(),
+                self.skipped_count,
+                self.special_files_markdown(),
+            ),
+            MoveStep::Done if total_count > 1 => format!(
+                "\n{} entries were {}{}",
+                total_count,
+                self.done_verb(),
+                self.special_files_markdown(),
+            ),
+            MoveStep::Done => format!(
+                "\n**{}** was {} to **{}**{}",
+                src,
+                self.done_verb(),
+                dst,
+                self.special_files_markdown(),
+            ),
+            MoveStep::Failed(e) => format!("\nThe transfer of **{}** failed:\n\n`{}`", src, e),
+        }
+    }
+}
+
+impl AppState for MoveState {
+
+    fn selected_path(&self) -> &Path {
+        &self.plan.src
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.plan.src,
+            line: 0,
+            stype: SelectionType::Any,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            MoveStep::AskBulk(_) => match cc.cmd {
+                Command::PatternEdit { raw, .. } if raw == "y" || raw == "Y" => {
+                    self.on_bulk_confirmed()
+                }
+                _ => AppStateCmdResult::PopState, // any other key cancels
+            },
+            MoveStep::AskOverwrite => match cc.cmd {
+                Command::PatternEdit { raw, .. } => self.on_choice(raw),
+                _ => AppStateCmdResult::Keep,
+            },
+            MoveStep::Copying(_) => AppStateCmdResult::Keep, // only esc cancels
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        if let MoveStep::Copying(job) = &mut self.step {
+            if internal_exec.internal != Internal::back {
+                return Ok(AppStateCmdResult::Keep);
+            }
+            job.cancel()?;
+            self.plan.restore_replaced()?;
+        }
+        Ok(AppStateCmdResult::PopStateAndRefresh { clear_cache: true })
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        match &self.step {
+            MoveStep::Copying(_) => Some("copying"),
+            _ => None,
+        }
+    }
+
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        // we copy until the user does something (for example hitting
+        // esc to cancel) or it's time to display the progress
+        let start = Instant::now();
+        while let MoveStep::Copying(job) = &mut self.step {
+            let res = if job.is_finished() {
+                job.finish().map(|_| true)
+            } else {
+                job.step().map(|_| false)
+            };
+            match res {
+                Ok(true) => {
+                    self.special_files.extend(job.special_files().iter().cloned());
+                    self.step = MoveStep::Done;
+                    match self.plan.forget_replaced() {
+                        Ok(()) => self.go_on(),
+                        Err(e) => self.fail(e),
+                    }
+                }
+                Ok(false) => {} // there are other entries to copy
+                Err(e) => {
+                    warn!("copy failed: {:?}", e);
+                    self.fail(e);
+                }
+            }
+            if dam.has_event() || start.elapsed() > DISPLAY_PERIOD {
+                break;
+            }
+        }
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            MoveStep::AskBulk(bulk) => format!(
+                "Transfer {} entries ? *y*/*N*",
+                bulk.count,
+            ),
+            MoveStep::AskOverwrite => format!(
+                "Overwrite `{}` ? *o*/*s*/*k*/*N*",
+                self.plan.dst.to_string_lossy(),
+            ),
+            MoveStep::Copying(job) => format!(
+                "{} `{}` {:>3}% hit *esc* to cancel",
+                if self.plan.is_copy() { "Copying" } else { "Moving" },
+                progress_bar(job.ratio()),
+                (job.ratio() * 100.0) as usize,
+            ),
+            MoveStep::Done | MoveStep::Failed(_) => "Hit any key to go back".to_string(),
+        })
+    }
+}
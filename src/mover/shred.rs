@@ -0,0 +1,138 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// a xorshift generator of the bytes overwriting the files. They
+/// don't need to be cryptographically random, only to replace
+/// the content
+struct Noise(u64);
+
+impl Noise {
+    fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self(seed | 1) // the state must never be 0
+    }
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// parse the argument of `:rm`, which, when given, must be `secure`,
+/// optionally followed by the number of passes
+pub fn parse_secure_option(arg: &str, default_passes: usize) -> Result<usize, &'static str> {
+    let mut tokens = arg.split_whitespace();
+    if tokens.next() != Some("secure") {
+        return Err("the only option of :rm is secure, optionally followed by a number of passes");
+    }
+    let passes = match tokens.next() {
+        Some(token) => token.parse::<usize>().ok().filter(|&n| n > 0)
+            .ok_or("the number of passes must be a positive integer")?,
+        None => default_passes,
+    };
+    if tokens.next().is_some() {
+        return Err("too many arguments for :rm");
+    }
+    Ok(passes)
+}
+
+/// count the files which would be overwritten by shred_path, and
+/// their total size
+pub fn shred_summary(path: &Path) -> io::Result<(usize, u64)> {
+    let md = fs::symlink_metadata(path)?;
+    if md.is_dir() {
+        let mut summary = (0, 0);
+        for entry in fs::read_dir(path)? {
+            let (count, size) = shred_summary(&entry?.path())?;
+            summary.0 += count;
+            summary.1 += size;
+        }
+        Ok(summary)
+    } else if md.is_file() {
+        Ok((1, md.len()))
+    } else {
+        Ok((0, 0))
+    }
+}
+
+/// overwrite the content of the file, each pass being synced to
+/// the disk, then rename and delete it
+fn shred_file(path: &Path, passes: usize, noise: &mut Noise) -> io::Result<()> {
+    let len = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut buf = vec![0; 64 * 1024];
+    for _ in 0..passes {
+        file.seek(SeekFrom::Start(0))?;
+        let mut remaining = len;
+        while remaining > 0 {
+            let n = remaining.min(buf.len() as u64) as usize;
+            noise.fill(&mut buf[..n]);
+            file.write_all(&buf[..n])?;
+            remaining -= n as u64;
+        }
+        file.sync_all()?;
+    }
+    file.set_len(0)?;
+    file.sync_all()?;
+    drop(file);
+    // the name is replaced too, as it may be sensitive
+    let anonymous = path.with_file_name(format!(".broot-shred-{:016x}", noise.next()));
+    fs::rename(path, &anonymous)?;
+    fs::remove_file(&anonymous)
+}
+
+fn shred_rec(path: &Path, passes: usize, noise: &mut Noise) -> io::Result<usize> {
+    let md = fs::symlink_metadata(path)?;
+    if md.is_dir() {
+        let mut count = 0;
+        for entry in fs::read_dir(path)? {
+            count += shred_rec(&entry?.path(), passes, noise)?;
+        }
+        fs::remove_dir(path)?;
+        Ok(count)
+    } else if md.is_file() {
+        shred_file(path, passes, noise)?;
+        Ok(1)
+    } else {
+        // symlinks and special files are removed without
+        // touching what they point to
+        fs::remove_file(path)?;
+        Ok(0)
+    }
+}
+
+/// overwrite passes times the content of the file, or of all the
+/// files of the directory, then delete it.
+///
+/// Return the number of overwritten files.
+pub fn shred_path(path: &Path, passes: usize) -> io::Result<usize> {
+    shred_rec(path, passes, &mut Noise::new())
+}
+
+#[cfg(test)]
+mod shred_tests {
+    use super::*;
+    #[test]
+    fn check_secure_option_parsing() {
+        assert_eq!(parse_secure_option("secure", 3), Ok(3));
+        assert_eq!(parse_secure_option(" secure 7 ", 3), Ok(7));
+        assert!(parse_secure_option("secure 0", 3).is_err());
+        assert!(parse_secure_option("secure -1", 3).is_err());
+        assert!(parse_secure_option("secure 2 3", 3).is_err());
+        assert!(parse_secure_option("", 3).is_err());
+        assert!(parse_secure_option("7", 3).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use {
+    super::shred::{shred_path, shred_summary},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        verb::*,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::{Area, FmtText, TextView},
+};
+
+/// what must be typed to confirm: a simple *y* is too easy to
+/// type by mistake for an operation which can't be undone
+const CONFIRMATION: &str = "yes";
+
+enum ShredStep {
+    Confirm,
+    Done(usize), // number of overwritten files
+    Failed(String),
+}
+
+/// a modal state warning the user before the secure deletion
+/// of the selected entries, and doing it once confirmed
+pub struct ShredState {
+    paths: Vec<PathBuf>,
+    passes: usize,
+    files_count: usize,
+    size: u64,
+    step: ShredStep,
+}
+
+impl ShredState {
+    pub fn new(paths: Vec<PathBuf>, passes: usize) -> io::Result<Self> {
+        let mut files_count = 0;
+        let mut size = 0;
+        for path in &paths {
+            let (count, path_size) = shred_summary(path)?;
+            files_count += count;
+            size += path_size;
+        }
+        Ok(Self {
+            paths,
+            passes,
+            files_count,
+            size,
+            step: ShredStep::Confirm,
+        })
+    }
+
+    fn shred(&mut self) {
+        let mut count = 0;
+        for path in &self.paths {
+            match shred_path(path, self.passes) {
+                Ok(n) => count += n,
+                Err(e) => {
+                    self.step = ShredStep::Failed(format!("{}: {}", path.to_string_lossy(), e));
+                    return;
+                }
+            }
+        }
+        self.step = ShredStep::Done(count);
+    }
+
+    fn markdown(&self) -> String {
+        match &self.step {
+            ShredStep::Confirm => {
+                let mut md = format!(
+                    "\n# ⚠ Secure deletion\n\n\
+                    **{} files** ({}) will be overwritten **{} times** with random bytes, then deleted:\n\n",
+                    self.files_count,
+                    file_size::fit_4(self.size),
+                    self.passes,
+                );
+                for path in &self.paths {
+                    md.push_str(&format!("* `{}`\n", path.to_string_lossy()));
+                }
+                md.push_str(
+                    "\n**This can't be undone**: nothing goes to the trash.\n\n\
+                    On SSDs, copy-on-write or journaling filesystems, or when there are \
+                    backups or snapshots, the old content may still be recoverable.\n\n\
+                    Type *yes* to confirm, any other key to cancel."
+                );
+                md
+            }
+            ShredStep::Done(count) => format!("\n{} files were overwritten and deleted", count),
+            ShredStep::Failed(e) => format!("\nThe secure deletion failed:\n\n`{}`", e),
+        }
+    }
+}
+
+impl AppState for ShredState {
+
+    fn selected_path(&self) -> &Path {
+        &self.paths[0]
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.paths[0],
+            line: 0,
+            stype: SelectionType::Any,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            ShredStep::Confirm => match cc.cmd {
+                Command::PatternEdit { raw, .. } if raw == CONFIRMATION => {
+                    self.shred();
+                    AppStateCmdResult::Keep
+                }
+                // the confirmation is being typed
+                Command::PatternEdit { raw, .. } if CONFIRMATION.starts_with(raw.as_str()) => {
+                    AppStateCmdResult::Keep
+                }
+                _ => AppStateCmdResult::PopState, // any other key cancels
+            },
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        _internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match self.step {
+            ShredStep::Confirm => AppStateCmdResult::PopState,
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: true },
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            ShredStep::Confirm => format!(
+                "Overwrite and delete {} files ? type *yes* to confirm",
+                self.files_count,
+            ),
+            _ => "Hit any key to go back".to_string(),
+        })
+    }
+}
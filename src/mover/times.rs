@@ -0,0 +1,74 @@
+use {
+    chrono::{Local, NaiveDate, NaiveDateTime, TimeZone},
+    filetime::FileTime,
+    std::{
+        io,
+        path::Path,
+        time::SystemTime,
+    },
+};
+
+/// the accepted formats of dates with a time, in local time
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+/// parse the time given to :set_times, which is either a number of
+/// seconds since the epoch, or a local date with an optional time
+/// (eg `2020-07-14` or `2020-07-14 08:30`)
+pub fn parse_time(s: &str) -> Result<FileTime, String> {
+    let s = s.trim();
+    if let Ok(seconds) = s.parse::<i64>() {
+        return Ok(FileTime::from_unix_time(seconds, 0));
+    }
+    let naive = DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_hms(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            format!("Invalid time: {:?} (expected eg `2020-07-14 08:30` or seconds since epoch)", s)
+        })?;
+    let local = Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("{:?} doesn't exist in the local time zone", s))?;
+    Ok(FileTime::from_unix_time(local.timestamp(), 0))
+}
+
+/// the time used when none is given
+pub fn now() -> FileTime {
+    FileTime::from_system_time(SystemTime::now())
+}
+
+/// set both the access and the modification times of the file,
+/// like `touch` does
+pub fn set_times(path: &Path, time: FileTime) -> io::Result<()> {
+    filetime::set_file_times(path, time, time)
+}
+
+#[cfg(test)]
+mod times_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_parse_time() {
+        assert_eq!(parse_time("1594715400"), Ok(FileTime::from_unix_time(1594715400, 0)));
+        assert_eq!(parse_time("2020-07-14"), parse_time("2020-07-14 00:00:00"));
+        assert_eq!(parse_time("2020-07-14T08:30"), parse_time("2020-07-14 08:30:00"));
+        assert_eq!(
+            parse_time("2020-07-14 08:30:10").unwrap().unix_seconds()
+                - parse_time("2020-07-14 08:30").unwrap().unix_seconds(),
+            10,
+        );
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("2020-13-01").is_err());
+    }
+}
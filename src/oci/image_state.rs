@@ -0,0 +1,231 @@
+use {
+    super::{extract, ImageSource},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        task_sync::Dam,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossbeam::channel::{bounded, Receiver, RecvTimeoutError},
+    std::{
+        io,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    },
+    tempfile::TempDir,
+    termimad::{Area, FmtText, TextView},
+};
+
+/// how long we wait for the extraction between two checks of the events
+const POLL_PERIOD: Duration = Duration::from_millis(20);
+
+enum ImageStep {
+    Running {
+        // the work dir comes back with the result, so that it's
+        // removed only when the extraction thread doesn't write in it
+        receiver: Receiver<(TempDir, io::Result<PathBuf>)>,
+        cancelled: Arc<AtomicBool>,
+    },
+    Done {
+        _dir: TempDir, // removed when the state is dropped
+        rootfs: PathBuf,
+    },
+    Failed(String),
+}
+
+/// a modal state extracting a container image in the background,
+/// then giving access to its merged filesystem as long as it's
+/// in the stack
+pub struct ImageState {
+    source: ImageSource,
+    work_path: PathBuf,
+    step: ImageStep,
+    tree_options: TreeOptions,
+}
+
+impl ImageState {
+    pub fn new(source: ImageSource, tree_options: TreeOptions) -> io::Result<Self> {
+        let dir = tempfile::Builder::new().prefix("broot-image-").tempdir()?;
+        let work_path = dir.path().to_path_buf();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = bounded(1);
+        let thread_source = source.clone();
+        let thread_cancelled = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            let res = extract(&thread_source, dir.path(), &thread_cancelled);
+            // if the state is gone, the dir is dropped, and removed, here
+            let _ = sender.send((dir, res));
+        });
+        Ok(Self {
+            source,
+            work_path,
+            step: ImageStep::Running { receiver, cancelled },
+            tree_options,
+        })
+    }
+
+    fn markdown(&self) -> String {
+        let name = self.source.name();
+        match &self.step {
+            ImageStep::Running { .. } => format!(
+                "\nThe image **{}** is being extracted into `{}`.\n\nHit *esc* to cancel",
+                name,
+                self.work_path.to_string_lossy(),
+            ),
+            ImageStep::Done { rootfs, .. } => format!(
+                "\nThe image **{}** was extracted into `{}`.\n\n\
+                Hit *enter* to browse it, or *esc* to leave and remove it",
+                name,
+                rootfs.to_string_lossy(),
+            ),
+            ImageStep::Failed(e) => format!(
+                "\nThe extraction of the image **{}** failed:\n\n`{}`",
+                name,
+                e,
+            ),
+        }
+    }
+}
+
+impl Drop for ImageState {
+    fn drop(&mut self) {
+        if let ImageStep::Running { cancelled, .. } = &self.step {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl AppState for ImageState {
+
+    fn selected_path(&self) -> &Path {
+        match &self.step {
+            ImageStep::Done { rootfs, .. } => rootfs,
+            _ => &self.work_path,
+        }
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: self.selected_path(),
+            line: 0,
+            stype: SelectionType::Directory,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match (&self.step, internal_exec.internal) {
+            (ImageStep::Running { cancelled, .. }, Internal::back) => {
+                cancelled.store(true, Ordering::Relaxed);
+                AppStateCmdResult::PopState
+            }
+            (ImageStep::Running { .. }, _) => AppStateCmdResult::Keep,
+            (ImageStep::Done { rootfs, .. }, Internal::open_stay) => internal_focus::on_path(
+                rootfs.clone(),
+                screen,
+                self.tree_options.clone(),
+                internal_exec.bang,
+                cc.con,
+            ),
+            (ImageStep::Done { .. }, _) => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+            (ImageStep::Failed(_), _) => AppStateCmdResult::PopState,
+        })
+    }
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        match &self.step {
+            ImageStep::Running { .. } => Some("extracting the image"),
+            _ => None,
+        }
+    }
+
+    fn do_pending_task(
+        &mut self,
+        _screen: &mut Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        // we wait for the extraction until the user does something
+        // (for example hitting esc to cancel)
+        while let ImageStep::Running { receiver, .. } = &self.step {
+            match receiver.recv_timeout(POLL_PERIOD) {
+                Ok((dir, Ok(rootfs))) => {
+                    self.step = ImageStep::Done { _dir: dir, rootfs };
+                }
+                Ok((_, Err(e))) => {
+                    self.step = ImageStep::Failed(e.to_string());
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if dam.has_event() {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.step = ImageStep::Failed("the extraction stopped".to_string());
+                }
+            }
+        }
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            ImageStep::Running { .. } => "Extracting the image, hit *esc* to cancel",
+            ImageStep::Done { .. } => "Hit *enter* to browse the image",
+            ImageStep::Failed(_) => "Hit *esc* to go back",
+        }.to_string())
+    }
+}
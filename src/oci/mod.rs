@@ -0,0 +1,284 @@
+//! Read-only exploration of container images.
+//!
+//! An image (a tarball as produced by `docker save`, or an image
+//! known by its tag to the local docker daemon) is extracted in the
+//! background into a temporary directory, its layers being applied
+//! in order (whiteouts included) so that the merged filesystem can be
+//! browsed like any other directory, and its files copied out with
+//! the usual verbs. The directory is removed when the state showing
+//! the image is left.
+
+mod image_state;
+
+pub use image_state::ImageState;
+
+use {
+    crate::{compression, mover::remove_path},
+    std::{
+        fs::{self, File},
+        io::{self, BufReader, Read},
+        path::{Component, Path, PathBuf},
+        process::{Command, Stdio},
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+        time::Duration,
+    },
+    tar::Archive,
+};
+
+/// prefix of the files marking, in a layer, the deletion of a file
+/// from the previous layers
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// name of the file marking, in a layer, that the content of the
+/// directory in the previous layers is hidden
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+/// how long we wait between two checks of the `docker save` process
+const SAVE_POLL_PERIOD: Duration = Duration::from_millis(50);
+
+/// where an image comes from
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    Tarball(PathBuf), // a tarball made by `docker save`
+    Tag(String), // an image known by the local docker daemon
+}
+
+impl ImageSource {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Tarball(path) => path.to_string_lossy().to_string(),
+            Self::Tag(tag) => tag.clone(),
+        }
+    }
+}
+
+fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled")
+}
+
+fn check_cancelled(cancelled: &AtomicBool) -> io::Result<()> {
+    if cancelled.load(Ordering::Relaxed) {
+        Err(cancelled_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// tell whether the file looks like an image tarball
+pub fn is_image_tarball(path: &Path) -> bool {
+    if path.extension().is_none_or(|ext| ext != "tar") {
+        return false;
+    }
+    File::open(path)
+        .map(|file| {
+            Archive::new(file)
+                .entries()
+                .map(|mut entries| {
+                    entries.any(|entry| {
+                        entry
+                            .ok()
+                            .and_then(|entry| entry.path().ok().map(|p| p == Path::new("manifest.json")))
+                            .unwrap_or(false)
+                    })
+                })
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
+
+/// extract the image in the work directory, which should be empty.
+/// Return the path to the merged filesystem.
+///
+/// Fail with an error of kind Interrupted when the cancel flag is raised.
+pub fn extract(source: &ImageSource, dir: &Path, cancelled: &AtomicBool) -> io::Result<PathBuf> {
+    match source {
+        ImageSource::Tarball(tarball) => extract_image_tarball_in(tarball, dir, cancelled),
+        ImageSource::Tag(tag) => {
+            let tarball = dir.join("image.tar");
+            save_tagged_image(tag, &tarball, cancelled)?;
+            let rootfs = extract_image_tarball_in(&tarball, dir, cancelled)?;
+            fs::remove_file(&tarball)?;
+            Ok(rootfs)
+        }
+    }
+}
+
+/// export the image with the given tag from the local docker daemon
+fn save_tagged_image(tag: &str, tarball: &Path, cancelled: &AtomicBool) -> io::Result<()> {
+    // the output is captured so that it doesn't mess the screen
+    let mut child = Command::new("docker")
+        .arg("save")
+        .arg("-o")
+        .arg(tarball)
+        .arg("--") // so that the tag can't be taken for an option
+        .arg(tag)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+            let mut stderr = String::new();
+            if let Some(mut child_stderr) = child.stderr.take() {
+                child_stderr.read_to_string(&mut stderr)?;
+            }
+            return Err(io::Error::other(stderr.trim().to_string()));
+        }
+        if cancelled.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(cancelled_error());
+        }
+        thread::sleep(SAVE_POLL_PERIOD);
+    }
+}
+
+fn extract_image_tarball_in(
+    tarball: &Path,
+    dir: &Path,
+    cancelled: &AtomicBool,
+) -> io::Result<PathBuf> {
+    let content_dir = dir.join("content");
+    let rootfs = dir.join("rootfs");
+    fs::create_dir(&content_dir)?;
+    fs::create_dir(&rootfs)?;
+    let mut archive = Archive::new(File::open(tarball)?);
+    for entry in archive.entries()? {
+        check_cancelled(cancelled)?;
+        // entries going out of the directory are skipped by unpack_in
+        entry?.unpack_in(&content_dir)?;
+    }
+    let manifest = fs::read_to_string(content_dir.join("manifest.json"))?;
+    for layer in read_layers(&manifest) {
+        let layer = enclosed_path(Path::new(&layer)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid layer path: {:?}", layer))
+        })?;
+        apply_layer(&content_dir.join(layer), &rootfs, cancelled)?;
+    }
+    fs::remove_dir_all(&content_dir)?;
+    Ok(rootfs)
+}
+
+/// read the paths of the layers (in application order) from the
+/// manifest.json file of a tarball made by `docker save`
+fn read_layers(manifest: &str) -> Vec<String> {
+    regex!(r#""Layers"\s*:\s*\[([^\]]*)\]"#)
+        .captures(manifest)
+        .map(|c| {
+            regex!(r#""([^"]+)""#)
+                .captures_iter(&c[1])
+                .map(|c| c[1].to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// the path made of the normal components of the given one (a leading
+/// `/` being ignored), or None if it contains a `..` component which
+/// could make it go out of the directory it's joined to
+fn enclosed_path(path: &Path) -> Option<PathBuf> {
+    let mut enclosed = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => enclosed.push(part),
+            Component::CurDir | Component::RootDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(enclosed)
+}
+
+/// apply a layer (a possibly compressed tar) over the rootfs.
+///
+/// Entries whose path would go out of the rootfs, either with `..`
+/// components or because of links written by previous entries,
+/// are ignored.
+fn apply_layer(layer_path: &Path, rootfs: &Path, cancelled: &AtomicBool) -> io::Result<()> {
+    let mut magic = [0; 2];
+    File::open(layer_path)?.read_exact(&mut magic)?;
+    let reader: Box<dyn Read> = if magic == [0x1f, 0x8b] {
+        Box::new(flate2::read::GzDecoder::new(File::open(layer_path)?))
+    } else {
+        compression::open(layer_path)?
+    };
+    let canonical_rootfs = rootfs.canonicalize()?;
+    let mut archive = Archive::new(BufReader::new(reader));
+    for entry in archive.entries()? {
+        check_cancelled(cancelled)?;
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        let relative_path = match enclosed_path(&path) {
+            Some(relative_path) => relative_path,
+            None => {
+                warn!("layer entry {:?} ignored as going out of the rootfs", &path);
+                continue;
+            }
+        };
+        let name = match relative_path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        // the real path of the parent directory, which must be in the rootfs,
+        // or None if it doesn't exist yet
+        let parent = rootfs.join(relative_path.parent().unwrap_or_else(|| Path::new("")));
+        let parent = match parent.canonicalize() {
+            Ok(parent) if parent.starts_with(&canonical_rootfs) => Some(parent),
+            Ok(_) => {
+                warn!("layer entry {:?} ignored as behind a link out of the rootfs", &path);
+                continue;
+            }
+            Err(_) => None,
+        };
+        if name == OPAQUE_WHITEOUT {
+            // the content of the directory in lower layers is hidden
+            if let Some(parent) = parent.filter(|p| p.is_dir()) {
+                for child in fs::read_dir(&parent)?.filter_map(Result::ok) {
+                    remove_path(&child.path())?;
+                }
+            }
+        } else if let Some(hidden_name) = name.strip_prefix(WHITEOUT_PREFIX) {
+            if hidden_name.is_empty() || hidden_name == "." || hidden_name == ".." {
+                continue;
+            }
+            if let Some(parent) = parent {
+                let hidden = parent.join(hidden_name);
+                if fs::symlink_metadata(&hidden).is_ok() {
+                    remove_path(&hidden)?;
+                }
+            }
+        } else {
+            if let Some(parent) = parent {
+                // a file or link of a lower layer is replaced
+                let dest = parent.join(&name);
+                if fs::symlink_metadata(&dest).is_ok_and(|md| !md.is_dir()) {
+                    fs::remove_file(&dest)?;
+                }
+            }
+            // unpack_in checks the destination is in the rootfs
+            if let Err(e) = entry.unpack_in(rootfs) {
+                // devices and some special files can't be extracted without
+                // privileges, which isn't a problem for a read-only exploration
+                debug!("can't unpack {:?} : {:?}", &path, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod oci_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_enclosed_path() {
+        assert_eq!(enclosed_path(Path::new("./etc/passwd")), Some(PathBuf::from("etc/passwd")));
+        assert_eq!(enclosed_path(Path::new("/etc/passwd")), Some(PathBuf::from("etc/passwd")));
+        assert_eq!(enclosed_path(Path::new("etc/../../passwd")), None);
+        assert_eq!(enclosed_path(Path::new("../.wh.passwd")), None);
+    }
+}
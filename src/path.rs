@@ -130,6 +130,65 @@ pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
     normalized
 }
 
+/// encode as %XX the bytes of the path which can't be written as
+/// is in an URI
+pub fn percent_encode(path: &Path) -> String {
+    let mut encoded = String::new();
+    for &b in path_to_bytes(path).iter() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char);
+            }
+            _ => {
+                encoded.push_str(&format!("%{:02X}", b));
+            }
+        }
+    }
+    encoded
+}
+
+/// decode the %XX escapes of a path, as found in URIs
+pub fn percent_decode(s: &str) -> PathBuf {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    bytes_to_path(decoded)
+}
+
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().replace('\\', "/").into_bytes()
+}
+
+#[cfg(unix)]
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).to_string())
+}
+
 #[cfg(test)]
 mod path_normalize_tests {
 
@@ -159,3 +218,24 @@ mod path_normalize_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod path_percent_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_percent_decode() {
+        assert_eq!(percent_decode("/home/me/a%20b.txt"), PathBuf::from("/home/me/a b.txt"));
+        assert_eq!(percent_decode("/tmp/100%"), PathBuf::from("/tmp/100%"));
+        assert_eq!(percent_decode("/tmp/%C3%A9t%C3%A9"), PathBuf::from("/tmp/été"));
+    }
+
+    #[test]
+    fn check_percent_encode() {
+        assert_eq!(percent_encode(Path::new("/home/me/a b.txt")), "/home/me/a%20b.txt");
+        assert_eq!(percent_encode(Path::new("/tmp/été")), "/tmp/%C3%A9t%C3%A9");
+        let path = Path::new("/tmp/50% [draft]#2.md");
+        assert_eq!(percent_decode(&percent_encode(path)), path);
+    }
+}
@@ -2,7 +2,6 @@
 use {
     super::*,
     crate::{
-        app::AppContext,
         content_search::ContentMatch,
         errors::PatternError,
     },
@@ -26,9 +25,14 @@ pub enum Pattern {
 
 impl Pattern {
 
+    /// build a pattern from its parsed representation.
+    ///
+    /// This doesn't depend on the application's state so that
+    /// the input preview and the execution of the pattern always
+    /// agree on its validity.
     pub fn new(
         raw_expr: &BeTree<PatternOperator, PatternParts>,
-        con: &AppContext,
+        search_modes: &SearchModeMap,
     ) -> Result<Self, PatternError> {
         let expr: BeTree<PatternOperator, Pattern> = raw_expr
             .try_map_atoms::<_, PatternError, _>(|pattern_parts| {
@@ -38,13 +42,13 @@ impl Pattern {
                         Pattern::None
                     } else {
                         let parts_mode = pattern_parts.mode();
-                        let mode = con.search_modes.search_mode(parts_mode)?;
+                        let mode = search_modes.search_mode(parts_mode)?;
                         let flags = pattern_parts.flags();
                         match mode {
                             SearchMode::NameFuzzy => Self::NameFuzzy(FuzzyPattern::from(core)),
                             SearchMode::PathFuzzy => Self::PathFuzzy(FuzzyPattern::from(core)),
                             SearchMode::NameRegex => {
-                                Self::NameRegex(RegexPattern::from(core, flags.as_deref().unwrap_or(""))?)
+                                Self::NameRegex(RegexPattern::from(core, flags.unwrap_or(""))?)
                             }
                             SearchMode::PathRegex => {
                                 Self::PathRegex(RegexPattern::from(core, flags.unwrap_or(""))?)
@@ -32,8 +32,16 @@ impl PatternParts {
         // self.parts can't be empty, by construct
         self.parts.last_mut().unwrap().push(c);
     }
-    pub fn add_part(&mut self) {
-        self.parts.push(String::new());
+    /// add a part, when possible (there can't be more than the
+    /// mode, the core and the flags). Return false when the
+    /// separator must instead be considered as a normal char.
+    pub fn add_part(&mut self) -> bool {
+        if self.parts.len() < 3 {
+            self.parts.push(String::new());
+            true
+        } else {
+            false
+        }
     }
     pub fn allow_inter_pattern_token(&self) -> bool {
         self.parts.len() != 2
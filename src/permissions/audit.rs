@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+const TYPE_MASK: u32 = 0o170_000;
+const TYPE_DIR: u32 = 0o040_000;
+const TYPE_SYMLINK: u32 = 0o120_000;
+
+/// the owner, group and mode (with the file type bits) of a file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ownership {
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+}
+
+#[cfg(unix)]
+impl From<&std::fs::Metadata> for Ownership {
+    fn from(md: &std::fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            uid: md.uid(),
+            gid: md.gid(),
+            mode: md.mode(),
+        }
+    }
+}
+
+/// what makes an entry fail the permission audit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PermissionAudit {
+    /// its owner, its group or its permissions differ from the
+    /// ones of its parent directory (execution bits of files
+    /// aren't compared)
+    Parent,
+    /// it has some of those mode bits
+    Bits(u32),
+}
+
+/// parse a symbolic spec like `o+w` or `ug+s`, returning the bits
+fn parse_symbolic(spec: &str) -> Option<u32> {
+    let plus = spec.find('+')?;
+    let who = &spec[..plus];
+    let perms = &spec[plus + 1..];
+    if perms.is_empty() {
+        return None;
+    }
+    let mut classes = 0; // 0o7 shifted for each of u, g and o
+    for c in who.chars() {
+        classes |= match c {
+            'u' => 0o700,
+            'g' => 0o070,
+            'o' => 0o007,
+            'a' => 0o777,
+            _ => return None,
+        };
+    }
+    if classes == 0 {
+        classes = 0o777;
+    }
+    let setid_bits = (if classes & 0o700 != 0 { 0o4000 } else { 0 })
+        | (if classes & 0o070 != 0 { 0o2000 } else { 0 });
+    let mut bits = 0;
+    for c in perms.chars() {
+        bits |= match c {
+            'r' => classes & 0o444,
+            'w' => classes & 0o222,
+            'x' => classes & 0o111,
+            's' => setid_bits,
+            't' => 0o1000,
+            _ => return None,
+        };
+    }
+    Some(bits)
+}
+
+impl FromStr for PermissionAudit {
+    type Err = String;
+    /// parse either `parent`, a named spec, an octal mask, or
+    /// comma separated symbolic specs (eg `o+w,u+s`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let bits = match s {
+            "" | "parent" => return Ok(Self::Parent),
+            "world-writable" => Some(0o002),
+            "group-writable" => Some(0o020),
+            "setuid" => Some(0o4000),
+            "setgid" => Some(0o2000),
+            _ if s.chars().all(|c| c.is_digit(8)) => u32::from_str_radix(s, 8).ok(),
+            _ => s
+                .split(',')
+                .map(parse_symbolic)
+                .try_fold(0, |acc, bits| bits.map(|bits| acc | bits)),
+        };
+        match bits {
+            Some(bits) if bits > 0 && bits <= 0o7777 => Ok(Self::Bits(bits)),
+            _ => Err(format!(
+                "Invalid audit spec: {:?} (expected eg `parent`, `world-writable`, `o+w,u+s` or `022`)",
+                s,
+            )),
+        }
+    }
+}
+
+impl PermissionAudit {
+    /// tell whether the entry fails the audit. Symbolic links
+    /// are never reported, as their permissions aren't used
+    pub fn fails(self, ownership: Ownership, parent: Option<Ownership>) -> bool {
+        let file_type = ownership.mode & TYPE_MASK;
+        if file_type == TYPE_SYMLINK {
+            return false;
+        }
+        match self {
+            Self::Parent => parent.is_some_and(|parent| {
+                let compared_bits = if file_type == TYPE_DIR { 0o7777 } else { 0o666 };
+                ownership.uid != parent.uid
+                    || ownership.gid != parent.gid
+                    || ownership.mode & compared_bits != parent.mode & compared_bits
+            }),
+            Self::Bits(bits) => ownership.mode & bits != 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+
+    use super::*;
+
+    fn file(mode: u32) -> Ownership {
+        Ownership { uid: 1000, gid: 1000, mode: 0o100_000 | mode }
+    }
+    fn dir(mode: u32) -> Ownership {
+        Ownership { uid: 1000, gid: 1000, mode: TYPE_DIR | mode }
+    }
+
+    #[test]
+    fn check_audit_specs() {
+        assert_eq!("".parse(), Ok(PermissionAudit::Parent));
+        assert_eq!("world-writable".parse(), Ok(PermissionAudit::Bits(0o002)));
+        assert_eq!("o+w".parse(), Ok(PermissionAudit::Bits(0o002)));
+        assert_eq!("go+w,u+s".parse(), Ok(PermissionAudit::Bits(0o4022)));
+        assert_eq!("022".parse(), Ok(PermissionAudit::Bits(0o022)));
+        assert!("o+".parse::<PermissionAudit>().is_err());
+        assert!("z+w".parse::<PermissionAudit>().is_err());
+        assert!("0".parse::<PermissionAudit>().is_err());
+    }
+
+    #[test]
+    fn check_audit_failures() {
+        let parent = Some(dir(0o755));
+        assert!(!PermissionAudit::Parent.fails(file(0o644), parent));
+        assert!(!PermissionAudit::Parent.fails(file(0o755), parent));
+        assert!(PermissionAudit::Parent.fails(file(0o664), parent));
+        assert!(PermissionAudit::Parent.fails(dir(0o777), parent));
+        assert!(PermissionAudit::Parent.fails(Ownership { uid: 0, ..file(0o644) }, parent));
+        assert!(!PermissionAudit::Parent.fails(file(0o600), None));
+        let world_writable = PermissionAudit::Bits(0o002);
+        assert!(world_writable.fails(file(0o666), parent));
+        assert!(!world_writable.fails(file(0o664), parent));
+        assert!(!world_writable.fails(Ownership { mode: TYPE_SYMLINK | 0o777, ..file(0) }, parent));
+    }
+}
@@ -0,0 +1,287 @@
+use {
+    super::{group_name, user_name},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        pattern::InputPattern,
+        skin::PanelSkin,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        fs,
+        io,
+        os::unix::fs::{MetadataExt, PermissionsExt},
+        path::{Path, PathBuf},
+    },
+    termimad::{Area, CompoundStyle},
+};
+
+const CLASSES: [&str; 3] = ["user", "group", "other"];
+const RIGHTS: [&str; 3] = ["read", "write", "exec"];
+
+/// the permission bit of the cell of the grid
+fn bit(row: usize, col: usize) -> u32 {
+    0o400 >> (row * 3 + col)
+}
+
+/// an application state letting the user toggle the permission
+/// bits of the selected entry, and apply them, possibly to the
+/// whole content of a directory
+pub struct ChmodState {
+    path: PathBuf,
+    is_dir: bool,
+    owner: String,
+    group: String,
+    initial_mode: u32, // only the 9 permission bits
+    mode: u32,
+    row: usize, // 0 to 2 for the grid, 3 for the recursive flag
+    col: usize,
+    recursive: bool,
+}
+
+impl ChmodState {
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        let md = fs::symlink_metadata(&path)?;
+        // set_permissions would change the target of the link, whose
+        // mode isn't the one which would be displayed
+        if md.file_type().is_symlink() {
+            return Err(io::Error::other(
+                "the permissions of a symlink can't be changed, focus its target",
+            ));
+        }
+        let mode = md.mode() & 0o777;
+        Ok(Self {
+            is_dir: md.is_dir(),
+            owner: user_name(md.uid()),
+            group: group_name(md.gid()),
+            path,
+            initial_mode: mode,
+            mode,
+            row: 0,
+            col: 0,
+            recursive: false,
+        })
+    }
+
+    fn rows_count(&self) -> usize {
+        if self.is_dir { 4 } else { 3 }
+    }
+
+    fn toggle(&mut self) {
+        if self.row < 3 {
+            self.mode ^= bit(self.row, self.col);
+        } else {
+            self.recursive ^= true;
+        }
+    }
+
+    /// apply the new permissions, returning the number of
+    /// modified entries
+    fn apply(&self) -> io::Result<usize> {
+        if !self.recursive {
+            let mut perms = fs::symlink_metadata(&self.path)?.permissions();
+            // the special bits (setuid, sticky, etc.) are kept
+            perms.set_mode((perms.mode() & !0o777) | self.mode);
+            fs::set_permissions(&self.path, perms)?;
+            return Ok(1);
+        }
+        // in recursive mode, only the changed bits are applied, so
+        // that, for example, files don't become executable because
+        // the directory is
+        let added = self.mode & !self.initial_mode;
+        let removed = self.initial_mode & !self.mode;
+        apply_changes(&self.path, added, removed)
+    }
+
+    fn mode_string(mode: u32) -> String {
+        let mut s = String::with_capacity(9);
+        for row in 0..3 {
+            for (col, c) in ['r', 'w', 'x'].iter().enumerate() {
+                s.push(if mode & bit(row, col) != 0 { *c } else { '-' });
+            }
+        }
+        format!("{} ({:03o})", s, mode)
+    }
+}
+
+/// add and remove the permission bits of the file and, if it's
+/// a directory, of its content. Symlinks aren't followed.
+fn apply_changes(path: &Path, added: u32, removed: u32) -> io::Result<usize> {
+    let md = fs::symlink_metadata(path)?;
+    if md.file_type().is_symlink() {
+        return Ok(0);
+    }
+    let mut perms = md.permissions();
+    perms.set_mode((perms.mode() | added) & !removed);
+    fs::set_permissions(path, perms)?;
+    let mut count = 1;
+    if md.is_dir() {
+        for entry in fs::read_dir(path)? {
+            count += apply_changes(&entry?.path(), added, removed)?;
+        }
+    }
+    Ok(count)
+}
+
+impl AppState for ChmodState {
+
+    fn selected_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.path,
+            line: 0,
+            stype: if self.is_dir { SelectionType::Directory } else { SelectionType::File },
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pat: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(if pat.raw == "y" || pat.raw == "Y" {
+            match self.apply() {
+                Ok(_) => AppStateCmdResult::PopStateAndRefresh { clear_cache: false },
+                Err(e) => AppStateCmdResult::DisplayError(format!("chmod failed: {}", e)),
+            }
+        } else {
+            AppStateCmdResult::Keep
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.row = (self.row + 1) % self.rows_count();
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.row = (self.row + self.rows_count() - 1) % self.rows_count();
+                AppStateCmdResult::Keep
+            }
+            Internal::next_match => {
+                self.col = (self.col + 1) % 3;
+                AppStateCmdResult::Keep
+            }
+            Internal::previous_match => {
+                self.col = (self.col + 2) % 3;
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay | Internal::toggle_mark => {
+                self.toggle();
+                AppStateCmdResult::Keep
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let styles = &panel_skin.styles;
+        let mut selected_style = styles.default.clone();
+        if let Some(c) = styles.selected_line.get_bg() {
+            selected_style.set_bg(c);
+        }
+        let width = state_area.width as usize;
+        let mut lines: Vec<Vec<(&CompoundStyle, String)>> = vec![
+            vec![(&styles.file, self.path.to_string_lossy().to_string())],
+            vec![(&styles.dates, format!("owner: {}  group: {}", self.owner, self.group))],
+            vec![],
+        ];
+        let mut header = vec![(&styles.default, format!("{:8}", ""))];
+        for right in &RIGHTS {
+            header.push((&styles.dates, format!("{:8}", right)));
+        }
+        lines.push(header);
+        for (row, class) in CLASSES.iter().enumerate() {
+            let mut line = vec![(&styles.dates, format!("{:8}", class))];
+            for col in 0..3 {
+                let checked = self.mode & bit(row, col) != 0;
+                let style = if row == self.row && col == self.col {
+                    &selected_style
+                } else {
+                    &styles.default
+                };
+                line.push((style, if checked { "[x]".to_string() } else { "[ ]".to_string() }));
+                line.push((&styles.default, "     ".to_string()));
+            }
+            lines.push(line);
+        }
+        lines.push(vec![]);
+        if self.is_dir {
+            let style = if self.row == 3 { &selected_style } else { &styles.default };
+            lines.push(vec![
+                (style, format!("[{}]", if self.recursive { 'x' } else { ' ' })),
+                (&styles.default, " apply the changes to the content of the directory".to_string()),
+            ]);
+            lines.push(vec![]);
+        }
+        lines.push(vec![(
+            &styles.default,
+            format!(
+                "{} → {}",
+                Self::mode_string(self.initial_mode),
+                Self::mode_string(self.mode),
+            ),
+        )]);
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            if let Some(line) = lines.get(y as usize) {
+                cw.queue_char(&styles.default, ' ')?;
+                for (style, s) in line {
+                    cw.queue_str(style, s)?;
+                }
+            }
+            cw.fill(&styles.default, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(
+            "*↑* *↓* *tab*: move, *enter*: toggle, *y*: apply, *esc*: cancel"
+        )
+    }
+}
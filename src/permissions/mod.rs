@@ -1,8 +1,18 @@
+mod audit;
+
+pub use audit::{Ownership, PermissionAudit};
+
 //////////////////// UNIX
 
 #[cfg(unix)]
 pub mod permissions_unix;
 
+#[cfg(unix)]
+mod chmod_state;
+
+#[cfg(unix)]
+pub use chmod_state::ChmodState;
+
 #[cfg(unix)]
 pub fn supported() -> bool {
     true
@@ -17,6 +17,11 @@ pub fn user_name(uid: u32) -> String {
     (*name).to_string()
 }
 
+#[cfg(unix)]
+pub fn user_id(name: &str) -> Option<u32> {
+    users::get_user_by_name(name).map(|u| u.uid())
+}
+
 #[cfg(unix)]
 pub fn group_name(gid: u32) -> String {
     lazy_static! {
@@ -0,0 +1,163 @@
+use {
+    crate::{
+        archive::{self, ArchiveEntry, ArchiveFormat},
+        command::ScrollCommand,
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        task_sync::Dam,
+    },
+    crossterm::{
+        cursor,
+        style::{Color, Print, SetForegroundColor},
+        QueueableCommand,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::Area,
+};
+
+/// a preview listing the entries of an archive, with their sizes
+pub struct ArchiveView {
+    path: PathBuf,
+    format: ArchiveFormat,
+    entries: Vec<ArchiveEntry>,
+    loaded: bool, // whether the entries were read
+    error: Option<String>,
+    scroll: usize,
+    page_height: usize,
+}
+
+impl ArchiveView {
+    /// prepare the view, failing if the file isn't a known archive.
+    /// As reading the entries may need decompressing the whole
+    /// archive, it's done later, in load_entries
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let format = ArchiveFormat::of_file(path)
+            .ok_or_else(|| io::Error::other("not an archive"))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            entries: Vec::new(),
+            loaded: false,
+            error: None,
+            scroll: 0,
+            page_height: 0,
+        })
+    }
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+    /// read the entries of the archive, unless interrupted
+    /// by an event, in which case it must be called again
+    pub fn load_entries(&mut self, dam: &Dam) {
+        match time!(Debug, "archive listing", archive::list_entries(&self.path, self.format, dam)) {
+            Ok(Some(entries)) => {
+                self.entries = entries;
+                self.loaded = true;
+            }
+            Ok(None) => {} // interrupted
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.loaded = true;
+            }
+        }
+    }
+    pub fn try_scroll(
+        &mut self,
+        cmd: ScrollCommand,
+    ) -> bool {
+        let old_scroll = self.scroll;
+        self.scroll = cmd.apply(self.scroll, self.entries.len(), self.page_height);
+        self.scroll != old_scroll
+    }
+    pub fn select_first(&mut self) {
+        self.scroll = 0;
+    }
+    pub fn select_last(&mut self) {
+        if self.page_height < self.entries.len() {
+            self.scroll = self.entries.len() - self.page_height;
+        }
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        let line_count = area.height as usize;
+        self.page_height = line_count;
+        let styles = &panel_skin.styles;
+        let scrollbar = area.scrollbar(self.scroll as i32, self.entries.len() as i32);
+        let scrollbar_fg = styles.scrollbar_thumb.get_fg()
+            .or_else(|| styles.preview.get_fg())
+            .unwrap_or(Color::White);
+        for y in 0..line_count {
+            w.queue(cursor::MoveTo(area.left, y as u16 + area.top))?;
+            let mut cw = CropWriter::new(w, area.width as usize - 1); // -1 for scrollbar
+            let cw = &mut cw;
+            if y == 0 && !self.loaded {
+                cw.queue_str(&styles.preview, "reading the archive...")?;
+            } else if let (0, Some(error)) = (y, &self.error) {
+                cw.queue_str(&styles.preview, &format!("the archive can't be read: {}", error))?;
+            } else if let Some(entry) = self.entries.get(self.scroll + y) {
+                if entry.is_dir {
+                    cw.queue_str(&styles.preview_line_number, "      ")?;
+                    cw.queue_str(&styles.directory, &entry.path)?;
+                } else {
+                    cw.queue_g_string(
+                        &styles.preview_line_number,
+                        format!(" {:>4} ", file_size::fit_4(entry.size)),
+                    )?;
+                    cw.queue_str(&styles.preview, &entry.path)?;
+                }
+            }
+            cw.fill(&styles.preview, LONG_SPACE)?;
+            if is_thumb(y, scrollbar) {
+                w.queue(SetForegroundColor(scrollbar_fg))?;
+                w.queue(Print('▐'))?;
+            } else {
+                w.queue(Print(' '))?;
+            }
+        }
+        Ok(())
+    }
+    pub fn display_info(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        if !self.loaded {
+            return Ok(());
+        }
+        let s = if self.entries.len() >= archive::MAX_ENTRIES {
+            format!("{}+ entries", archive::MAX_ENTRIES)
+        } else {
+            format!("{} entries", self.entries.len())
+        };
+        if s.len() > area.width as usize {
+            return Ok(());
+        }
+        w.queue(cursor::MoveTo(
+            area.left + area.width - s.len() as u16,
+            area.top,
+        ))?;
+        panel_skin.styles.default.queue(w, s)?;
+        Ok(())
+    }
+}
+
+fn is_thumb(y: usize, scrollbar: Option<(u16, u16)>) -> bool {
+    if let Some((sctop, scbottom)) = scrollbar {
+        let y = y as u16;
+        if sctop <= y && y <= scbottom {
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,213 @@
+use {
+    crate::{
+        command::ScrollCommand,
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        git::{self, DiffLine, DiffLineKind, Hunk},
+        skin::PanelSkin,
+    },
+    crossterm::{
+        cursor,
+        style::{Color, Print, SetForegroundColor},
+        QueueableCommand,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::Area,
+};
+
+/// a preview of the changes of a file, as the hunks staged in the
+/// index then the ones of the working directory. One hunk is selected,
+/// to be staged or unstaged
+pub struct DiffView {
+    path: PathBuf,
+    hunks: Vec<Hunk>,
+    lines: Vec<DiffLine>, // the lines of all hunks, each one starting with its header
+    hunk_starts: Vec<usize>, // the index in lines of the header of each hunk
+    selected_hunk: usize,
+    scroll: usize,
+    page_height: usize,
+}
+
+fn read_hunks(path: &Path) -> io::Result<Vec<Hunk>> {
+    let hunks = git::file_hunks(path)
+        .map_err(|e| io::Error::other(e.message().to_string()))?;
+    if hunks.is_empty() {
+        return Err(io::Error::other("no modification in git"));
+    }
+    Ok(hunks)
+}
+
+impl DiffView {
+    /// compute the diff, failing if the file isn't in a git
+    /// repository or isn't modified
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let mut dv = Self {
+            path: path.to_path_buf(),
+            hunks: Vec::new(),
+            lines: Vec::new(),
+            hunk_starts: Vec::new(),
+            selected_hunk: 0,
+            scroll: 0,
+            page_height: 0,
+        };
+        dv.set_hunks(read_hunks(path)?);
+        Ok(dv)
+    }
+    fn set_hunks(&mut self, hunks: Vec<Hunk>) {
+        self.lines.clear();
+        self.hunk_starts.clear();
+        for hunk in &hunks {
+            self.hunk_starts.push(self.lines.len());
+            self.lines.push(DiffLine {
+                kind: DiffLineKind::Header,
+                content: format!(
+                    "{} {}",
+                    if hunk.staged { "staged  " } else { "unstaged" },
+                    hunk.header,
+                ),
+            });
+            self.lines.extend(hunk.lines.iter().cloned());
+        }
+        self.hunks = hunks;
+        self.selected_hunk = self.selected_hunk.min(self.hunks.len() - 1);
+        self.try_scroll(ScrollCommand::Lines(0));
+    }
+    /// compute the diff again, keeping the selection and the scroll
+    /// when possible, failing if the file isn't modified anymore
+    pub fn reload(&mut self) -> io::Result<()> {
+        let hunks = read_hunks(&self.path)?;
+        self.set_hunks(hunks);
+        Ok(())
+    }
+    /// select the next (or previous) hunk, scrolling to show it.
+    /// Return false if there's no other hunk in this direction
+    pub fn select_next_hunk(&mut self, forward: bool) -> bool {
+        let idx = if forward {
+            self.selected_hunk + 1
+        } else if self.selected_hunk > 0 {
+            self.selected_hunk - 1
+        } else {
+            return false;
+        };
+        if idx >= self.hunks.len() {
+            return false;
+        }
+        self.selected_hunk = idx;
+        let start = self.hunk_starts[idx];
+        if start < self.scroll || start >= self.scroll + self.page_height {
+            self.try_scroll(ScrollCommand::Lines(start as i32 - self.scroll as i32));
+        }
+        true
+    }
+    /// stage the selected hunk, or unstage it if it's staged.
+    /// The diff must be reloaded after that
+    pub fn toggle_hunk_stage(&self) -> Result<(), git2::Error> {
+        git::toggle_hunk_stage(&self.path, &self.hunks[self.selected_hunk])
+    }
+    pub fn try_scroll(
+        &mut self,
+        cmd: ScrollCommand,
+    ) -> bool {
+        let old_scroll = self.scroll;
+        self.scroll = cmd.apply(self.scroll, self.lines.len(), self.page_height);
+        self.scroll != old_scroll
+    }
+    pub fn select_first(&mut self) {
+        self.scroll = 0;
+    }
+    pub fn select_last(&mut self) {
+        if self.page_height < self.lines.len() {
+            self.scroll = self.lines.len() - self.page_height;
+        }
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        let line_count = area.height as usize;
+        self.page_height = line_count;
+        let styles = &panel_skin.styles;
+        let scrollbar = area.scrollbar(self.scroll as i32, self.lines.len() as i32);
+        let scrollbar_fg = styles.scrollbar_thumb.get_fg()
+            .or_else(|| styles.preview.get_fg())
+            .unwrap_or(Color::White);
+        for y in 0..line_count {
+            w.queue(cursor::MoveTo(area.left, y as u16 + area.top))?;
+            let mut cw = CropWriter::new(w, area.width as usize - 1); // -1 for scrollbar
+            let cw = &mut cw;
+            let idx = self.scroll + y;
+            if let Some(line) = self.lines.get(idx) {
+                let selected = idx == self.hunk_starts[self.selected_hunk];
+                let (mut style, prefix) = match line.kind {
+                    DiffLineKind::Header => (
+                        styles.preview_line_number.clone(),
+                        if selected { "▶" } else { " " },
+                    ),
+                    DiffLineKind::Addition => (styles.git_insertions.clone(), "+"),
+                    DiffLineKind::Deletion => (styles.git_deletions.clone(), "-"),
+                    DiffLineKind::Context => (styles.preview.clone(), " "),
+                };
+                if selected {
+                    if let Some(c) = styles.selected_line.get_bg() {
+                        style.set_bg(c);
+                    }
+                }
+                cw.queue_str(&style, prefix)?;
+                cw.queue_str(&style, &line.content)?;
+                if selected {
+                    cw.fill(&style, LONG_SPACE)?;
+                }
+            }
+            cw.fill(&styles.preview, LONG_SPACE)?;
+            if is_thumb(y, scrollbar) {
+                w.queue(SetForegroundColor(scrollbar_fg))?;
+                w.queue(Print('▐'))?;
+            } else {
+                w.queue(Print(' '))?;
+            }
+        }
+        Ok(())
+    }
+    pub fn display_info(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        let count = |kind| self.lines.iter().filter(|line| line.kind == kind).count();
+        let insertions = format!("+{}", count(DiffLineKind::Addition));
+        let deletions = format!("-{}", count(DiffLineKind::Deletion));
+        let position = format!("hunk {}/{} ", self.selected_hunk + 1, self.hunks.len());
+        let len = position.len() + insertions.len() + 1 + deletions.len();
+        if len > area.width as usize {
+            return Ok(());
+        }
+        w.queue(cursor::MoveTo(
+            area.left + area.width - len as u16,
+            area.top,
+        ))?;
+        let styles = &panel_skin.styles;
+        styles.default.queue(w, position)?;
+        styles.git_insertions.queue(w, insertions)?;
+        styles.default.queue(w, ' ')?;
+        styles.git_deletions.queue(w, deletions)?;
+        Ok(())
+    }
+}
+
+fn is_thumb(y: usize, scrollbar: Option<(u16, u16)>) -> bool {
+    if let Some((sctop, scbottom)) = scrollbar {
+        let y = y as u16;
+        if sctop <= y && y <= scbottom {
+            return true;
+        }
+    }
+    false
+}
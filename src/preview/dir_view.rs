@@ -0,0 +1,77 @@
+use {
+    crate::{
+        app::AppContext,
+        display::{DisplayableTree, Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        task_sync::Dam,
+        tree::{Tree, TreeOptions},
+        tree_build::TreeBuilder,
+    },
+    std::path::PathBuf,
+    termimad::Area,
+};
+
+/// a preview of a directory: the tree broot would display
+/// if the directory was focused, with the same options
+pub struct DirView {
+    path: PathBuf,
+    options: TreeOptions,
+    tree: Option<Tree>, // built on display, when the height is known
+    built_height: u16,
+}
+
+impl DirView {
+    pub fn new(path: PathBuf, options: TreeOptions) -> Self {
+        Self {
+            path,
+            options,
+            tree: None,
+            built_height: 0,
+        }
+    }
+    /// build the tree so that it fits the given height
+    fn build(&mut self, height: u16, con: &AppContext) {
+        self.built_height = height;
+        let builder = TreeBuilder::from(
+            self.path.clone(),
+            self.options.clone(),
+            height as usize,
+            con,
+        );
+        self.tree = match builder {
+            Ok(builder) => builder.build(false, &Dam::unlimited()),
+            Err(e) => {
+                warn!("error while previewing {:?} : {:?}", &self.path, e);
+                None
+            }
+        };
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if area.height != self.built_height {
+            self.build(area.height, con);
+        }
+        if let Some(tree) = &self.tree {
+            let dp = DisplayableTree {
+                tree,
+                skin: &panel_skin.styles,
+                cols: &con.cols,
+                show_selection_mark: false,
+                marked: &[],
+                unpaired: None,
+                ext_colors: &con.ext_colors,
+                area: area.clone(),
+                in_app: true,
+            };
+            dp.write_on(w)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use {
+    crate::{
+        command::ScrollCommand,
+        display::{Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+    },
+    std::{
+        fs,
+        io,
+        path::Path,
+    },
+    termimad::{Area, FmtText, TextView},
+};
+
+/// a preview rendering the file as formatted markdown
+/// (headers, lists, code blocks, tables...)
+pub struct MarkdownView {
+    markdown: String,
+    scroll: i32,
+    page_height: usize,
+}
+
+impl MarkdownView {
+    /// read the file, failing if it can't be read as UTF8
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let markdown = fs::read_to_string(path)?;
+        Ok(Self {
+            markdown,
+            scroll: 0,
+            page_height: 0,
+        })
+    }
+    pub fn try_scroll(
+        &mut self,
+        cmd: ScrollCommand,
+    ) -> bool {
+        let old_scroll = self.scroll;
+        // the scroll is fixed at display, when the length
+        // of the formatted text is known
+        self.scroll = self.scroll.saturating_add(cmd.to_lines(self.page_height)).max(0);
+        self.scroll != old_scroll
+    }
+    pub fn select_first(&mut self) {
+        self.scroll = 0;
+    }
+    pub fn select_last(&mut self) {
+        self.scroll = i32::MAX;
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.preview.queue_bg(w)?;
+        screen.clear_area_to_right(w, area)?;
+        self.page_height = area.height as usize;
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &self.markdown,
+            Some((area.width - 1) as usize), // 1 char left for scrollbar
+        );
+        let mut text_view = TextView::from(area, &fmt_text);
+        self.scroll = text_view.set_scroll(self.scroll);
+        Ok(text_view.write_on(w)?)
+    }
+}
@@ -0,0 +1,139 @@
+use {
+    crate::{
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        magic,
+        skin::PanelSkin,
+    },
+    chrono::{DateTime, Local},
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        fs::{self, FileType, Metadata},
+        io,
+        path::Path,
+        time::SystemTime,
+    },
+    termimad::Area,
+};
+
+#[cfg(unix)]
+use {
+    crate::permissions,
+    std::os::unix::fs::{FileTypeExt, MetadataExt},
+    umask::Mode,
+};
+
+/// a card describing the file, for the files whose
+/// content can't or shouldn't be previewed (sockets,
+/// devices, huge files, etc.)
+pub struct MetadataView {
+    fields: Vec<(&'static str, String)>,
+}
+
+impl MetadataView {
+    /// gather the metadata of the file. This never fails: the
+    /// fields which can't be read are just not displayed
+    pub fn new(path: &Path) -> Self {
+        let mut fields = vec![
+            ("path", path.to_string_lossy().to_string()),
+        ];
+        match fs::symlink_metadata(path) {
+            Ok(link_metadata) => {
+                if link_metadata.file_type().is_symlink() {
+                    if let Ok(target) = fs::read_link(path) {
+                        fields.push(("link target", target.to_string_lossy().to_string()));
+                    }
+                }
+                // the other fields describe the target of the link
+                let metadata = fs::metadata(path).unwrap_or(link_metadata);
+                Self::push_metadata_fields(&mut fields, &metadata);
+            }
+            Err(e) => {
+                fields.push(("error", e.to_string()));
+            }
+        }
+        if let Some(mime) = magic::mime_type(path) {
+            fields.push(("mime type", mime));
+        }
+        Self { fields }
+    }
+    fn push_metadata_fields(
+        fields: &mut Vec<(&'static str, String)>,
+        metadata: &Metadata,
+    ) {
+        fields.push(("type", file_type_name(metadata.file_type()).to_string()));
+        fields.push((
+            "size",
+            format!("{} ({} bytes)", file_size::fit_4(metadata.len()), metadata.len()),
+        ));
+        let dates: [(&'static str, io::Result<SystemTime>); 3] = [
+            ("created", metadata.created()),
+            ("modified", metadata.modified()),
+            ("accessed", metadata.accessed()),
+        ];
+        for (name, time) in dates.iter() {
+            if let Ok(time) = time {
+                let date_time: DateTime<Local> = (*time).into();
+                fields.push((*name, date_time.format("%Y/%m/%d %T").to_string()));
+            }
+        }
+        #[cfg(unix)]
+        {
+            fields.push(("permissions", Mode::from(metadata.mode()).to_string()));
+            fields.push(("owner", permissions::user_name(metadata.uid())));
+            fields.push(("group", permissions::group_name(metadata.gid())));
+        }
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        let styles = &panel_skin.styles;
+        let name_width = self.fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for y in 0..area.height {
+            w.queue(cursor::MoveTo(area.left, y + area.top))?;
+            let mut cw = CropWriter::new(w, area.width as usize);
+            if let Some((name, value)) = self.fields.get(y as usize) {
+                cw.queue_g_string(
+                    &styles.preview_line_number,
+                    format!(" {:>w$} ", name, w = name_width),
+                )?;
+                cw.queue_str(&styles.preview, " ")?;
+                cw.queue_str(&styles.preview, value)?;
+            }
+            cw.fill(&styles.preview, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+}
+
+fn file_type_name(file_type: FileType) -> &'static str {
+    #[cfg(unix)]
+    {
+        if file_type.is_socket() {
+            return "socket";
+        }
+        if file_type.is_fifo() {
+            return "fifo";
+        }
+        if file_type.is_block_device() {
+            return "block device";
+        }
+        if file_type.is_char_device() {
+            return "character device";
+        }
+    }
+    if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_symlink() {
+        "symbolic link"
+    } else {
+        "file"
+    }
+}
@@ -1,9 +1,21 @@
+mod archive_view;
+mod diff_view;
+mod dir_view;
+mod markdown_view;
+mod metadata_view;
 mod preview;
 mod preview_state;
+mod structured_view;
 
 pub use {
+    archive_view::ArchiveView,
+    diff_view::DiffView,
+    dir_view::DirView,
+    markdown_view::MarkdownView,
+    metadata_view::MetadataView,
     preview::Preview,
     preview_state::PreviewState,
+    structured_view::StructuredView,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -18,4 +30,14 @@ pub enum PreviewMode {
 
     /// show the content of the file as hex
     Hex,
+
+    /// render the content as formatted markdown
+    Markdown,
+
+    /// show a JSON, YAML or TOML file as a tree whose
+    /// objects and arrays can be folded
+    Structured,
+
+    /// show the changes of the file since git's HEAD
+    Diff,
 }
@@ -1,24 +1,39 @@
 
 use {
-    super::PreviewMode,
+    super::{ArchiveView, DiffView, DirView, MarkdownView, MetadataView, PreviewMode, StructuredView},
     crate::{
         app::{AppContext, LineNumber},
         command::{ScrollCommand},
+        compression::Compression,
         display::{Screen, W},
         errors::ProgramError,
+        handlers::{Handler, HandlerCommand},
         hex::HexView,
         image::ImageView,
+        logs,
+        magic,
         pattern::InputPattern,
         skin::PanelSkin,
         syntactic::SyntacticView,
         task_sync::Dam,
+        tree::TreeOptions,
     },
     std::path::Path,
     termimad::Area,
 };
 
+/// files bigger than that are described instead of being
+/// previewed, unless a preview mode is explicitly asked
+const MAX_SIZE_FOR_AUTOMATIC_PREVIEW: u64 = 200_000_000;
+
 pub enum Preview {
+    Archive(ArchiveView),
+    Diff(DiffView),
+    Dir(Box<DirView>),
     Image(ImageView),
+    Markdown(MarkdownView),
+    Metadata(MetadataView),
+    Structured(StructuredView),
     Syntactic(SyntacticView),
     Hex(HexView),
     IOError,
@@ -27,20 +42,69 @@ pub enum Preview {
 impl Preview {
     /// build a preview, never failing (but the preview can be Preview::IOError).
     /// If the prefered mode can't be applied, an other mode is chosen.
+    /// Directories are previewed as trees built with the given options.
     pub fn new(
         path: &Path,
         prefered_mode: Option<PreviewMode>,
+        tree_options: &TreeOptions,
         con: &AppContext,
     ) -> Self {
+        if path.is_dir() {
+            return Self::Dir(Box::new(DirView::new(
+                path.to_path_buf(),
+                tree_options.without_pattern(),
+            )));
+        }
+        if !path.is_file() {
+            // opening a FIFO would block, and the file may also
+            // have been removed since it was selected
+            info!("not previewing the content of {:?} which isn't a regular file", path);
+            return Self::Metadata(MetadataView::new(path));
+        }
         match prefered_mode {
             Some(PreviewMode::Hex) => Self::hex(path),
             Some(PreviewMode::Image) => Self::image(path),
             Some(PreviewMode::Text) => Self::unfiltered_text(path, con),
+            Some(PreviewMode::Markdown) => MarkdownView::new(path)
+                .map(Self::Markdown)
+                .unwrap_or_else(|_| Self::unfiltered_text(path, con)),
+            Some(PreviewMode::Structured) => StructuredView::new(path)
+                .map(Self::Structured)
+                .unwrap_or_else(|_| Self::unfiltered_text(path, con)),
+            Some(PreviewMode::Diff) => DiffView::new(path)
+                .map(Self::Diff)
+                .unwrap_or_else(|_| Self::unfiltered_text(path, con)),
             None => {
-                // automatic behavior: image, text, hex
+                if path.metadata().is_ok_and(|m| m.len() > MAX_SIZE_FOR_AUTOMATIC_PREVIEW) {
+                    return Self::Metadata(MetadataView::new(path));
+                }
+                // the chain configured for the extension, if any, is
+                // tried before the automatic behavior
+                for handler in con.handlers.previewers(path) {
+                    if let Some(preview) = Self::with_handler(path, handler, con) {
+                        return preview;
+                    }
+                }
+                // automatic behavior: archive, image, text, hex, the
+                // content being checked when the extension is misleading
+                if let Ok(archive_view) = ArchiveView::new(path) {
+                    return Self::Archive(archive_view);
+                }
                 ImageView::new(path)
                     .map(Self::Image)
-                    .unwrap_or_else(|_| Self::unfiltered_text(path, con))
+                    .unwrap_or_else(|_| {
+                        // a known binary format isn't tried as text, unless
+                        // it's a compressed file which is decompressed
+                        let binary = Compression::of_path(path).is_none()
+                            && magic::mime_of_file(path).ok()
+                                .flatten()
+                                .is_some_and(magic::is_binary);
+                        if binary {
+                            Self::hex(path)
+                        } else {
+                            Self::unfiltered_text(path, con)
+                        }
+                    })
 
             }
         }
@@ -59,6 +123,15 @@ impl Preview {
             PreviewMode::Image => {
                 ImageView::new(path).map(Self::Image)
             }
+            PreviewMode::Markdown => {
+                Ok(MarkdownView::new(path).map(Self::Markdown)?)
+            }
+            PreviewMode::Structured => {
+                Ok(StructuredView::new(path).map(Self::Structured)?)
+            }
+            PreviewMode::Diff => {
+                Ok(DiffView::new(path).map(Self::Diff)?)
+            }
             PreviewMode::Text => {
                 Ok(SyntacticView::new(path, InputPattern::none(), &mut Dam::unlimited(), con)
                     .transpose()
@@ -67,6 +140,45 @@ impl Preview {
             }
         }
     }
+    /// try to build a preview with a handler of the chain of
+    /// the file's extension
+    fn with_handler(
+        path: &Path,
+        handler: &Handler,
+        con: &AppContext,
+    ) -> Option<Self> {
+        let preview = match handler {
+            Handler::Preview(mode) => Self::with_mode(path, *mode, con),
+            Handler::Convert(command) => Self::converted(path, command, con),
+            _ => {
+                return None; // not a previewer
+            }
+        };
+        match preview {
+            Ok(preview) => Some(preview),
+            Err(e) => {
+                info!("handler {:?} can't preview {:?}: {}", handler, path, e);
+                None
+            }
+        }
+    }
+    /// build a preview of the output of a converting command: an
+    /// image view if it can be decoded as an image, a text view if
+    /// it's UTF8 text
+    pub fn converted(
+        path: &Path,
+        command: &HandlerCommand,
+        con: &AppContext,
+    ) -> Result<Self, ProgramError> {
+        let content = command.output(path)?;
+        if let Ok(image_view) = ImageView::from_bytes(&content) {
+            return Ok(Self::Image(image_view));
+        }
+        Ok(SyntacticView::from_converted(path, content, InputPattern::none(), &mut Dam::unlimited(), con)
+            .transpose()
+            .expect("syntactic view without pattern shouldn't be none")
+            .map(Self::Syntactic)?)
+    }
     /// build an image view, unless the file can't be interpreted
     /// as an image, in which case a hex view is used
     pub fn image(path: &Path) -> Self {
@@ -83,7 +195,13 @@ impl Preview {
         con: &AppContext,
     ) -> Self {
         match SyntacticView::new(path, InputPattern::none(), &mut Dam::unlimited(), con) {
-            Ok(Some(sv)) => Self::Syntactic(sv),
+            Ok(Some(mut sv)) => {
+                if logs::is_log_file(path) {
+                    // logs are tailed: we start at the most recent lines
+                    sv.select_last();
+                }
+                Self::Syntactic(sv)
+            }
             // not previewable as UTF8 text
             // we'll try reading it as binary
             _ => Self::hex(path),
@@ -99,8 +217,8 @@ impl Preview {
         con: &AppContext,
     ) -> Option<Self> {
         match self {
-            Self::Syntactic(_) => {
-                match SyntacticView::new(path, pattern, dam, con) {
+            Self::Syntactic(sv) => {
+                match sv.filtered(pattern, dam, con) {
 
                     // normal finished loading
                     Ok(Some(sv)) => Some(Self::Syntactic(sv)),
@@ -131,9 +249,12 @@ impl Preview {
     pub fn get_mode(&self) -> Option<PreviewMode> {
         match self {
             Self::Image(_) => Some(PreviewMode::Image),
+            Self::Markdown(_) => Some(PreviewMode::Markdown),
+            Self::Structured(_) => Some(PreviewMode::Structured),
+            Self::Diff(_) => Some(PreviewMode::Diff),
             Self::Syntactic(_) => Some(PreviewMode::Text),
             Self::Hex(_) => Some(PreviewMode::Hex),
-            Self::IOError => None,
+            Self::Archive(_) | Self::Dir(_) | Self::Metadata(_) | Self::IOError => None,
         }
     }
     pub fn pattern(&self) -> InputPattern {
@@ -147,6 +268,10 @@ impl Preview {
         cmd: ScrollCommand,
     ) -> bool {
         match self {
+            Self::Archive(av) => av.try_scroll(cmd),
+            Self::Diff(dv) => dv.try_scroll(cmd),
+            Self::Markdown(mv) => mv.try_scroll(cmd),
+            Self::Structured(sv) => sv.try_scroll(cmd),
             Self::Syntactic(sv) => sv.try_scroll(cmd),
             Self::Hex(hv) => hv.try_scroll(cmd),
             _ => false,
@@ -179,41 +304,138 @@ impl Preview {
     pub fn try_select_y(&mut self, y: u16) -> bool {
         match self {
             Self::Syntactic(sv) => sv.try_select_y(y),
+            Self::Structured(sv) => sv.try_select_y(y),
             _ => false,
         }
     }
     pub fn select_previous_line(&mut self) {
         match self {
             Self::Syntactic(sv) => sv.select_previous_line(),
+            Self::Structured(sv) => sv.select_previous_line(),
             Self::Hex(hv) => {
                 hv.try_scroll(ScrollCommand::Lines(-1));
             }
+            Self::Archive(av) => {
+                av.try_scroll(ScrollCommand::Lines(-1));
+            }
+            Self::Diff(dv) => {
+                dv.try_scroll(ScrollCommand::Lines(-1));
+            }
+            Self::Markdown(mv) => {
+                mv.try_scroll(ScrollCommand::Lines(-1));
+            }
             _ => {}
         }
     }
     pub fn select_next_line(&mut self) {
         match self {
             Self::Syntactic(sv) => sv.select_next_line(),
+            Self::Structured(sv) => sv.select_next_line(),
             Self::Hex(hv) => {
                 hv.try_scroll(ScrollCommand::Lines(1));
             }
+            Self::Archive(av) => {
+                av.try_scroll(ScrollCommand::Lines(1));
+            }
+            Self::Diff(dv) => {
+                dv.try_scroll(ScrollCommand::Lines(1));
+            }
+            Self::Markdown(mv) => {
+                mv.try_scroll(ScrollCommand::Lines(1));
+            }
             _ => {}
         }
     }
     pub fn select_first(&mut self) {
         match self {
             Self::Syntactic(sv) => sv.select_first(),
+            Self::Structured(sv) => sv.select_first(),
             Self::Hex(hv) => hv.select_first(),
+            Self::Archive(av) => av.select_first(),
+            Self::Diff(dv) => dv.select_first(),
+            Self::Markdown(mv) => mv.select_first(),
             _ => {}
         }
     }
     pub fn select_last(&mut self) {
         match self {
             Self::Syntactic(sv) => sv.select_last(),
+            Self::Structured(sv) => sv.select_last(),
             Self::Hex(hv) => hv.select_last(),
+            Self::Archive(av) => av.select_last(),
+            Self::Diff(dv) => dv.select_last(),
+            Self::Markdown(mv) => mv.select_last(),
             _ => {}
         }
     }
+    /// highlight the matches of the pattern without filtering
+    pub fn set_search(&mut self, pattern: &InputPattern) {
+        if let Self::Syntactic(sv) = self {
+            if let Err(e) = sv.set_search(pattern) {
+                warn!("error while searching {:?} : {:?}", &sv.path, e);
+            }
+        }
+    }
+    /// read more of the content of a text preview, when it was
+    /// truncated. Return false if there was nothing more to read
+    pub fn load_more(&mut self, con: &AppContext) -> bool {
+        match self {
+            Self::Syntactic(sv) => sv.load_more(con).unwrap_or_else(|e| {
+                warn!("error while reading {:?} : {:?}", &sv.path, e);
+                false
+            }),
+            _ => false,
+        }
+    }
+    /// whether the content of the preview must still be read,
+    /// with load, which is done as a pending task
+    pub fn needs_loading(&self) -> bool {
+        match self {
+            Self::Archive(av) => !av.is_loaded(),
+            _ => false,
+        }
+    }
+    /// read the content of the preview, unless interrupted
+    pub fn load(&mut self, dam: &Dam) {
+        if let Self::Archive(av) = self {
+            av.load_entries(dam);
+        }
+    }
+    /// whether the preview can be cached and reused when the
+    /// file is selected again without having been modified
+    pub fn is_cacheable(&self) -> bool {
+        match self {
+            // a directory may change without its own date changing
+            Self::Dir(_) | Self::Metadata(_) | Self::IOError => false,
+            _ => true,
+        }
+    }
+    /// fold or unfold the selected node of a structured view.
+    /// Return false if the preview isn't a structured one, or if
+    /// the selection can't be folded
+    pub fn toggle_fold(&mut self) -> bool {
+        match self {
+            Self::Structured(sv) => sv.toggle_fold(),
+            _ => false,
+        }
+    }
+    /// select the next (or previous) line with a match, or
+    /// the next (or previous) hunk of a diff
+    pub fn select_next_match(&mut self, forward: bool) -> bool {
+        match self {
+            Self::Syntactic(sv) => sv.select_next_match(forward),
+            Self::Diff(dv) => dv.select_next_hunk(forward),
+            _ => false,
+        }
+    }
+    /// stage the selected hunk of a diff, or unstage it if it's
+    /// staged. Return None if the preview isn't a diff
+    pub fn toggle_hunk_stage(&self) -> Option<Result<(), git2::Error>> {
+        match self {
+            Self::Diff(dv) => Some(dv.toggle_hunk_stage()),
+            _ => None,
+        }
+    }
     pub fn display(
         &mut self,
         w: &mut W,
@@ -223,7 +445,13 @@ impl Preview {
         con: &AppContext,
     ) -> Result<(), ProgramError> {
         match self {
+            Self::Archive(av) => av.display(w, screen, panel_skin, area),
+            Self::Diff(dv) => dv.display(w, screen, panel_skin, area),
+            Self::Dir(dv) => dv.display(w, screen, panel_skin, area, con),
             Self::Image(iv) => iv.display(w, screen, panel_skin, area, con),
+            Self::Markdown(mv) => mv.display(w, screen, panel_skin, area),
+            Self::Metadata(mv) => mv.display(w, screen, panel_skin, area),
+            Self::Structured(sv) => sv.display(w, screen, panel_skin, area),
             Self::Syntactic(sv) => sv.display(w, screen, panel_skin, area, con),
             Self::Hex(hv) => hv.display(w, screen, panel_skin, area),
             Self::IOError => {
@@ -244,6 +472,8 @@ impl Preview {
             Self::Image(iv) => iv.display_info(w, screen, panel_skin, area),
             Self::Syntactic(sv) => sv.display_info(w, screen, panel_skin, area),
             Self::Hex(hv) => hv.display_info(w, screen, panel_skin, area),
+            Self::Archive(av) => av.display_info(w, screen, panel_skin, area),
+            Self::Diff(dv) => dv.display_info(w, screen, panel_skin, area),
             _ => Ok(()),
         }
     }
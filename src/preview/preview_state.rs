@@ -6,19 +6,42 @@ use {
         display::{CropWriter, LONG_SPACE, Screen, W},
         errors::ProgramError,
         flag::Flag,
+        magic,
         pattern::InputPattern,
         skin::PanelSkin,
         task_sync::Dam,
+        temp_text::TempText,
+        tree::TreeOptions,
         verb::*,
     },
     crossterm::{
         cursor,
         QueueableCommand,
     },
-    std::path::{Path, PathBuf},
+    std::{
+        mem,
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
     termimad::Area,
 };
 
+/// how many previews of previously selected files are kept
+/// so that they're not read again
+const CACHE_SIZE: usize = 5;
+
+/// a preview kept so that it's not built again if its
+/// file is selected again without having been modified
+struct CachedPreview {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    preview: Preview,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
 /// an application state dedicated to previewing files.
 /// It's usually the only state in its panel and is kept when
 /// the selection changes (other panels indirectly call
@@ -32,6 +55,12 @@ pub struct PreviewState {
     filtered_preview: Option<Preview>,
     removed_pattern: InputPattern,
     prefered_mode: Option<PreviewMode>,
+    tree_options: TreeOptions, // the options of the tree of a previewed directory
+    follow: bool, // whether the file is followed, like with `tail -f`
+    followed_len: Option<u64>, // the size of the followed file when last read
+    cache: Vec<CachedPreview>, // the most recently left previews at the end
+    mime: Option<String>, // the mime type of the file, detected from its content if possible
+    temp_text: Option<TempText>, // the temporary file previewed, removed with the state
 }
 
 impl PreviewState {
@@ -42,7 +71,9 @@ impl PreviewState {
         con: &AppContext,
     ) -> PreviewState {
         let preview_area = Area::uninitialized(); // will be fixed at drawing time
-        let preview = Preview::new(&path, prefered_mode, con);
+        let tree_options = con.launch_args.tree_options.without_pattern();
+        let preview = Preview::new(&path, prefered_mode, &tree_options, con);
+        let mime = Self::mime_type(&path);
         PreviewState {
             preview_area,
             dirty: true,
@@ -52,6 +83,75 @@ impl PreviewState {
             filtered_preview: None,
             removed_pattern: InputPattern::none(),
             prefered_mode,
+            tree_options,
+            follow: false,
+            followed_len: None,
+            cache: Vec::new(),
+            mime,
+            temp_text: None,
+        }
+    }
+    /// build a state previewing a temporary text, which is
+    /// followed as long as it's being written
+    pub fn of_temp_text(
+        temp_text: TempText,
+        con: &AppContext,
+    ) -> PreviewState {
+        let mut state = Self::new(
+            temp_text.path().to_path_buf(),
+            InputPattern::none(),
+            Some(PreviewMode::Text),
+            con,
+        );
+        state.follow = temp_text.is_running();
+        state.followed_len = state.file_len();
+        state.temp_text = Some(temp_text);
+        state
+    }
+    fn mime_type(path: &Path) -> Option<String> {
+        if path.is_file() {
+            magic::mime_type(path)
+        } else {
+            None
+        }
+    }
+    /// keep the current preview in cache, as it's being replaced
+    fn cache_preview(&mut self) {
+        let preview = mem::replace(&mut self.preview, Preview::IOError);
+        if !preview.is_cacheable() {
+            return;
+        }
+        if self.cache.len() >= CACHE_SIZE {
+            self.cache.remove(0);
+        }
+        self.cache.push(CachedPreview {
+            path: self.path.clone(),
+            modified: modified(&self.path),
+            preview,
+        });
+    }
+    /// take the preview of the file out of the cache, if there's
+    /// one and the file wasn't modified since it was built
+    fn take_cached_preview(&mut self, path: &Path) -> Option<Preview> {
+        let idx = self.cache.iter().position(|c| c.path == path)?;
+        let cached = self.cache.remove(idx);
+        if cached.modified.is_some() && cached.modified == modified(path) {
+            Some(cached.preview)
+        } else {
+            None
+        }
+    }
+    fn file_len(&self) -> Option<u64> {
+        self.path.metadata().ok().map(|m| m.len())
+    }
+    /// reread the followed file if it changed, and show its end
+    fn check_followed_file(&mut self, con: &AppContext) {
+        let len = self.file_len();
+        if len != self.followed_len {
+            self.followed_len = len;
+            self.set_selected_path(self.path.clone(), None, con);
+            self.mut_preview().select_last();
+            self.dirty = true;
         }
     }
     fn mut_preview(&mut self) -> &mut Preview {
@@ -69,6 +169,8 @@ impl PreviewState {
             Ok(preview) => {
                 self.preview = preview;
                 self.prefered_mode = Some(mode);
+                // the cached previews may have been built with another mode
+                self.cache.clear();
                 AppStateCmdResult::Keep
             }
             Err(e) => {
@@ -82,8 +184,18 @@ impl PreviewState {
 
 impl AppState for PreviewState {
 
+    fn get_type(&self) -> AppStateType {
+        AppStateType::Preview
+    }
+
+    fn is_following(&self) -> bool {
+        self.follow
+    }
+
     fn get_pending_task(&self) -> Option<&'static str> {
-        if self.pending_pattern.is_some() {
+        if self.preview.needs_loading() {
+            Some("reading")
+        } else if self.pending_pattern.is_some() {
             Some("searching")
         } else {
             None
@@ -102,6 +214,8 @@ impl AppState for PreviewState {
                     self.preview.try_select_line_number(number);
                 }
                 self.removed_pattern = filtered_preview.pattern();
+                // the matches stay highlighted in the whole text
+                self.preview.set_search(&self.removed_pattern);
             }
         } else {
             if !self.preview.is_filterable() {
@@ -121,6 +235,10 @@ impl AppState for PreviewState {
         con: &AppContext,
         dam: &mut Dam,
     ) {
+        if self.preview.needs_loading() {
+            self.preview.load(dam);
+            return;
+        }
         if self.pending_pattern.is_some() {
             let old_selection = self
                 .filtered_preview.as_ref().and_then(|p| p.get_selected_line_number())
@@ -143,25 +261,62 @@ impl AppState for PreviewState {
         &self.path
     }
 
-    fn set_selected_path(&mut self, path: PathBuf, con: &AppContext) {
+    fn set_selected_path(
+        &mut self,
+        path: PathBuf,
+        tree_options: Option<&TreeOptions>,
+        con: &AppContext,
+    ) {
+        if self.follow && path != self.path {
+            // the followed file stays displayed while browsing
+            return;
+        }
         if let Some(fp) = &self.filtered_preview {
             self.pending_pattern = fp.pattern();
         };
-        self.preview = Preview::new(&path, self.prefered_mode, con);
+        if let Some(tree_options) = tree_options {
+            self.tree_options = tree_options.without_pattern();
+        }
+        self.preview = if path == self.path {
+            // it's a refresh, the file must be read again
+            Preview::new(&path, self.prefered_mode, &self.tree_options, con)
+        } else {
+            self.cache_preview();
+            self.take_cached_preview(&path).unwrap_or_else(|| {
+                Preview::new(&path, self.prefered_mode, &self.tree_options, con)
+            })
+        };
+        if self.removed_pattern.is_some() {
+            self.preview.set_search(&self.removed_pattern);
+        }
+        self.mime = Self::mime_type(&path);
         self.path = path;
     }
 
     fn selection(&self) -> Selection<'_> {
         Selection {
             path: &self.path,
-            stype: SelectionType::File,
+            stype: SelectionType::of(&self.path),
             line: self.preview.get_selected_line_number().unwrap_or(0),
+            marked: &[],
         }
     }
 
     fn refresh(&mut self, _screen: &Screen, con: &AppContext) -> Command {
         self.dirty = true;
-        self.set_selected_path(self.path.clone(), con);
+        // a diff is computed again in place, so that the selected hunk stays selected
+        if let Preview::Diff(dv) = &mut self.preview {
+            if dv.reload().is_ok() {
+                return Command::empty();
+            }
+        }
+        // the file was probably only slightly changed, so the same
+        // line is selected again instead of going back to the top
+        let selected_line = self.preview.get_selected_line_number();
+        self.set_selected_path(self.path.clone(), None, con);
+        if let Some(number) = selected_line {
+            self.preview.try_select_line_number(number);
+        }
         Command::empty()
     }
 
@@ -191,6 +346,13 @@ impl AppState for PreviewState {
             warn!("area too small for preview");
             return Ok(());
         }
+        if self.follow {
+            let finished = self.temp_text.as_mut().is_some_and(|t| t.check_finished());
+            self.check_followed_file(con);
+            if finished {
+                self.follow = false;
+            }
+        }
         let mut preview_area = state_area.clone();
         preview_area.height -= 1;
         preview_area.top += 1;
@@ -204,12 +366,15 @@ impl AppState for PreviewState {
             self.dirty = false;
         }
         let styles = &panel_skin.styles;
-        w.queue(cursor::MoveTo(state_area.left, 0))?;
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
         let mut cw = CropWriter::new(w, state_area.width as usize);
         let file_name = self.path.file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "???".to_string());
         cw.queue_str(&styles.default, &file_name)?;
+        if self.follow {
+            cw.queue_str(&styles.default, " (following)")?;
+        }
         let info_area = Area::new(
             state_area.left + state_area.width - cw.allowed as u16,
             state_area.top,
@@ -234,7 +399,11 @@ impl AppState for PreviewState {
         ssb.has_previous_state = has_previous_state;
         ssb.is_filtered = self.filtered_preview.is_some();
         ssb.has_removed_pattern = self.removed_pattern.is_some();
-        ssb.status()
+        let mut status = ssb.status();
+        if let Some(mime) = &self.mime {
+            status.message = format!("`{}` {}", mime, status.message);
+        }
+        status
     }
 
     fn on_internal(
@@ -254,6 +423,25 @@ impl AppState for PreviewState {
                     Ok(AppStateCmdResult::PopState)
                 }
             }
+            Internal::goto_line => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                Ok(match arg.map(|arg| arg.trim().parse::<LineNumber>()) {
+                    Some(Ok(number)) if number > 0 => {
+                        if self.mut_preview().try_select_line_number(number) {
+                            AppStateCmdResult::Keep
+                        } else {
+                            AppStateCmdResult::DisplayError(
+                                format!("line {} isn't in the preview", number)
+                            )
+                        }
+                    }
+                    _ => AppStateCmdResult::DisplayError(
+                        "a line number is expected, eg `:goto 120`".to_string()
+                    ),
+                })
+            }
             Internal::line_down => {
                 self.mut_preview().select_next_line();
                 Ok(AppStateCmdResult::Keep)
@@ -278,6 +466,15 @@ impl AppState for PreviewState {
             Internal::panel_left if self.removed_pattern.is_some() => {
                 debug!("restoring pattern");
                 self.pending_pattern = self.removed_pattern.take();
+                self.preview.set_search(&InputPattern::none());
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::next_match => {
+                self.mut_preview().select_next_match(true);
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::previous_match => {
+                self.mut_preview().select_next_match(false);
                 Ok(AppStateCmdResult::Keep)
             }
             Internal::panel_right if self.filtered_preview.is_some() => {
@@ -294,6 +491,95 @@ impl AppState for PreviewState {
             Internal::preview_image => self.set_mode(PreviewMode::Image, cc.con),
             Internal::preview_text => self.set_mode(PreviewMode::Text, cc.con),
             Internal::preview_binary => self.set_mode(PreviewMode::Hex, cc.con),
+            Internal::preview_toggle_binary => {
+                let mode = if self.preview.get_mode() == Some(PreviewMode::Hex) {
+                    PreviewMode::Text
+                } else {
+                    PreviewMode::Hex
+                };
+                self.set_mode(mode, cc.con)
+            }
+            Internal::preview_toggle_diff => {
+                let mode = if self.preview.get_mode() == Some(PreviewMode::Diff) {
+                    PreviewMode::Text
+                } else {
+                    PreviewMode::Diff
+                };
+                self.set_mode(mode, cc.con)
+            }
+            Internal::preview_load_more => {
+                if self.preview.load_more(cc.con) {
+                    if let Some(fp) = self.filtered_preview.take() {
+                        self.pending_pattern = fp.pattern();
+                    }
+                    Ok(AppStateCmdResult::Keep)
+                } else {
+                    Ok(AppStateCmdResult::DisplayError(
+                        "nothing more to read in this preview".to_string()
+                    ))
+                }
+            }
+            Internal::preview_toggle_follow => {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.followed_len = self.file_len();
+                    self.mut_preview().select_last();
+                }
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::preview_toggle_structured => {
+                let mode = if self.preview.get_mode() == Some(PreviewMode::Structured) {
+                    PreviewMode::Text
+                } else {
+                    PreviewMode::Structured
+                };
+                self.set_mode(mode, cc.con)
+            }
+            Internal::git_stage_hunk => {
+                Ok(match self.preview.toggle_hunk_stage() {
+                    // the diff and the git status of the trees must be refreshed
+                    Some(Ok(())) => AppStateCmdResult::RefreshState { clear_cache: true },
+                    Some(Err(e)) => AppStateCmdResult::DisplayError(
+                        format!("staging failed: {}", e.message())
+                    ),
+                    None => AppStateCmdResult::DisplayError(
+                        "only the hunks of a diff preview can be staged".to_string()
+                    ),
+                })
+            }
+            Internal::toggle_fold => {
+                if self.preview.toggle_fold() {
+                    Ok(AppStateCmdResult::Keep)
+                } else {
+                    Ok(AppStateCmdResult::DisplayError(
+                        "only the objects and arrays of a structured preview can be folded".to_string()
+                    ))
+                }
+            }
+            // in a structured preview, enter and → fold and unfold
+            // the containers
+            Internal::open_stay if self.preview.get_mode() == Some(PreviewMode::Structured) => {
+                if self.preview.toggle_fold() {
+                    Ok(AppStateCmdResult::Keep)
+                } else {
+                    self.on_internal_generic(
+                        w,
+                        internal_exec,
+                        input_invocation,
+                        trigger_type,
+                        cc,
+                        screen,
+                    )
+                }
+            }
+            Internal::preview_toggle_markdown => {
+                let mode = if self.preview.get_mode() == Some(PreviewMode::Markdown) {
+                    PreviewMode::Text
+                } else {
+                    PreviewMode::Markdown
+                };
+                self.set_mode(mode, cc.con)
+            }
             _ => self.on_internal_generic(
                 w,
                 internal_exec,
@@ -0,0 +1,317 @@
+use {
+    crate::{
+        command::ScrollCommand,
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+    },
+    crossterm::{
+        cursor,
+        style::{Color, Print, SetForegroundColor},
+        QueueableCommand,
+    },
+    serde_json::Value,
+    std::{
+        fs,
+        io,
+        path::Path,
+    },
+    termimad::{Area, CompoundStyle},
+};
+
+/// a value of the structured file: either a container whose
+/// children can be folded, or a scalar
+struct Node {
+    key: Option<String>, // the key in the parent object, if any
+    kind: NodeKind,
+    folded: bool,
+}
+
+enum NodeKind {
+    Object(Vec<Node>),
+    Array(Vec<Node>),
+    Scalar(String), // the value as it would be written in JSON
+}
+
+/// a displayed line: the path to the node (the indexes of the
+/// children from the root), whose length is the depth
+struct VisibleLine {
+    path: Vec<usize>,
+}
+
+/// a preview of a JSON, YAML or TOML file as a tree whose
+/// objects and arrays can be folded and unfolded
+pub struct StructuredView {
+    root: Node,
+    lines: Vec<VisibleLine>,
+    selection_idx: usize,
+    scroll: usize,
+    page_height: usize,
+}
+
+impl Node {
+    fn from_value(key: Option<String>, value: Value) -> Self {
+        let kind = match value {
+            Value::Object(map) => NodeKind::Object(
+                map.into_iter()
+                    .map(|(k, v)| Node::from_value(Some(k), v))
+                    .collect()
+            ),
+            Value::Array(arr) => NodeKind::Array(
+                arr.into_iter()
+                    .map(|v| Node::from_value(None, v))
+                    .collect()
+            ),
+            scalar => NodeKind::Scalar(scalar.to_string()),
+        };
+        Self {
+            key,
+            kind,
+            folded: false,
+        }
+    }
+    fn children(&self) -> &[Node] {
+        match &self.kind {
+            NodeKind::Object(children) | NodeKind::Array(children) => children,
+            NodeKind::Scalar(_) => &[],
+        }
+    }
+    fn child_mut(&mut self, idx: usize) -> Option<&mut Node> {
+        match &mut self.kind {
+            NodeKind::Object(children) | NodeKind::Array(children) => children.get_mut(idx),
+            NodeKind::Scalar(_) => None,
+        }
+    }
+    fn is_container(&self) -> bool {
+        !matches!(self.kind, NodeKind::Scalar(_))
+    }
+}
+
+/// parse the file according to its extension, failing if
+/// it's not a JSON, YAML or TOML file or if it's invalid
+fn parse(path: &Path) -> io::Result<Value> {
+    let ext = path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let content = fs::read_to_string(path)?;
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    match ext.as_deref() {
+        Some("json") => serde_json::from_str(&content).map_err(|e| invalid(e.to_string())),
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string())),
+        Some("toml") => toml::from_str(&content).map_err(|e| invalid(e.to_string())),
+        _ => Err(invalid("not a JSON, YAML or TOML file".to_string())),
+    }
+}
+
+impl StructuredView {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let root = Node::from_value(None, parse(path)?);
+        let mut sv = Self {
+            root,
+            lines: Vec::new(),
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+        };
+        sv.compute_lines();
+        Ok(sv)
+    }
+    /// compute the list of visible lines, skipping the
+    /// children of the folded nodes
+    fn compute_lines(&mut self) {
+        fn add_lines(node: &Node, path: Vec<usize>, lines: &mut Vec<VisibleLine>) {
+            let folded = node.folded;
+            let children = node.children();
+            lines.push(VisibleLine { path: path.clone() });
+            if !folded {
+                for (idx, child) in children.iter().enumerate() {
+                    let mut child_path = path.clone();
+                    child_path.push(idx);
+                    add_lines(child, child_path, lines);
+                }
+            }
+        }
+        self.lines.clear();
+        add_lines(&self.root, Vec::new(), &mut self.lines);
+    }
+    fn node(&self, path: &[usize]) -> &Node {
+        let mut node = &self.root;
+        for &idx in path {
+            node = &node.children()[idx];
+        }
+        node
+    }
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut Node> {
+        let mut node = &mut self.root;
+        for &idx in path {
+            node = node.child_mut(idx)?;
+        }
+        Some(node)
+    }
+    /// fold the selected object or array if it's unfolded,
+    /// unfold it if it's folded.
+    /// Return false when the selection isn't a container
+    pub fn toggle_fold(&mut self) -> bool {
+        let path = match self.lines.get(self.selection_idx) {
+            Some(line) => line.path.clone(),
+            None => return false,
+        };
+        match self.node_mut(&path) {
+            Some(node) if node.is_container() => {
+                node.folded = !node.folded;
+            }
+            _ => {
+                return false;
+            }
+        }
+        self.compute_lines();
+        // lines before the selection aren't changed by
+        // folding, so the selection index is still valid
+        self.ensure_selection_is_visible();
+        true
+    }
+    fn ensure_selection_is_visible(&mut self) {
+        if self.page_height == 0 {
+            return;
+        }
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+    pub fn try_scroll(
+        &mut self,
+        cmd: ScrollCommand,
+    ) -> bool {
+        let old_scroll = self.scroll;
+        self.scroll = cmd.apply(self.scroll, self.lines.len(), self.page_height);
+        if self.page_height > 0 {
+            // the selection is kept in the visible part
+            if self.selection_idx < self.scroll {
+                self.selection_idx = self.scroll;
+            } else if self.selection_idx >= self.scroll + self.page_height {
+                self.selection_idx = self.scroll + self.page_height - 1;
+            }
+        }
+        self.scroll != old_scroll
+    }
+    pub fn try_select_y(&mut self, y: u16) -> bool {
+        let idx = y as usize + self.scroll;
+        if idx < self.lines.len() {
+            self.selection_idx = idx;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn select_previous_line(&mut self) {
+        if self.selection_idx > 0 {
+            self.selection_idx -= 1;
+        } else {
+            self.selection_idx = self.lines.len() - 1;
+        }
+        self.ensure_selection_is_visible();
+    }
+    pub fn select_next_line(&mut self) {
+        if self.selection_idx + 1 < self.lines.len() {
+            self.selection_idx += 1;
+        } else {
+            self.selection_idx = 0;
+        }
+        self.ensure_selection_is_visible();
+    }
+    pub fn select_first(&mut self) {
+        self.selection_idx = 0;
+        self.scroll = 0;
+    }
+    pub fn select_last(&mut self) {
+        self.selection_idx = self.lines.len() - 1;
+        self.ensure_selection_is_visible();
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        if area.height as usize != self.page_height {
+            self.page_height = area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let scrollbar = area.scrollbar(self.scroll as i32, self.lines.len() as i32);
+        let scrollbar_fg = styles.scrollbar_thumb.get_fg()
+            .or_else(|| styles.preview.get_fg())
+            .unwrap_or(Color::White);
+        for y in 0..area.height as usize {
+            w.queue(cursor::MoveTo(area.left, y as u16 + area.top))?;
+            let mut cw = CropWriter::new(w, area.width as usize - 1); // -1 for scrollbar
+            let line_idx = self.scroll + y;
+            let selected = line_idx == self.selection_idx;
+            let with_bg = |style: &CompoundStyle| {
+                let mut style = style.clone();
+                if selected {
+                    if let Some(c) = styles.selected_line.get_bg() {
+                        style.set_bg(c);
+                    }
+                }
+                style
+            };
+            let tree_style = with_bg(&styles.tree);
+            let key_style = with_bg(&styles.directory);
+            let value_style = with_bg(&styles.preview);
+            let info_style = with_bg(&styles.preview_line_number);
+            if let Some(line) = self.lines.get(line_idx) {
+                let node = self.node(&line.path);
+                cw.queue_g_string(&tree_style, "  ".repeat(line.path.len()))?;
+                let marker = match (node.is_container(), node.folded) {
+                    (false, _) => ' ',
+                    (true, false) => '▾',
+                    (true, true) => '▸',
+                };
+                cw.queue_g_string(&tree_style, format!("{} ", marker))?;
+                if let Some(key) = &node.key {
+                    cw.queue_str(&key_style, key)?;
+                    cw.queue_str(&tree_style, ": ")?;
+                } else if let Some(&idx) = line.path.last() {
+                    cw.queue_g_string(&tree_style, format!("{}: ", idx))?;
+                }
+                match &node.kind {
+                    NodeKind::Scalar(s) => {
+                        cw.queue_str(&value_style, s)?;
+                    }
+                    NodeKind::Object(children) => {
+                        let brackets = if node.folded { "{…}" } else { "{" };
+                        cw.queue_str(&tree_style, brackets)?;
+                        cw.queue_g_string(&info_style, format!(" {} keys", children.len()))?;
+                    }
+                    NodeKind::Array(children) => {
+                        let brackets = if node.folded { "[…]" } else { "[" };
+                        cw.queue_str(&tree_style, brackets)?;
+                        cw.queue_g_string(&info_style, format!(" {} items", children.len()))?;
+                    }
+                }
+            }
+            cw.fill(&value_style, LONG_SPACE)?;
+            if is_thumb(y, scrollbar) {
+                w.queue(SetForegroundColor(scrollbar_fg))?;
+                w.queue(Print('▐'))?;
+            } else {
+                w.queue(Print(' '))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_thumb(y: usize, scrollbar: Option<(u16, u16)>) -> bool {
+    if let Some((sctop, scbottom)) = scrollbar {
+        let y = y as u16;
+        if sctop <= y && y <= scbottom {
+            return true;
+        }
+    }
+    false
+}
@@ -13,6 +13,7 @@ use {
 };
 
 /// parsed content of a [skin] line of the conf.toml file
+#[derive(Clone)]
 pub struct SkinEntry {
     focused: CompoundStyle,
     unfocused: Option<CompoundStyle>,
@@ -119,13 +119,18 @@ StyleMap! {
     directory: ansi(110), None, [Bold] / ansi(110), None, []
     exe: Some(Cyan), None, []
     link: Some(Magenta), None, []
+    special: Some(Yellow), None, []
     pruning: gray(12), None, [Italic]
+    dimmed: gray(13), None, [Italic] / gray(9), None, [Italic]
     perm__: gray(5), None, []
     perm_r: ansi(94), None, []
     perm_w: ansi(132), None, []
     perm_x: ansi(65), None, []
     owner: ansi(138), None, []
     group: ansi(131), None, []
+    owner_mismatch: ansi(202), None, [Underlined]
+    immutable: None, None, [Italic]
+    unpaired: ansi(178), None, [Underlined]
     count: ansi(138), gray(4), []
     dates: ansi(66), None, []
     sparse: ansi(214), None, []
@@ -146,6 +151,7 @@ StyleMap! {
     flag_label: gray(15), gray(2), []
     flag_value: ansi(178), gray(2), [Bold]
     input: Some(White), gray(2), [] / gray(15), None, []
+    input_ghost: gray(10), gray(2), [Italic]
     status_error: gray(22), ansi(124), []
     status_job: ansi(220), gray(5), []
     status_normal: gray(20), gray(4), [] / gray(2), gray(2), []
@@ -157,6 +163,9 @@ StyleMap! {
     purpose_italic: ansi(178), gray(2), []
     purpose_bold: ansi(178), gray(2), [Bold]
     purpose_ellipsis: gray(20), gray(2), []
+    panel_title: gray(22), gray(5), [Bold] / gray(14), gray(3), []
+    tab: gray(15), gray(2), []
+    tab_current: gray(22), gray(5), [Bold]
     scrollbar_track: gray(7), None, [] / gray(4), None, []
     scrollbar_thumb: gray(22), None, [] / gray(14), None, []
     help_paragraph: gray(20), None, []
@@ -173,6 +182,8 @@ StyleMap! {
     hex_ascii_whitespace: ansi(143), None, []
     hex_ascii_other: ansi(215), None, []
     hex_non_ascii: ansi(167), None, []
+    diff_added: None, ansi(22), []
+    diff_removed: None, ansi(52), []
 }
 
 impl fmt::Debug for StyleMap {
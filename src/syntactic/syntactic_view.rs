@@ -3,6 +3,7 @@ use {
     crate::{
         app::{AppContext, LineNumber},
         command::{ScrollCommand},
+        compression,
         display::{CropWriter, LONG_SPACE, Screen, W},
         errors::ProgramError,
         pattern::{InputPattern, NameMatch},
@@ -68,6 +69,10 @@ pub struct SyntacticView {
     page_height: usize,
     selection_idx: Option<usize>, // index in lines of the selection, if any
     total_lines_count: usize, // including lines not filtered out
+    decompressed: Option<Vec<u8>>, // content of the file, when it's compressed or converted
+    converted: bool, // whether the content comes from a converting command
+    max_bytes: u64, // the lines after this offset aren't read
+    truncated: bool, // whether some content wasn't read because of max_bytes
 }
 
 impl SyntacticView {
@@ -80,6 +85,43 @@ impl SyntacticView {
         pattern: InputPattern,
         dam: &mut Dam,
         con: &AppContext,
+    ) -> io::Result<Option<Self>> {
+        Self::build(path, None, pattern, con.max_preview_size, dam, con)
+    }
+
+    /// return a prepared text view of the content a converting
+    /// command produced from the file
+    pub fn from_converted(
+        path: &Path,
+        content: Vec<u8>,
+        pattern: InputPattern,
+        dam: &mut Dam,
+        con: &AppContext,
+    ) -> io::Result<Option<Self>> {
+        Self::build(path, Some(content), pattern, con.max_preview_size, dam, con)
+    }
+
+    /// return a view of the same content, with the same size
+    /// limit, keeping only the lines matching the pattern.
+    /// Return Ok(None) if there was an event before the end
+    /// of filtering.
+    pub fn filtered(
+        &self,
+        pattern: InputPattern,
+        dam: &mut Dam,
+        con: &AppContext,
+    ) -> io::Result<Option<Self>> {
+        let converted = self.converted_content().map(|content| content.to_vec());
+        Self::build(&self.path, converted, pattern, self.max_bytes, dam, con)
+    }
+
+    fn build(
+        path: &Path,
+        converted: Option<Vec<u8>>,
+        pattern: InputPattern,
+        max_bytes: u64,
+        dam: &mut Dam,
+        con: &AppContext,
     ) -> io::Result<Option<Self>> {
         let mut sv = Self {
             path: path.to_path_buf(),
@@ -89,6 +131,10 @@ impl SyntacticView {
             page_height: 0,
             selection_idx: None,
             total_lines_count: 0,
+            converted: converted.is_some(),
+            decompressed: converted,
+            max_bytes,
+            truncated: false,
         };
         if sv.read_lines(dam, con)? {
             sv.select_first();
@@ -100,9 +146,31 @@ impl SyntacticView {
 
     /// return true when there was no interruption
     fn read_lines(&mut self, dam: &mut Dam, con: &AppContext) -> io::Result<bool> {
-        let f = File::open(&self.path)?;
-        let with_style = f.metadata()?.len() < MAX_SIZE_FOR_STYLING;
-        let mut reader = BufReader::new(f);
+        // compressed files are decompressed in memory, the offsets
+        // of the lines then being relative to the decompressed content
+        let decompressed = if self.converted {
+            self.decompressed.take()
+        } else {
+            compression::read_decompressed(
+                &self.path,
+                compression::MAX_DECOMPRESSED_SIZE,
+            )?
+        };
+        let (content_len, mut reader): (u64, Box<dyn BufRead + '_>) = match &decompressed {
+            Some(bytes) => (
+                bytes.len() as u64,
+                Box::new(bytes.as_slice()),
+            ),
+            None => {
+                let f = File::open(&self.path)?;
+                (
+                    f.metadata()?.len(),
+                    Box::new(BufReader::new(f)),
+                )
+            }
+        };
+        let with_style = content_len.min(self.max_bytes) < MAX_SIZE_FOR_STYLING;
+        self.truncated = content_len > self.max_bytes;
         self.lines.clear();
         let mut line = String::new();
         self.total_lines_count = 0;
@@ -117,7 +185,7 @@ impl SyntacticView {
             None
         };
         let pattern = &self.pattern.pattern;
-        while reader.read_line(&mut line)? > 0 {
+        while offset < self.max_bytes as usize && reader.read_line(&mut line)? > 0 {
             number += 1;
             self.total_lines_count += 1;
             let start = offset;
@@ -150,6 +218,33 @@ impl SyntacticView {
                 return Ok(false);
             }
         }
+        drop(reader);
+        self.decompressed = decompressed;
+        Ok(true)
+    }
+
+    /// the content produced by a converting command, if the
+    /// view doesn't show the file itself
+    pub fn converted_content(&self) -> Option<&[u8]> {
+        if self.converted {
+            self.decompressed.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// read the next part of the content, when it was truncated.
+    /// Return false if there was nothing more to read
+    pub fn load_more(&mut self, con: &AppContext) -> io::Result<bool> {
+        if !self.truncated {
+            return Ok(false);
+        }
+        let selected_line = self.get_selected_line_number();
+        self.max_bytes += con.max_preview_size;
+        self.read_lines(&mut Dam::unlimited(), con)?;
+        if let Some(number) = selected_line {
+            self.try_select_line_number(number);
+        }
         Ok(true)
     }
 
@@ -204,7 +299,15 @@ impl SyntacticView {
         }
     }
     pub fn select_last(&mut self) {
+        if self.lines.is_empty() {
+            return;
+        }
         self.selection_idx = Some(self.lines.len()-1);
+        if self.page_height == 0 {
+            // the view isn't yet displayed, the scroll will be
+            // fixed at display time
+            return;
+        }
         if self.page_height < self.lines.len() {
             self.scroll = self.lines.len() - self.page_height;
         }
@@ -248,6 +351,61 @@ impl SyntacticView {
         self.ensure_selection_is_visible();
     }
 
+    /// highlight the matches of the pattern in the lines, without
+    /// filtering out the lines which don't match.
+    /// Return the number of matching lines.
+    pub fn set_search(&mut self, pattern: &InputPattern) -> io::Result<usize> {
+        if self.pattern.is_some() || self.lines.is_empty() {
+            // the lines are already filtered and highlighted
+            return Ok(0);
+        }
+        let mmap;
+        let bytes: &[u8] = match &self.decompressed {
+            Some(bytes) => bytes,
+            None => {
+                let file = File::open(&self.path)?;
+                mmap = unsafe { Mmap::map(&file)? };
+                &mmap
+            }
+        };
+        let mut count = 0;
+        for line in self.lines.iter_mut() {
+            line.name_match = if pattern.is_some() && line.start + line.len <= bytes.len() {
+                let string = String::from_utf8_lossy(&bytes[line.start..line.start+line.len]);
+                pattern.pattern.search_string(&string)
+            } else {
+                None
+            };
+            if line.name_match.is_some() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// select the next (or previous) line with a match,
+    /// starting from the selection and wrapping around
+    pub fn select_next_match(&mut self, forward: bool) -> bool {
+        let len = self.lines.len();
+        if len == 0 {
+            return false;
+        }
+        let start = self.selection_idx.unwrap_or(if forward { len - 1 } else { 0 });
+        for i in 1..=len {
+            let idx = if forward {
+                (start + i) % len
+            } else {
+                (start + len - i % len) % len
+            };
+            if self.lines[idx].name_match.is_some() {
+                self.selection_idx = Some(idx);
+                self.ensure_selection_is_visible();
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn try_scroll(
         &mut self,
         cmd: ScrollCommand,
@@ -275,7 +433,10 @@ impl SyntacticView {
             self.ensure_selection_is_visible();
         }
         let max_number_len = self.lines.last().map_or(0, |l|l.number).to_string().len();
-        let show_line_number = area.width > 55 || ( self.pattern.is_some() && area.width > 8 );
+        let show_line_number = match con.show_line_numbers {
+            Some(show) => show && area.width > 8,
+            None => area.width > 55 || ( self.pattern.is_some() && area.width > 8 ),
+        };
         let line_count = area.height as usize;
         let styles = &panel_skin.styles;
         let normal_fg  = styles.preview.get_fg()
@@ -306,7 +467,16 @@ impl SyntacticView {
             if let Some(line) = self.lines.get(line_idx) {
                 let mut regions = &line.regions;
                 let regions_ur;
-                if regions.is_empty() && line.len > 0 {
+                let decompressed = self.decompressed.as_ref()
+                    .filter(|_| regions.is_empty() && line.len > 0);
+                if let Some(bytes) = decompressed {
+                    let string = String::from_utf8_lossy(&bytes[line.start..line.start+line.len]);
+                    regions_ur = vec![Region {
+                        fg: normal_fg,
+                        string: string.to_string(),
+                    }];
+                    regions = &regions_ur;
+                } else if regions.is_empty() && line.len > 0 {
                     if op_mmap.is_none() {
                         let file = File::open(&self.path)?;
                         let mmap = unsafe { Mmap::map(&file)? };
@@ -398,6 +568,10 @@ impl SyntacticView {
         } else {
             format!("{}", self.total_lines_count)
         };
+        if self.truncated {
+            // there are more lines, not yet read
+            s.push('+');
+        }
         if s.len() > width {
             return Ok(());
         }
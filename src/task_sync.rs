@@ -1,6 +1,9 @@
 use {
     crossbeam::channel::{self, bounded, Receiver},
-    std::thread,
+    std::{
+        thread,
+        time::{Duration, Instant},
+    },
     termimad::Event,
 };
 
@@ -121,6 +124,19 @@ impl Dam {
         !self.receiver.is_empty()
     }
 
+    /// wait until there's an event or the duration elapsed, without
+    /// consuming the event. Return true when there's an event.
+    pub fn wait_for_event(&self, duration: Duration) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            if self.has_event() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        self.has_event()
+    }
+
     /// block until next event (including the one which
     ///  may have been pushed back into the dam).
     /// no event means the source is dead (i.e. we
@@ -0,0 +1,106 @@
+//! temporary text files, made to be displayed in a preview
+//! then removed when the preview is closed
+
+use {
+    std::{
+        fs::File,
+        io::{self, Write},
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+    },
+    tempfile::TempPath,
+};
+
+/// a text written in a temporary file, either at once or
+/// progressively by a background task.
+///
+/// The file is removed, and the task asked to stop, when the
+/// TempText is dropped (usually with the preview state owning it).
+pub struct TempText {
+    path: TempPath,
+    stop: Arc<AtomicBool>,
+    task_running: Arc<AtomicBool>,
+    running: bool, // whether the task was running when last checked
+}
+
+impl TempText {
+
+    fn create(name: &str) -> io::Result<(File, TempPath)> {
+        let suffix = format!("-{}", name);
+        let file = tempfile::Builder::new()
+            .prefix("broot-")
+            .suffix(&suffix)
+            .tempfile()?;
+        Ok(file.into_parts())
+    }
+
+    /// write the text in a new temporary file whose name
+    /// ends with the given one
+    pub fn write(name: &str, text: &str) -> io::Result<Self> {
+        let (mut file, path) = Self::create(name)?;
+        file.write_all(text.as_bytes())?;
+        Ok(Self {
+            path,
+            stop: Arc::new(AtomicBool::new(false)),
+            task_running: Arc::new(AtomicBool::new(false)),
+            running: false,
+        })
+    }
+
+    /// create the temporary file and start a thread writing it.
+    ///
+    /// The task is given the stop flag, which it should check
+    /// regularly as it's raised when the text isn't wanted anymore.
+    pub fn spawn<F>(name: &str, task: F) -> io::Result<Self>
+    where
+        F: FnOnce(&mut File, &AtomicBool) -> io::Result<()> + Send + 'static,
+    {
+        let (mut file, path) = Self::create(name)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let task_running = Arc::new(AtomicBool::new(true));
+        let thread_stop = Arc::clone(&stop);
+        let thread_running = Arc::clone(&task_running);
+        thread::spawn(move || {
+            if let Err(e) = task(&mut file, &thread_stop) {
+                warn!("temp text task failed: {:?}", e);
+                let _ = writeln!(file, "error: {}", e);
+            }
+            thread_running.store(false, Ordering::SeqCst);
+        });
+        Ok(Self {
+            path,
+            stop,
+            task_running,
+            running: true,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// tell whether the writing task ended since the last call
+    pub fn check_finished(&mut self) -> bool {
+        if self.running && !self.task_running.load(Ordering::SeqCst) {
+            self.running = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// tell whether the text is still being written
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+impl Drop for TempText {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,162 @@
+use {
+    super::{self as trash_can, TrashItem},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{Screen, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        verb::*,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::{Area, FmtText, TextView},
+};
+
+/// max number of items listed in the confirmation
+const MAX_LISTED_ITEMS: usize = 20;
+
+enum EmptyTrashStep {
+    Confirm,
+    Done(usize), // number of deleted items
+    Failed(String),
+}
+
+/// a modal state asking for the confirmation before definitively
+/// deleting the items of the trash
+pub struct EmptyTrashState {
+    dir: PathBuf,
+    items: Vec<TrashItem>,
+    step: EmptyTrashStep,
+}
+
+impl EmptyTrashState {
+    /// return the state, or None if the trash is empty
+    pub fn new() -> io::Result<Option<Self>> {
+        let items = trash_can::list()?;
+        if items.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            dir: trash_can::trash_dir()?,
+            items,
+            step: EmptyTrashStep::Confirm,
+        }))
+    }
+
+    fn markdown(&self) -> String {
+        match &self.step {
+            EmptyTrashStep::Confirm => {
+                let mut md = format!(
+                    "\nThe **{}** items of the trash will be definitively deleted:\n\n",
+                    self.items.len(),
+                );
+                for item in self.items.iter().take(MAX_LISTED_ITEMS) {
+                    md.push_str(&format!("* `{}`\n", item.display_path()));
+                }
+                if self.items.len() > MAX_LISTED_ITEMS {
+                    md.push_str(&format!("* ...and {} other ones\n", self.items.len() - MAX_LISTED_ITEMS));
+                }
+                md.push_str("\nType *y* to confirm, any other key to cancel.");
+                md
+            }
+            EmptyTrashStep::Done(count) => format!("\n{} items were definitively deleted", count),
+            EmptyTrashStep::Failed(e) => format!("\nThe trash couldn't be emptied:\n\n`{}`", e),
+        }
+    }
+}
+
+impl AppState for EmptyTrashState {
+
+    fn selected_path(&self) -> &Path {
+        &self.dir
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.dir,
+            line: 0,
+            stype: SelectionType::Directory,
+            marked: &[],
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_command(
+        &mut self,
+        _w: &mut W,
+        cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match &self.step {
+            EmptyTrashStep::Confirm => match cc.cmd {
+                Command::PatternEdit { raw, .. } if raw == "y" || raw == "Y" => {
+                    self.step = match trash_can::empty() {
+                        Ok(count) => EmptyTrashStep::Done(count),
+                        Err(e) => EmptyTrashStep::Failed(e.to_string()),
+                    };
+                    AppStateCmdResult::Keep
+                }
+                _ => AppStateCmdResult::PopState, // any other key cancels
+            },
+            // the previous state, which may be the trash, is refreshed
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: false },
+        })
+    }
+
+    fn on_internal(
+        &mut self,
+        _w: &mut W,
+        _internal_exec: &InternalExecution,
+        _input_invocation: Option<&VerbInvocation>,
+        _trigger_type: TriggerType,
+        _cc: &CmdContext,
+        _screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match self.step {
+            EmptyTrashStep::Confirm => AppStateCmdResult::PopState,
+            _ => AppStateCmdResult::PopStateAndRefresh { clear_cache: false },
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        panel_skin.styles.default.queue_bg(w)?;
+        screen.clear_area_to_right(w, &state_area)?;
+        let mut text_area = state_area.clone();
+        text_area.pad_for_max_width(120);
+        let md = self.markdown();
+        let fmt_text = FmtText::from(
+            &panel_skin.help_skin,
+            &md,
+            Some((text_area.width - 1) as usize),
+        );
+        let text_view = TextView::from(&text_area, &fmt_text);
+        Ok(text_view.write_on(w)?)
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(match &self.step {
+            EmptyTrashStep::Confirm => format!(
+                "Definitively delete the {} items of the trash ? *y*/*N*",
+                self.items.len(),
+            ),
+            _ => "Hit any key to go back".to_string(),
+        })
+    }
+}
@@ -0,0 +1,126 @@
+//! The trash of the freedesktop specification: trashed files are
+//! in `files/` and, for each one, a `.trashinfo` file in `info/`
+//! gives the original path and the deletion date.
+//!
+//! Only the home trash is handled.
+
+use {
+    super::{remove, TrashItem},
+    crate::path::percent_decode,
+    chrono::NaiveDateTime,
+    std::{
+        fs,
+        io,
+        path::PathBuf,
+    },
+};
+
+/// return the home trash directory
+pub fn trash_dir() -> io::Result<PathBuf> {
+    directories::BaseDirs::new()
+        .map(|base_dirs| base_dirs.data_dir().join("Trash"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))
+}
+
+/// parse the content of a trashinfo file into the original
+/// path and the deletion date
+fn parse_info(content: &str) -> Option<(PathBuf, Option<NaiveDateTime>)> {
+    let mut original_path = None;
+    let mut deletion_date = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("Path=") {
+            original_path = Some(percent_decode(path));
+        } else if let Some(date) = line.strip_prefix("DeletionDate=") {
+            deletion_date = NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S").ok();
+        }
+    }
+    original_path.map(|path| (path, deletion_date))
+}
+
+/// list the items of the trash
+pub fn list() -> io::Result<Vec<TrashItem>> {
+    let trash_dir = trash_dir()?;
+    let info_dir = trash_dir.join("info");
+    let files_dir = trash_dir.join("files");
+    let mut items = Vec::new();
+    if !info_dir.exists() {
+        return Ok(items);
+    }
+    for entry in fs::read_dir(&info_dir)? {
+        let info_path = entry?.path();
+        if info_path.extension().and_then(|e| e.to_str()) != Some("trashinfo") {
+            continue;
+        }
+        let name = match info_path.file_stem() {
+            Some(stem) => stem.to_string_lossy().to_string(),
+            None => continue,
+        };
+        let path = files_dir.join(&name);
+        if fs::symlink_metadata(&path).is_err() {
+            continue; // orphan info file
+        }
+        let content = match fs::read_to_string(&info_path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if let Some((mut original_path, deletion_date)) = parse_info(&content) {
+            if original_path.is_relative() {
+                // relative paths are relative to the parent of the trash
+                if let Some(parent) = trash_dir.parent() {
+                    original_path = parent.join(original_path);
+                }
+            }
+            items.push(TrashItem {
+                original_path: Some(original_path),
+                deletion_date,
+                path,
+                info_path: Some(info_path),
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// definitively delete all the items of the trash.
+/// Return the number of removed items.
+pub fn empty() -> io::Result<usize> {
+    let trash_dir = trash_dir()?;
+    let mut count = 0;
+    let files_dir = trash_dir.join("files");
+    if files_dir.exists() {
+        for entry in fs::read_dir(&files_dir)? {
+            remove(&entry?.path())?;
+            count += 1;
+        }
+    }
+    let info_dir = trash_dir.join("info");
+    if info_dir.exists() {
+        for entry in fs::read_dir(&info_dir)? {
+            remove(&entry?.path())?;
+        }
+    }
+    let directory_sizes = trash_dir.join("directorysizes");
+    if directory_sizes.exists() {
+        fs::remove_file(directory_sizes)?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod freedesktop_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_parse_info() {
+        let content = "[Trash Info]\nPath=/home/me/notes%201.md\nDeletionDate=2020-08-04T21:12:03\n";
+        let (path, date) = parse_info(content).unwrap();
+        assert_eq!(path, PathBuf::from("/home/me/notes 1.md"));
+        assert_eq!(
+            date,
+            NaiveDateTime::parse_from_str("2020-08-04 21:12:03", "%Y-%m-%d %H:%M:%S").ok(),
+        );
+        assert!(parse_info("[Trash Info]\n").is_none());
+    }
+}
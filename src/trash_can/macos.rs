@@ -0,0 +1,56 @@
+//! The trash of macOS: trashed files are directly in `~/.Trash`.
+//!
+//! The original locations are only known by the Finder, so the
+//! items can be listed and deleted but not restored. Reading the
+//! trash may also need the "Full Disk Access" permission.
+
+use {
+    super::{remove, TrashItem},
+    std::{
+        fs,
+        io,
+        path::PathBuf,
+    },
+};
+
+/// a file kept by the Finder, which isn't a trashed item
+const FINDER_FILE: &str = ".DS_Store";
+
+/// return the trash directory of the user
+pub fn trash_dir() -> io::Result<PathBuf> {
+    directories::BaseDirs::new()
+        .map(|base_dirs| base_dirs.home_dir().join(".Trash"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))
+}
+
+/// list the items of the trash
+pub fn list() -> io::Result<Vec<TrashItem>> {
+    let trash_dir = trash_dir()?;
+    let mut items = Vec::new();
+    if !trash_dir.exists() {
+        return Ok(items);
+    }
+    for entry in fs::read_dir(&trash_dir)? {
+        let entry = entry?;
+        if entry.file_name() == FINDER_FILE {
+            continue;
+        }
+        items.push(TrashItem {
+            original_path: None,
+            deletion_date: None,
+            path: entry.path(),
+            info_path: None,
+        });
+    }
+    Ok(items)
+}
+
+/// definitively delete all the items of the trash.
+/// Return the number of removed items.
+pub fn empty() -> io::Result<usize> {
+    let items = list()?;
+    for item in &items {
+        remove(&item.path)?;
+    }
+    Ok(items.len())
+}
@@ -0,0 +1,103 @@
+//! Access to the content of the trash: the one of the freedesktop
+//! specification on Linux and other unix systems, `~/.Trash` on
+//! macOS, and the recycle bin of the system drive on Windows.
+
+mod empty_trash_state;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod freedesktop;
+#[cfg(target_os = "macos")]
+mod macos;
+mod trash_state;
+#[cfg(windows)]
+mod windows;
+
+pub use {
+    empty_trash_state::EmptyTrashState,
+    trash_state::TrashState,
+};
+
+#[cfg(all(unix, not(target_os = "macos")))]
+use freedesktop as platform;
+#[cfg(target_os = "macos")]
+use macos as platform;
+#[cfg(windows)]
+use windows as platform;
+
+use {
+    chrono::NaiveDateTime,
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// an item of the trash
+#[derive(Debug, Clone)]
+pub struct TrashItem {
+    pub original_path: Option<PathBuf>, // unknown on some platforms
+    pub deletion_date: Option<NaiveDateTime>,
+    pub path: PathBuf, // current path, in the trash
+    info_path: Option<PathBuf>, // the file describing the item, if any
+}
+
+/// return the directory of the trash
+pub fn trash_dir() -> io::Result<PathBuf> {
+    platform::trash_dir()
+}
+
+/// list the items of the trash, the most recently deleted first
+pub fn list() -> io::Result<Vec<TrashItem>> {
+    let mut items = platform::list()?;
+    items.sort_by_key(|item| std::cmp::Reverse(item.deletion_date));
+    Ok(items)
+}
+
+/// definitively delete all the items of the trash.
+/// Return the number of removed items.
+pub fn empty() -> io::Result<usize> {
+    platform::empty()
+}
+
+impl TrashItem {
+    /// the original path of the item or, when it's unknown, its
+    /// name in the trash
+    pub fn display_path(&self) -> String {
+        match &self.original_path {
+            Some(path) => path.to_string_lossy().to_string(),
+            None => self.path.file_name().map_or_else(
+                || self.path.to_string_lossy().to_string(),
+                |name| name.to_string_lossy().to_string(),
+            ),
+        }
+    }
+    /// move the item back to its original path, failing if
+    /// there's already a file there
+    pub fn restore(&self) -> io::Result<()> {
+        let original_path = self.original_path.as_ref().ok_or_else(|| {
+            io::Error::other("the original location isn't known")
+        })?;
+        if fs::symlink_metadata(original_path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{:?} already exists", original_path),
+            ));
+        }
+        if let Some(parent) = original_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&self.path, original_path)?;
+        if let Some(info_path) = &self.info_path {
+            fs::remove_file(info_path)?;
+        }
+        Ok(())
+    }
+}
+
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
@@ -0,0 +1,280 @@
+use {
+    super::{self as trash_can, TrashItem},
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, LONG_SPACE, Screen, W},
+        errors::ProgramError,
+        pattern::InputPattern,
+        skin::PanelSkin,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::Area,
+};
+
+/// an application state listing the items of the trash,
+/// the most recently deleted first
+pub struct TrashState {
+    items: Vec<TrashItem>,
+    dir: PathBuf, // the trash directory, selected when the trash is empty
+    filtered: Vec<usize>, // indexes of the items matching the pattern
+    pattern: InputPattern,
+    selection_idx: usize, // index in filtered
+    scroll: usize,
+    page_height: usize,
+    date_time_format: &'static str,
+}
+
+impl TrashState {
+    pub fn new(date_time_format: &'static str) -> io::Result<TrashState> {
+        let mut state = TrashState {
+            items: Vec::new(),
+            dir: trash_can::trash_dir()?,
+            filtered: Vec::new(),
+            pattern: InputPattern::none(),
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+            date_time_format,
+        };
+        state.reload()?;
+        Ok(state)
+    }
+
+    /// read again the content of the trash
+    fn reload(&mut self) -> io::Result<()> {
+        self.items = trash_can::list()?;
+        self.apply_pattern();
+        Ok(())
+    }
+
+    fn selected_item(&self) -> Option<&TrashItem> {
+        self.filtered
+            .get(self.selection_idx)
+            .map(|&idx| &self.items[idx])
+    }
+
+    fn move_selection(&mut self, dy: i32) {
+        if self.filtered.is_empty() {
+            return;
+        }
+        let max = self.filtered.len() as i32 - 1;
+        self.selection_idx = (self.selection_idx as i32 + dy).max(0).min(max) as usize;
+        self.ensure_selection_is_visible();
+    }
+
+    fn ensure_selection_is_visible(&mut self) {
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    fn apply_pattern(&mut self) {
+        let pattern = &self.pattern.pattern;
+        self.filtered = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                pattern.is_none()
+                    || pattern.score_of_string(&item.display_path()).is_some()
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.selection_idx = 0;
+        self.scroll = 0;
+    }
+
+    /// move the selected item back to its original location
+    fn restore_selection(&mut self) -> Result<AppStateCmdResult, ProgramError> {
+        let item = match self.selected_item() {
+            Some(item) => item.clone(),
+            None => return Ok(AppStateCmdResult::DisplayError("nothing to restore".to_string())),
+        };
+        if let Err(e) = item.restore() {
+            return Ok(AppStateCmdResult::DisplayError(format!("restoration failed: {}", e)));
+        }
+        let selection_idx = self.selection_idx;
+        self.reload()?;
+        self.selection_idx = selection_idx.min(self.filtered.len().max(1) - 1);
+        self.ensure_selection_is_visible();
+        Ok(AppStateCmdResult::DisplayMessage(format!(
+            "*{}* restored",
+            item.display_path(),
+        )))
+    }
+}
+
+impl AppState for TrashState {
+
+    fn selected_path(&self) -> &Path {
+        self.selected_item().map_or(&self.dir, |item| &item.path)
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        match self.selected_item() {
+            Some(item) => Selection {
+                path: &item.path,
+                line: 0,
+                stype: if item.path.is_dir() {
+                    SelectionType::Directory
+                } else {
+                    SelectionType::File
+                },
+                marked: &[],
+            },
+            None => Selection {
+                path: &self.dir,
+                line: 0,
+                stype: SelectionType::Directory,
+                marked: &[],
+            },
+        }
+    }
+
+    fn refresh(&mut self, _screen: &Screen, _con: &AppContext) -> Command {
+        let _ = self.reload();
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pat: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = pat;
+        self.apply_pattern();
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: &mut Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let page_height = self.page_height as i32;
+        Ok(match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(1);
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-1);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.move_selection(-(self.filtered.len() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.move_selection(self.filtered.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::restore => self.restore_selection()?,
+            Internal::refresh => {
+                self.refresh(screen, cc.con);
+                AppStateCmdResult::Keep
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: &Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height as usize != self.page_height {
+            self.page_height = state_area.height as usize;
+            self.ensure_selection_is_visible();
+        }
+        let styles = &panel_skin.styles;
+        let width = state_area.width as usize;
+        for y in 0..state_area.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + y))?;
+            let mut cw = CropWriter::new(w, width);
+            let idx = self.scroll + y as usize;
+            let item = match self.filtered.get(idx) {
+                Some(&item_idx) => &self.items[item_idx],
+                None => {
+                    cw.fill(&styles.default, LONG_SPACE)?;
+                    continue;
+                }
+            };
+            let selected = idx == self.selection_idx;
+            let mut date_style = styles.dates.clone();
+            let mut path_style = if item.path.is_dir() {
+                styles.directory.clone()
+            } else {
+                styles.file.clone()
+            };
+            let mut text_style = styles.default.clone();
+            if selected {
+                if let Some(c) = styles.selected_line.get_bg() {
+                    date_style.set_bg(c);
+                    path_style.set_bg(c);
+                    text_style.set_bg(c);
+                }
+            }
+            if con.show_selection_mark {
+                cw.queue_char(&text_style, if selected { '▶' } else { ' ' })?;
+            }
+            let date = match item.deletion_date {
+                Some(date) => date.format(self.date_time_format).to_string(),
+                None => "?".to_string(),
+            };
+            cw.queue_g_string(&date_style, date)?;
+            cw.queue_char(&text_style, ' ')?;
+            cw.queue_g_string(&path_style, item.display_path())?;
+            cw.fill(&text_style, LONG_SPACE)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.items.is_empty() {
+            Status::from_message("The trash is empty. Hit *esc* to go back")
+        } else {
+            Status::from_message(format!(
+                "{} items in the trash. Use *:restore* to put back the selection, *esc* to go back",
+                self.filtered.len(),
+            ))
+        }
+    }
+}
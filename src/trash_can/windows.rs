@@ -0,0 +1,156 @@
+//! The recycle bin of Windows: each user has a directory, named
+//! after their SID, in the `$Recycle.Bin` directory of the drive.
+//! A trashed file is renamed `$R<id>` there, and a `$I<id>` file
+//! gives its original path, its size and its deletion date.
+//!
+//! Only the recycle bin of the system drive is handled.
+
+use {
+    super::{remove, TrashItem},
+    chrono::{Local, NaiveDateTime, TimeZone},
+    std::{
+        convert::TryInto,
+        env,
+        fs,
+        io,
+        path::PathBuf,
+        process::Command,
+    },
+};
+
+/// number of seconds between 1601-01-01, the origin of Windows
+/// file times, and the unix epoch
+const EPOCH_DIFFERENCE: i64 = 11_644_473_600;
+
+/// return the SID of the current user, as given by whoami
+fn user_sid() -> io::Result<String> {
+    let output = Command::new("whoami")
+        .args(&["/user", "/fo", "csv", "/nh"])
+        .output()?;
+    // the output is like "domain\user","S-1-5-21-..."
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .rsplit(',')
+        .next()
+        .map(|sid| sid.trim_matches('"').to_string())
+        .filter(|sid| sid.starts_with("S-"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the SID of the user wasn't found"))
+}
+
+/// return the recycle bin directory of the user
+pub fn trash_dir() -> io::Result<PathBuf> {
+    let drive = env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+    Ok(PathBuf::from(format!("{}\\$Recycle.Bin", drive)).join(user_sid()?))
+}
+
+fn u64_at(bytes: &[u8], idx: usize) -> Option<u64> {
+    bytes.get(idx..idx + 8)?.try_into().ok().map(u64::from_le_bytes)
+}
+
+/// parse the content of a $I file into the original path and
+/// the deletion date
+fn parse_info(bytes: &[u8]) -> Option<(PathBuf, Option<NaiveDateTime>)> {
+    let path_bytes = match u64_at(bytes, 0)? {
+        // up to Windows 8, the path has a fixed length of 260 chars
+        1 => bytes.get(24..24 + 520)?,
+        // since Windows 10, the path is preceded by its length in chars
+        2 => {
+            let len: [u8; 4] = bytes.get(24..28)?.try_into().ok()?;
+            let len = u32::from_le_bytes(len) as usize;
+            bytes.get(28..28 + 2 * len)?
+        }
+        _ => return None,
+    };
+    let chars: Vec<u16> = path_bytes
+        .chunks(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&c| c != 0)
+        .collect();
+    let original_path = PathBuf::from(String::from_utf16_lossy(&chars));
+    // the deletion date is a file time, in 100ns since 1601
+    let deletion_date = u64_at(bytes, 16)
+        .map(|file_time| (file_time / 10_000_000) as i64 - EPOCH_DIFFERENCE)
+        .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+        .map(|date| date.naive_local());
+    Some((original_path, deletion_date))
+}
+
+/// list the items of the trash
+pub fn list() -> io::Result<Vec<TrashItem>> {
+    let trash_dir = trash_dir()?;
+    let mut items = Vec::new();
+    if !trash_dir.exists() {
+        return Ok(items);
+    }
+    for entry in fs::read_dir(&trash_dir)? {
+        let info_path = entry?.path();
+        let name = match info_path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.starts_with("$I") => name,
+            _ => continue,
+        };
+        let path = trash_dir.join(format!("$R{}", &name[2..]));
+        if fs::symlink_metadata(&path).is_err() {
+            continue; // orphan info file
+        }
+        let bytes = match fs::read(&info_path) {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+        if let Some((original_path, deletion_date)) = parse_info(&bytes) {
+            items.push(TrashItem {
+                original_path: Some(original_path),
+                deletion_date,
+                path,
+                info_path: Some(info_path),
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// definitively delete all the items of the trash.
+/// Return the number of removed items.
+pub fn empty() -> io::Result<usize> {
+    let trash_dir = trash_dir()?;
+    let mut count = 0;
+    if !trash_dir.exists() {
+        return Ok(count);
+    }
+    for entry in fs::read_dir(&trash_dir)? {
+        let path = entry?.path();
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.starts_with("$R") => {
+                remove(&path)?;
+                count += 1;
+            }
+            Some(name) if name.starts_with("$I") => {
+                remove(&path)?;
+            }
+            _ => {} // for example desktop.ini
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod windows_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_parse_info() {
+        let path: Vec<u16> = "C:\\notes.md".encode_utf16().collect();
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&2u64.to_le_bytes());
+        bytes.extend_from_slice(&1234u64.to_le_bytes());
+        bytes.extend_from_slice(&132_410_000_000_000_000u64.to_le_bytes());
+        bytes.extend_from_slice(&(path.len() as u32 + 1).to_le_bytes());
+        for c in path.iter().chain(&[0]) {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        let (original_path, date) = parse_info(&bytes).unwrap();
+        assert_eq!(original_path, PathBuf::from("C:\\notes.md"));
+        assert!(date.is_some());
+        assert!(parse_info(&bytes[..20]).is_none());
+    }
+}
@@ -1,4 +1,6 @@
 
+mod page_selection;
+mod refresh_deltas;
 mod sort;
 mod special_path;
 mod tree;
@@ -7,10 +9,12 @@ mod tree_line_type;
 mod tree_options;
 
 pub use {
+    page_selection::PageSelection,
+    refresh_deltas::{LineDelta, RefreshDeltas, DELTAS_DURATION},
     sort::Sort,
     special_path::*,
     tree::Tree,
     tree_line::TreeLine,
-    tree_line_type::TreeLineType,
+    tree_line_type::{SpecialType, TreeLineType},
     tree_options::TreeOptions,
 };
@@ -0,0 +1,45 @@
+use {
+    crate::errors::ConfError,
+};
+
+/// how the selection is moved when the tree is scrolled by a page
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PageSelection {
+    /// the selection is moved to the first visible line only when
+    /// it would go out of view
+    #[default]
+    Follow,
+    /// the selection keeps its position on screen, like the cursor
+    /// of most editors
+    Screen,
+    /// the selection stays on its line, even when it goes out of
+    /// view, like the position in a document in less
+    Line,
+}
+
+impl PageSelection {
+    pub fn from(raw: &str) -> Result<Self, ConfError> {
+        match raw {
+            "follow" => Ok(Self::Follow),
+            "screen" => Ok(Self::Screen),
+            "line" => Ok(Self::Line),
+            _ => Err(ConfError::InvalidPageSelection {
+                raw: raw.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod page_selection_tests {
+    use super::*;
+    #[test]
+    fn check_page_selection_parsing() {
+        assert_eq!(PageSelection::from("follow").unwrap(), PageSelection::Follow);
+        assert_eq!(PageSelection::from("screen").unwrap(), PageSelection::Screen);
+        assert_eq!(PageSelection::from("line").unwrap(), PageSelection::Line);
+        assert!(PageSelection::from("Line").is_err());
+        assert!(PageSelection::from("").is_err());
+        assert_eq!(PageSelection::default(), PageSelection::Follow);
+    }
+}
@@ -0,0 +1,102 @@
+use {
+    super::*,
+    std::{
+        collections::HashMap,
+        fs,
+        path::Path,
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+/// how long the changes are displayed after a refresh
+pub const DELTAS_DURATION: Duration = Duration::from_secs(5);
+
+/// how a line changed since the previous build of the tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineDelta {
+    Added,
+    Resized,
+}
+
+impl LineDelta {
+    pub fn marker(self) -> char {
+        match self {
+            Self::Added => '+',
+            Self::Resized => 'Δ',
+        }
+    }
+}
+
+/// what changed in a tree on its last refresh
+#[derive(Debug, Clone, Copy)]
+pub struct RefreshDeltas {
+    pub time: Instant,
+    pub removed: usize, // number of entries which don't exist anymore
+}
+
+/// the time the file was created or last moved (or, when it
+/// can't be known, last modified)
+fn change_time(metadata: &fs::Metadata) -> Option<SystemTime> {
+    #[cfg(unix)]
+    {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(metadata.ctime().max(0) as u64))
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.created().or_else(|_| metadata.modified()).ok()
+    }
+}
+
+impl RefreshDeltas {
+    /// compare the lines of a new build with the ones of the previous
+    /// build (made at `previous_time`) and set the deltas of the new lines.
+    ///
+    /// A line which wasn't displayed before isn't considered as added
+    /// unless the file is more recent than the previous build (it may just
+    /// have found some room). Return None when nothing changed.
+    pub fn compute(
+        old_lines: &[TreeLine],
+        previous_time: SystemTime,
+        new_lines: &mut [TreeLine],
+    ) -> Option<Self> {
+        let old: HashMap<&Path, u64> = old_lines
+            .iter()
+            .filter(|line| line.is_selectable())
+            .map(|line| (line.path.as_path(), line.metadata.len()))
+            .collect();
+        let mut changed = false;
+        for line in new_lines.iter_mut() {
+            if !line.is_selectable() {
+                continue;
+            }
+            line.delta = match old.get(line.path.as_path()) {
+                Some(&len) if line.is_file() && len != line.metadata.len() => Some(LineDelta::Resized),
+                Some(_) => None,
+                None if change_time(&line.metadata).is_some_and(|t| t > previous_time) => {
+                    Some(LineDelta::Added)
+                }
+                None => None,
+            };
+            changed |= line.delta.is_some();
+        }
+        let removed = old
+            .keys()
+            .filter(|path| fs::symlink_metadata(path).is_err())
+            .count();
+        if changed || removed > 0 {
+            Some(Self {
+                time: Instant::now(),
+                removed,
+            })
+        } else {
+            None
+        }
+    }
+    /// whether the deltas must still be displayed
+    pub fn is_recent(&self) -> bool {
+        self.time.elapsed() < DELTAS_DURATION
+    }
+}
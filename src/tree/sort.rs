@@ -17,4 +17,13 @@ impl Sort {
             _ => true,
         }
     }
+    /// the name of the sort, as displayed in panel titles
+    pub fn name(self) -> &'static str {
+        match self {
+            Sort::None => "none",
+            Sort::Count => "count",
+            Sort::Date => "date",
+            Sort::Size => "size",
+        }
+    }
 }
@@ -13,6 +13,7 @@ pub enum SpecialHandling {
     Enter,
     NoEnter,
     Hide,
+    Show, // shown even when hidden or gitignored
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +37,7 @@ impl SpecialPath {
             "enter" => SpecialHandling::Enter,
             "noenter" => SpecialHandling::NoEnter,
             "hide" => SpecialHandling::Hide,
+            "show" | "alwaysshow" => SpecialHandling::Show,
             _ => {
                 return Err(ProgramError::Unrecognized { token: value.to_string() })
             }
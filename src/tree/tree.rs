@@ -6,15 +6,18 @@ use {
         app::AppContext,
         errors,
         file_sum::FileSum,
-        git::TreeGitStatus,
+        git::{self, TreeGitStatus},
+        pattern::Candidate,
         task_sync::ComputationResult,
         task_sync::Dam,
         tree_build::TreeBuilder,
     },
     std::{
         cmp::Ord,
+        fs,
         mem,
         path::{Path, PathBuf},
+        time::SystemTime,
     },
 };
 
@@ -27,6 +30,8 @@ pub struct Tree {
     pub nb_gitignored: u32, // number of times a gitignore pattern excluded a file
     pub total_search: bool, // whether the search was made on all children
     pub git_status: ComputationResult<TreeGitStatus>,
+    pub build_time: SystemTime,
+    pub refresh_deltas: Option<RefreshDeltas>, // changes found on last refresh
 }
 
 impl Tree {
@@ -50,6 +55,10 @@ impl Tree {
             .unwrap(); // should not fail
                        // we save the old selection to try restore it
         let selected_path = self.selected_line().path.to_path_buf();
+        if con.show_refresh_deltas {
+            self.refresh_deltas = RefreshDeltas::compute(&self.lines, self.build_time, &mut tree.lines);
+        }
+        self.build_time = tree.build_time;
         mem::swap(&mut self.lines, &mut tree.lines);
         self.scroll = 0;
         if !self.try_select_path(&selected_path) {
@@ -65,6 +74,21 @@ impl Tree {
     /// - sort the lines
     /// - compute left branchs
     pub fn after_lines_changed(&mut self) {
+        if self.options.is_flat() {
+            // there's no branch in a flat list, we just rank the lines
+            self.lines[1..].sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.cmp(b)));
+            for i in 1..self.lines.len() {
+                for d in 0..self.lines[i].left_branchs.len() {
+                    self.lines[i].left_branchs[d] = false;
+                }
+            }
+            // the sums are displayed but, as sorting the siblings would
+            // break the ranking, the selected sort doesn't apply
+            if self.options.needs_sum() {
+                time!(Debug, "fetch_file_sum", self.fetch_regular_file_sums());
+            }
+            return;
+        }
         // we sort the lines (this is mandatory to avoid crashes)
         self.lines[1..].sort();
 
@@ -174,9 +198,42 @@ impl Tree {
         }
     }
 
-    pub fn try_scroll(&mut self, dy: i32, page_height: i32) {
+    /// scroll by dy lines, moving the selection according to
+    /// the page_selection behavior
+    pub fn try_scroll(&mut self, dy: i32, page_height: i32, page_selection: PageSelection) {
+        let old_scroll = self.scroll;
         self.scroll = (self.scroll + dy).max(0).min(self.lines.len() as i32 - 5);
-        self.select_visible_line(page_height);
+        match page_selection {
+            PageSelection::Follow => {
+                self.select_visible_line(page_height);
+            }
+            PageSelection::Screen => {
+                // when the scroll is blocked at an end, the selection
+                // still moves
+                let delta = if self.scroll == old_scroll {
+                    dy
+                } else {
+                    self.scroll - old_scroll
+                };
+                let l = self.lines.len();
+                let mut sel = (self.selection as i32 + delta).max(0).min(l as i32 - 1) as usize;
+                while sel + 1 < l && !self.lines[sel].is_selectable() {
+                    sel += 1;
+                }
+                if self.lines[sel].is_selectable() {
+                    self.selection = sel;
+                }
+                self.select_visible_line(page_height);
+            }
+            PageSelection::Line => {}
+        }
+    }
+
+    /// scroll by dy lines without changing the selection,
+    /// which may go out of view
+    pub fn scroll_view(&mut self, dy: i32, page_height: i32) {
+        let max_scroll = (self.lines.len() as i32 - page_height).max(0);
+        self.scroll = (self.scroll + dy).max(0).min(max_scroll);
     }
 
     /// try to select a line (works if y+scroll falls on a selectable line)
@@ -254,6 +311,212 @@ impl Tree {
         }
         false
     }
+    /// update the tree after a file was renamed, without rebuilding it:
+    /// the paths of the line and of its descendants are changed, then
+    /// the lines are sorted again. Return false when the path isn't
+    /// in the tree.
+    pub fn rename_path(&mut self, old_path: &Path, new_path: &Path) -> bool {
+        if !self.lines.iter().any(|line| line.path == old_path) {
+            return false;
+        }
+        let root = if self.root() == old_path {
+            new_path.to_path_buf()
+        } else {
+            self.root().clone()
+        };
+        for line in self.lines.iter_mut() {
+            let rest = match line.path.strip_prefix(old_path) {
+                Ok(rest) => rest.to_path_buf(),
+                Err(_) => continue,
+            };
+            line.path = if rest.as_os_str().is_empty() {
+                new_path.to_path_buf()
+            } else {
+                new_path.join(rest)
+            };
+            if let Ok(subpath) = line.path.strip_prefix(&root) {
+                line.subpath = TreeLine::make_displayable_name(&subpath.to_string_lossy());
+            }
+            if line.path == new_path {
+                if let Some(name) = new_path.file_name() {
+                    line.name = TreeLine::make_displayable_name(&name.to_string_lossy());
+                }
+                if let Ok(metadata) = fs::symlink_metadata(new_path) {
+                    line.metadata = metadata;
+                }
+            }
+        }
+        self.after_lines_changed();
+        self.try_select_path(new_path);
+        true
+    }
+    /// reload the metadata of the lines of paths which were just
+    /// changed. The sums of the directories containing them are reset
+    /// so that they're computed again (the sum cache must have been
+    /// cleared before).
+    pub fn reload_metadata(&mut self, paths: &[PathBuf]) {
+        for line in self.lines.iter_mut() {
+            if paths.contains(&line.path) {
+                if let Ok(metadata) = fs::symlink_metadata(&line.path) {
+                    line.metadata = metadata;
+                }
+            }
+            if line.is_real_dir()
+                && paths.iter().any(|path| path.starts_with(&line.path))
+            {
+                line.sum = None;
+            }
+        }
+        if self.options.needs_sum() {
+            self.fetch_regular_file_sums();
+        }
+    }
+    /// update the lines of paths which may have been changed, created
+    /// or removed by an external command, keeping the selection when
+    /// possible. The children of those paths which are listed directories
+    /// are checked too. New paths are only added when the tree isn't
+    /// filtered.
+    ///
+    /// Return false, without changing anything, when there are too many
+    /// new paths: the tree should then be rebuilt.
+    pub fn refresh_paths(&mut self, paths: &[PathBuf], page_height: i32) -> bool {
+        // the directories whose children are all listed
+        let listed_dirs: Vec<PathBuf> = self.lines.iter()
+            .filter(|line| line.is_dir() && line.unlisted == 0 && paths.contains(&line.path))
+            .filter(|line| {
+                let children = self.lines.iter().filter(|l| l.path.parent() == Some(&line.path));
+                let mut children = children.peekable();
+                (line.depth == 0 || children.peek().is_some())
+                    && children.all(|l| l.line_type != TreeLineType::Pruning)
+            })
+            .map(|line| line.path.clone())
+            .collect();
+        let mut paths = paths.to_vec();
+        for dir in &listed_dirs {
+            if let Ok(entries) = fs::read_dir(dir) {
+                paths.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()));
+            }
+            paths.extend(
+                self.lines.iter()
+                    .filter(|line| line.path.parent() == Some(dir))
+                    .map(|line| line.path.clone()),
+            );
+        }
+        paths.sort();
+        paths.dedup();
+        let can_insert = self.options.pattern.is_none();
+        let is_new = |path: &PathBuf| {
+            !self.lines.iter().any(|line| &line.path == path)
+                && (self.options.show_hidden || !path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with('.')))
+                && fs::symlink_metadata(path).is_ok()
+        };
+        let new_paths: Vec<PathBuf> = if can_insert {
+            paths.iter().filter(|path| is_new(path)).cloned().collect()
+        } else {
+            Vec::new()
+        };
+        if new_paths.len() > page_height.max(0) as usize {
+            return false;
+        }
+        let selected_path = self.selected_line().path.clone();
+        let mut existing_paths = Vec::new();
+        for path in paths {
+            if fs::symlink_metadata(&path).is_err() {
+                self.remove_path(&path);
+            } else {
+                existing_paths.push(path);
+            }
+        }
+        for path in &new_paths {
+            self.insert_path(path);
+        }
+        self.try_select_path(&selected_path);
+        self.reload_metadata(&existing_paths);
+        if !self.git_status.is_none() {
+            self.git_status = ComputationResult::NotComputed;
+        }
+        // the head may have moved
+        for line in self.lines.iter_mut() {
+            line.last_commit = ComputationResult::NotComputed;
+        }
+        self.make_selection_visible(page_height);
+        true
+    }
+    /// add the line of a path which was just created, and the lines
+    /// of its missing ancestors, then select it. Return false when
+    /// the path can't be in this tree or doesn't match its pattern.
+    pub fn insert_path(&mut self, path: &Path) -> bool {
+        if self.lines.iter().any(|line| line.path == path) {
+            return self.try_select_path(path);
+        }
+        let line = match self.created_line(path) {
+            Some(line) => line,
+            None => return false,
+        };
+        if self.options.pattern.pattern.score_of(Candidate::from(&line)).is_none() {
+            return false;
+        }
+        self.insert_line(line)
+    }
+    /// build the line of a path of the tree, other than its root
+    fn created_line(&self, path: &Path) -> Option<TreeLine> {
+        let subpath = match path.strip_prefix(self.root()) {
+            Ok(subpath) if !subpath.as_os_str().is_empty() => subpath.to_string_lossy().to_string(),
+            _ => return None,
+        };
+        TreeLine::created(path.to_path_buf(), &subpath).ok()
+    }
+    /// add the line, and the lines of its missing ancestors (which
+    /// don't have to match the pattern), then select it
+    fn insert_line(&mut self, line: TreeLine) -> bool {
+        let path = line.path.clone();
+        if let Some(parent) = path.parent() {
+            if !self.lines.iter().any(|line| line.path == parent) {
+                let inserted = self
+                    .created_line(parent)
+                    .is_some_and(|parent_line| self.insert_line(parent_line));
+                if !inserted {
+                    return false;
+                }
+            }
+        }
+        let mut lines = mem::take(&mut self.lines).into_vec();
+        lines.push(line);
+        self.lines = lines.into_boxed_slice();
+        self.after_lines_changed();
+        self.try_select_path(&path)
+    }
+    /// remove the line of a path which was just deleted, and the lines
+    /// of its descendants. When it was selected, the selection goes to
+    /// the next sibling, or the previous one, or the parent.
+    /// Return false when the path isn't in the tree (or is its root).
+    pub fn remove_path(&mut self, path: &Path) -> bool {
+        let idx = match self.lines.iter().position(|line| line.path == path) {
+            Some(0) | None => return false,
+            Some(idx) => idx,
+        };
+        let selected_path = self.selected_line().path.clone();
+        let mut lines = mem::take(&mut self.lines).into_vec();
+        lines.retain(|line| !line.path.starts_with(path));
+        self.lines = lines.into_boxed_slice();
+        self.after_lines_changed();
+        if self.try_select_path(&selected_path) {
+            return true;
+        }
+        let parent = path.parent();
+        let is_sibling = |line: &TreeLine| line.is_selectable() && line.path.parent() == parent;
+        self.selection = if idx < self.lines.len() && is_sibling(&self.lines[idx]) {
+            idx
+        } else {
+            (0..idx)
+                .rev()
+                .find(|&i| is_sibling(&self.lines[i]) || Some(self.lines[i].path.as_path()) == parent)
+                .unwrap_or(0)
+        };
+        true
+    }
     pub fn try_select_first(&mut self) -> bool {
         for idx in 0..self.lines.len() {
             let line = &self.lines[idx];
@@ -314,13 +577,50 @@ impl Tree {
                 .lines
                 .iter()
                 .skip(1)
-                .any(|line| line.line_type == TreeLineType::Dir && line.sum.is_none())
+                .any(|line| self.is_missing_sum(line))
+    }
+
+    /// tell whether the sum of the line should be computed but isn't.
+    /// Sums of the directories of immutable stores are only computed
+    /// on demand as those stores are huge and their content never changes.
+    fn is_missing_sum(&self, line: &TreeLine) -> bool {
+        line.is_real_dir()
+            && line.sum.is_none()
+            && (self.options.sum_immutable || !line.immutable)
     }
 
     pub fn is_missing_git_status_computation(&self) -> bool {
         self.git_status.is_not_computed()
     }
 
+    /// tell whether a last commit column is displayed but some
+    /// lines don't have their last commit yet
+    pub fn is_missing_last_commits(&self) -> bool {
+        (self.options.shows_git_author() || self.options.shows_git_commit_date())
+            && self.lines.iter().any(|line| line.last_commit.is_not_computed())
+    }
+
+    /// fetch the last commits of the lines which don't have them,
+    /// in one pass. Nothing is changed if the dam interrupts it.
+    pub fn fetch_last_commits(&mut self, dam: &Dam) {
+        let indexes: Vec<usize> = (0..self.lines.len())
+            .filter(|&i| self.lines[i].last_commit.is_not_computed())
+            .collect();
+        let paths: Vec<&Path> = indexes.iter().map(|&i| self.lines[i].path.as_path()).collect();
+        let last_commits = match git::get_last_commits(self.root(), &paths, dam) {
+            Some(last_commits) => last_commits,
+            None => {
+                return; // interrupted
+            }
+        };
+        for (i, last_commit) in indexes.into_iter().zip(last_commits) {
+            self.lines[i].last_commit = match last_commit {
+                Some(last_commit) => ComputationResult::Done(last_commit),
+                None => ComputationResult::None,
+            };
+        }
+    }
+
     /// fetches the file_sums of regular files (thus avoiding the
     /// long computation which is needed for directories)
     pub fn fetch_regular_file_sums(&mut self) {
@@ -338,7 +638,7 @@ impl Tree {
     ///  has_dir_missing_sum returns false
     pub fn fetch_some_missing_dir_sum(&mut self, dam: &Dam) {
         for i in 1..self.lines.len() {
-            if self.lines[i].sum.is_none() && self.lines[i].line_type == TreeLineType::Dir {
+            if self.is_missing_sum(&self.lines[i]) {
                 self.lines[i].sum = FileSum::from_dir(&self.lines[i].path, dam);
                 self.sort_siblings();
                 return;
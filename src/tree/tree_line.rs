@@ -5,12 +5,14 @@ use {
     crate::{
         app::{Selection, SelectionType},
         file_sum::FileSum,
-        git::LineGitStatus,
+        git::{LastCommit, LineGitStatus, SubmoduleStatus},
+        task_sync::ComputationResult,
     },
     std::{
         cmp::{self, Ord, Ordering, PartialOrd},
         fs,
-        path::PathBuf,
+        io,
+        path::{Path, PathBuf},
     },
 };
 
@@ -34,12 +36,60 @@ pub struct TreeLine {
     pub unlisted: usize, // number of not listed children (Dir) or brothers (Pruning)
     pub score: i32,      // 0 if there's no pattern
     pub direct_match: bool,
+    pub normally_hidden: bool, // whether the file would be hidden with default options
+    pub owner_mismatch: bool,  // whether the owner isn't the expected one
+    pub audit_failed: bool,    // whether the entry fails the permission audit
+    pub immutable: bool,       // whether the file is in an immutable store
+    pub nb_matches: usize,     // number of matching descendants (0 when no pattern)
     pub sum: Option<FileSum>, // None when not measured
     pub metadata: fs::Metadata,
     pub git_status: Option<LineGitStatus>,
+    pub last_commit: ComputationResult<LastCommit>, // only fetched when displayed
+    pub submodule_status: Option<SubmoduleStatus>, // only computed with git info
+    pub delta: Option<LineDelta>, // change since the previous build, if computed
 }
 
 impl TreeLine {
+    /// build the line of a file or directory which was just created
+    /// from broot, and thus has neither child nor match
+    pub fn created(path: PathBuf, subpath: &str) -> io::Result<TreeLine> {
+        let metadata = fs::symlink_metadata(&path)?;
+        let line_type = if metadata.is_dir() {
+            TreeLineType::Dir
+        } else if let Some(special_type) = SpecialType::of(&metadata.file_type()) {
+            TreeLineType::Special(special_type)
+        } else {
+            TreeLineType::File
+        };
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |n| TreeLine::make_displayable_name(&n.to_string_lossy()));
+        let depth = Path::new(subpath).components().count() as u16;
+        Ok(TreeLine {
+            left_branchs: vec![false; depth as usize].into_boxed_slice(),
+            depth,
+            normally_hidden: name.starts_with('.'),
+            name,
+            subpath: TreeLine::make_displayable_name(subpath),
+            path,
+            line_type,
+            has_error: false,
+            nb_kept_children: 0,
+            unlisted: 0,
+            score: 0,
+            direct_match: false,
+            owner_mismatch: false,
+            audit_failed: false,
+            immutable: false,
+            nb_matches: 0,
+            sum: None,
+            metadata,
+            git_status: None,
+            last_commit: ComputationResult::NotComputed,
+            submodule_status: None,
+            delta: None,
+        })
+    }
     pub fn make_displayable_name(name: &str) -> String {
         name.replace('\n', "")
     }
@@ -50,11 +100,14 @@ impl TreeLine {
         }
     }
     pub fn is_dir(&self) -> bool {
-        match &self.line_type {
-            TreeLineType::Dir => true,
-            TreeLineType::SymLinkToDir(_) => true,
-            _ => false,
-        }
+        matches!(
+            &self.line_type,
+            TreeLineType::Dir | TreeLineType::Submodule | TreeLineType::SymLinkToDir(_)
+        )
+    }
+    /// tell whether the line is a directory, and not a link to one
+    pub fn is_real_dir(&self) -> bool {
+        matches!(&self.line_type, TreeLineType::Dir | TreeLineType::Submodule)
     }
     pub fn is_file(&self) -> bool {
         match &self.line_type {
@@ -79,7 +132,8 @@ impl TreeLine {
         use TreeLineType::*;
         match &self.line_type {
             File | SymLinkToFile(_) => SelectionType::File,
-            Dir | SymLinkToDir(_) => SelectionType::Directory,
+            Dir | Submodule | SymLinkToDir(_) => SelectionType::Directory,
+            Special(_) => SelectionType::Any, // verbs for files would block or fail
             Pruning => SelectionType::Any, // should not happen today
         }
     }
@@ -88,6 +142,7 @@ impl TreeLine {
             path: &self.path,
             stype: self.selection_type(),
             line: 0,
+            marked: &[],
         }
     }
     #[cfg(unix)]
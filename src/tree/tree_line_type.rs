@@ -1,3 +1,9 @@
+use {
+    std::{
+        fmt,
+        fs,
+    },
+};
 
 /// The type of a line which can be displayed as
 /// part of a tree
@@ -5,7 +11,70 @@
 pub enum TreeLineType {
     File,
     Dir,
+    Submodule,             // the root directory of a git submodule
     SymLinkToDir(String),
     SymLinkToFile(String), // (to file or to symlink)
+    Special(SpecialType),  // neither a regular file, a dir nor a link
     Pruning,               // a "xxx unlisted" line
 }
+
+/// The type of a file which is neither a regular file, a directory
+/// nor a link. Such files can't be read like regular ones (opening
+/// a FIFO blocks, a device may be endless)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecialType {
+    BlockDevice,
+    CharDevice,
+    Fifo,
+    Socket,
+    Unknown,
+}
+
+impl SpecialType {
+    /// return the special type of a file, or None if it's a
+    /// regular file, a directory or a link
+    pub fn of(file_type: &fs::FileType) -> Option<Self> {
+        if file_type.is_file() || file_type.is_dir() || file_type.is_symlink() {
+            return None;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_block_device() {
+                return Some(Self::BlockDevice);
+            }
+            if file_type.is_char_device() {
+                return Some(Self::CharDevice);
+            }
+            if file_type.is_fifo() {
+                return Some(Self::Fifo);
+            }
+            if file_type.is_socket() {
+                return Some(Self::Socket);
+            }
+        }
+        Some(Self::Unknown)
+    }
+    /// the char written after the name in the tree
+    pub fn marker(self) -> char {
+        match self {
+            Self::BlockDevice => '#',
+            Self::CharDevice => '%',
+            Self::Fifo => '|',
+            Self::Socket => '=',
+            Self::Unknown => '?',
+        }
+    }
+}
+
+impl fmt::Display for SpecialType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BlockDevice => "block device",
+            Self::CharDevice => "character device",
+            Self::Fifo => "FIFO",
+            Self::Socket => "socket",
+            Self::Unknown => "special file",
+        })
+    }
+}
@@ -1,7 +1,14 @@
 use {
     super::Sort,
-    crate::pattern::*,
+    crate::{
+        display::{BranchGlyphs, Col, ResponsiveCols},
+        errors::ConfError,
+        git::GitIgnoreMode,
+        pattern::*,
+        permissions::PermissionAudit,
+    },
     clap::ArgMatches,
+    git2::Status,
 };
 
 /// Options defining how the tree should be build and|or displayed
@@ -13,13 +20,25 @@ pub struct TreeOptions {
     pub show_dates: bool,  // whether to show the last modified date
     pub show_sizes: bool,  // whether to show sizes of files and dirs
     pub show_git_file_info: bool,
+    pub show_git_author: bool, // whether to show the author of the last commit
+    pub show_git_commit_date: bool, // whether to show the date of the last commit
     pub trim_root: bool,            // whether to cut out direct children of root
+    pub flat: bool,                 // whether to display search results as a flat ranked list
     pub show_permissions: bool,     // show classic rwx unix permissions (only on unix)
-    pub respect_git_ignore: bool,   // hide files as requested by .gitignore ?
+    pub git_ignore_mode: GitIgnoreMode, // which gitignore rules hide files
+    pub respect_ignore_files: bool, // whether the .ignore and .fdignore files hide files
+    pub respect_broot_ignore: bool, // whether the .brootignore files hide files
     pub filter_by_git_status: bool, // only show files whose git status is not nul
+    pub git_status_filter: Option<Status>, // when filtering, the kept statuses (None for all)
+    pub filter_by_owner_mismatch: bool, // only show files whose owner isn't the expected one
+    pub permission_audit: Option<PermissionAudit>, // only show entries failing this audit
     pub pattern: InputPattern,           // an optional filtering/scoring pattern
     pub date_time_format: &'static str,
     pub sort: Sort,
+    pub branch_glyphs: BranchGlyphs, // how the branches of the tree are drawn
+    pub sum_immutable: bool, // whether to compute the sums of dirs in immutable stores
+    pub responsive_cols: ResponsiveCols, // columns hidden or shown depending on the width
+    pub panel_width: Option<u16>, // width of the panel displaying the tree, once known
 }
 
 impl TreeOptions {
@@ -32,26 +51,78 @@ impl TreeOptions {
             show_dates: self.show_dates,
             show_sizes: self.show_sizes,
             show_permissions: self.show_permissions,
-            respect_git_ignore: self.respect_git_ignore,
+            git_ignore_mode: self.git_ignore_mode,
+            respect_ignore_files: self.respect_ignore_files,
+            respect_broot_ignore: self.respect_broot_ignore,
             filter_by_git_status: self.filter_by_git_status,
+            git_status_filter: self.git_status_filter,
+            filter_by_owner_mismatch: self.filter_by_owner_mismatch,
+            permission_audit: self.permission_audit,
             show_git_file_info: self.show_git_file_info,
+            show_git_author: self.show_git_author,
+            show_git_commit_date: self.show_git_commit_date,
             trim_root: self.trim_root,
+            flat: self.flat,
             pattern: InputPattern::none(),
             date_time_format: self.date_time_format,
             sort: self.sort,
+            branch_glyphs: self.branch_glyphs.clone(),
+            sum_immutable: self.sum_immutable,
+            responsive_cols: self.responsive_cols.clone(),
+            panel_width: self.panel_width,
         }
     }
+    /// take the settings of the other options, keeping the
+    /// pattern and the width of the panel
+    pub fn set_settings_from(&mut self, other: &TreeOptions) {
+        let pattern = std::mem::replace(&mut self.pattern, InputPattern::none());
+        let panel_width = self.panel_width;
+        *self = other.without_pattern();
+        self.pattern = pattern;
+        self.panel_width = panel_width;
+    }
+    /// tell whether the column is displayed, taking into account
+    /// the width of the panel, `enabled` being the option of the column
+    fn shows_col(&self, col: Col, enabled: bool) -> bool {
+        match self.panel_width {
+            Some(width) => self.responsive_cols.is_shown(col, enabled, width),
+            None => enabled,
+        }
+    }
+    pub fn shows_counts(&self) -> bool {
+        self.shows_col(Col::Count, self.show_counts)
+    }
+    pub fn shows_dates(&self) -> bool {
+        self.shows_col(Col::Date, self.show_dates)
+    }
+    pub fn shows_sizes(&self) -> bool {
+        self.shows_col(Col::Size, self.show_sizes)
+    }
+    pub fn shows_permissions(&self) -> bool {
+        self.shows_col(Col::Permission, self.show_permissions)
+    }
+    pub fn shows_git_author(&self) -> bool {
+        self.shows_col(Col::GitAuthor, self.show_git_author)
+    }
+    pub fn shows_git_commit_date(&self) -> bool {
+        self.shows_col(Col::GitCommitDate, self.show_git_commit_date)
+    }
+    /// whether the tree is displayed as a flat list of matching
+    /// paths, sorted by score (only makes sense with a pattern)
+    pub fn is_flat(&self) -> bool {
+        self.flat && self.pattern.is_some()
+    }
     /// counts must be computed, either for sorting or just for display
     pub fn needs_counts(&self) -> bool {
-        self.show_counts || self.sort == Sort::Count
+        self.shows_counts() || self.sort == Sort::Count
     }
     /// dates must be computed, either for sorting or just for display
     pub fn needs_dates(&self) -> bool {
-        self.show_dates || self.sort == Sort::Date
+        self.shows_dates() || self.sort == Sort::Date
     }
     /// sizes must be computed, either for sorting or just for display
     pub fn needs_sizes(&self) -> bool {
-        self.show_sizes || self.sort == Sort::Size
+        self.shows_sizes() || self.sort == Sort::Size
     }
     pub fn needs_sum(&self) -> bool {
         self.needs_counts() || self.needs_dates() || self.needs_sizes()
@@ -61,6 +132,48 @@ impl TreeOptions {
     pub fn set_date_time_format(&mut self, format: String) {
         self.date_time_format = Box::leak(format.into_boxed_str());
     }
+    /// the launch arguments which, applied on default options,
+    /// give the same display settings as these options
+    pub fn to_launch_args(&self) -> Vec<&'static str> {
+        let flag = |on: bool, yes: &'static str, no: &'static str| if on { yes } else { no };
+        let mut args = vec![
+            flag(self.show_sizes, "--sizes", "--no-sizes"),
+            flag(self.only_folders, "--only-folders", "--no-only-folders"),
+            flag(self.show_hidden, "--hidden", "--no-hidden"),
+            flag(self.show_dates, "--dates", "--no-dates"),
+            flag(self.show_permissions, "--permissions", "--no-permissions"),
+            match self.git_ignore_mode {
+                GitIgnoreMode::All => "--no-show-gitignored",
+                GitIgnoreMode::RepoOnly => "--repo-gitignore-only",
+                GitIgnoreMode::Nothing => "--show-gitignored",
+            },
+            flag(self.respect_ignore_files, "--ignore-files", "--no-ignore-files"),
+            flag(self.respect_broot_ignore, "--broot-ignore", "--no-broot-ignore"),
+            flag(self.show_git_file_info, "--show-git-info", "--no-show-git-info"),
+            flag(self.trim_root, "--trim-root", "--no-trim-root"),
+            flag(self.flat, "--flat", "--no-flat"),
+        ];
+        args.push(match self.sort {
+            Sort::None => "--no-sort",
+            Sort::Count => "--sort-by-count",
+            Sort::Date => "--sort-by-date",
+            Sort::Size => "--sort-by-size",
+        });
+        if self.filter_by_git_status {
+            args.push("--git-status");
+        }
+        args
+    }
+    /// change tree options according to flags written as in
+    /// the `default_flags` setting, eg "gh"
+    pub fn apply_flags(&mut self, flags: &str) -> Result<(), ConfError> {
+        let clap_app = crate::clap::clap_app().setting(clap::AppSettings::NoBinaryName);
+        let flags_args = format!("-{}", flags);
+        let matches = clap_app.get_matches_from_safe(vec![&flags_args])
+            .map_err(|_| ConfError::InvalidDefaultFlags { flags: flags.to_string() })?;
+        self.apply(&matches);
+        Ok(())
+    }
     /// change tree options according to broot launch arguments
     pub fn apply(&mut self, cli_args: &ArgMatches<'_>) {
         if cli_args.is_present("sizes") {
@@ -70,7 +183,9 @@ impl TreeOptions {
         }
         if cli_args.is_present("whale-spotting") {
             self.show_hidden = true;
-            self.respect_git_ignore = false;
+            self.git_ignore_mode = GitIgnoreMode::Nothing;
+            self.respect_ignore_files = false;
+            self.respect_broot_ignore = false;
             self.sort = Sort::Size;
             self.show_sizes = true;
         }
@@ -83,6 +198,9 @@ impl TreeOptions {
             self.filter_by_git_status = true;
             self.show_hidden = true;
         }
+        if cli_args.is_present("owner-mismatch") {
+            self.filter_by_owner_mismatch = true;
+        }
         if cli_args.is_present("hidden") {
             self.show_hidden = true;
         } else if cli_args.is_present("no-hidden") {
@@ -99,9 +217,21 @@ impl TreeOptions {
             self.show_permissions = false;
         }
         if cli_args.is_present("show-gitignored") {
-            self.respect_git_ignore = false;
+            self.git_ignore_mode = GitIgnoreMode::Nothing;
+        } else if cli_args.is_present("repo-gitignore-only") {
+            self.git_ignore_mode = GitIgnoreMode::RepoOnly;
         } else if cli_args.is_present("no-show-gitignored") {
-            self.respect_git_ignore = true;
+            self.git_ignore_mode = GitIgnoreMode::All;
+        }
+        if cli_args.is_present("ignore-files") {
+            self.respect_ignore_files = true;
+        } else if cli_args.is_present("no-ignore-files") {
+            self.respect_ignore_files = false;
+        }
+        if cli_args.is_present("broot-ignore") {
+            self.respect_broot_ignore = true;
+        } else if cli_args.is_present("no-broot-ignore") {
+            self.respect_broot_ignore = false;
         }
         if cli_args.is_present("show-git-info") {
             self.show_git_file_info = true;
@@ -128,6 +258,42 @@ impl TreeOptions {
         } else if cli_args.is_present("no-trim-root") {
             self.trim_root = false;
         }
+        if cli_args.is_present("flat") {
+            self.flat = true;
+        } else if cli_args.is_present("no-flat") {
+            self.flat = false;
+        }
+    }
+    /// undo the changes made by applying flags, which turned the
+    /// `before` options into the `after` ones, but not the changes
+    /// made to the other options since
+    pub fn revert_flags(&mut self, before: &TreeOptions, after: &TreeOptions) {
+        macro_rules! revert {
+            ($($field:ident),*) => {
+                $(
+                    if before.$field != after.$field {
+                        self.$field = before.$field;
+                    }
+                )*
+            }
+        }
+        revert!(
+            show_hidden,
+            only_folders,
+            show_counts,
+            show_dates,
+            show_sizes,
+            show_git_file_info,
+            trim_root,
+            flat,
+            show_permissions,
+            git_ignore_mode,
+            respect_ignore_files,
+            respect_broot_ignore,
+            filter_by_git_status,
+            filter_by_owner_mismatch,
+            sort
+        );
     }
 }
 
@@ -140,13 +306,47 @@ impl Default for TreeOptions {
             show_dates: false,
             show_sizes: false,
             show_git_file_info: false,
+            show_git_author: false,
+            show_git_commit_date: false,
             trim_root: false,
+            flat: false,
             show_permissions: false,
-            respect_git_ignore: true,
+            git_ignore_mode: GitIgnoreMode::All,
+            respect_ignore_files: true,
+            respect_broot_ignore: true,
             filter_by_git_status: false,
+            git_status_filter: None,
+            filter_by_owner_mismatch: false,
+            permission_audit: None,
             pattern: InputPattern::none(),
             date_time_format: "%Y/%m/%d %R",
             sort: Sort::None,
+            branch_glyphs: BranchGlyphs::default(),
+            sum_immutable: false,
+            responsive_cols: ResponsiveCols::default(),
+            panel_width: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tree_options_tests {
+
+    use super::*;
+
+    #[test]
+    fn check_revert_flags() {
+        let before = TreeOptions::default();
+        let mut after = before.clone();
+        after.apply_flags("hs").unwrap();
+        assert!(after.show_hidden && after.show_sizes);
+        // the user then shows the dates and hides the sizes
+        let mut options = after.clone();
+        options.show_dates = true;
+        options.show_sizes = false;
+        options.revert_flags(&before, &after);
+        assert!(!options.show_hidden);
+        assert!(!options.show_sizes);
+        assert!(options.show_dates);
+    }
+}
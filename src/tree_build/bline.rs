@@ -2,13 +2,19 @@ use {
     super::bid::BId,
     crate::{
         errors::TreeBuildError,
-        git::GitIgnoreChain,
+        git::{self, GitIgnoreChain},
+        immutable,
+        permissions::Ownership,
+        task_sync::ComputationResult,
         tree::*,
     },
     id_arena::Arena,
     std::{fs, path::PathBuf, result::Result},
 };
 
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
 /// like a tree line, but with the info needed during the build
 /// This structure isn't usable independantly from the tree builder
 pub struct BLine {
@@ -23,8 +29,12 @@ pub struct BLine {
     pub has_error: bool,
     pub has_match: bool,
     pub direct_match: bool,
+    pub normally_hidden: bool, // hidden or gitignored, only listed due to options
     pub score: i32,
     pub nb_kept_children: i32, // used during the trimming step
+    pub uid: Option<u32>,      // owner, only fetched when needed
+    pub ownership: Option<Ownership>, // only fetched for a permission audit
+    pub nb_matches: usize,     // number of matching descendants among the seen ones
     pub git_ignore_chain: GitIgnoreChain,
     pub special_handling: SpecialHandling,
 }
@@ -43,6 +53,14 @@ impl BLine {
         };
         if let Ok(md) = fs::metadata(&path) {
             let file_type = md.file_type();
+            #[cfg(unix)]
+            let uid = Some(md.uid());
+            #[cfg(not(unix))]
+            let uid = None;
+            #[cfg(unix)]
+            let ownership = Some(Ownership::from(&md));
+            #[cfg(not(unix))]
+            let ownership = None;
             Ok(blines.alloc(BLine {
                 parent_id: None,
                 path,
@@ -55,8 +73,12 @@ impl BLine {
                 has_error: false,
                 has_match: true,
                 direct_match: false,
+                normally_hidden: false,
                 score: 0,
                 nb_kept_children: 0,
+                uid,
+                ownership,
+                nb_matches: 0,
                 git_ignore_chain,
                 special_handling: SpecialHandling::None,
             }))
@@ -97,7 +119,11 @@ impl BLine {
     pub fn to_tree_line(&self) -> std::io::Result<TreeLine> {
         let mut has_error = self.has_error;
         let line_type = if self.file_type.is_dir() {
-            TreeLineType::Dir
+            if git::is_submodule_root(&self.path) {
+                TreeLineType::Submodule
+            } else {
+                TreeLineType::Dir
+            }
         } else if self.file_type.is_symlink() {
             if let Ok(target) = fs::read_link(&self.path) {
                 let target = target.to_string_lossy().into_owned();
@@ -119,6 +145,8 @@ impl BLine {
                 has_error = true;
                 TreeLineType::SymLinkToFile(String::from("????"))
             }
+        } else if let Some(special_type) = SpecialType::of(&self.file_type) {
+            TreeLineType::Special(special_type)
         } else {
             TreeLineType::File
         };
@@ -143,9 +171,17 @@ impl BLine {
             unlisted,
             score: self.score,
             direct_match: self.direct_match,
+            normally_hidden: self.normally_hidden,
+            owner_mismatch: false, // computed by the builder
+            audit_failed: false, // computed by the builder
+            immutable: immutable::is_immutable(&self.path),
+            nb_matches: self.nb_matches,
             sum: None,
             metadata,
             git_status: None,
+            last_commit: ComputationResult::NotComputed,
+            submodule_status: None,
+            delta: None,
         })
     }
 }
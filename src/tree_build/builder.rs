@@ -6,7 +6,7 @@ use {
     crate::{
         app::AppContext,
         errors::TreeBuildError,
-        git::{GitIgnoreChain, GitIgnorer, LineStatusComputer},
+        git::{GitIgnoreChain, GitIgnorer, IgnoreStatus, LineStatusComputer},
         pattern::Candidate,
         task_sync::ComputationResult,
         task_sync::Dam,
@@ -20,12 +20,15 @@ use {
         fs,
         path::PathBuf,
         result::Result,
-        time::{Duration, Instant},
+        time::{Duration, Instant, SystemTime},
     },
 };
 
 #[cfg(unix)]
-use std::os::unix::ffi::OsStrExt;
+use std::os::unix::{ffi::OsStrExt, fs::MetadataExt};
+
+#[cfg(unix)]
+use crate::permissions::Ownership;
 
 #[cfg(target_os = "windows")]
 use std::ffi::OsStr;
@@ -73,7 +76,15 @@ impl<'c> TreeBuilder<'c> {
         con: &'c AppContext,
     ) -> Result<TreeBuilder<'c>, TreeBuildError> {
         let mut blines = Arena::new();
-        let mut git_ignorer = time!(Debug, "GitIgnorer::default", GitIgnorer::default());
+        let mut git_ignorer = time!(
+            Debug,
+            "GitIgnorer::new",
+            GitIgnorer::new(
+                options.git_ignore_mode,
+                options.respect_ignore_files,
+                options.respect_broot_ignore,
+            ),
+        );
         let root_ignore_chain = git_ignorer.root_chain(&path);
         let line_status_computer = if options.filter_by_git_status || options.show_git_file_info {
             time!(
@@ -114,14 +125,20 @@ impl<'c> TreeBuilder<'c> {
         if name.is_empty() {
             return None;
         }
-        if !self.options.show_hidden && name.as_bytes()[0] == b'.' {
+        let path = e.path();
+        let special_handling = self.con.special_paths.find(&path);
+        if special_handling == SpecialHandling::Hide {
+            return None;
+        }
+        let always_shown = special_handling == SpecialHandling::Show;
+        let hidden = name.as_bytes()[0] == b'.';
+        if !self.options.show_hidden && hidden && !always_shown {
             return None;
         }
         let name = name.to_string_lossy();
         let name = name.to_string();
         let mut has_match = true;
         let mut score = 10000 - i32::from(depth); // we dope less deep entries
-        let path = e.path();
         let file_type = match e.file_type() {
             Ok(ft) => ft,
             Err(_) => {
@@ -150,12 +167,50 @@ impl<'c> TreeBuilder<'c> {
         };
         if has_match && self.options.filter_by_git_status {
             if let Some(line_status_computer) = &self.line_status_computer {
-                if !line_status_computer.is_interesting(&path) {
+                let kept = match self.options.git_status_filter {
+                    Some(statuses) => line_status_computer.has_status(&path, statuses),
+                    None => line_status_computer.is_interesting(&path),
+                };
+                if !kept {
                     has_match = false;
                 }
             }
         }
-        if file_type.is_file() || file_type.is_symlink() {
+        #[cfg(unix)]
+        let uid = if self.options.filter_by_owner_mismatch {
+            let uid = e.metadata().ok().map(|md| md.uid());
+            // we need the uid of directories even when they don't match,
+            // as they're the reference for their children
+            if has_match {
+                let parent_uid = self.blines[parent_id].uid;
+                if !uid.is_some_and(|uid| self.is_owner_mismatch(parent_uid, uid)) {
+                    has_match = false;
+                }
+            }
+            uid
+        } else {
+            None
+        };
+        #[cfg(not(unix))]
+        let uid = None;
+        #[cfg(unix)]
+        let ownership = if let Some(audit) = self.options.permission_audit {
+            let ownership = e.metadata().ok().map(|md| Ownership::from(&md));
+            // as for the uid, directories are the reference for their children
+            if has_match {
+                let parent_ownership = self.blines[parent_id].ownership;
+                if !ownership.is_some_and(|o| audit.fails(o, parent_ownership)) {
+                    has_match = false;
+                }
+            }
+            ownership
+        } else {
+            None
+        };
+        #[cfg(not(unix))]
+        let ownership = None;
+        if !file_type.is_dir() {
+            // regular files, links and special files (devices, sockets, etc.)
             if !has_match {
                 return None;
             }
@@ -163,19 +218,16 @@ impl<'c> TreeBuilder<'c> {
                 return None;
             }
         }
-        let special_handling = self.con.special_paths.find(&path);
-        if special_handling == SpecialHandling::Hide {
-            return None;
-        }
-        if self.options.respect_git_ignore {
-            let parent_chain = &self.blines[parent_id].git_ignore_chain;
-            if !self
-                .git_ignorer
-                .accepts(parent_chain, &path, &name, file_type.is_dir())
-            {
+        let parent_chain = &self.blines[parent_id].git_ignore_chain;
+        let gitignored = match self.git_ignorer.status(parent_chain, &path, &name, file_type.is_dir()) {
+            IgnoreStatus::Hidden if !always_shown => {
                 return None;
             }
+            IgnoreStatus::Hidden | IgnoreStatus::Dimmed => true,
+            IgnoreStatus::NotIgnored => false,
         };
+        // the children of a hidden or ignored directory are dimmed too
+        let normally_hidden = hidden || gitignored || self.blines[parent_id].normally_hidden;
         Some(BLine {
             parent_id: Some(parent_id),
             path,
@@ -188,13 +240,26 @@ impl<'c> TreeBuilder<'c> {
             has_error: false,
             has_match,
             direct_match,
+            normally_hidden,
             score,
             nb_kept_children: 0,
+            uid,
+            ownership,
+            nb_matches: 0,
             git_ignore_chain: GitIgnoreChain::default(),
             special_handling,
         })
     }
 
+    /// tell whether the owner of a file isn't the expected one, which
+    /// is either the one set in configuration or the owner of the parent
+    #[cfg(unix)]
+    fn is_owner_mismatch(&self, parent_uid: Option<u32>, uid: u32) -> bool {
+        self.con.expected_owner_uid
+            .or(parent_uid)
+            .is_some_and(|expected_uid| expected_uid != uid)
+    }
+
     /// returns true when there are direct matches among children
     fn load_children(&mut self, bid: BId) -> bool {
         let mut has_child_match = false;
@@ -208,14 +273,14 @@ impl<'c> TreeBuilder<'c> {
                     .filter_map(|e| self.make_line(bid, e, child_depth))
                     .collect();
                 for mut bl in lines {
-                    if self.options.respect_git_ignore {
-                        let parent_chain = &self.blines[bid].git_ignore_chain;
-                        bl.git_ignore_chain = if bl.file_type.is_dir() {
-                            self.git_ignorer.deeper_chain(parent_chain, &bl.path)
-                        } else {
-                            parent_chain.clone()
-                        };
-                    }
+                    // the chain is needed even when not respecting the gitignore
+                    // rules, to know what entries must be dimmed
+                    let parent_chain = &self.blines[bid].git_ignore_chain;
+                    bl.git_ignore_chain = if bl.file_type.is_dir() {
+                        self.git_ignorer.deeper_chain(parent_chain, &bl.path)
+                    } else {
+                        parent_chain.clone()
+                    };
                     if bl.has_match {
                         self.blines[bid].has_match = true;
                         has_child_match = true;
@@ -345,12 +410,17 @@ impl<'c> TreeBuilder<'c> {
     ///  strictly necessary to fill the screen.
     /// This function keeps only the best ones while taking care of not
     ///  removing a parent before its children.
+    /// In flat mode, only the direct matches are displayed so only
+    ///  them are counted.
     fn trim_excess(&mut self, out_blines: &[BId]) {
+        let flat = self.options.is_flat();
         let mut count = 1;
         for id in out_blines[1..].iter() {
             if self.blines[*id].has_match {
                 //debug!("bline before trimming: {:?}", &self.blines[*idx].path);
-                count += 1;
+                if !flat || self.blines[*id].direct_match {
+                    count += 1;
+                }
                 let parent_id = self.blines[*id].parent_id.unwrap();
                 // (we can unwrap because only the root can have a None parent)
                 self.blines[parent_id].nb_kept_children += 1;
@@ -381,7 +451,9 @@ impl<'c> TreeBuilder<'c> {
                         score: parent.score,
                     });
                 }
-                count -= 1;
+                if !flat || self.blines[sli.id].direct_match {
+                    count -= 1;
+                }
             } else {
                 debug!("trimming prematurely interrupted");
                 break;
@@ -389,16 +461,64 @@ impl<'c> TreeBuilder<'c> {
         }
     }
 
+    /// count, for every directory, the matching descendants among
+    /// the lines which were seen during the search (trimmed ones included)
+    fn count_matches(&mut self) {
+        let matching_ids: Vec<BId> = self.blines
+            .iter()
+            .filter(|(_, bline)| bline.direct_match)
+            .map(|(id, _)| id)
+            .collect();
+        for id in matching_ids {
+            let mut parent_id = self.blines[id].parent_id;
+            while let Some(pid) = parent_id {
+                self.blines[pid].nb_matches += 1;
+                parent_id = self.blines[pid].parent_id;
+            }
+        }
+    }
+
     /// make a tree from the builder's specific structure
     fn take(mut self, out_blines: &[BId]) -> Tree {
+        if self.options.pattern.is_some() {
+            self.count_matches();
+        }
+        let flat = self.options.is_flat();
         let mut lines: Vec<TreeLine> = Vec::new();
-        for id in out_blines.iter() {
+        for (i, id) in out_blines.iter().enumerate() {
+            if flat && i > 0 && !self.blines[*id].direct_match {
+                // in flat mode, the non matching ancestors aren't listed
+                continue;
+            }
             if self.blines[*id].has_match {
                 // we need to count the children, so we load them
                 if self.blines[*id].file_type.is_dir() && self.blines[*id].children.is_none() {
                     self.load_children(*id);
                 }
-                if let Ok(tree_line) = self.blines[*id].to_tree_line() {
+                if let Ok(mut tree_line) = self.blines[*id].to_tree_line() {
+                    #[cfg(unix)]
+                    {
+                        let uid = tree_line.metadata.uid();
+                        self.blines[*id].uid = Some(uid);
+                        if let Some(parent_id) = self.blines[*id].parent_id {
+                            let parent_uid = self.blines[parent_id].uid.or_else(|| {
+                                fs::metadata(&self.blines[parent_id].path)
+                                    .ok()
+                                    .map(|md| md.uid())
+                            });
+                            self.blines[parent_id].uid = parent_uid;
+                            tree_line.owner_mismatch = self.is_owner_mismatch(parent_uid, uid);
+                        }
+                        if let Some(audit) = self.options.permission_audit {
+                            let parent_ownership = self.blines[*id]
+                                .parent_id
+                                .and_then(|parent_id| self.blines[parent_id].ownership);
+                            tree_line.audit_failed = audit.fails(
+                                Ownership::from(&tree_line.metadata),
+                                parent_ownership,
+                            );
+                        }
+                    }
                     lines.push(tree_line);
                 } else {
                     // I guess the file went missing during tree computation
@@ -417,6 +537,8 @@ impl<'c> TreeBuilder<'c> {
             nb_gitignored: self.nb_gitignored,
             total_search: self.total_search,
             git_status: ComputationResult::None,
+            build_time: SystemTime::now(),
+            refresh_deltas: None,
         };
         tree.after_lines_changed();
         if let Some(computer) = self.line_status_computer {
@@ -427,6 +549,11 @@ impl<'c> TreeBuilder<'c> {
             // not display that type
             for mut line in tree.lines.iter_mut() {
                 line.git_status = computer.line_status(&line.path);
+                line.submodule_status = computer.submodule_status(&line.path);
+                // uninitialized submodules have no .git file
+                if line.submodule_status.is_some() && line.line_type == TreeLineType::Dir {
+                    line.line_type = TreeLineType::Submodule;
+                }
             }
         }
         tree
@@ -440,6 +567,13 @@ impl<'c> TreeBuilder<'c> {
         match self.gather_lines(total_search, dam) {
             Some(out_blines) => {
                 self.trim_excess(&out_blines);
+                if let Some(computer) = &mut self.line_status_computer {
+                    let blines = &self.blines;
+                    let paths = out_blines.iter().map(|&id| blines[id].path.as_path());
+                    if !computer.compute_submodule_statuses(paths, dam) {
+                        return None; // interrupted
+                    }
+                }
                 Some(self.take(&out_blines))
             }
             None => None, // interrupted
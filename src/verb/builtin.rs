@@ -10,73 +10,109 @@ pub fn builtin_verbs() -> Vec<Verb> {
     use super::{ExternalExecutionMode::*, Internal::*};
     vec![
         Verb::internal(back),
+        Verb::internal(back_to).with_shortcut("bt"),
         Verb::from(super::cd::CD.clone())
             .with_description("change directory and quit (mapped to *alt*-*enter*)"),
         #[cfg(unix)]
         Verb::external(
             "chmod {args}",
-            "chmod {args} {file}",
+            "chmod {args} {files}",
             StayInBroot,
         ).unwrap(),
+        Verb::external(
+            "submodule_update",
+            "git -C {parent} submodule update --init --recursive -- {file-name}",
+            StayInBroot,
+        ).unwrap()
+            .with_description("initialize or update the selected git submodule")
+            .with_shortcut("smu"),
         Verb::internal(open_preview),
         Verb::internal(close_preview),
         Verb::internal(toggle_preview),
+        Verb::internal(branch_glyphs),
+        Verb::internal(broadcast_options),
+        Verb::internal(broadcast_pattern).with_shortcut("bp"),
+        Verb::internal(archive),
+        Verb::internal(audit_permissions).with_shortcut("audit"),
+        Verb::internal(browse_image),
+        Verb::internal(grep_logs),
+        Verb::internal(resolve_chain),
+        Verb::internal(open_list),
         Verb::internal(preview_image),
         Verb::internal(preview_text),
         Verb::internal(preview_binary),
+        Verb::internal(preview_load_more).with_shortcut("more"),
+        Verb::internal(preview_toggle_binary).with_shortcut("hex"),
+        Verb::internal(preview_toggle_markdown).with_shortcut("mdv"),
+        Verb::internal(preview_toggle_diff).with_shortcut("gdiff"),
+        Verb::internal(preview_toggle_follow).with_shortcut("tail"),
+        Verb::internal(preview_toggle_structured).with_shortcut("struct"),
+        Verb::internal(bulk_rename).with_shortcut("brn"),
+        Verb::internal(chmod),
         Verb::internal(close_panel_ok),
         Verb::internal(close_panel_cancel)
             .with_key(BACK_TAB)
             .with_control_key('w'),
+        Verb::internal(clear_marks),
         Verb::external(
             "copy {newpath:path-from-parent}",
             "/bin/cp -r {file} {newpath:path-from-parent}",
             StayInBroot,
         )
-			.unwrap()
-			.with_shortcut("cp"),
+			.unwrap(),
 		Verb::internal(copy_path)
             .with_alt_key( 'c' ),
-        Verb::external(
-            "copy_to_panel",
-            "/bin/cp -r {file} {other-panel-directory}",
-            StayInBroot,
-        )
-			.unwrap()
-			.with_shortcut("cpp"),
+        Verb::internal(copy_content),
+        Verb::internal(copy_to).with_shortcut("cp"),
+        Verb::internal(copy_to_panel).with_shortcut("cpp"),
+        Verb::internal(cycle_git_ignore).with_shortcut("cgi"),
+        Verb::internal(diff),
+        Verb::internal(extract),
+        Verb::internal(file_copy).with_shortcut("fc"),
+        Verb::internal(file_cut).with_shortcut("fx"),
+        Verb::internal(file_paste).with_shortcut("fv"),
+        Verb::internal(find_duplicates).with_shortcut("dup"),
         // :focus is also hardcoded on Enter on directories
         // but ctrl-f is useful for focusing on a file's parent
         // (and keep the filter)
         Verb::internal(focus)
             .with_control_key('f'),
+        Verb::internal(focus_in_panel).with_shortcut("fp"),
+        Verb::internal(goto_line).with_shortcut("goto"),
+        Verb::internal(git_blame).with_shortcut("blame"),
+        Verb::internal(git_filter).with_shortcut("gfi"),
+        Verb::internal(git_log).with_shortcut("gl"),
+        Verb::internal(git_repos).with_shortcut("repos"),
+        Verb::internal(git_restore).with_shortcut("gr"),
+        Verb::internal(git_stage_hunk)
+            .with_alt_key('s')
+            .with_shortcut("sh"),
+        Verb::internal(git_show),
+        Verb::internal(hardlink).with_shortcut("hl"),
+        Verb::internal(hash),
+        Verb::internal(hash_check),
         Verb::internal(help).with_key(F1).with_shortcut("?"),
+        Verb::internal(input_history_next).with_key(ALT_DOWN),
+        Verb::internal(input_history_prev).with_key(ALT_UP),
+        Verb::internal(jobs),
         Verb::internal(line_down).with_key(DOWN),
         Verb::internal(line_up).with_key(UP),
-        Verb::external(
-            "mkdir {subpath}",
-            "/bin/mkdir -p {subpath:path-from-directory}",
-            StayInBroot,
-        )
-        .unwrap()
-        .with_shortcut("md"),
+        Verb::internal(link_duplicates),
+        Verb::internal(mkdir).with_shortcut("md"),
         Verb::external(
             "move {newpath:path-from-parent}",
             "/bin/mv {file} {newpath:path-from-parent}",
             StayInBroot,
         )
-        .unwrap()
-        .with_shortcut("mv"),
-        Verb::external(
-            "move_to_panel",
-            "/bin/mv {file} {other-panel-directory}",
-            StayInBroot,
-        )
-        .unwrap()
-        .with_shortcut("mvp"),
+        .unwrap(),
+        Verb::internal(move_to).with_shortcut("mv"),
+        Verb::internal(move_to_panel).with_shortcut("mvp"),
         Verb::internal_bang(start_end_panel)
             .with_control_key('p'),
         Verb::internal(next_match)
             .with_key(TAB),
+        Verb::internal(next_panel)
+            .with_shortcut("np"),
         Verb::internal(no_sort)
             .with_shortcut("ns"),
         Verb::internal(open_stay)
@@ -103,31 +139,58 @@ pub fn builtin_verbs() -> Vec<Verb> {
         Verb::internal(print_path).with_shortcut("pp"),
         Verb::internal(print_relative_path).with_shortcut("prp"),
         Verb::internal(print_tree).with_shortcut("pt"),
+        Verb::internal(profile),
         Verb::internal(quit)
             .with_control_key('c')
             .with_control_key('q')
             .with_shortcut("q"),
         Verb::internal(refresh).with_key(F5),
+        Verb::internal(reload_config),
+        Verb::internal(rename),
+        Verb::internal(resize_panel).with_shortcut("rp"),
         Verb::internal(sort_by_count).with_shortcut("sc"),
         Verb::internal(sort_by_date).with_shortcut("sd"),
         Verb::internal(sort_by_size).with_shortcut("ss"),
-        Verb::external(
-            "rm",
-            "/bin/rm -rf {file}",
-            StayInBroot,
-        ).unwrap(),
+        Verb::internal(remove_duplicates),
+        Verb::internal(restore),
+        Verb::internal(rm),
+        Verb::internal(rm_permanently),
+        Verb::internal(scroll_down).with_key(CTRL_DOWN),
+        Verb::internal(scroll_up).with_key(CTRL_UP),
+        Verb::internal(select_glob),
+        Verb::internal(set_times),
+        Verb::internal(symlink).with_shortcut("sl"),
+        Verb::internal(tab_close),
+        Verb::internal(tab_new),
+        Verb::internal(tab_next),
         Verb::internal(toggle_counts).with_shortcut("counts"),
         Verb::internal(toggle_dates).with_shortcut("dates"),
         Verb::internal(toggle_files).with_shortcut("files"),
+        Verb::internal(toggle_flat).with_shortcut("flat"),
+        Verb::internal(toggle_fold).with_shortcut("fold"),
         Verb::internal(toggle_git_ignore).with_shortcut("gi"),
+        Verb::internal(toggle_git_author).with_shortcut("ga"),
+        Verb::internal(toggle_git_commit_date).with_shortcut("gcd"),
         Verb::internal(toggle_git_file_info).with_shortcut("gf"),
         Verb::internal(toggle_git_status).with_shortcut("gs"),
         Verb::internal(toggle_hidden).with_shortcut("h"),
+        Verb::internal(toggle_ignore_files).with_shortcut("if"),
+        Verb::internal(toggle_broot_ignore).with_shortcut("bi"),
+        Verb::internal(toggle_link_panels).with_shortcut("link"),
+        Verb::internal(toggle_mark)
+            .with_control_key('t')
+            .with_shortcut("mark"),
+        Verb::internal(toggle_owner_mismatch).with_shortcut("om"),
+        Verb::internal(toggle_pattern_broadcast).with_shortcut("pb"),
         #[cfg(unix)]
         Verb::internal(toggle_perm).with_shortcut("perm"),
         Verb::internal(toggle_sizes).with_shortcut("sizes"),
         Verb::internal(toggle_trim_root),
         Verb::internal(total_search).with_control_key('s'),
+        Verb::internal(touch),
+        Verb::internal(trash),
+        // no key nor shortcut, to avoid an accidental use
+        Verb::internal(empty_trash),
         Verb::internal(up_tree).with_shortcut("up"),
     ]
 }
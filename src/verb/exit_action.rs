@@ -0,0 +1,33 @@
+use {
+    crate::errors::ConfError,
+};
+
+/// what broot does when an external command launched by a verb
+/// not leaving broot has exited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitAction {
+    /// refresh the lines of the paths given to the command, and
+    /// of their parents, keeping the filter
+    Refresh,
+    /// keep the view unchanged. On failure, the exit code is
+    /// displayed in the status line
+    Keep,
+    /// display what the command wrote on stderr in a new panel
+    ShowStderr,
+    /// quit broot
+    Quit,
+}
+
+impl ExitAction {
+    pub fn from(raw: &str) -> Result<Self, ConfError> {
+        match raw {
+            "refresh" => Ok(Self::Refresh),
+            "keep" => Ok(Self::Keep),
+            "show_stderr" => Ok(Self::ShowStderr),
+            "quit" => Ok(Self::Quit),
+            _ => Err(ConfError::InvalidExitAction {
+                action: raw.to_string(),
+            }),
+        }
+    }
+}
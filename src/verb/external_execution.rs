@@ -1,24 +1,47 @@
 //! Special groups:
 //! {file}
+//! {file-name}
+//! {file-stem}
+//! {file-extension}
 //! {directory}
 //! {parent}
+//! {line}
 //! {other-panel-file}
+//! {other-panel-file-name}
 //! {other-panel-directory}
 //! {other-panel-parent}
+//! {files}
+//!
+//! A `{ask:label}` group (for example `{ask:branch name}`) is a value
+//! the user is asked for: it's typed as argument of the verb, the
+//! label being displayed as a hint.
+//!
+//! When entries are marked, `{files}` is replaced with the list of
+//! their paths while a verb using `{file}` (or another group related
+//! to the selection) is executed once per marked entry.
+//!
+//! The grammar of a group is `{name}` or `{name:format}`. Other
+//! groups are read from the user input, according to the invocation
+//! pattern. A group preceded by a `$` (like `${EDITOR}`) is left to
+//! the shell.
 
 use {
-    super::{ExternalExecutionMode, VerbInvocation},
+    super::{ExitAction, ExternalExecutionMode, VerbInvocation, WorkingDir},
     crate::{
         app::*,
         display::W,
         errors::{ConfError, ProgramError},
-        launchable::Launchable,
+        jobs,
+        launchable::{Launchable, ProgramExit},
         path,
         path_anchor::PathAnchor,
+        preview::PreviewState,
+        temp_text::TempText,
     },
     regex::{Captures, Regex},
     std::{
         collections::HashMap,
+        ffi::OsStr,
         fs::OpenOptions,
         io::Write,
         path::{Path, PathBuf},
@@ -37,6 +60,100 @@ lazy_static! {
     static ref GROUP: Regex = Regex::new(r"\{([^{}:]+)(?::([^{}:]+))?\}").unwrap();
 }
 
+/// names of the groups whose value doesn't come from the user input
+static BUILTIN_GROUPS: &[&str] = &[
+    "file",
+    "file-name",
+    "file-stem",
+    "file-extension",
+    "directory",
+    "parent",
+    "line",
+    "other-panel-file",
+    "other-panel-file-name",
+    "other-panel-directory",
+    "other-panel-parent",
+    "files",
+];
+
+/// names of the builtin groups whose value isn't a path
+static NOT_PATH_GROUPS: &[&str] = &[
+    "file-name",
+    "file-stem",
+    "file-extension",
+    "line",
+    "other-panel-file-name",
+];
+
+/// the formats which may follow the name of a group
+static GROUP_FORMATS: &[&str] = &["path-from-directory", "path-from-parent"];
+
+/// name of the groups whose value is asked to the user, the
+/// format being the label of the value (as in `{ask:branch name}`)
+const ASK: &str = "ask";
+
+/// return the labels of the `{ask:label}` groups of the execution
+/// pattern, in order and without duplicates
+fn ask_labels(execution_str: &str) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for caps in GROUP.captures_iter(execution_str) {
+        if &caps[1] != ASK || execution_str[..caps.get(0).unwrap().start()].ends_with('$') {
+            continue;
+        }
+        if let Some(label) = caps.get(2) {
+            let label = label.as_str().to_string();
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+    }
+    labels
+}
+
+/// replace a group, the `{ask:label}` ones being looked for with
+/// their label
+fn do_replacement(ec: &Captures<'_>, map: &HashMap<String, String>) -> String {
+    if &ec[1] == ASK {
+        if let Some(value) = ec.get(2).and_then(|label| map.get(&format!("{}:{}", ASK, label.as_str()))) {
+            return value.to_string();
+        }
+    }
+    path::do_exec_replacement(ec, map)
+}
+
+/// split the execution pattern in tokens, on the whitespaces
+/// which aren't in a group (like in `{ask:branch name}`)
+fn exec_pattern_tokens(exec_pattern: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut in_group = false;
+    for (idx, c) in exec_pattern.char_indices() {
+        if c.is_whitespace() && !in_group {
+            if let Some(s) = start.take() {
+                tokens.push(&exec_pattern[s..idx]);
+            }
+            continue;
+        }
+        match c {
+            '{' => in_group = true,
+            '}' => in_group = false,
+            _ => {}
+        }
+        if start.is_none() {
+            start = Some(idx);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&exec_pattern[s..]);
+    }
+    tokens
+}
+
+/// make a string from a part of a file name (its stem, extension, etc.)
+fn name_part_to_string(part: Option<&OsStr>, for_shell: bool) -> String {
+    part.map_or_else(String::new, |part| path_to_string(Path::new(part), for_shell))
+}
+
 /// Definition of how the user input should be interpreted
 /// to be executed in an external command.
 #[derive(Debug, Clone)]
@@ -61,13 +178,41 @@ pub struct ExternalExecution {
 
     pub arg_anchor: PathAnchor,
 
-    /// whether the working dir of the external process must be set
-    /// to the current directory
-    pub set_working_dir: bool,
+    /// the directory the external process must be started in
+    pub working_dir: WorkingDir,
+
+    /// what to do when the process, launched without leaving
+    /// broot, exits with success
+    pub on_success: ExitAction,
+
+    /// what to do when the process, launched without leaving
+    /// broot, fails
+    pub on_failure: ExitAction,
 
     /// whether we need to have a secondary panel for execution
     /// (which is the case when an invocation has {other-panel-file})
     pub need_another_panel: bool,
+
+    /// labels of the values asked to the user, read as arguments
+    asks: Vec<String>,
+}
+
+/// return the groups of the execution pattern whose value must
+/// be read from the user input (i.e. not the predefined ones nor
+/// the shell variables)
+pub fn input_groups(execution_str: &str) -> Vec<String> {
+    let mut groups: Vec<String> = Vec::new();
+    for caps in GROUP.captures_iter(execution_str) {
+        let group = caps.get(0).unwrap();
+        if execution_str[..group.start()].ends_with('$') {
+            continue;
+        }
+        let group = group.as_str().to_string();
+        if !BUILTIN_GROUPS.contains(&&caps[1]) && &caps[1] != ASK && !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    groups
 }
 
 impl ExternalExecution {
@@ -76,12 +221,44 @@ impl ExternalExecution {
         execution_str: &str,
         exec_mode: ExternalExecutionMode,
     ) -> Result<Self, ConfError> {
-        let invocation_pattern = VerbInvocation::from(invocation_str);
+        let mut invocation_pattern = VerbInvocation::from(invocation_str);
         let mut args_parser = None;
         let mut arg_selection_type = None;
         let mut arg_anchor = PathAnchor::Unspecified;
         let mut need_another_panel = false;
-        if let Some(args) = &invocation_pattern.args {
+        let asks = ask_labels(execution_str);
+        if !asks.is_empty() {
+            if invocation_pattern.args.is_some() {
+                return Err(ConfError::AskWithArgs {
+                    invocation: invocation_str.to_string(),
+                });
+            }
+            // the asked values are typed as arguments, separated with
+            // spaces (the last one may contain spaces)
+            let spec = (0..asks.len())
+                .map(|i| {
+                    if i + 1 < asks.len() {
+                        format!(r"(?P<ask_{}>\S+)", i)
+                    } else {
+                        format!("(?P<ask_{}>.+)", i)
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(r"\s+");
+            args_parser = match Regex::new(&format!("^{}$", spec)) {
+                Ok(regex) => Some(regex),
+                Err(_) => {
+                    return Err(ConfError::InvalidVerbInvocation { invocation: spec });
+                }
+            };
+            // the labels are displayed when the arguments are missing
+            invocation_pattern.args = Some(
+                asks.iter()
+                    .map(|label| format!("{{{}}}", label))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            );
+        } else if let Some(args) = &invocation_pattern.args {
             let spec = GROUP.replace_all(args, r"(?P<$1>.+)");
             let spec = format!("^{}$", spec);
             args_parser = match Regex::new(&spec) {
@@ -103,8 +280,31 @@ impl ExternalExecution {
                 }
             }
         }
-        for group in GROUP.find_iter(execution_str) {
-            if group.as_str().starts_with("{other-panel-") {
+        for caps in GROUP.captures_iter(execution_str) {
+            let group = caps.get(0).unwrap();
+            if execution_str[..group.start()].ends_with('$') {
+                continue; // a shell variable
+            }
+            let name = &caps[1];
+            if name == ASK && caps.get(2).is_some() {
+                continue; // the format is the label
+            }
+            let is_arg = args_parser
+                .as_ref()
+                .is_some_and(|r| r.capture_names().flatten().any(|n| n == name));
+            if !is_arg && !BUILTIN_GROUPS.contains(&name) {
+                return Err(ConfError::UnknownVerbGroup {
+                    group: group.as_str().to_string(),
+                });
+            }
+            if let Some(format) = caps.get(2) {
+                if !GROUP_FORMATS.contains(&format.as_str()) {
+                    return Err(ConfError::UnknownVerbGroup {
+                        group: group.as_str().to_string(),
+                    });
+                }
+            }
+            if name.starts_with("other-panel-") {
                 need_another_panel = true;
             }
         }
@@ -116,7 +316,10 @@ impl ExternalExecution {
             arg_selection_type,
             arg_anchor,
             need_another_panel,
-            set_working_dir: false,
+            working_dir: WorkingDir::Unchanged,
+            on_success: ExitAction::Refresh,
+            on_failure: ExitAction::Keep,
+            asks,
         })
     }
 
@@ -124,6 +327,12 @@ impl ExternalExecution {
         &self.invocation_pattern.name
     }
 
+    /// whether the user must type arguments before the
+    /// verb can be executed
+    pub fn needs_args(&self) -> bool {
+        self.args_parser.as_ref().is_some_and(|regex| !regex.is_match(""))
+    }
+
     /// Assuming the verb has been matched, check whether the arguments
     /// are OK according to the regex. Return none when there's no problem
     /// and return the error to display if arguments don't match
@@ -172,15 +381,29 @@ impl ExternalExecution {
         let parent_str = path_to_string(parent, for_shell);
         map.insert("line".to_string(), sel.line.to_string());
         map.insert("file".to_string(), file_str.to_string());
+        map.insert("file-name".to_string(), name_part_to_string(file.file_name(), for_shell));
+        map.insert("file-stem".to_string(), name_part_to_string(file.file_stem(), for_shell));
+        map.insert("file-extension".to_string(), name_part_to_string(file.extension(), for_shell));
         map.insert("parent".to_string(), parent_str.to_string());
         let dir_str = if file.is_dir() { file_str } else { parent_str };
         map.insert("directory".to_string(), dir_str);
+        let files_str = if sel.marked.is_empty() {
+            path_to_string(file, true)
+        } else {
+            sel.marked
+                .iter()
+                .map(|path| path_to_string(path, true))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        map.insert("files".to_string(), files_str);
         if self.need_another_panel {
             if let Some(other_file) = other_file {
                 let other_parent = other_file.parent().unwrap_or(other_file);
                 let other_file_str = path_to_string(other_file, for_shell);
                 let other_parent_str = path_to_string(other_parent, for_shell);
                 map.insert("other-panel-file".to_string(), other_file_str.to_string());
+                map.insert("other-panel-file-name".to_string(), name_part_to_string(other_file.file_name(), for_shell));
                 map.insert("other-panel-parent".to_string(), other_parent_str.to_string());
                 let other_dir_str = if other_file.is_dir() { other_file_str } else { other_parent_str };
                 map.insert("other-panel-directory".to_string(), other_dir_str);
@@ -204,23 +427,78 @@ impl ExternalExecution {
                         map.insert(name.to_string(), c.as_str().to_string());
                     }
                 }
+                for (i, label) in self.asks.iter().enumerate() {
+                    if let Some(value) = map.remove(&format!("ask_{}", i)) {
+                        map.insert(format!("{}:{}", ASK, label), value);
+                    }
+                }
             }
         }
         map
     }
 
+    /// whether the verb must be executed once per marked path (which
+    /// is the case when there are marked paths and the execution
+    /// pattern doesn't receive them all at once with {files})
+    pub fn runs_per_marked(&self, sel: Selection<'_>) -> bool {
+        !sel.marked.is_empty()
+            && !GROUP.captures_iter(&self.exec_pattern).any(|caps| &caps[1] == "files")
+    }
+
     pub fn to_cmd_result(
         &self,
         w: &mut W,
         sel: Selection<'_>,
         other_file: &Option<PathBuf>,
         args: &Option<String>,
+        panel_root: Option<&Path>,
+        con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        if !self.runs_per_marked(sel) {
+            return self.to_single_cmd_result(w, sel, other_file, args, panel_root, con);
+        }
+        if self.exec_mode.is_leave_broot() && !self.exec_mode.is_from_shell() {
+            return Ok(AppStateCmdResult::DisplayError(
+                "this verb can't be executed on several marked entries, try {files}".to_string(),
+            ));
+        }
+        let mut result = AppStateCmdResult::Keep;
+        let mut changed_paths = Vec::new();
+        for path in sel.marked {
+            result = self.to_single_cmd_result(
+                w,
+                Selection::of_marked(path),
+                other_file,
+                args,
+                panel_root,
+                con,
+            )?;
+            match &mut result {
+                AppStateCmdResult::DisplayError(_) => break,
+                AppStateCmdResult::RefreshPaths { paths } => changed_paths.append(paths),
+                _ => {}
+            }
+        }
+        if let AppStateCmdResult::RefreshPaths { paths } = &mut result {
+            // the paths of all the executions are refreshed
+            *paths = changed_paths;
+        }
+        Ok(result)
+    }
+
+    fn to_single_cmd_result(
+        &self,
+        w: &mut W,
+        sel: Selection<'_>,
+        other_file: &Option<PathBuf>,
+        args: &Option<String>,
+        panel_root: Option<&Path>,
         con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
         if self.exec_mode.is_from_shell() {
             self.exec_from_shell_cmd_result(sel, other_file, args, con)
         } else {
-            self.exec_cmd_result(w, sel, other_file, args)
+            self.exec_cmd_result(w, sel, other_file, args, panel_root, con)
         }
     }
 
@@ -260,24 +538,45 @@ impl ExternalExecution {
         sel: Selection<'_>,
         other_file: &Option<PathBuf>,
         args: &Option<String>,
+        panel_root: Option<&Path>,
+        con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
         let launchable = Launchable::program(
             self.exec_token(sel, other_file, args),
-            if self.set_working_dir {
-                Some(path::closest_dir(sel.path))
-            } else {
-                None
-            },
+            self.working_dir.resolve(sel, panel_root, &con.launch_args.root),
         )?;
+        if self.exec_mode.is_background() {
+            return Ok(match launchable {
+                Launchable::Program { exe, args, working_dir } => {
+                    match jobs::launch(exe, args, working_dir) {
+                        Ok(id) => AppStateCmdResult::DisplayMessage(format!(
+                            "Job {} launched in background. Use *:jobs* to see its output",
+                            id,
+                        )),
+                        Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+                    }
+                }
+                _ => AppStateCmdResult::DisplayError(
+                    "only programs can be launched in background".to_string(),
+                ),
+            });
+        }
         if self.exec_mode.is_leave_broot() {
             Ok(AppStateCmdResult::from(launchable))
         } else {
             info!("Executing not leaving, launchable {:?}", launchable);
-            let execution = launchable.execute(Some(w));
+            let capture_stderr = self.on_success == ExitAction::ShowStderr
+                || self.on_failure == ExitAction::ShowStderr;
+            let execution = launchable.execute_program(
+                Some(w),
+                con.launch_args.is_inline(),
+                capture_stderr,
+            );
             match execution {
-                Ok(()) => {
-                    debug!("ok");
-                    Ok(AppStateCmdResult::RefreshState { clear_cache: true })
+                Ok(exit) => {
+                    debug!("exit status: {:?}", exit.status);
+                    let changed_paths = self.referenced_paths(sel, other_file, args);
+                    Ok(self.exit_cmd_result(exit, changed_paths, con))
                 }
                 Err(e) => {
                     warn!("launchable failed : {:?}", e);
@@ -287,6 +586,88 @@ impl ExternalExecution {
         }
     }
 
+    /// the paths the command may have changed: the ones given by the
+    /// groups of the execution pattern (the arguments being read as
+    /// paths), with their parents
+    fn referenced_paths(
+        &self,
+        sel: Selection<'_>,
+        other_file: &Option<PathBuf>,
+        args: &Option<String>,
+    ) -> Vec<PathBuf> {
+        let map = self.replacement_map(sel, other_file, args, false);
+        let mut paths = Vec::new();
+        for caps in GROUP.captures_iter(&self.exec_pattern) {
+            let name = &caps[1];
+            if NOT_PATH_GROUPS.contains(&name) {
+                continue;
+            }
+            if name == "files" {
+                if sel.marked.is_empty() {
+                    paths.push(sel.path.to_path_buf());
+                } else {
+                    paths.extend(sel.marked.iter().cloned());
+                }
+                continue;
+            }
+            let value = do_replacement(&caps, &map);
+            if !value.is_empty() {
+                paths.push(path::path_from(sel.path, self.arg_anchor, &value));
+            }
+        }
+        let parents: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect();
+        paths.extend(parents);
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// build the cmd result applying the action configured for
+    /// the way the process exited
+    fn exit_cmd_result(
+        &self,
+        exit: ProgramExit,
+        changed_paths: Vec<PathBuf>,
+        con: &AppContext,
+    ) -> AppStateCmdResult {
+        let success = exit.status.success();
+        let failure_message = || match exit.status.code() {
+            Some(code) => format!("Command failed with exit code {}", code),
+            None => "Command was interrupted".to_string(),
+        };
+        let action = if success { self.on_success } else { self.on_failure };
+        match action {
+            ExitAction::Refresh => AppStateCmdResult::RefreshPaths { paths: changed_paths },
+            ExitAction::Keep if success => AppStateCmdResult::Keep,
+            ExitAction::Keep => AppStateCmdResult::DisplayError(failure_message()),
+            ExitAction::Quit => AppStateCmdResult::Quit,
+            ExitAction::ShowStderr => {
+                let stderr = exit.stderr.as_deref().unwrap_or("");
+                if stderr.trim().is_empty() {
+                    return if success {
+                        AppStateCmdResult::Keep
+                    } else {
+                        AppStateCmdResult::DisplayError(failure_message())
+                    };
+                }
+                let temp_text = match TempText::write("stderr.txt", stderr) {
+                    Ok(temp_text) => temp_text,
+                    Err(e) => {
+                        return AppStateCmdResult::DisplayError(e.to_string());
+                    }
+                };
+                AppStateCmdResult::NewPanel {
+                    state: Box::new(PreviewState::of_temp_text(temp_text, con)),
+                    purpose: PanelPurpose::None,
+                    direction: HDir::Right,
+                }
+            }
+        }
+    }
+
     /// build the token which can be used to launch en executable.
     /// This doesn't make sense for a built-in.
     fn exec_token(
@@ -297,16 +678,43 @@ impl ExternalExecution {
         args: &Option<String>,
     ) -> Vec<String> {
         let map = self.replacement_map(sel, other_file, args, false);
-        self.exec_pattern
-            .split_whitespace()
-            .map(|token| {
-                GROUP
-                    .replace_all(token, |ec: &Captures<'_>| {
-                        path::do_exec_replacement(ec, &map)
-                    })
-                    .to_string()
+        let mut tokens = Vec::new();
+        for token in exec_pattern_tokens(&self.exec_pattern) {
+            if token == "{files}" {
+                // each path is given as its own argument, so there's
+                // no need for quoting
+                if sel.marked.is_empty() {
+                    tokens.push(path_to_string(sel.path, false));
+                } else {
+                    tokens.extend(sel.marked.iter().map(|path| path_to_string(path, false)));
+                }
+            } else {
+                tokens.push(
+                    GROUP
+                        .replace_all(token, |ec: &Captures<'_>| {
+                            do_replacement(ec, &map)
+                        })
+                        .to_string(),
+                );
+            }
+        }
+        tokens
+    }
+
+    /// build the execution string with the groups replaced but
+    /// without escaping, for when it's read by broot itself
+    pub fn exec_string(
+        &self,
+        sel: Selection<'_>,
+        other_file: &Option<PathBuf>,
+        args: &Option<String>,
+    ) -> String {
+        let map = self.replacement_map(sel, other_file, args, false);
+        GROUP
+            .replace_all(&self.exec_pattern, |ec: &Captures<'_>| {
+                do_replacement(ec, &map)
             })
-            .collect()
+            .to_string()
     }
 
     /// build a shell compatible command, with escapings
@@ -320,7 +728,7 @@ impl ExternalExecution {
         let map = self.replacement_map(sel, other_file, args, true);
         GROUP
             .replace_all(&self.exec_pattern, |ec: &Captures<'_>| {
-                path::do_exec_replacement(ec, &map)
+                do_replacement(ec, &map)
             })
             .to_string()
             .split_whitespace()
@@ -337,3 +745,59 @@ impl ExternalExecution {
             .join(" ")
     }
 }
+
+#[cfg(test)]
+mod external_execution_tests {
+
+    use super::*;
+
+    fn new(invocation: &str, execution: &str) -> Result<ExternalExecution, ConfError> {
+        ExternalExecution::new(invocation, execution, ExternalExecutionMode::FromParentShell)
+    }
+
+    fn sel(path: &Path) -> Selection<'_> {
+        Selection {
+            path,
+            line: 3,
+            stype: SelectionType::File,
+            marked: &[],
+        }
+    }
+
+    #[test]
+    fn check_group_validation() {
+        assert!(new("e", "nvim +{line} {file}").is_ok());
+        assert!(new("e", "echo ${HOME} {file-stem}").is_ok()); // a shell variable
+        assert!(new("cp {dest}", "cp {file} {dest:path-from-parent}").is_ok());
+        assert!(new("tag", "git tag {ask:tag name}").is_ok());
+        assert!(new("e", "echo {unknown}").is_err());
+        assert!(new("e", "echo {file:unknown-format}").is_err());
+        assert!(new("tag {name}", "git tag {ask:tag name}").is_err());
+        assert!(!new("e", "echo {file}").unwrap().need_another_panel);
+        assert!(new("d", "diff {file} {other-panel-file-name}").unwrap().need_another_panel);
+    }
+
+    #[test]
+    fn check_input_groups() {
+        assert_eq!(
+            input_groups("cp {file} {dest} ${HOME} {ask:label} {dest}"),
+            vec!["{dest}".to_string()],
+        );
+    }
+
+    #[test]
+    fn check_replacements() {
+        let path = Path::new("/not-a-dir/report.tar.gz");
+        let ee = new("e", "echo {file-name} {file-stem} {file-extension} {line}").unwrap();
+        assert_eq!(
+            ee.shell_exec_string(sel(path), &None, &None),
+            "echo report.tar.gz report.tar gz 3",
+        );
+        let ee = new("tag", "git tag {ask:tag name} {parent}").unwrap();
+        assert!(ee.needs_args());
+        assert_eq!(
+            ee.shell_exec_string(sel(path), &None, &Some("v1.2".to_string())),
+            "git tag v1.2 /not-a-dir",
+        );
+    }
+}
@@ -8,6 +8,10 @@ pub enum ExternalExecutionMode {
 
     /// executed in a sub process without quitting broot
     StayInBroot,
+
+    /// executed in a background process, broot staying usable, with
+    /// the output captured
+    Background,
 }
 
 impl ExternalExecutionMode {
@@ -17,19 +21,22 @@ impl ExternalExecutionMode {
             _ => false,
         }
     }
+    pub fn is_background(self) -> bool {
+        matches!(self, Self::Background)
+    }
     pub fn is_leave_broot(self) -> bool {
-        match self {
-            Self::StayInBroot => false,
-            _ => true,
-        }
+        !matches!(self, Self::StayInBroot | Self::Background)
     }
 
     pub fn from_conf(
         from_shell: Option<bool>,  // default is false
         leave_broot: Option<bool>, // default is true
+        background: Option<bool>,  // default is false
     ) -> Self {
         if from_shell.unwrap_or(false) {
             Self::FromParentShell
+        } else if background.unwrap_or(false) {
+            Self::Background
         } else if leave_broot.unwrap_or(true) {
             Self::LeaveBroot
         } else {
@@ -0,0 +1,79 @@
+use {
+    crate::errors::ConfError,
+    regex::Regex,
+    std::{
+        fmt,
+        path::Path,
+    },
+};
+
+/// a pattern on file names, either a glob (like `*.rs`)
+/// or a regex, written between slashes (like `/^test_.*\.py$/`)
+#[derive(Debug, Clone)]
+enum NamePattern {
+    Glob(glob::Pattern),
+    Regex(Regex),
+}
+
+/// a restriction of a verb to the files whose name
+/// matches one of the patterns
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    patterns: Vec<NamePattern>,
+    raw: Vec<String>,
+}
+
+impl NamePattern {
+    fn from(raw: &str) -> Result<Self, ConfError> {
+        let invalid = || ConfError::InvalidVerbFilter {
+            filter: raw.to_string(),
+        };
+        if raw.len() > 1 && raw.starts_with('/') && raw.ends_with('/') {
+            Regex::new(&raw[1..raw.len() - 1])
+                .map(NamePattern::Regex)
+                .map_err(|_| invalid())
+        } else {
+            glob::Pattern::new(raw)
+                .map(NamePattern::Glob)
+                .map_err(|_| invalid())
+        }
+    }
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NamePattern::Glob(glob) => glob.matches(name),
+            NamePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl FileFilter {
+    pub fn new(raw: Vec<String>) -> Result<Self, ConfError> {
+        let patterns = raw
+            .iter()
+            .map(|s| NamePattern::from(s))
+            .collect::<Result<Vec<NamePattern>, ConfError>>()?;
+        Ok(Self { patterns, raw })
+    }
+    /// tell whether the name of the file matches one of the patterns
+    pub fn accepts(&self, path: &Path) -> bool {
+        match path.file_name() {
+            Some(name) => {
+                let name = name.to_string_lossy();
+                self.patterns.iter().any(|p| p.matches(&name))
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for FileFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, raw) in self.raw.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{}`", raw)?;
+        }
+        Ok(())
+    }
+}
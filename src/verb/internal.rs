@@ -53,11 +53,45 @@ macro_rules! Internals {
 
 
 Internals! {
+    archive: "pack the marked entries, or the selection, into a zip, tar or tar.gz archive",
+    audit_permissions: "show only the entries whose ownership or permissions fail an audit",
     back: "revert to the previous state (mapped to *esc*)",
+    back_to: "list the states of the panel, or go back to the one at the given position",
+    browse_image: "browse a container image (the selected tarball or a tag given as argument)",
+    branch_glyphs: "change the glyphs used to draw the branches (light, heavy, ascii, none)",
+    broadcast_options: "apply the tree options of the panel (hidden files, sort, etc.) to all panels",
+    broadcast_pattern: "apply the pattern of the panel to all tree panels",
+    bulk_rename: "rename the marked entries, or the ones of the filtered tree, by editing their paths in $EDITOR",
+    chmod: "edit the permissions of the selection",
     close_panel_ok: "close the panel, validating the selected path",
     close_panel_cancel: "close the panel, not using the selected path",
+    clear_marks: "unmark all the marked entries",
+    copy_content: "copy the content of the selected text file to system clipboard",
     copy_path: "copy path to system clipboard (mapped to *alt-c*)",
+    copy_to: "copy the selection to the given path, with checks for overwrites",
+    copy_to_panel: "copy the selection, or the marked entries, to the root of the other panel",
+    cycle_git_ignore: "cycle between respecting all gitignore rules, only the ones of the repositories, and none",
+    diff: "compare the selected file with the marked one, or with the selection of the other panel",
+    empty_trash: "definitively delete all the items of the trash",
+    extract: "extract the selected archive into the given directory, or next to it",
+    file_copy: "put the marked entries, or the selection, in the clipboard, to be pasted elsewhere",
+    file_cut: "put the marked entries, or the selection, in the clipboard, to be moved when pasted",
+    file_paste: "copy (or move, if they were cut) the files of the clipboard to the selected directory",
+    find_duplicates: "search the files with identical content in the displayed directory",
     focus: "display the directory (mapped to *enter*)",
+    goto_line: "select the line with the given number in the preview",
+    focus_in_panel: "display the selected directory in the adjacent panel (left or right)",
+    git_blame: "show who last changed each line of the selected file",
+    git_filter: "show only the files having the given git states, eg `conflicted` or `modified,untracked`",
+    git_repos: "list the git repositories under the current root, with their branch and state",
+    git_restore: "discard the local modifications of the selected file, after confirmation",
+    git_log: "list the commits which changed the selected file or directory",
+    git_stage_hunk: "stage the selected hunk of a diff preview, or unstage it if it's staged",
+    git_show: "show the changes of the commit selected in a git log",
+    grep_logs: "search a regex in the logs of the directory, even compressed",
+    hardlink: "create a hard link to the selection in the directory of the other panel, or at the given path",
+    hash: "compute the digests (md5, sha1 or sha256) of the selected file or of the marked ones, and copy them",
+    hash_check: "check the files listed in the selected checksums file (as written by sha256sum)",
     help: "display broot's help",
     input_del_char_left: "delete the char left of the cursor",
     input_del_char_below: "delete the char left at the cursor's position",
@@ -69,12 +103,22 @@ Internals! {
     input_go_to_start: "move the cursor to the start of input",
     input_go_word_left: "move the cursor one word to the left",
     input_go_word_right: "move the cursor one word to the right",
+    input_history_next: "replace the input with the next entry of its history",
+    input_history_prev: "replace the input with the previous entry of its history, among the ones starting with what was typed",
+    input_set: "replace the content of the input with the argument (usually bound to a key)",
+    jobs: "display the jobs launched in background and their output",
     line_down: "move one line down",
     line_up: "move one line up",
+    link_duplicates: "keep the selected copy of identical files, replacing the other ones with hard links",
+    mkdir: "create a directory, and its missing parents, relative to the selected directory",
+    move_to: "move the selection to the given path, with checks for overwrites and other devices",
+    move_to_panel: "move the selection, or the marked entries, to the root of the other panel",
+    open_list: "open a list defined in configuration, whose name is given as argument",
     open_stay: "open file or directory according to OS (stay in broot)",
     open_stay_filter: "display the directory, keeping the current pattern",
     open_leave: "open file or directory according to OS (quit broot)",
     next_match: "select the next match",
+    next_panel: "focus the next panel, cycling back to the first one",
     no_sort: "don't sort",
     page_down: "scroll one page down",
     page_up: "scroll one page up",
@@ -88,37 +132,98 @@ Internals! {
     preview_image: "preview the selection as image",
     preview_text: "preview the selection as text",
     preview_binary: "preview the selection as binary",
+    preview_load_more: "read the next part of a text preview which was truncated",
+    preview_toggle_binary: "switch the preview between the binary and the text views",
+    preview_toggle_markdown: "switch the preview between the rendered markdown and the text views",
+    preview_toggle_diff: "switch the preview between the git diff and the text views",
+    preview_toggle_follow: "follow the end of the previewed file, like `tail -f`",
+    preview_toggle_structured: "switch the preview between the foldable tree and the text views",
     print_path: "print path and leaves broot",
     print_relative_path: "print relative path and leaves broot",
     print_tree: "print tree and leaves broot",
+    profile: "apply the flags of a profile defined in configuration, whose name is given as argument",
     start_end_panel: "either open or close an additional panel",
     quit: "quit Broot",
     refresh: "refresh tree and clear size cache",
+    reload_config: "read the configuration again and apply it, keeping the panels",
+    resize_panel: "grow (or shrink, with a negative value) the panel by some cells",
+    rename: "rename the selection, the input being pre-filled with its current name",
+    resolve_chain: "display the chain of symlinks leading to the real path of the selection",
+    remove_duplicates: "keep the selected copy of identical files, moving the other ones to the trash",
+    restore: "move the selected item of the trash back to its original location",
+    rm: "move the selection to the trash, or, with the secure option, overwrite it before deleting it",
+    rm_permanently: "delete the selection, without going through the trash",
     //restore_pattern: "restore a pattern which was just removed",
+    scroll_down: "scroll one line down, without moving the selection",
+    symlink: "create a symbolic link to the selection in the directory of the other panel, or at the given path",
+    tab_close: "close the current tab, with its panels",
+    tab_new: "open a new tab on the selected directory",
+    tab_next: "display the next tab, cycling back to the first one",
+    scroll_up: "scroll one line up, without moving the selection",
     select_first: "select the first file",
+    select_glob: "mark the entries matching a glob and select the first one",
     select_last: "select the last file",
+    set_times: "set the access and modification times of the selection to now, or to the given time",
     sort_by_count: "sort by count",
     sort_by_date: "sort by date",
     sort_by_size: "sort by size",
+    toggle_broot_ignore: "toggle use of the .brootignore files",
     toggle_counts: "toggle showing number of files in directories",
+    toggle_link_panels: "toggle selecting the same relative path in the other panel",
     toggle_dates: "toggle showing last modified dates",
     toggle_files: "toggle showing files (or just folders)",
+    toggle_flat: "toggle displaying search results as a flat list",
+    toggle_fold: "fold or unfold the selected object or array of a structured preview",
     toggle_git_ignore: "toggle use of .gitignore",
+    toggle_git_author: "toggle showing the author of the last commit",
+    toggle_git_commit_date: "toggle showing the date of the last commit",
     toggle_git_file_info: "toggle display of git file information",
     toggle_git_status: "toggle showing only files relevant for git status",
     toggle_hidden: "toggle showing hidden files",
+    toggle_ignore_files: "toggle use of the .ignore and .fdignore files",
+    toggle_mark: "mark or unmark the selection, for verbs to be executed on all marked entries",
+    toggle_owner_mismatch: "toggle showing only files whose owner isn't the expected one",
+    toggle_pattern_broadcast: "toggle applying the patterns typed in a panel to all tree panels",
     toggle_perm: "toggle showing file permissions",
     toggle_sizes: "toggle showing sizes",
     toggle_trim_root: "toggle removing nodes at first level too",
     total_search: "search again but on all children",
+    touch: "create an empty file relative to the selected directory",
+    trash: "list the items of the trash, with their original paths and deletion dates",
     up_tree: "focus the parent of the current root",
 }
 
 impl Internal {
-    /// whether this internal accept a path as (optional) argument
-    pub fn accept_path(self) -> bool {
+    /// whether this internal accept an (optional) argument, which
+    /// is usually a path
+    pub fn accept_arg(self) -> bool {
         match self {
+            Internal::back_to => true, // the argument is the position of the state
             Internal::focus => true,
+            Internal::focus_in_panel => true, // the argument is the direction of the panel
+            Internal::goto_line => true, // the argument is the line number
+            Internal::grep_logs => true, // the argument is a regex
+            Internal::branch_glyphs => true, // the argument is a glyph set name
+            Internal::browse_image => true, // the argument is an image tag
+            Internal::open_list => true, // the argument is the name of the list
+            Internal::profile => true, // the argument is the name of the profile
+            Internal::archive => true, // the argument is the path of the archive
+            Internal::audit_permissions => true, // the argument is the audit spec
+            Internal::git_filter => true, // the argument is a list of git states
+            Internal::copy_to => true, // the argument is the destination
+            Internal::extract => true, // the argument is the destination
+            Internal::hardlink => true, // the argument is the path of the link
+            Internal::hash => true, // the argument is the algorithm
+            Internal::symlink => true, // the argument is the path of the link
+            Internal::move_to => true, // the argument is the destination
+            Internal::rename => true, // the argument is the new name
+            Internal::rm => true, // the argument is the secure option, with the number of passes
+            Internal::mkdir => true, // the argument is the path of the new directory
+            Internal::touch => true, // the argument is the path of the new file
+            Internal::input_set => true, // the argument is the new content of the input
+            Internal::resize_panel => true, // the argument is the number of cells
+            Internal::select_glob => true, // the argument is the glob
+            Internal::set_times => true, // the argument is the time
             _ => false,
         }
     }
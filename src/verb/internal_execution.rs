@@ -32,7 +32,7 @@ impl InternalExecution {
         invocation: &VerbInvocation,
         _other_path: &Option<PathBuf>,
     ) -> Option<String> {
-        if invocation.args.is_some() && !self.internal.accept_path() {
+        if invocation.args.is_some() && !self.internal.accept_arg() {
             Some(format!("{} doesn't take arguments", invocation.name))
         } else {
             None
@@ -41,15 +41,22 @@ impl InternalExecution {
     pub fn try_from(invocation_str: &str) -> Result<Self, ConfError> {
         let invocation = VerbInvocation::from(invocation_str);
         let internal = Internal::try_from(&invocation.name)?;
-        if invocation.args.is_some() && !internal.accept_path() {
+        if invocation.args.is_some() && !internal.accept_arg() {
             return Err(ConfError::UnexpectedInternalArg {
                 invocation: invocation_str.to_string(),
             });
         }
+        let arg = if internal == Internal::input_set {
+            // the text to put in the input is kept verbatim, with its
+            // leading colon and trailing spaces
+            invocation_str.split_once(' ').map(|x| x.1).map(|s| s.to_string())
+        } else {
+            invocation.args
+        };
         Ok(Self {
             internal,
             bang: invocation.bang,
-            arg: invocation.args,
+            arg,
         })
     }
     pub fn as_desc_code(&self) -> Option<String> {
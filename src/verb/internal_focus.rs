@@ -72,6 +72,26 @@ pub fn new_panel_on_path(
     }
 }
 
+/// build a tree state on the path (or its parent if it's a file),
+/// to be pushed on the adjacent panel in the given direction
+pub fn new_state_in_panel_on_path(
+    path: PathBuf,
+    screen: &mut Screen,
+    tree_options: TreeOptions,
+    con: &AppContext,
+    direction: HDir,
+) -> AppStateCmdResult {
+    let path = path::closest_dir(&path);
+    match BrowserState::new(path, tree_options, screen, con, &Dam::unlimited()) {
+        Ok(Some(os)) => AppStateCmdResult::NewStateInPanel {
+            state: Box::new(os),
+            direction,
+        },
+        Ok(None) => AppStateCmdResult::Keep, // this isn't supposed to happen
+        Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+    }
+}
+
 /// general implementation for verbs based on the :focus internal with optionally
 /// a bang or an argument.
 pub fn on_internal(
@@ -1,31 +1,39 @@
 mod builtin;
 mod cd;
+mod exit_action;
 mod external_execution;
 mod external_execution_mode;
+mod file_filter;
 mod internal;
 mod internal_execution;
 pub mod internal_focus;
+mod sequence_execution;
 mod verb;
 mod verb_conf;
 mod verb_description;
 mod verb_execution;
 mod verb_invocation;
 mod verb_store;
+mod working_dir;
 
 pub use {
     cd::CD,
+    exit_action::ExitAction,
     external_execution::ExternalExecution,
     external_execution_mode::ExternalExecutionMode,
+    file_filter::FileFilter,
     //focus::{
     //    on_include,
     //    on_path,
     //},
     internal::Internal,
     internal_execution::InternalExecution,
+    sequence_execution::SequenceExecution,
     verb::Verb,
     verb_conf::VerbConf,
     verb_description::VerbDescription,
     verb_execution::VerbExecution,
     verb_invocation::VerbInvocation,
     verb_store::{PrefixSearchResult, VerbStore},
+    working_dir::WorkingDir,
 };
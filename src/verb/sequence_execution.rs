@@ -0,0 +1,79 @@
+use {
+    super::*,
+    crate::{
+        app::Selection,
+        command::Sequence,
+        errors::ConfError,
+    },
+    std::path::PathBuf,
+};
+
+/// separator of the commands in the execution of a sequence verb
+pub const SEQUENCE_SEPARATOR: &str = "&&";
+
+/// A verb execution made of several commands (internals, other
+/// verbs, patterns) which are executed in order, as if typed
+/// by the user
+#[derive(Debug, Clone)]
+pub struct SequenceExecution {
+    /// the external execution is only used to parse the arguments
+    /// and to replace the groups in the commands
+    template: ExternalExecution,
+}
+
+impl SequenceExecution {
+    /// tell whether the execution string of a verb defines a sequence,
+    /// like `:mkdir {subpath} && :focus {subpath}`
+    pub fn is_sequence(execution_str: &str) -> bool {
+        execution_str.starts_with(':') && execution_str.contains(SEQUENCE_SEPARATOR)
+    }
+    pub fn new(
+        invocation_str: &str,
+        execution_str: &str,
+    ) -> Result<Self, ConfError> {
+        let commands: Vec<&str> = execution_str
+            .split(SEQUENCE_SEPARATOR)
+            .map(str::trim)
+            .collect();
+        if commands.iter().any(|c| c.is_empty()) {
+            return Err(ConfError::InvalidVerbSequence {
+                sequence: execution_str.to_string(),
+            });
+        }
+        let template = ExternalExecution::new(
+            invocation_str,
+            &commands.join(SEQUENCE_SEPARATOR),
+            ExternalExecutionMode::StayInBroot,
+        )?;
+        Ok(Self { template })
+    }
+    pub fn name(&self) -> &str {
+        self.template.name()
+    }
+    /// the commands, before replacement of the groups
+    pub fn raw(&self) -> &str {
+        &self.template.exec_pattern
+    }
+    pub fn needs_args(&self) -> bool {
+        self.template.needs_args()
+    }
+    pub fn check_args(
+        &self,
+        invocation: &VerbInvocation,
+        other_path: &Option<PathBuf>,
+    ) -> Option<String> {
+        self.template.check_args(invocation, other_path)
+    }
+    /// build the sequence to execute, the groups being replaced
+    pub fn to_sequence(
+        &self,
+        sel: Selection<'_>,
+        other_file: &Option<PathBuf>,
+        args: &Option<String>,
+    ) -> Sequence {
+        Sequence::new(
+            SEQUENCE_SEPARATOR.to_string(),
+            self.template.exec_string(sel, other_file, args),
+        )
+    }
+}
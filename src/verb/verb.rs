@@ -26,10 +26,12 @@ pub struct Verb {
     /// names (like "cd", "focus", "focus_tab", "c") by which
     /// a verb can be called.
     /// Can be empty if the verb is only called with a key shortcut.
-    /// Right now there's no way for it to contain more than 2 elements
-    /// but this may change.
+    /// The names after the first one are shortcuts or aliases.
     pub names: Vec<String>,
 
+    /// description of the alternate names, for the help screen
+    pub aliases_desc: String,
+
     /// key shortcuts
     pub keys: Vec<KeyEvent>,
 
@@ -44,6 +46,13 @@ pub struct Verb {
 
     /// the type of selection this verb applies to
     pub selection_condition: SelectionType,
+
+    /// when set, the verb only applies to the files whose name
+    /// matches this filter
+    pub file_filter: Option<FileFilter>,
+
+    /// whether the user must confirm before the verb is executed
+    pub needs_confirmation: bool,
 }
 
 impl From<ExternalExecution> for Verb {
@@ -68,11 +77,14 @@ impl Verb {
         }
         Self {
             names,
+            aliases_desc: "".to_string(),
             keys: Vec::new(),
             keys_desc: "".to_string(),
             execution,
             description,
             selection_condition: SelectionType::Any,
+            file_filter: None,
+            needs_confirmation: false,
         }
     }
 
@@ -130,9 +142,19 @@ impl Verb {
         self
     }
     pub fn with_shortcut(mut self, shortcut: &str) -> Self {
-        self.names.push(shortcut.to_string());
+        self.add_name(shortcut);
         self
     }
+    /// add an alternate name, unless the verb already has it
+    pub fn add_name(&mut self, name: &str) {
+        if self.names.iter().any(|n| n == name) {
+            return;
+        }
+        self.names.push(name.to_string());
+        if self.names.len() > 1 {
+            self.aliases_desc = self.names[1..].join(", ");
+        }
+    }
 
     /// Assuming the verb has been matched, check whether the arguments
     /// are OK according to the regex. Return none when there's no problem
@@ -145,6 +167,17 @@ impl Verb {
         match &self.execution {
             VerbExecution::Internal(internal_exec) => internal_exec.check_args(invocation, other_path),
             VerbExecution::External(external_exec) => external_exec.check_args(invocation, other_path),
+            VerbExecution::Sequence(seq_exec) => seq_exec.check_args(invocation, other_path),
+        }
+    }
+
+    /// whether the verb can't be executed without arguments
+    /// typed by the user
+    pub fn needs_args(&self) -> bool {
+        match &self.execution {
+            VerbExecution::Internal(_) => false,
+            VerbExecution::External(external_exec) => external_exec.needs_args(),
+            VerbExecution::Sequence(seq_exec) => seq_exec.needs_args(),
         }
     }
 
@@ -159,10 +192,24 @@ impl Verb {
         } else {
             let name = self.names.get(0).unwrap_or(&invocation.name);
             let markdown = match &self.execution {
+                VerbExecution::External(external_exec) if external_exec.runs_per_marked(sel) => {
+                    let first = Selection::of_marked(&sel.marked[0]);
+                    let exec_desc = external_exec.shell_exec_string(first, other_path, &invocation.args);
+                    format!(
+                        "Hit *enter* to **{}** on the {} marked entries: `{}`, ...",
+                        name,
+                        sel.marked.len(),
+                        &exec_desc,
+                    )
+                }
                 VerbExecution::External(external_exec) => {
                     let exec_desc = external_exec.shell_exec_string(sel, other_path, &invocation.args);
                     format!("Hit *enter* to **{}**: `{}`", name, &exec_desc)
                 }
+                VerbExecution::Sequence(seq_exec) => {
+                    let sequence = seq_exec.to_sequence(sel, other_path, &invocation.args);
+                    format!("Hit *enter* to **{}**: `{}`", name, &sequence.raw)
+                }
                 VerbExecution::Internal(internal_exec) => {
                     let pb;
                     let arg = invocation.args.as_ref().or_else(|| internal_exec.arg.as_ref());
@@ -185,6 +232,26 @@ impl Verb {
         }
     }
 
+    /// tell whether the verb can be applied to the selection,
+    /// according to its type and to the file filter
+    pub fn applies_to(&self, sel: Selection<'_>) -> bool {
+        sel.stype.respects(self.selection_condition)
+            && self.file_filter.as_ref().is_none_or(|filter| filter.accepts(sel.path))
+    }
+
+    /// the error to display when the verb is invoked on a selection
+    /// it doesn't apply to
+    pub fn selection_condition_error(&self, name: &str) -> String {
+        if let Some(filter) = &self.file_filter {
+            return format!("{} only applies to {}", name, filter);
+        }
+        match self.selection_condition {
+            SelectionType::File => format!("{} only applies to files", name),
+            SelectionType::Directory => format!("{} only applies to directories", name),
+            SelectionType::Any => format!("{} can't be applied to this selection", name),
+        }
+    }
+
     /// in case the verb take only one argument of type path, return
     /// the selection type of this unique argument
     pub fn get_arg_selection_type(&self) -> Option<SelectionType> {
@@ -208,9 +275,24 @@ impl Verb {
         }
     }
 
-    pub fn set_working_dir(&mut self, b: bool) {
+    pub fn set_exit_actions(
+        &mut self,
+        on_success: Option<ExitAction>,
+        on_failure: Option<ExitAction>,
+    ) {
+        if let VerbExecution::External(external) = &mut self.execution {
+            if let Some(action) = on_success {
+                external.on_success = action;
+            }
+            if let Some(action) = on_failure {
+                external.on_failure = action;
+            }
+        }
+    }
+
+    pub fn set_working_dir(&mut self, working_dir: WorkingDir) {
         if let VerbExecution::External(external) = &mut self.execution {
-            external.set_working_dir = b;
+            external.working_dir = working_dir;
         }
     }
 }
@@ -1,5 +1,5 @@
 use {
-    super::*,
+    super::{external_execution::input_groups, *},
     crate::{
         app::SelectionType,
         errors::ConfError,
@@ -9,29 +9,108 @@ use {
 };
 
 /// what's needed to handle a verb
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VerbConf {
     pub shortcut: Option<String>,
+    pub names: Vec<String>, // alternate names, aliases of the invocation one
     pub invocation: Option<String>,
     pub key: Option<KeyEvent>,
     pub execution: String,
     pub description: Option<String>,
     pub from_shell: Option<bool>,
     pub leave_broot: Option<bool>,
-    pub set_working_dir: Option<bool>,
+    pub background: Option<bool>,
+    pub working_dir: Option<WorkingDir>,
+    pub on_success: Option<ExitAction>,
+    pub on_failure: Option<ExitAction>,
     pub selection_condition: SelectionType,
+    pub file_filter: Option<FileFilter>,
+    pub confirm: Option<bool>,
+}
+
+impl VerbConf {
+    /// build a verb conf from a definition given on launch, either
+    /// `name:execution` or `invocation:execution`.
+    ///
+    /// When only a name is given, the invocation is made of this name
+    /// followed by the groups of the execution which aren't predefined,
+    /// so that `x:rg {pattern} {directory}` is invoked as `:x some_regex`.
+    pub fn from_cli_definition(definition: &str) -> Result<Self, ConfError> {
+        // we split at the first colon which isn't in a group
+        let mut depth = 0;
+        let split_idx = definition.char_indices().find(|&(_, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                ':' if depth == 0 => return true,
+                _ => {}
+            }
+            false
+        });
+        let (invocation, execution) = match split_idx {
+            Some((idx, _)) => (definition[..idx].trim(), definition[idx + 1..].trim()),
+            None => {
+                return Err(ConfError::InvalidVerbInvocation {
+                    invocation: definition.to_string(),
+                });
+            }
+        };
+        if invocation.is_empty() || execution.is_empty() {
+            return Err(ConfError::InvalidVerbInvocation {
+                invocation: definition.to_string(),
+            });
+        }
+        let invocation = if invocation.contains(char::is_whitespace) || invocation.contains('{') {
+            invocation.to_string()
+        } else {
+            let mut invocation = invocation.to_string();
+            if !execution.starts_with(':') || SequenceExecution::is_sequence(execution) {
+                for group in input_groups(execution) {
+                    invocation.push(' ');
+                    invocation.push_str(&group);
+                }
+            }
+            invocation
+        };
+        Ok(Self {
+            shortcut: None,
+            names: Vec::new(),
+            invocation: Some(invocation),
+            key: None,
+            execution: execution.to_string(),
+            description: None,
+            from_shell: None,
+            leave_broot: None,
+            background: None,
+            working_dir: None,
+            on_success: None,
+            on_failure: None,
+            selection_condition: SelectionType::Any,
+            file_filter: None,
+            confirm: None,
+        })
+    }
 }
 
 impl TryFrom<&VerbConf> for Verb {
     type Error = ConfError;
     fn try_from(verb_conf: &VerbConf) -> Result<Self, Self::Error> {
-        // if there's a ':' or ' ' at starts, it's an internal.
+        // if there are several commands separated by "&&", it's a sequence.
+        // Else if there's a ':' or ' ' at starts, it's an internal.
         // In other cases it's an external.
         // (we might support adding aliases to externals in the
         // future. In such cases we'll check among previously
         // added externals if no internal is found with the name)
         let mut s: &str = &verb_conf.execution;
-        let mut verb = if s.starts_with(':') || s.starts_with(' ') {
+        let mut verb = if SequenceExecution::is_sequence(s) {
+            let seq_exec = SequenceExecution::new(
+                verb_conf.invocation.as_deref().unwrap_or(""),
+                s,
+            )?;
+            let name = Some(seq_exec.name().to_string());
+            let description = VerbDescription::from_code(seq_exec.raw().to_string());
+            Verb::new(name, VerbExecution::Sequence(seq_exec), description)
+        } else if s.starts_with(':') || s.starts_with(' ') {
             s = &s[1..];
             let internal_execution = InternalExecution::try_from(s)?;
             let name = verb_conf.invocation.as_ref().map(|inv| {
@@ -55,6 +134,7 @@ impl TryFrom<&VerbConf> for Verb {
                 ExternalExecutionMode::from_conf(
                     verb_conf.from_shell,
                     verb_conf.leave_broot,
+                    verb_conf.background,
                 ),
             )?
         };
@@ -62,15 +142,21 @@ impl TryFrom<&VerbConf> for Verb {
             verb = verb.with_key(key);
         }
         if let Some(shortcut) = &verb_conf.shortcut {
-            verb.names.push(shortcut.to_string());
+            verb.add_name(shortcut);
+        }
+        for name in &verb_conf.names {
+            verb.add_name(name);
         }
         if let Some(description) = &verb_conf.description {
             verb.description = VerbDescription::from_text(description.to_string());
         }
-        if let Some(b) = verb_conf.set_working_dir {
-            verb.set_working_dir(b);
+        if let Some(working_dir) = &verb_conf.working_dir {
+            verb.set_working_dir(working_dir.clone());
         }
+        verb.set_exit_actions(verb_conf.on_success, verb_conf.on_failure);
         verb.selection_condition = verb_conf.selection_condition;
+        verb.file_filter = verb_conf.file_filter.clone();
+        verb.needs_confirmation = verb_conf.confirm.unwrap_or(false);
         Ok(verb)
     }
 }
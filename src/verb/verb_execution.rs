@@ -1,4 +1,4 @@
-use super::{ExternalExecution, InternalExecution};
+use super::{ExternalExecution, InternalExecution, SequenceExecution};
 
 /// how a verb must be executed
 #[derive(Debug, Clone)]
@@ -10,4 +10,8 @@ pub enum VerbExecution {
     /// the verb execution refers to a command that will be executed by the system,
     /// outside of broot.
     External(ExternalExecution),
+
+    /// the verb execution is a sequence of commands, executed in order.
+    /// Executions in conf made of commands separated by "&&" are of this type.
+    Sequence(SequenceExecution),
 }
@@ -65,25 +65,39 @@ impl From<&str> for VerbInvocation {
     /// parse a string being or describing the invocation of a verb with its
     /// arguments and optional bang. The leading space or colon must
     /// have been stripped before.
+    ///
+    /// This never fails nor panics: a malformed invocation (for
+    /// example with a bang in the middle of the name) just gives
+    /// a name which won't match any verb.
     fn from(invocation: &str) -> Self {
-        let caps = regex!(
-            r"(?x)
-                ^
-                (?P<bang_before>!)?
-                (?P<name>[^!\s]*)
-                (?P<bang_after>!)?
-                (?:[\s:]+(?P<args>.*))?
-                \s*
-                $
-            "
-        )
-        .captures(invocation)
-        .unwrap();
-        let bang_before = caps.name("bang_before").is_some();
-        let bang_after = caps.name("bang_after").is_some();
-        let bang = bang_before || bang_after;
-        let name = caps.name("name").unwrap().as_str().to_string();
-        let args = caps.name("args").map(|c| c.as_str().to_string());
+        let mut bang = false;
+        let mut s = invocation;
+        if s.starts_with('!') {
+            bang = true;
+            s = &s[1..];
+        }
+        let is_separator = |c: char| c == ':' || c.is_whitespace();
+        let mut name_end = s
+            .find(|c: char| c == '!' || c.is_whitespace())
+            .unwrap_or(s.len());
+        let mut rest = &s[name_end..];
+        if rest.starts_with('!') {
+            let after_bang = &rest[1..];
+            if after_bang.is_empty() || after_bang.starts_with(is_separator) {
+                bang = true;
+                rest = after_bang;
+            } else {
+                // the bang isn't at the end of the name: it's part of it
+                name_end = s.find(char::is_whitespace).unwrap_or(s.len());
+                rest = &s[name_end..];
+            }
+        }
+        let name = s[..name_end].to_string();
+        let args = if rest.is_empty() {
+            None
+        } else {
+            Some(rest.trim_start_matches(is_separator).to_string())
+        };
         VerbInvocation { name, args, bang }
     }
 }
@@ -109,5 +123,23 @@ mod verb_invocation_tests {
             VerbInvocation::from("cp ../"),
             VerbInvocation::new("cp", Some("../"), false),
         );
+        assert_eq!(
+            VerbInvocation::from("cp! :a b"),
+            VerbInvocation::new("cp", Some("a b"), true),
+        );
+        assert_eq!(
+            VerbInvocation::from("a!b c"),
+            VerbInvocation::new("a!b", Some("c"), false),
+        );
+        assert_eq!(
+            VerbInvocation::from("!!"),
+            VerbInvocation::new("", None, true),
+        );
+    }
+    #[test]
+    fn check_malformed_verb_invocation_parsing() {
+        for raw in &["", "!", "!!!", "a!!", "!a!b!", " ", "\\t:", "é!ü c"] {
+            let _ = VerbInvocation::from(*raw);
+        }
     }
 }
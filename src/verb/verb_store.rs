@@ -5,7 +5,7 @@ use {
         Verb,
     },
     crate::{
-        app::SelectionType,
+        app::{Selection, SelectionType},
         conf::Conf,
         keys,
     },
@@ -51,21 +51,43 @@ impl VerbStore {
     }
 
     pub fn search<'v>(&'v self, prefix: &str) -> PrefixSearchResult<'v, &Verb> {
+        self.search_filtered(prefix, None)
+    }
+
+    /// search among the verbs which apply to the given selection
+    /// (its type and its name)
+    pub fn search_sel<'v>(
+        &'v self,
+        prefix: &str,
+        sel: Selection<'_>,
+    ) -> PrefixSearchResult<'v, &'v Verb> {
+        self.search_filtered(prefix, Some(sel))
+    }
+
+    fn search_filtered<'v>(
+        &'v self,
+        prefix: &str,
+        sel: Option<Selection<'_>>,
+    ) -> PrefixSearchResult<'v, &'v Verb> {
         let mut found_index = 0;
         let mut nb_found = 0;
         let mut completions: Vec<&str> = Vec::new();
         for (index, verb) in self.verbs.iter().enumerate() {
-            for name in &verb.names {
-                if name.starts_with(prefix) {
-                    if name == prefix {
-                        return PrefixSearchResult::Match(name, &verb);
-                    }
-                    found_index = index;
-                    nb_found += 1;
-                    completions.push(name);
+            if let Some(sel) = sel {
+                if !verb.applies_to(sel) {
                     continue;
                 }
             }
+            if let Some(name) = verb.names.iter().find(|name| *name == prefix) {
+                return PrefixSearchResult::Match(name, verb);
+            }
+            // a verb whose several names start with the prefix is
+            // counted only once
+            if let Some(name) = verb.names.iter().find(|name| name.starts_with(prefix)) {
+                found_index = index;
+                nb_found += 1;
+                completions.push(name);
+            }
         }
         match nb_found {
             0 => PrefixSearchResult::NoMatch,
@@ -0,0 +1,140 @@
+use {
+    crate::{
+        app::Selection,
+        errors::ConfError,
+        path,
+    },
+    std::path::{Path, PathBuf},
+};
+
+/// where the process of an external verb is started
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkingDir {
+    /// the working dir of broot isn't changed
+    Unchanged,
+    /// the selected directory, or the parent of the selected file
+    Directory,
+    /// the parent of the selection
+    Parent,
+    /// the root of the tree displayed in the panel
+    PanelRoot,
+    /// the root broot was launched on
+    InitialRoot,
+    /// a fixed directory
+    Fixed(PathBuf),
+}
+
+impl WorkingDir {
+    /// read the value of the `working_dir` attribute of a verb conf
+    pub fn from(raw: &str) -> Result<Self, ConfError> {
+        match raw {
+            "directory" | "dir" => Ok(Self::Directory),
+            "parent" => Ok(Self::Parent),
+            "root" => Ok(Self::PanelRoot),
+            "initial_root" => Ok(Self::InitialRoot),
+            _ => {
+                let path = PathBuf::from(raw);
+                if path.is_absolute() && path.is_dir() {
+                    Ok(Self::Fixed(path))
+                } else {
+                    Err(ConfError::InvalidWorkingDir {
+                        working_dir: raw.to_string(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// compute the directory the process must be started in, if it
+    /// must be changed. When there's no tree in the panel, the root
+    /// is replaced with the directory of the selection.
+    pub fn resolve(
+        &self,
+        sel: Selection<'_>,
+        panel_root: Option<&Path>,
+        initial_root: &Path,
+    ) -> Option<PathBuf> {
+        match self {
+            Self::Unchanged => None,
+            Self::Directory => Some(path::closest_dir(sel.path)),
+            Self::Parent => Some(
+                sel.path
+                    .parent()
+                    .map_or_else(|| path::closest_dir(sel.path), Path::to_path_buf),
+            ),
+            Self::PanelRoot => Some(
+                panel_root.map_or_else(|| path::closest_dir(sel.path), Path::to_path_buf),
+            ),
+            Self::InitialRoot => Some(initial_root.to_path_buf()),
+            Self::Fixed(path) => Some(path.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod working_dir_tests {
+
+    use {
+        super::*,
+        crate::app::SelectionType,
+        std::fs,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn check_from() {
+        assert_eq!(WorkingDir::from("dir").unwrap(), WorkingDir::Directory);
+        assert_eq!(WorkingDir::from("parent").unwrap(), WorkingDir::Parent);
+        assert_eq!(WorkingDir::from("root").unwrap(), WorkingDir::PanelRoot);
+        assert_eq!(WorkingDir::from("initial_root").unwrap(), WorkingDir::InitialRoot);
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            WorkingDir::from(&dir.path().to_string_lossy()).unwrap(),
+            WorkingDir::Fixed(dir.path().to_path_buf()),
+        );
+        assert!(WorkingDir::from("some/relative/dir").is_err());
+        let missing = dir.path().join("missing");
+        assert!(WorkingDir::from(&missing.to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn check_resolve() {
+        let dir = TempDir::new().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        let file = sub.join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let initial_root = Path::new("/");
+        let file_sel = Selection {
+            path: &file,
+            line: 0,
+            stype: SelectionType::File,
+            marked: &[],
+        };
+        let dir_sel = Selection {
+            path: &sub,
+            line: 0,
+            stype: SelectionType::Directory,
+            marked: &[],
+        };
+        let resolve = |wd: WorkingDir, sel, panel_root| wd.resolve(sel, panel_root, initial_root);
+        assert_eq!(resolve(WorkingDir::Unchanged, file_sel, None), None);
+        assert_eq!(resolve(WorkingDir::Directory, file_sel, None), Some(sub.clone()));
+        assert_eq!(resolve(WorkingDir::Directory, dir_sel, None), Some(sub.clone()));
+        assert_eq!(resolve(WorkingDir::Parent, dir_sel, None), Some(dir.path().to_path_buf()));
+        assert_eq!(
+            resolve(WorkingDir::PanelRoot, file_sel, Some(dir.path())),
+            Some(dir.path().to_path_buf()),
+        );
+        // without a tree in the panel, the directory of the selection is used
+        assert_eq!(resolve(WorkingDir::PanelRoot, file_sel, None), Some(sub.clone()));
+        assert_eq!(
+            resolve(WorkingDir::InitialRoot, file_sel, None),
+            Some(initial_root.to_path_buf()),
+        );
+        assert_eq!(
+            resolve(WorkingDir::Fixed(sub.clone()), file_sel, None),
+            Some(sub.clone()),
+        );
+    }
+}